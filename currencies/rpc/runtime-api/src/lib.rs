@@ -0,0 +1,21 @@
+//! Runtime API definition for currencies module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use orml_traits::{AccountData, AccountInfo};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait CurrenciesApi<AccountId, CurrencyId, Balance> where
+		AccountId: Codec,
+		CurrencyId: Codec,
+		Balance: Codec,
+	{
+		fn currencies_of_account(who: AccountId) -> Vec<(CurrencyId, AccountData<Balance>)>;
+
+		fn effective_balance(currency_id: CurrencyId, who: AccountId) -> Balance;
+
+		fn account_info(who: AccountId, currency_id: CurrencyId) -> AccountInfo<Balance>;
+	}
+}
@@ -6,11 +6,15 @@
 //! `BasicCurrencyExtended`, and a multi-currency which implements `MultiCurrency`.
 //!
 //! It also provides an adapter, to adapt `frame_support::traits::Currency` implementations into
-//! `BasicCurrencyExtended`.
+//! `BasicCurrencyExtended`, and `CurrencyAsFrameCurrency`, to adapt `Trait::MultiCurrency` under a given currency ID
+//! into `frame_support::traits::Currency`. `EventfulBasicCurrency` wraps a `BasicCurrency`
+//! implementation, like the adapter, to emit the events of this module for its balance changes.
 //!
 //! The currencies module provides functionality of both `MultiCurrencyExtended` and `BasicCurrencyExtended`, via
 //! unified interfaces, and all calls would be delegated to the underlying multi-currency and base currency system.
-//! A native currency ID could be set by `Trait::GetNativeCurrencyId`, to identify the native currency.
+//! A native currency ID could be set by `Trait::GetNativeCurrencyId`, to identify the native currency. Which
+//! currencies are delegated to the native currency is decided by `Trait::CurrencyRouter`, e.g. to also route a
+//! wrapped native currency to it, and `NativeCurrencyIdRouter` routes only the native currency ID.
 //!
 //! ### Implementations
 //!
@@ -19,6 +23,8 @@
 //! - `MultiCurrency` - Abstraction over a fungible multi-currency system.
 //! - `MultiCurrencyExtended` - Extended `MultiCurrency` with additional helper types and methods, like updating balance
 //! by a given signed integer amount.
+//! - `StoredMap` - Read and write the account data of the native currency, by depositing, withdrawing, reserving and
+//! unreserving.
 //!
 //! ## Interface
 //!
@@ -27,21 +33,40 @@
 //! - `transfer` - Transfer some balance to another account, in a given currency.
 //! - `transfer_native_currency` - Transfer some balance to another account, in native currency set in
 //! `Trait::NativeCurrency`.
+//! - `transfer_native_currency_keep_alive` - Transfer some balance to another account, in native currency, leaving at
+//! least the existential deposit in the sender.
+//! - `set_proxy` - Set the proxy account allowed to transfer a currency on behalf of the caller.
+//! - `proxy_transfer` - Transfer some balance of an account to another account, by its proxy account.
 //! - `update_balance` - Update balance by signed integer amount, in a given currency, root origin required.
+//! - `multi_update_balance` - Update balances of multiple accounts, either all or nothing, or skipping invalid
+//! updates, as set by `BatchFailurePolicy`, root origin required.
+//!
+//! ### Runtime APIs
+//!
+//! The currencies module provides helpers for the runtime APIs declared in `orml-currencies-rpc-runtime-api`:
+//!
+//! - `currencies_of_account` - All currencies with a non-zero balance held by an account.
+//! - `account_info` - The free, reserved and frozen balances, locks and total balance of an account in a currency.
+//! - `effective_balance` - The free balance of an account excluding any frozen by locks, via `MultiCurrency`.
+//! - `asset_ids` - All known currencies, including the native currency.
+//! - `full_summary` - The free, reserved and frozen balances of an account in the native currency, including its
+//! locks, and all other currencies it holds.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-	decl_error, decl_event, decl_module, decl_storage,
+	decl_error, decl_event, decl_module, decl_storage, ensure,
 	traits::{
-		Currency as PalletCurrency, ExistenceRequirement, Get, LockableCurrency as PalletLockableCurrency,
-		ReservableCurrency as PalletReservableCurrency, WithdrawReason,
+		Currency as PalletCurrency, ExistenceRequirement, Get, Imbalance, LockableCurrency as PalletLockableCurrency,
+		ReservableCurrency as PalletReservableCurrency, SignedImbalance, StoredMap, TryDrop, WithdrawReason,
+		WithdrawReasons,
 	},
 };
-use rstd::{convert::TryInto, marker};
+use rstd::{convert::TryInto, marker, mem, prelude::*};
 use sp_runtime::{
-	traits::{CheckedSub, StaticLookup, Zero},
-	DispatchError, DispatchResult,
+	traits::{AtLeast32Bit, CheckedSub, Convert, Saturating, StaticLookup, Zero},
+	DispatchError, DispatchResult, RuntimeDebug,
 };
 // FIXME: `pallet/frame-` prefix should be used for all pallet modules, but currently `frame_system`
 // would cause compiling error in `decl_module!` and `construct_runtime!`
@@ -49,10 +74,12 @@ use sp_runtime::{
 use frame_system::{self as system, ensure_root, ensure_signed};
 
 use orml_traits::{
-	arithmetic::Signed, BalanceStatus, BasicCurrency, BasicCurrencyExtended, BasicLockableCurrency,
-	BasicReservableCurrency, LockIdentifier, MultiCurrency, MultiCurrencyExtended, MultiLockableCurrency,
+	arithmetic::Signed, AccountData, AccountInfo, BalanceStatus, BasicCurrency, BasicCurrencyExtended,
+	BasicCurrencyExtendedTransfer, BasicLockableCurrency, BasicReservableCurrency, LockIdentifier, MultiCurrency,
+	MultiCurrencyAccountData, MultiCurrencyExtended, MultiCurrencyIssuance, MultiLockableCurrency,
 	MultiReservableCurrency,
 };
+use orml_utilities::with_transaction_result;
 
 mod mock;
 mod tests;
@@ -64,19 +91,70 @@ type CurrencyIdOf<T> =
 type AmountOf<T> =
 	<<T as Trait>::MultiCurrency as MultiCurrencyExtended<<T as frame_system::Trait>::AccountId>>::Amount;
 
+/// How `multi_update_balance` handles an invalid update.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BatchFailurePolicy {
+	/// Abort the whole batch, and apply no update.
+	AbortAll,
+	/// Skip the invalid update, and apply the rest.
+	SkipInvalid,
+}
+
+/// The backend a currency is delegated to.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum CurrencyBackend {
+	/// `Trait::NativeCurrency`.
+	Native,
+	/// `Trait::MultiCurrency`.
+	Multi,
+}
+
+/// Route the currency ID given by `GetNativeCurrencyId` to the native currency, and all others to the multi-currency.
+pub struct NativeCurrencyIdRouter<GetNativeCurrencyId>(marker::PhantomData<GetNativeCurrencyId>);
+
+impl<CurrencyId, GetNativeCurrencyId> Convert<CurrencyId, CurrencyBackend>
+	for NativeCurrencyIdRouter<GetNativeCurrencyId>
+where
+	CurrencyId: PartialEq,
+	GetNativeCurrencyId: Get<CurrencyId>,
+{
+	fn convert(currency_id: CurrencyId) -> CurrencyBackend {
+		if currency_id == GetNativeCurrencyId::get() {
+			CurrencyBackend::Native
+		} else {
+			CurrencyBackend::Multi
+		}
+	}
+}
+
 pub trait Trait: frame_system::Trait {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 	type MultiCurrency: MultiCurrencyExtended<Self::AccountId>
 		+ MultiLockableCurrency<Self::AccountId>
-		+ MultiReservableCurrency<Self::AccountId>;
+		+ MultiReservableCurrency<Self::AccountId>
+		+ MultiCurrencyAccountData<Self::AccountId>;
 	type NativeCurrency: BasicCurrencyExtended<Self::AccountId, Balance = BalanceOf<Self>, Amount = AmountOf<Self>>
 		+ BasicLockableCurrency<Self::AccountId, Balance = BalanceOf<Self>>
-		+ BasicReservableCurrency<Self::AccountId, Balance = BalanceOf<Self>>;
+		+ BasicReservableCurrency<Self::AccountId, Balance = BalanceOf<Self>>
+		+ BasicCurrencyExtendedTransfer<Self::AccountId>;
 	type GetNativeCurrencyId: Get<CurrencyIdOf<Self>>;
+	/// The backend each currency is delegated to. `NativeCurrencyIdRouter` routes only `GetNativeCurrencyId` to the
+	/// native currency.
+	type CurrencyRouter: Convert<CurrencyIdOf<Self>, CurrencyBackend>;
+	/// The maximum number of balance deltas applied by `apply_deltas` at once.
+	type MaxDeltas: Get<u32>;
+	/// The maximum number of balance updates applied by `multi_update_balance` at once.
+	type MaxBatchUpdates: Get<u32>;
+	/// The frozen balance of an account in the native currency, e.g. the largest of its `pallet_balances` locks, as
+	/// it's not exposed by `BasicLockableCurrency`.
+	type NativeFrozenBalance: Convert<Self::AccountId, BalanceOf<Self>>;
 }
 
 decl_storage! {
-	trait Store for Module<T: Trait> as Currencies {}
+	trait Store for Module<T: Trait> as Currencies {
+		/// The proxy account allowed to transfer a currency on behalf of an account.
+		pub ProxyAccounts get(fn proxy_account): map hasher(blake2_128_concat) (CurrencyIdOf<T>, T::AccountId) => Option<T::AccountId>;
+	}
 }
 
 decl_event!(
@@ -94,6 +172,14 @@ decl_event!(
 		Deposited(CurrencyId, AccountId, Balance),
 		/// Withdraw success (currency_id, who, amount)
 		Withdrawn(CurrencyId, AccountId, Balance),
+		/// Batch balance update failed, and no update is applied (index, error)
+		BatchUpdateFailed(u32, DispatchError),
+		/// Batch balance update applied with invalid updates skipped (succeeded, skipped_indices)
+		BatchPartial(u32, Vec<u32>),
+		/// Proxy account set (currency_id, owner, proxy)
+		ProxySet(CurrencyId, AccountId, Option<AccountId>),
+		/// Transfer made by a proxy account on behalf of its owner (currency_id, proxy, owner, dest, amount)
+		ProxyTransferred(CurrencyId, AccountId, AccountId, AccountId, Balance),
 	}
 );
 
@@ -102,6 +188,12 @@ decl_error! {
 	pub enum Error for Module<T: Trait> {
 		AmountIntoBalanceFailed,
 		BalanceTooLow,
+		TooManyDeltas,
+		TooManyUpdates,
+		ExistentialDeposit,
+		NotProxy,
+		BelowMinimumReceived,
+		DeadAccount,
 	}
 }
 
@@ -138,6 +230,58 @@ decl_module! {
 			Self::deposit_event(RawEvent::Transferred(T::GetNativeCurrencyId::get(), from, to, amount));
 		}
 
+		/// Transfer native currency balance from one account to another, leaving at least the existential deposit in
+		/// the sender.
+		pub fn transfer_native_currency_keep_alive(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: BalanceOf<T>,
+		) {
+			let from = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(dest)?;
+			T::NativeCurrency::transfer_with_reason(
+				&from,
+				&to,
+				amount,
+				WithdrawReason::Transfer.into(),
+				ExistenceRequirement::KeepAlive,
+			)?;
+
+			Self::deposit_event(RawEvent::Transferred(T::GetNativeCurrencyId::get(), from, to, amount));
+		}
+
+		/// Set the proxy account allowed to transfer `currency_id` on behalf of the caller, or remove it if `None`.
+		pub fn set_proxy(origin, currency_id: CurrencyIdOf<T>, proxy: Option<T::AccountId>) {
+			let who = ensure_signed(origin)?;
+			match &proxy {
+				Some(proxy) => <ProxyAccounts<T>>::insert((currency_id, who.clone()), proxy),
+				None => <ProxyAccounts<T>>::remove((currency_id, who.clone())),
+			}
+
+			Self::deposit_event(RawEvent::ProxySet(currency_id, who, proxy));
+		}
+
+		/// Transfer some balance of `owner` to another account. The caller must be the proxy account of `owner` for
+		/// `currency_id`.
+		pub fn proxy_transfer(
+			origin,
+			owner: <T::Lookup as StaticLookup>::Source,
+			dest: <T::Lookup as StaticLookup>::Source,
+			currency_id: CurrencyIdOf<T>,
+			#[compact] amount: BalanceOf<T>,
+		) {
+			let proxy = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+			let to = T::Lookup::lookup(dest)?;
+			ensure!(
+				Self::proxy_account((currency_id, owner.clone())) == Some(proxy.clone()),
+				Error::<T>::NotProxy
+			);
+			<Self as MultiCurrency<T::AccountId>>::transfer(currency_id, &owner, &to, amount)?;
+
+			Self::deposit_event(RawEvent::ProxyTransferred(currency_id, proxy, owner, to, amount));
+		}
+
 		/// Update balance of an account. This is a root call.
 		pub fn update_balance(
 			origin,
@@ -149,17 +293,194 @@ decl_module! {
 			let dest = T::Lookup::lookup(who)?;
 			<Self as MultiCurrencyExtended<T::AccountId>>::update_balance(currency_id, &dest, amount)?;
 		}
+
+		/// Update balances of multiple accounts. This is a root call.
+		///
+		/// With `BatchFailurePolicy::AbortAll`, it's all or nothing: updates are applied in sequence in a storage
+		/// transaction, which is rolled back on the first failing update, and `BatchUpdateFailed` is deposited with
+		/// its index.
+		///
+		/// With `BatchFailurePolicy::SkipInvalid`, each update is applied in its own storage transaction, failing ones
+		/// are skipped, and `BatchPartial` is deposited if any is skipped.
+		pub fn multi_update_balance(
+			origin,
+			updates: Vec<(<T::Lookup as StaticLookup>::Source, CurrencyIdOf<T>, AmountOf<T>)>,
+			policy: BatchFailurePolicy,
+		) {
+			ensure_root(origin)?;
+			ensure!(updates.len() <= T::MaxBatchUpdates::get() as usize, Error::<T>::TooManyUpdates);
+
+			let mut resolved = Vec::with_capacity(updates.len());
+			for (who, currency_id, amount) in updates {
+				resolved.push((T::Lookup::lookup(who)?, currency_id, amount));
+			}
+
+			if policy == BatchFailurePolicy::SkipInvalid {
+				let mut skipped = Vec::new();
+				for (index, (who, currency_id, amount)) in resolved.iter().enumerate() {
+					let result = with_transaction_result(|| {
+						<Self as MultiCurrencyExtended<T::AccountId>>::update_balance(*currency_id, who, *amount)
+					});
+					if result.is_err() {
+						skipped.push(index as u32);
+					}
+				}
+				if !skipped.is_empty() {
+					let succeeded = (resolved.len() - skipped.len()) as u32;
+					Self::deposit_event(RawEvent::BatchPartial(succeeded, skipped));
+				}
+				return Ok(());
+			}
+
+			let mut failed_index = 0;
+			let result = with_transaction_result(|| {
+				for (index, (who, currency_id, amount)) in resolved.iter().enumerate() {
+					failed_index = index as u32;
+					<Self as MultiCurrencyExtended<T::AccountId>>::update_balance(*currency_id, who, *amount)?;
+				}
+				Ok(())
+			});
+			if let Err(e) = result {
+				// deposited after the rollback, so it's not discarded with the updates
+				Self::deposit_event(RawEvent::BatchUpdateFailed(failed_index, e));
+				return Err(e);
+			}
+		}
 	}
 }
 
-impl<T: Trait> Module<T> {}
+impl<T: Trait> Module<T> {
+	/// The native currency ID, as set by `Trait::GetNativeCurrencyId`.
+	pub fn native_currency_id() -> CurrencyIdOf<T> {
+		T::GetNativeCurrencyId::get()
+	}
+
+	/// Whether `currency_id` is delegated to `Trait::NativeCurrency`, as routed by `Trait::CurrencyRouter`.
+	pub fn is_native(currency_id: CurrencyIdOf<T>) -> bool {
+		T::CurrencyRouter::convert(currency_id) == CurrencyBackend::Native
+	}
+
+	/// The `(free, reserved)` balance of `who` in `currency_id`, from `Trait::NativeCurrency` for the native
+	/// currency and `Trait::MultiCurrency` otherwise.
+	pub fn free_reserved(currency_id: CurrencyIdOf<T>, who: &T::AccountId) -> (BalanceOf<T>, BalanceOf<T>) {
+		if Self::is_native(currency_id) {
+			(
+				T::NativeCurrency::free_balance(who),
+				T::NativeCurrency::reserved_balance(who),
+			)
+		} else {
+			(
+				T::MultiCurrency::free_balance(currency_id, who),
+				T::MultiCurrency::reserved_balance(currency_id, who),
+			)
+		}
+	}
+
+	/// All currencies with a non-zero total balance held by `who`, including the native currency.
+	///
+	/// The native currency comes first. Its `frozen` is always zero, as locks of the native currency are not
+	/// exposed by `BasicLockableCurrency`. Other currencies routed to the native currency are not listed apart.
+	///
+	/// NOTE: This iterates over all currencies, and should only be used off-chain, e.g. by runtime APIs.
+	pub fn currencies_of_account(who: &T::AccountId) -> Vec<(CurrencyIdOf<T>, AccountData<BalanceOf<T>>)> {
+		let mut currencies = Vec::new();
+		if !T::NativeCurrency::total_balance(who).is_zero() {
+			currencies.push((
+				T::GetNativeCurrencyId::get(),
+				AccountData {
+					free: T::NativeCurrency::free_balance(who),
+					reserved: T::NativeCurrency::reserved_balance(who),
+					frozen: Zero::zero(),
+				},
+			));
+		}
+		currencies.extend(
+			T::MultiCurrency::currencies_of(who)
+				.into_iter()
+				.filter(|(currency_id, _)| !Self::is_native(*currency_id)),
+		);
+		currencies
+	}
+
+	/// The account data of `who` in the native currency, with `frozen` by `Trait::NativeFrozenBalance`, followed by
+	/// those of all other currencies with a non-zero total balance.
+	///
+	/// NOTE: This iterates over all currencies, and should only be used off-chain, e.g. by runtime APIs.
+	pub fn full_summary(who: &T::AccountId) -> Vec<(CurrencyIdOf<T>, AccountData<BalanceOf<T>>)> {
+		let mut summary = Vec::new();
+		summary.push((
+			T::GetNativeCurrencyId::get(),
+			AccountData {
+				frozen: T::NativeFrozenBalance::convert(who.clone()),
+				..Self::native_account_data(who)
+			},
+		));
+		summary.extend(
+			T::MultiCurrency::currencies_of(who)
+				.into_iter()
+				.filter(|(currency_id, _)| !Self::is_native(*currency_id)),
+		);
+		summary
+	}
+
+	/// All known currencies, for other modules, e.g. asset metadata registries, to enumerate them.
+	///
+	/// The native currency comes first, followed by those known by `Trait::MultiCurrency` and not routed to the native
+	/// currency.
+	///
+	/// NOTE: This iterates over all currencies, and should only be used off-chain, e.g. by runtime APIs.
+	pub fn asset_ids() -> Vec<CurrencyIdOf<T>> {
+		let native_currency_id = T::GetNativeCurrencyId::get();
+		let mut asset_ids = Vec::new();
+		asset_ids.push(native_currency_id);
+		asset_ids.extend(
+			T::MultiCurrency::currency_ids()
+				.into_iter()
+				.filter(|currency_id| !Self::is_native(*currency_id)),
+		);
+		asset_ids
+	}
+
+	/// The balance information and locks of `who` under `currency_id`, in a single read path.
+	///
+	/// For the native currency, `frozen` is always zero and `locks` empty, as locks of the native currency are not
+	/// exposed by `BasicLockableCurrency`.
+	pub fn account_info(who: &T::AccountId, currency_id: CurrencyIdOf<T>) -> AccountInfo<BalanceOf<T>> {
+		if Self::is_native(currency_id) {
+			AccountInfo {
+				free: T::NativeCurrency::free_balance(who),
+				reserved: T::NativeCurrency::reserved_balance(who),
+				frozen: Zero::zero(),
+				locks: Vec::new(),
+				total_balance: T::NativeCurrency::total_balance(who),
+			}
+		} else {
+			T::MultiCurrency::account_info(currency_id, who)
+		}
+	}
+
+	/// Apply signed balance `deltas` to `who` in sequence, all or nothing, and deposit a `BalanceUpdated` event per
+	/// entry.
+	///
+	/// The deltas are applied in a storage transaction, which is rolled back if any of them fails.
+	pub fn apply_deltas(who: &T::AccountId, deltas: Vec<(CurrencyIdOf<T>, AmountOf<T>)>) -> DispatchResult {
+		ensure!(deltas.len() <= T::MaxDeltas::get() as usize, Error::<T>::TooManyDeltas);
+
+		with_transaction_result(|| {
+			for (currency_id, amount) in deltas {
+				<Self as MultiCurrencyExtended<T::AccountId>>::update_balance(currency_id, who, amount)?;
+			}
+			Ok(())
+		})
+	}
+}
 
 impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 	type CurrencyId = CurrencyIdOf<T>;
 	type Balance = BalanceOf<T>;
 
 	fn total_issuance(currency_id: Self::CurrencyId) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::total_issuance()
 		} else {
 			T::MultiCurrency::total_issuance(currency_id)
@@ -167,7 +488,7 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn total_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::total_balance(who)
 		} else {
 			T::MultiCurrency::total_balance(currency_id, who)
@@ -175,15 +496,49 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn free_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::free_balance(who)
 		} else {
 			T::MultiCurrency::free_balance(currency_id, who)
 		}
 	}
 
+	/// Locks of the native currency are not exposed by `BasicLockableCurrency`, so the effective balance of the
+	/// native currency is its free balance.
+	fn effective_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::free_balance(who)
+		} else {
+			T::MultiCurrency::effective_balance(currency_id, who)
+		}
+	}
+
+	/// As for `effective_balance`, locks of the native currency are not taken into account.
+	fn transferable_keep_alive(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::transferable_keep_alive(who)
+		} else {
+			T::MultiCurrency::transferable_keep_alive(currency_id, who)
+		}
+	}
+
+	fn balances_batch(currency_id: Self::CurrencyId, accounts: &[T::AccountId]) -> Vec<AccountData<Self::Balance>> {
+		if Self::is_native(currency_id) {
+			accounts
+				.iter()
+				.map(|who| AccountData {
+					free: T::NativeCurrency::free_balance(who),
+					reserved: T::NativeCurrency::reserved_balance(who),
+					frozen: Zero::zero(),
+				})
+				.collect()
+		} else {
+			T::MultiCurrency::balances_batch(currency_id, accounts)
+		}
+	}
+
 	fn ensure_can_withdraw(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::ensure_can_withdraw(who, amount)
 		} else {
 			T::MultiCurrency::ensure_can_withdraw(currency_id, who, amount)
@@ -199,7 +554,7 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 		if amount.is_zero() {
 			return Ok(());
 		}
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::transfer(from, to, amount)?;
 		} else {
 			T::MultiCurrency::transfer(currency_id, from, to, amount)?;
@@ -208,11 +563,41 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 		Ok(())
 	}
 
+	fn transfer_exact_out(
+		currency_id: Self::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		receive_amount: Self::Balance,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			<Self as MultiCurrency<T::AccountId>>::transfer(currency_id, from, to, receive_amount)
+		} else {
+			T::MultiCurrency::transfer_exact_out(currency_id, from, to, receive_amount)
+		}
+	}
+
+	/// The native currency charges no fee-on-transfer, so fails with `BelowMinimumReceived` if `amount` is less than
+	/// `min_received`.
+	fn transfer_min_received(
+		currency_id: Self::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: Self::Balance,
+		min_received: Self::Balance,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			ensure!(amount >= min_received, Error::<T>::BelowMinimumReceived);
+			<Self as MultiCurrency<T::AccountId>>::transfer(currency_id, from, to, amount)
+		} else {
+			T::MultiCurrency::transfer_min_received(currency_id, from, to, amount, min_received)
+		}
+	}
+
 	fn deposit(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
 		if amount.is_zero() {
 			return Ok(());
 		}
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::deposit(who, amount)?;
 		} else {
 			T::MultiCurrency::deposit(currency_id, who, amount)?;
@@ -225,7 +610,7 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 		if amount.is_zero() {
 			return Ok(());
 		}
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::withdraw(who, amount)?;
 		} else {
 			T::MultiCurrency::withdraw(currency_id, who, amount)?;
@@ -234,8 +619,34 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 		Ok(())
 	}
 
+	fn withdraw_in_order(
+		who: &T::AccountId,
+		currencies: &[(Self::CurrencyId, Self::Balance)],
+		total_needed: Self::Balance,
+	) -> rstd::result::Result<Vec<(Self::CurrencyId, Self::Balance)>, DispatchError> {
+		with_transaction_result(|| {
+			let mut withdrawals = Vec::new();
+			let mut remaining = total_needed;
+			for (currency_id, max_amount) in currencies.iter() {
+				if remaining.is_zero() {
+					break;
+				}
+				let amount = remaining
+					.min(*max_amount)
+					.min(Self::effective_balance(*currency_id, who));
+				if !amount.is_zero() {
+					Self::withdraw(*currency_id, who, amount)?;
+					withdrawals.push((*currency_id, amount));
+					remaining -= amount;
+				}
+			}
+			ensure!(remaining.is_zero(), Error::<T>::BalanceTooLow);
+			Ok(withdrawals)
+		})
+	}
+
 	fn can_slash(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> bool {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::can_slash(who, amount)
 		} else {
 			T::MultiCurrency::can_slash(currency_id, who, amount)
@@ -243,7 +654,7 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn slash(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::slash(who, amount)
 		} else {
 			T::MultiCurrency::slash(currency_id, who, amount)
@@ -255,7 +666,7 @@ impl<T: Trait> MultiCurrencyExtended<T::AccountId> for Module<T> {
 	type Amount = AmountOf<T>;
 
 	fn update_balance(currency_id: Self::CurrencyId, who: &T::AccountId, by_amount: Self::Amount) -> DispatchResult {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::update_balance(who, by_amount)?;
 		} else {
 			T::MultiCurrency::update_balance(currency_id, who, by_amount)?;
@@ -269,7 +680,7 @@ impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
 	type Moment = T::BlockNumber;
 
 	fn set_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::set_lock(lock_id, who, amount);
 		} else {
 			T::MultiCurrency::set_lock(lock_id, currency_id, who, amount);
@@ -277,7 +688,7 @@ impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn extend_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::extend_lock(lock_id, who, amount);
 		} else {
 			T::MultiCurrency::extend_lock(lock_id, currency_id, who, amount);
@@ -285,25 +696,58 @@ impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn remove_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId) {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::remove_lock(lock_id, who);
 		} else {
 			T::MultiCurrency::remove_lock(lock_id, currency_id, who);
 		}
 	}
+
+	fn try_set_lock(
+		lock_id: LockIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::set_lock(lock_id, who, amount);
+			Ok(())
+		} else {
+			T::MultiCurrency::try_set_lock(lock_id, currency_id, who, amount)
+		}
+	}
+
+	fn try_extend_lock(
+		lock_id: LockIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::extend_lock(lock_id, who, amount);
+			Ok(())
+		} else {
+			T::MultiCurrency::try_extend_lock(lock_id, currency_id, who, amount)
+		}
+	}
 }
 
 impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 	fn can_reserve(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> bool {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::can_reserve(who, value)
 		} else {
 			T::MultiCurrency::can_reserve(currency_id, who, value)
 		}
 	}
 
+	/// The native currency is always reservable.
+	fn reservable(currency_id: Self::CurrencyId) -> bool {
+		Self::is_native(currency_id) || T::MultiCurrency::reservable(currency_id)
+	}
+
 	fn slash_reserved(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::slash_reserved(who, value)
 		} else {
 			T::MultiCurrency::slash_reserved(currency_id, who, value)
@@ -311,7 +755,7 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn reserved_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::reserved_balance(who)
 		} else {
 			T::MultiCurrency::reserved_balance(currency_id, who)
@@ -319,7 +763,7 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn reserve(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> DispatchResult {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::reserve(who, value)
 		} else {
 			T::MultiCurrency::reserve(currency_id, who, value)
@@ -327,7 +771,7 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 	}
 
 	fn unreserve(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> Self::Balance {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::unreserve(who, value)
 		} else {
 			T::MultiCurrency::unreserve(currency_id, who, value)
@@ -341,7 +785,7 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 		value: Self::Balance,
 		status: BalanceStatus,
 	) -> rstd::result::Result<Self::Balance, DispatchError> {
-		if currency_id == T::GetNativeCurrencyId::get() {
+		if Self::is_native(currency_id) {
 			T::NativeCurrency::repatriate_reserved(slashed, beneficiary, value, status)
 		} else {
 			T::MultiCurrency::repatriate_reserved(currency_id, slashed, beneficiary, value, status)
@@ -349,6 +793,80 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 	}
 }
 
+impl<T: Trait> Module<T> {
+	/// The account data of `who` in the native currency. `frozen` is always zero, as locks of the native currency are
+	/// not exposed by `BasicLockableCurrency`.
+	fn native_account_data(who: &T::AccountId) -> AccountData<BalanceOf<T>> {
+		AccountData {
+			free: T::NativeCurrency::free_balance(who),
+			reserved: T::NativeCurrency::reserved_balance(who),
+			frozen: Zero::zero(),
+		}
+	}
+
+	/// Set the free and reserved balance of `who` in the native currency to those of `account`, by unreserving,
+	/// depositing or withdrawing, then reserving, either all or nothing. `frozen` is ignored.
+	fn set_native_account_data(who: &T::AccountId, account: AccountData<BalanceOf<T>>) -> DispatchResult {
+		with_transaction_result(|| {
+			let reserved = T::NativeCurrency::reserved_balance(who);
+			if account.reserved < reserved {
+				T::NativeCurrency::unreserve(who, reserved - account.reserved);
+			}
+
+			let to_reserve = account
+				.reserved
+				.saturating_sub(T::NativeCurrency::reserved_balance(who));
+			let free = T::NativeCurrency::free_balance(who);
+			let target_free = account.free.saturating_add(to_reserve);
+			if target_free > free {
+				T::NativeCurrency::deposit(who, target_free - free)?;
+			} else if target_free < free {
+				T::NativeCurrency::withdraw(who, free - target_free)?;
+			}
+			T::NativeCurrency::reserve(who, to_reserve)
+		})
+	}
+}
+
+/// The account data of the native currency, for `frame_system` and other modules to read and write account data
+/// generically.
+///
+/// NOTE: `StoredMap` can't report failures, so any change rejected by the native currency, e.g. a withdrawal below
+/// its locks, is not applied at all, leaving the account data unchanged, and changes of `frozen` are always ignored.
+impl<T: Trait> StoredMap<T::AccountId, AccountData<BalanceOf<T>>> for Module<T> {
+	fn get(k: &T::AccountId) -> AccountData<BalanceOf<T>> {
+		Self::native_account_data(k)
+	}
+
+	fn is_explicit(k: &T::AccountId) -> bool {
+		!T::NativeCurrency::total_balance(k).is_zero()
+	}
+
+	fn mutate<R>(k: &T::AccountId, f: impl FnOnce(&mut AccountData<BalanceOf<T>>) -> R) -> R {
+		let mut account = Self::native_account_data(k);
+		let result = f(&mut account);
+		let _ = Self::set_native_account_data(k, account);
+		result
+	}
+
+	fn mutate_exists<R>(k: &T::AccountId, f: impl FnOnce(&mut Option<AccountData<BalanceOf<T>>>) -> R) -> R {
+		let mut maybe_account = Some(Self::native_account_data(k)).filter(|_| Self::is_explicit(k));
+		let result = f(&mut maybe_account);
+		let _ = Self::set_native_account_data(k, maybe_account.unwrap_or_default());
+		result
+	}
+
+	fn try_mutate_exists<R, E>(
+		k: &T::AccountId,
+		f: impl FnOnce(&mut Option<AccountData<BalanceOf<T>>>) -> rstd::result::Result<R, E>,
+	) -> rstd::result::Result<R, E> {
+		let mut maybe_account = Some(Self::native_account_data(k)).filter(|_| Self::is_explicit(k));
+		let result = f(&mut maybe_account)?;
+		let _ = Self::set_native_account_data(k, maybe_account.unwrap_or_default());
+		Ok(result)
+	}
+}
+
 pub struct Currency<T, GetCurrencyId>(marker::PhantomData<T>, marker::PhantomData<GetCurrencyId>);
 
 impl<T, GetCurrencyId> BasicCurrency<T::AccountId> for Currency<T, GetCurrencyId>
@@ -370,6 +888,10 @@ where
 		<Module<T>>::free_balance(GetCurrencyId::get(), who)
 	}
 
+	fn transferable_keep_alive(who: &T::AccountId) -> Self::Balance {
+		<Module<T>>::transferable_keep_alive(GetCurrencyId::get(), who)
+	}
+
 	fn ensure_can_withdraw(who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
 		<Module<T>>::ensure_can_withdraw(GetCurrencyId::get(), who, amount)
 	}
@@ -500,6 +1022,11 @@ where
 		BalanceConvert::from(Currency::free_balance(who)).into()
 	}
 
+	fn transferable_keep_alive(who: &AccountId) -> Self::Balance {
+		let free_balance = Currency::free_balance(who).saturating_sub(Currency::minimum_balance());
+		BalanceConvert::from(free_balance).into()
+	}
+
 	fn ensure_can_withdraw(who: &AccountId, amount: Self::Balance) -> DispatchResult {
 		let new_balance_pallet = {
 			let new_balance = Self::free_balance(who)
@@ -567,6 +1094,41 @@ where
 	}
 }
 
+// Adapt `frame_support::traits::Currency`
+impl<AccountId, T, Currency, BalanceConvert> BasicCurrencyExtendedTransfer<AccountId>
+	for BasicCurrencyAdapter<T, Currency, BalanceConvert>
+where
+	T: Trait,
+	Currency: PalletCurrency<AccountId>,
+	BalanceConvert: From<PalletBalanceOf<AccountId, Currency>>
+		+ Into<PalletBalanceOf<AccountId, Currency>>
+		+ From<BalanceOf<T>>
+		+ Into<BalanceOf<T>>,
+{
+	/// The transferred amount is withdrawn from `from` and resolved into `to`, which must meet the existential deposit
+	/// after receiving it.
+	fn transfer_with_reason(
+		from: &AccountId,
+		to: &AccountId,
+		amount: Self::Balance,
+		reason: WithdrawReasons,
+		existence: ExistenceRequirement,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+
+		let amount_pallet: PalletBalanceOf<AccountId, Currency> = BalanceConvert::from(amount).into();
+		ensure!(
+			Currency::total_balance(to).saturating_add(amount_pallet) >= Currency::minimum_balance(),
+			Error::<T>::ExistentialDeposit
+		);
+		let imbalance = Currency::withdraw(from, amount_pallet, reason, existence)?;
+		Currency::resolve_creating(to, imbalance);
+		Ok(())
+	}
+}
+
 // Adapt `frame_support::traits::LockableCurrency`
 impl<AccountId, T, Currency, BalanceConvert> BasicLockableCurrency<AccountId>
 	for BasicCurrencyAdapter<T, Currency, BalanceConvert>
@@ -645,3 +1207,537 @@ where
 			.map(|a| BalanceConvert::from(a).into())
 	}
 }
+
+/// Wrap a native `BasicCurrency` implementation, e.g. `BasicCurrencyAdapter`, to emit the `Transferred`,
+/// `Deposited`, `Withdrawn` and `BalanceUpdated` events of this module, under `Trait::GetNativeCurrencyId`, for its
+/// balance changes, so they are visible as those made via this module.
+///
+/// NOTE: This module emits these events for its own native currency operations, so the wrapper is meant for other
+/// users of the native currency, e.g. the native currency of `orml_tokens`, and shouldn't be set as
+/// `Trait::NativeCurrency`, or the events would be emitted twice.
+pub struct EventfulBasicCurrency<T, Currency>(marker::PhantomData<(T, Currency)>);
+
+impl<T, Currency> BasicCurrency<T::AccountId> for EventfulBasicCurrency<T, Currency>
+where
+	T: Trait,
+	Currency: BasicCurrency<T::AccountId, Balance = BalanceOf<T>>,
+{
+	type Balance = BalanceOf<T>;
+
+	fn total_issuance() -> Self::Balance {
+		Currency::total_issuance()
+	}
+
+	fn total_balance(who: &T::AccountId) -> Self::Balance {
+		Currency::total_balance(who)
+	}
+
+	fn free_balance(who: &T::AccountId) -> Self::Balance {
+		Currency::free_balance(who)
+	}
+
+	fn transferable_keep_alive(who: &T::AccountId) -> Self::Balance {
+		Currency::transferable_keep_alive(who)
+	}
+
+	fn ensure_can_withdraw(who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		Currency::ensure_can_withdraw(who, amount)
+	}
+
+	fn transfer(from: &T::AccountId, to: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		Currency::transfer(from, to, amount)?;
+		<Module<T>>::deposit_event(RawEvent::Transferred(
+			T::GetNativeCurrencyId::get(),
+			from.clone(),
+			to.clone(),
+			amount,
+		));
+		Ok(())
+	}
+
+	fn deposit(who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		Currency::deposit(who, amount)?;
+		<Module<T>>::deposit_event(RawEvent::Deposited(T::GetNativeCurrencyId::get(), who.clone(), amount));
+		Ok(())
+	}
+
+	fn withdraw(who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		Currency::withdraw(who, amount)?;
+		<Module<T>>::deposit_event(RawEvent::Withdrawn(T::GetNativeCurrencyId::get(), who.clone(), amount));
+		Ok(())
+	}
+
+	fn can_slash(who: &T::AccountId, amount: Self::Balance) -> bool {
+		Currency::can_slash(who, amount)
+	}
+
+	fn slash(who: &T::AccountId, amount: Self::Balance) -> Self::Balance {
+		Currency::slash(who, amount)
+	}
+}
+
+impl<T, Currency> BasicCurrencyExtended<T::AccountId> for EventfulBasicCurrency<T, Currency>
+where
+	T: Trait,
+	Currency: BasicCurrencyExtended<T::AccountId, Balance = BalanceOf<T>, Amount = AmountOf<T>>,
+{
+	type Amount = AmountOf<T>;
+
+	fn update_balance(who: &T::AccountId, by_amount: Self::Amount) -> DispatchResult {
+		Currency::update_balance(who, by_amount)?;
+		<Module<T>>::deposit_event(RawEvent::BalanceUpdated(
+			T::GetNativeCurrencyId::get(),
+			who.clone(),
+			by_amount,
+		));
+		Ok(())
+	}
+}
+
+impl<T, Currency> BasicCurrencyExtendedTransfer<T::AccountId> for EventfulBasicCurrency<T, Currency>
+where
+	T: Trait,
+	Currency: BasicCurrencyExtendedTransfer<T::AccountId, Balance = BalanceOf<T>>,
+{
+	fn transfer_with_reason(
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: Self::Balance,
+		reason: WithdrawReasons,
+		existence: ExistenceRequirement,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		Currency::transfer_with_reason(from, to, amount, reason, existence)?;
+		<Module<T>>::deposit_event(RawEvent::Transferred(
+			T::GetNativeCurrencyId::get(),
+			from.clone(),
+			to.clone(),
+			amount,
+		));
+		Ok(())
+	}
+}
+
+impl<T, Currency> BasicLockableCurrency<T::AccountId> for EventfulBasicCurrency<T, Currency>
+where
+	T: Trait,
+	Currency: BasicLockableCurrency<T::AccountId, Balance = BalanceOf<T>>,
+{
+	type Moment = Currency::Moment;
+
+	fn set_lock(lock_id: LockIdentifier, who: &T::AccountId, amount: Self::Balance) {
+		Currency::set_lock(lock_id, who, amount)
+	}
+
+	fn extend_lock(lock_id: LockIdentifier, who: &T::AccountId, amount: Self::Balance) {
+		Currency::extend_lock(lock_id, who, amount)
+	}
+
+	fn remove_lock(lock_id: LockIdentifier, who: &T::AccountId) {
+		Currency::remove_lock(lock_id, who)
+	}
+}
+
+impl<T, Currency> BasicReservableCurrency<T::AccountId> for EventfulBasicCurrency<T, Currency>
+where
+	T: Trait,
+	Currency: BasicReservableCurrency<T::AccountId, Balance = BalanceOf<T>>,
+{
+	fn can_reserve(who: &T::AccountId, value: Self::Balance) -> bool {
+		Currency::can_reserve(who, value)
+	}
+
+	fn slash_reserved(who: &T::AccountId, value: Self::Balance) -> Self::Balance {
+		Currency::slash_reserved(who, value)
+	}
+
+	fn reserved_balance(who: &T::AccountId) -> Self::Balance {
+		Currency::reserved_balance(who)
+	}
+
+	fn reserve(who: &T::AccountId, value: Self::Balance) -> DispatchResult {
+		Currency::reserve(who, value)
+	}
+
+	fn unreserve(who: &T::AccountId, value: Self::Balance) -> Self::Balance {
+		Currency::unreserve(who, value)
+	}
+
+	fn repatriate_reserved(
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		value: Self::Balance,
+		status: BalanceStatus,
+	) -> rstd::result::Result<Self::Balance, DispatchError> {
+		Currency::repatriate_reserved(slashed, beneficiary, value, status)
+	}
+}
+
+/// Adapt `Trait::MultiCurrency`, under the currency ID `GetCurrencyId`, to `frame_support::traits::Currency`,
+/// `ReservableCurrency` and `LockableCurrency`, with the existential deposit `GetExistentialDeposit`. The inverse of
+/// `BasicCurrencyAdapter`.
+///
+/// As with `pallet_balances`, the total issuance is only changed when the returned imbalances are dropped: balance
+/// changes are applied by the underlying currency when made, and the change of total issuance they caused is deferred
+/// to the imbalance, by `MultiCurrencyIssuance`. Withdraw reasons of locks are ignored, and as `LockableCurrency` is
+/// infallible, locks are set by `MultiLockableCurrency::set_lock` rather than `try_set_lock`.
+pub struct CurrencyAsFrameCurrency<T, GetCurrencyId, GetExistentialDeposit>(
+	marker::PhantomData<(T, GetCurrencyId, GetExistentialDeposit)>,
+);
+
+macro_rules! impl_imbalance {
+	($name:ident, $opposite:ident) => {
+		impl<T, GetCurrencyId> $name<T, GetCurrencyId>
+		where
+			T: Trait,
+			T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+			GetCurrencyId: Get<CurrencyIdOf<T>>,
+		{
+			/// Create an imbalance of `amount`.
+			fn new(amount: BalanceOf<T>) -> Self {
+				$name(amount, marker::PhantomData)
+			}
+		}
+
+		impl<T, GetCurrencyId> Imbalance<BalanceOf<T>> for $name<T, GetCurrencyId>
+		where
+			T: Trait,
+			T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+			GetCurrencyId: Get<CurrencyIdOf<T>>,
+		{
+			type Opposite = $opposite<T, GetCurrencyId>;
+
+			fn zero() -> Self {
+				Self::new(Zero::zero())
+			}
+
+			fn drop_zero(self) -> rstd::result::Result<(), Self> {
+				if self.0.is_zero() {
+					Ok(())
+				} else {
+					Err(self)
+				}
+			}
+
+			fn split(self, amount: BalanceOf<T>) -> (Self, Self) {
+				let first = self.0.min(amount);
+				let second = self.0 - first;
+				mem::forget(self);
+				(Self::new(first), Self::new(second))
+			}
+
+			fn merge(mut self, other: Self) -> Self {
+				self.0 = self.0.saturating_add(other.0);
+				mem::forget(other);
+				self
+			}
+
+			fn subsume(&mut self, other: Self) {
+				self.0 = self.0.saturating_add(other.0);
+				mem::forget(other);
+			}
+
+			fn offset(self, other: Self::Opposite) -> rstd::result::Result<Self, Self::Opposite> {
+				let (amount, other_amount) = (self.0, other.0);
+				mem::forget((self, other));
+				if amount >= other_amount {
+					Ok(Self::new(amount - other_amount))
+				} else {
+					Err($opposite::new(other_amount - amount))
+				}
+			}
+
+			fn peek(&self) -> BalanceOf<T> {
+				self.0
+			}
+		}
+
+		impl<T, GetCurrencyId> TryDrop for $name<T, GetCurrencyId>
+		where
+			T: Trait,
+			T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+			GetCurrencyId: Get<CurrencyIdOf<T>>,
+		{
+			fn try_drop(self) -> rstd::result::Result<(), Self> {
+				self.drop_zero()
+			}
+		}
+	};
+}
+
+/// An increase of balance made by `CurrencyAsFrameCurrency`. Increases the total issuance when dropped.
+#[must_use]
+pub struct PositiveImbalance<T, GetCurrencyId>(BalanceOf<T>, marker::PhantomData<GetCurrencyId>)
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>;
+
+/// A decrease of balance made by `CurrencyAsFrameCurrency`. Decreases the total issuance when dropped.
+#[must_use]
+pub struct NegativeImbalance<T, GetCurrencyId>(BalanceOf<T>, marker::PhantomData<GetCurrencyId>)
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>;
+
+impl_imbalance!(PositiveImbalance, NegativeImbalance);
+impl_imbalance!(NegativeImbalance, PositiveImbalance);
+
+impl<T, GetCurrencyId> Drop for PositiveImbalance<T, GetCurrencyId>
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>,
+{
+	fn drop(&mut self) {
+		T::MultiCurrency::increase_total_issuance(GetCurrencyId::get(), self.0);
+	}
+}
+
+impl<T, GetCurrencyId> Drop for NegativeImbalance<T, GetCurrencyId>
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>,
+{
+	fn drop(&mut self) {
+		T::MultiCurrency::decrease_total_issuance(GetCurrencyId::get(), self.0);
+	}
+}
+
+impl<T, GetCurrencyId, GetExistentialDeposit> CurrencyAsFrameCurrency<T, GetCurrencyId, GetExistentialDeposit>
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>,
+{
+	/// Ensure `value` could be withdrawn from `who` without killing it if `existence` is `KeepAlive`.
+	fn ensure_keep_alive(who: &T::AccountId, value: BalanceOf<T>, existence: ExistenceRequirement) -> DispatchResult {
+		if existence == ExistenceRequirement::KeepAlive {
+			ensure!(
+				value <= T::MultiCurrency::transferable_keep_alive(GetCurrencyId::get(), who),
+				Error::<T>::ExistentialDeposit
+			);
+		}
+		Ok(())
+	}
+
+	/// Revert the increase of the total issuance since it was `previous_issuance`, deferring it to the returned
+	/// imbalance.
+	fn defer_issuance_increase(previous_issuance: BalanceOf<T>) -> PositiveImbalance<T, GetCurrencyId> {
+		let currency_id = GetCurrencyId::get();
+		let increase = T::MultiCurrency::total_issuance(currency_id).saturating_sub(previous_issuance);
+		T::MultiCurrency::decrease_total_issuance(currency_id, increase);
+		PositiveImbalance::new(increase)
+	}
+
+	/// Revert the decrease of the total issuance since it was `previous_issuance`, deferring it to the returned
+	/// imbalance.
+	fn defer_issuance_decrease(previous_issuance: BalanceOf<T>) -> NegativeImbalance<T, GetCurrencyId> {
+		let currency_id = GetCurrencyId::get();
+		let decrease = previous_issuance.saturating_sub(T::MultiCurrency::total_issuance(currency_id));
+		T::MultiCurrency::increase_total_issuance(currency_id, decrease);
+		NegativeImbalance::new(decrease)
+	}
+}
+
+// Adapt `MultiCurrency`
+impl<T, GetCurrencyId, GetExistentialDeposit> PalletCurrency<T::AccountId>
+	for CurrencyAsFrameCurrency<T, GetCurrencyId, GetExistentialDeposit>
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>,
+	GetExistentialDeposit: Get<BalanceOf<T>>,
+{
+	type Balance = BalanceOf<T>;
+	type PositiveImbalance = PositiveImbalance<T, GetCurrencyId>;
+	type NegativeImbalance = NegativeImbalance<T, GetCurrencyId>;
+
+	fn total_balance(who: &T::AccountId) -> Self::Balance {
+		T::MultiCurrency::total_balance(GetCurrencyId::get(), who)
+	}
+
+	fn can_slash(who: &T::AccountId, value: Self::Balance) -> bool {
+		T::MultiCurrency::can_slash(GetCurrencyId::get(), who, value)
+	}
+
+	fn total_issuance() -> Self::Balance {
+		T::MultiCurrency::total_issuance(GetCurrencyId::get())
+	}
+
+	fn minimum_balance() -> Self::Balance {
+		GetExistentialDeposit::get()
+	}
+
+	fn burn(amount: Self::Balance) -> Self::PositiveImbalance {
+		let total_issuance = Self::total_issuance();
+		T::MultiCurrency::decrease_total_issuance(GetCurrencyId::get(), amount);
+		PositiveImbalance::new(total_issuance - Self::total_issuance())
+	}
+
+	fn issue(amount: Self::Balance) -> Self::NegativeImbalance {
+		let total_issuance = Self::total_issuance();
+		T::MultiCurrency::increase_total_issuance(GetCurrencyId::get(), amount);
+		NegativeImbalance::new(Self::total_issuance() - total_issuance)
+	}
+
+	fn free_balance(who: &T::AccountId) -> Self::Balance {
+		T::MultiCurrency::free_balance(GetCurrencyId::get(), who)
+	}
+
+	fn ensure_can_withdraw(
+		who: &T::AccountId,
+		amount: Self::Balance,
+		_reasons: WithdrawReasons,
+		_new_balance: Self::Balance,
+	) -> DispatchResult {
+		T::MultiCurrency::ensure_can_withdraw(GetCurrencyId::get(), who, amount)
+	}
+
+	fn transfer(
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		value: Self::Balance,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		Self::ensure_keep_alive(source, value, existence_requirement)?;
+		T::MultiCurrency::transfer(GetCurrencyId::get(), source, dest, value)
+	}
+
+	fn slash(who: &T::AccountId, value: Self::Balance) -> (Self::NegativeImbalance, Self::Balance) {
+		let total_issuance = Self::total_issuance();
+		let gap = T::MultiCurrency::slash(GetCurrencyId::get(), who, value);
+		(Self::defer_issuance_decrease(total_issuance), gap)
+	}
+
+	fn deposit_into_existing(
+		who: &T::AccountId,
+		value: Self::Balance,
+	) -> rstd::result::Result<Self::PositiveImbalance, DispatchError> {
+		ensure!(!Self::total_balance(who).is_zero(), Error::<T>::DeadAccount);
+		let total_issuance = Self::total_issuance();
+		T::MultiCurrency::deposit(GetCurrencyId::get(), who, value)?;
+		Ok(Self::defer_issuance_increase(total_issuance))
+	}
+
+	/// If the deposit is rejected by the underlying currency, a zero imbalance is returned.
+	fn deposit_creating(who: &T::AccountId, value: Self::Balance) -> Self::PositiveImbalance {
+		let total_issuance = Self::total_issuance();
+		if T::MultiCurrency::deposit(GetCurrencyId::get(), who, value).is_err() {
+			return PositiveImbalance::zero();
+		}
+		Self::defer_issuance_increase(total_issuance)
+	}
+
+	fn withdraw(
+		who: &T::AccountId,
+		value: Self::Balance,
+		_reasons: WithdrawReasons,
+		liveness: ExistenceRequirement,
+	) -> rstd::result::Result<Self::NegativeImbalance, DispatchError> {
+		Self::ensure_keep_alive(who, value, liveness)?;
+		let total_issuance = Self::total_issuance();
+		T::MultiCurrency::withdraw(GetCurrencyId::get(), who, value)?;
+		Ok(Self::defer_issuance_decrease(total_issuance))
+	}
+
+	/// If the change is rejected by the underlying currency, it's not applied, and a zero imbalance of the sign of the
+	/// change is returned.
+	fn make_free_balance_be(
+		who: &T::AccountId,
+		balance: Self::Balance,
+	) -> SignedImbalance<Self::Balance, Self::PositiveImbalance> {
+		let currency_id = GetCurrencyId::get();
+		let free_balance = Self::free_balance(who);
+		let total_issuance = Self::total_issuance();
+		if balance >= free_balance {
+			if T::MultiCurrency::deposit(currency_id, who, balance - free_balance).is_err() {
+				return SignedImbalance::Positive(PositiveImbalance::zero());
+			}
+			SignedImbalance::Positive(Self::defer_issuance_increase(total_issuance))
+		} else {
+			if T::MultiCurrency::withdraw(currency_id, who, free_balance - balance).is_err() {
+				return SignedImbalance::Negative(NegativeImbalance::zero());
+			}
+			SignedImbalance::Negative(Self::defer_issuance_decrease(total_issuance))
+		}
+	}
+}
+
+// Adapt `MultiReservableCurrency`
+impl<T, GetCurrencyId, GetExistentialDeposit> PalletReservableCurrency<T::AccountId>
+	for CurrencyAsFrameCurrency<T, GetCurrencyId, GetExistentialDeposit>
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>,
+	GetExistentialDeposit: Get<BalanceOf<T>>,
+{
+	fn can_reserve(who: &T::AccountId, value: Self::Balance) -> bool {
+		T::MultiCurrency::can_reserve(GetCurrencyId::get(), who, value)
+	}
+
+	fn slash_reserved(who: &T::AccountId, value: Self::Balance) -> (Self::NegativeImbalance, Self::Balance) {
+		let total_issuance = Self::total_issuance();
+		let gap = T::MultiCurrency::slash_reserved(GetCurrencyId::get(), who, value);
+		(Self::defer_issuance_decrease(total_issuance), gap)
+	}
+
+	fn reserved_balance(who: &T::AccountId) -> Self::Balance {
+		T::MultiCurrency::reserved_balance(GetCurrencyId::get(), who)
+	}
+
+	fn reserve(who: &T::AccountId, value: Self::Balance) -> DispatchResult {
+		T::MultiCurrency::reserve(GetCurrencyId::get(), who, value)
+	}
+
+	fn unreserve(who: &T::AccountId, value: Self::Balance) -> Self::Balance {
+		T::MultiCurrency::unreserve(GetCurrencyId::get(), who, value)
+	}
+
+	fn repatriate_reserved(
+		slashed: &T::AccountId,
+		beneficiary: &T::AccountId,
+		value: Self::Balance,
+		status: BalanceStatus,
+	) -> rstd::result::Result<Self::Balance, DispatchError> {
+		T::MultiCurrency::repatriate_reserved(GetCurrencyId::get(), slashed, beneficiary, value, status)
+	}
+}
+
+// Adapt `MultiLockableCurrency`
+impl<T, GetCurrencyId, GetExistentialDeposit> PalletLockableCurrency<T::AccountId>
+	for CurrencyAsFrameCurrency<T, GetCurrencyId, GetExistentialDeposit>
+where
+	T: Trait,
+	T::MultiCurrency: MultiCurrencyIssuance<T::AccountId>,
+	GetCurrencyId: Get<CurrencyIdOf<T>>,
+	GetExistentialDeposit: Get<BalanceOf<T>>,
+{
+	type Moment = <T::MultiCurrency as MultiLockableCurrency<T::AccountId>>::Moment;
+
+	fn set_lock(id: LockIdentifier, who: &T::AccountId, amount: Self::Balance, _reasons: WithdrawReasons) {
+		T::MultiCurrency::set_lock(id, GetCurrencyId::get(), who, amount)
+	}
+
+	fn extend_lock(id: LockIdentifier, who: &T::AccountId, amount: Self::Balance, _reasons: WithdrawReasons) {
+		T::MultiCurrency::extend_lock(id, GetCurrencyId::get(), who, amount)
+	}
+
+	fn remove_lock(id: LockIdentifier, who: &T::AccountId) {
+		T::MultiCurrency::remove_lock(id, GetCurrencyId::get(), who)
+	}
+}
@@ -2,10 +2,14 @@
 
 #![cfg(test)]
 
-use frame_support::{impl_outer_event, impl_outer_origin, parameter_types};
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, traits::Contains};
 use pallet_balances;
 use primitives::H256;
-use sp_runtime::{testing::Header, traits::IdentityLookup, Perbill};
+use sp_runtime::{
+	testing::{Header, TestSignature, UintAuthorityId},
+	traits::{Convert, IdentityLookup},
+	Perbill, Permill,
+};
 
 use tokens;
 
@@ -79,6 +83,89 @@ impl pallet_balances::Trait for Runtime {
 
 pub type PalletBalances = pallet_balances::Module<Runtime>;
 
+parameter_types! {
+	pub const TreasuryFeeShare: Permill = Permill::zero();
+	pub const TreasuryAccount: AccountId = 0;
+	pub const DustSplit: Permill = Permill::one();
+	pub const MaxReserves: u32 = 50;
+	pub const MaxNameLen: u32 = 32;
+	pub const MaxLabelLen: u32 = 32;
+	pub const MaxDecimals: u8 = 18;
+	pub const MaxLockRatio: Permill = Permill::one();
+	pub const MaxLockRemovals: u32 = 50;
+	pub const MaxInterestHolders: u32 = 50;
+	pub const MaxBatchTransfers: u32 = 50;
+	pub const SnapshotBlock: u64 = 10;
+	pub const AccountSnapshotPeriod: u64 = 0;
+	pub const MaxAccountSnapshots: u32 = 0;
+	pub const MaxBalanceHistory: u32 = 0;
+	pub const ReapGracePeriod: u64 = 10;
+	pub const MaxReapsPerBlock: u32 = 50;
+	pub const MigrationBatchSize: u32 = 50;
+	pub const TokensEventVerbosity: tokens::EventVerbosity = tokens::EventVerbosity::Full;
+	pub const TokensNativeCurrencyId: Option<CurrencyId> = None;
+	pub const TokensDepositBounce: tokens::DepositBounce<AccountId> = tokens::DepositBounce::Fail;
+}
+
+pub struct ProtectedAccounts;
+impl Contains<AccountId> for ProtectedAccounts {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![]
+	}
+}
+
+pub struct FeeExemptCurrencies;
+impl Contains<CurrencyId> for FeeExemptCurrencies {
+	fn sorted_members() -> Vec<CurrencyId> {
+		vec![]
+	}
+}
+
+pub struct TokensMaxBalancePerAccount;
+impl Convert<CurrencyId, Option<Balance>> for TokensMaxBalancePerAccount {
+	fn convert(_currency_id: CurrencyId) -> Option<Balance> {
+		None
+	}
+}
+
+/// The largest `pallet_balances` lock of an account.
+pub struct NativeFrozenBalance;
+impl Convert<AccountId, Balance> for NativeFrozenBalance {
+	fn convert(who: AccountId) -> Balance {
+		PalletBalances::locks(&who)
+			.iter()
+			.map(|lock| lock.amount)
+			.max()
+			.unwrap_or_default()
+	}
+}
+
+/// Route the wrapped native currency to the native currency too.
+pub struct CurrencyRouter;
+impl Convert<CurrencyId, CurrencyBackend> for CurrencyRouter {
+	fn convert(currency_id: CurrencyId) -> CurrencyBackend {
+		if currency_id == WRAPPED_NATIVE_ID {
+			CurrencyBackend::Native
+		} else {
+			NativeCurrencyIdRouter::<GetNativeCurrencyId>::convert(currency_id)
+		}
+	}
+}
+
+pub struct TokensGranularity;
+impl Convert<CurrencyId, Balance> for TokensGranularity {
+	fn convert(_currency_id: CurrencyId) -> Balance {
+		1
+	}
+}
+
+pub struct TokensDefaultMetadata;
+impl Convert<CurrencyId, Option<(Vec<u8>, Vec<u8>, u8)>> for TokensDefaultMetadata {
+	fn convert(_currency_id: CurrencyId) -> Option<(Vec<u8>, Vec<u8>, u8)> {
+		None
+	}
+}
+
 impl tokens::Trait for Runtime {
 	type Event = TestEvent;
 	type Balance = Balance;
@@ -86,14 +173,74 @@ impl tokens::Trait for Runtime {
 	type CurrencyId = CurrencyId;
 	type ExistentialDeposit = ExistentialDeposit;
 	type DustRemoval = ();
+	type DustSplit = DustSplit;
+	type DustReceiver = TreasuryAccount;
+	type ReapReservedDust = ();
+	type DustEventMode = ();
+	type OnIssuanceChange = ();
+	type OnTransferRecord = ();
+	type OnAccountRecreate = ();
+	type MultiLocation = u32;
+	type TeleportOrigin = frame_system::EnsureRoot<AccountId>;
+	type TargetBalanceOrigin = frame_system::EnsureRoot<AccountId>;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type TransferFee = ();
+	type FeeExemptAccounts = ProtectedAccounts;
+	type FeeExemptCurrencies = FeeExemptCurrencies;
+	type TreasuryFeeShare = TreasuryFeeShare;
+	type Treasury = TreasuryAccount;
+	type ServiceFee = ();
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = [u8; 8];
+	type LockPurpose = [u8; 8];
+	type MaxLabelLen = MaxLabelLen;
+	type MaxNameLen = MaxNameLen;
+	type MaxDecimals = MaxDecimals;
+	type MetadataOrigin = frame_system::EnsureRoot<AccountId>;
+	type DefaultMetadata = TokensDefaultMetadata;
+	type MaxLockRatio = MaxLockRatio;
+	type MaxLockRemovals = MaxLockRemovals;
+	type EventVerbosity = TokensEventVerbosity;
+	type EventModel = ();
+	type MaxInterestHolders = MaxInterestHolders;
+	type MaxBatchTransfers = MaxBatchTransfers;
+	type PendingWithdrawals = ();
+	type TransferAllPolicy = ();
+	type CurrencyFilter = ();
+	type BlockedRecipients = ProtectedAccounts;
+	type DepositBounce = TokensDepositBounce;
+	type MaxBalancePerAccount = TokensMaxBalancePerAccount;
+	type StaleLockPolicy = ();
+	type Granularity = TokensGranularity;
+	type GranularityPolicy = ();
+	type SlashAccountingMode = ();
+	type IntentSignature = TestSignature;
+	type IntentSigner = UintAuthorityId;
+	type ProtectedAccounts = ProtectedAccounts;
+	type ProtocolAccounts = ProtectedAccounts;
+	type SnapshotBlock = SnapshotBlock;
+	type AccountSnapshotPeriod = AccountSnapshotPeriod;
+	type MaxAccountSnapshots = MaxAccountSnapshots;
+	type MaxBalanceHistory = MaxBalanceHistory;
+	type SnapshotOrigin = frame_system::EnsureRoot<AccountId>;
+	type ReapGracePeriod = ReapGracePeriod;
+	type MaxReapsPerBlock = MaxReapsPerBlock;
+	type MigrationBatchSize = MigrationBatchSize;
+	type NativeCurrencyId = TokensNativeCurrencyId;
+	type NativeCurrency = AdaptedBasicCurrency;
 }
 pub type Tokens = tokens::Module<Runtime>;
 
 pub const NATIVE_CURRENCY_ID: CurrencyId = 1;
 pub const X_TOKEN_ID: CurrencyId = 2;
+pub const Y_TOKEN_ID: CurrencyId = 3;
+pub const WRAPPED_NATIVE_ID: CurrencyId = 4;
 
 parameter_types! {
 	pub const GetNativeCurrencyId: CurrencyId = NATIVE_CURRENCY_ID;
+	pub const GetXTokenId: CurrencyId = X_TOKEN_ID;
+	pub const MaxDeltas: u32 = 4;
+	pub const MaxBatchUpdates: u32 = 4;
 }
 
 impl Trait for Runtime {
@@ -101,10 +248,16 @@ impl Trait for Runtime {
 	type MultiCurrency = Tokens;
 	type NativeCurrency = AdaptedBasicCurrency;
 	type GetNativeCurrencyId = GetNativeCurrencyId;
+	type CurrencyRouter = CurrencyRouter;
+	type MaxDeltas = MaxDeltas;
+	type MaxBatchUpdates = MaxBatchUpdates;
+	type NativeFrozenBalance = NativeFrozenBalance;
 }
 pub type Currencies = Module<Runtime>;
 pub type NativeCurrency = NativeCurrencyOf<Runtime>;
 pub type AdaptedBasicCurrency = BasicCurrencyAdapter<Runtime, PalletBalances, Balance>;
+pub type EventfulNativeCurrency = EventfulBasicCurrency<Runtime, AdaptedBasicCurrency>;
+pub type XTokenCurrency = CurrencyAsFrameCurrency<Runtime, GetXTokenId, ExistentialDeposit>;
 
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
@@ -161,6 +314,7 @@ impl ExtBuilder {
 				.into_iter()
 				.filter(|(_, currency_id, _)| *currency_id != NATIVE_CURRENCY_ID)
 				.collect::<Vec<_>>(),
+			non_reservable_currencies: vec![],
 		}
 		.assimilate_storage(&mut t)
 		.unwrap();
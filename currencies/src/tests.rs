@@ -3,11 +3,13 @@
 #![cfg(test)]
 
 use super::*;
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_err, assert_noop, assert_ok};
 use mock::{
-	AccountId, AdaptedBasicCurrency, Currencies, ExtBuilder, NativeCurrency, Origin, PalletBalances, System, TestEvent,
-	Tokens, ALICE, BOB, EVA, ID_1, NATIVE_CURRENCY_ID, X_TOKEN_ID,
+	AccountId, AdaptedBasicCurrency, Currencies, EventfulNativeCurrency, ExtBuilder, GetNativeCurrencyId,
+	NativeCurrency, Origin, PalletBalances, Runtime, System, TestEvent, Tokens, XTokenCurrency, ALICE, BOB, EVA, ID_1,
+	NATIVE_CURRENCY_ID, WRAPPED_NATIVE_ID, X_TOKEN_ID, Y_TOKEN_ID,
 };
+use orml_traits::BalanceLock;
 use sp_runtime::traits::BadOrigin;
 
 #[test]
@@ -23,6 +25,18 @@ fn multi_lockable_currency_should_work() {
 		});
 }
 
+#[test]
+fn effective_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Currencies::set_lock(ID_1, X_TOKEN_ID, &ALICE, 30);
+			assert_eq!(Currencies::effective_balance(X_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Currencies::effective_balance(NATIVE_CURRENCY_ID, &ALICE), 100);
+		});
+}
+
 #[test]
 fn multi_reservable_currency_should_work() {
 	ExtBuilder::default()
@@ -101,6 +115,52 @@ fn multi_currency_should_work() {
 		});
 }
 
+#[test]
+fn transfer_exact_out_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Currencies::transfer_exact_out(X_TOKEN_ID, &ALICE, &BOB, 50));
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 150);
+
+			assert_ok!(Currencies::transfer_exact_out(NATIVE_CURRENCY_ID, &ALICE, &BOB, 50));
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &ALICE), 50);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &BOB), 150);
+		});
+}
+
+#[test]
+fn transfer_min_received_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Currencies::transfer_min_received(NATIVE_CURRENCY_ID, &ALICE, &BOB, 50, 51),
+				Error::<Runtime>::BelowMinimumReceived
+			);
+			assert_noop!(
+				Currencies::transfer_min_received(X_TOKEN_ID, &ALICE, &BOB, 50, 51),
+				tokens::Error::<Runtime>::BelowMinimumReceived
+			);
+
+			assert_ok!(Currencies::transfer_min_received(
+				NATIVE_CURRENCY_ID,
+				&ALICE,
+				&BOB,
+				50,
+				50
+			));
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &ALICE), 50);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &BOB), 150);
+			assert_ok!(Currencies::transfer_min_received(X_TOKEN_ID, &ALICE, &BOB, 50, 50));
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 150);
+		});
+}
+
 #[test]
 fn multi_currency_extended_should_work() {
 	ExtBuilder::default()
@@ -280,3 +340,766 @@ fn call_event_should_work() {
 			assert!(System::events().iter().any(|record| record.event == transferred_event));
 		});
 }
+
+#[test]
+fn free_reserved_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Currencies::reserve(NATIVE_CURRENCY_ID, &ALICE, 20));
+			assert_ok!(Currencies::reserve(X_TOKEN_ID, &ALICE, 30));
+
+			assert_eq!(Currencies::free_reserved(NATIVE_CURRENCY_ID, &ALICE), (80, 20));
+			assert_eq!(
+				Currencies::free_reserved(NATIVE_CURRENCY_ID, &ALICE),
+				(
+					PalletBalances::free_balance(&ALICE),
+					PalletBalances::reserved_balance(&ALICE)
+				)
+			);
+
+			let account = Tokens::accounts(X_TOKEN_ID, ALICE);
+			assert_eq!(Currencies::free_reserved(X_TOKEN_ID, &ALICE), (70, 30));
+			assert_eq!(
+				Currencies::free_reserved(X_TOKEN_ID, &ALICE),
+				(account.free, account.reserved)
+			);
+		});
+}
+
+#[test]
+fn currencies_of_account_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Currencies::reserve(X_TOKEN_ID, &ALICE, 30));
+			assert_eq!(
+				Currencies::currencies_of_account(&ALICE),
+				vec![
+					(
+						NATIVE_CURRENCY_ID,
+						AccountData {
+							free: 100,
+							reserved: 0,
+							frozen: 0,
+						}
+					),
+					(
+						X_TOKEN_ID,
+						AccountData {
+							free: 70,
+							reserved: 30,
+							frozen: 0,
+						}
+					),
+				]
+			);
+			assert_eq!(Currencies::currencies_of_account(&EVA), vec![]);
+		});
+}
+
+#[test]
+fn full_summary_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, NATIVE_CURRENCY_ID, 100),
+			(ALICE, X_TOKEN_ID, 100),
+			(ALICE, Y_TOKEN_ID, 50),
+		])
+		.build()
+		.execute_with(|| {
+			Currencies::set_lock(ID_1, NATIVE_CURRENCY_ID, &ALICE, 40);
+			assert_ok!(Currencies::reserve(NATIVE_CURRENCY_ID, &ALICE, 10));
+			Currencies::set_lock(ID_1, X_TOKEN_ID, &ALICE, 20);
+			assert_ok!(Currencies::reserve(Y_TOKEN_ID, &ALICE, 30));
+			let summary = Currencies::full_summary(&ALICE);
+			assert_eq!(summary.len(), 3);
+			assert_eq!(
+				summary[0],
+				(
+					NATIVE_CURRENCY_ID,
+					AccountData {
+						free: 90,
+						reserved: 10,
+						frozen: 40,
+					}
+				)
+			);
+			// tokens follow in storage order
+			assert!(summary.contains(&(
+				X_TOKEN_ID,
+				AccountData {
+					free: 100,
+					reserved: 0,
+					frozen: 20,
+				}
+			)));
+			assert!(summary.contains(&(
+				Y_TOKEN_ID,
+				AccountData {
+					free: 20,
+					reserved: 30,
+					frozen: 0,
+				}
+			)));
+
+			// the native currency is always included
+			assert_eq!(
+				Currencies::full_summary(&EVA),
+				vec![(NATIVE_CURRENCY_ID, Default::default())]
+			);
+		});
+}
+
+#[test]
+fn native_currency_id_should_work() {
+	assert_eq!(Currencies::native_currency_id(), NATIVE_CURRENCY_ID);
+}
+
+#[test]
+fn account_info_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Currencies::reserve(X_TOKEN_ID, &ALICE, 30));
+			Currencies::set_lock(ID_1, X_TOKEN_ID, &ALICE, 50);
+			assert_eq!(
+				Currencies::account_info(&ALICE, X_TOKEN_ID),
+				AccountInfo {
+					free: 70,
+					reserved: 30,
+					frozen: 50,
+					locks: vec![BalanceLock { id: ID_1, amount: 50 }],
+					total_balance: 100,
+				}
+			);
+			assert_ok!(Currencies::reserve(NATIVE_CURRENCY_ID, &ALICE, 10));
+			assert_eq!(
+				Currencies::account_info(&ALICE, NATIVE_CURRENCY_ID),
+				AccountInfo {
+					free: 90,
+					reserved: 10,
+					frozen: 0,
+					locks: vec![],
+					total_balance: 100,
+				}
+			);
+			assert_eq!(Currencies::account_info(&EVA, X_TOKEN_ID), AccountInfo::default());
+		});
+}
+
+#[test]
+fn withdraw_in_order_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Currencies::set_lock(ID_1, X_TOKEN_ID, &ALICE, 40);
+			assert_eq!(
+				Currencies::withdraw_in_order(&ALICE, &[(X_TOKEN_ID, 100), (NATIVE_CURRENCY_ID, 50)], 90),
+				Ok(vec![(X_TOKEN_ID, 60), (NATIVE_CURRENCY_ID, 30)])
+			);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 40);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &ALICE), 70);
+
+			assert_eq!(
+				Currencies::withdraw_in_order(&BOB, &[(NATIVE_CURRENCY_ID, 20), (X_TOKEN_ID, 100)], 50),
+				Ok(vec![(NATIVE_CURRENCY_ID, 20), (X_TOKEN_ID, 30)])
+			);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &BOB), 80);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 70);
+		});
+}
+
+#[test]
+fn withdraw_in_order_is_all_or_nothing() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Currencies::withdraw_in_order(&ALICE, &[(X_TOKEN_ID, 100), (NATIVE_CURRENCY_ID, 50)], 151),
+				Error::<Runtime>::BalanceTooLow
+			);
+			assert_noop!(
+				Currencies::withdraw_in_order(&ALICE, &[(X_TOKEN_ID, 80), (X_TOKEN_ID, 80)], 101),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn apply_deltas_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Currencies::apply_deltas(
+				&ALICE,
+				vec![(X_TOKEN_ID, 50), (NATIVE_CURRENCY_ID, -30), (X_TOKEN_ID, -20)]
+			));
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 130);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &ALICE), 70);
+
+			for (currency_id, amount) in vec![(X_TOKEN_ID, 50), (NATIVE_CURRENCY_ID, -30), (X_TOKEN_ID, -20)] {
+				let update_balance_event = TestEvent::currencies(RawEvent::BalanceUpdated(currency_id, ALICE, amount));
+				assert!(System::events()
+					.iter()
+					.any(|record| record.event == update_balance_event));
+			}
+		});
+}
+
+#[test]
+fn apply_deltas_should_be_atomic() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Currencies::apply_deltas(&ALICE, vec![(X_TOKEN_ID, 50), (NATIVE_CURRENCY_ID, -200)]),
+				pallet_balances::Error::<Runtime, pallet_balances::DefaultInstance>::InsufficientBalance,
+			);
+			assert_noop!(
+				Currencies::apply_deltas(
+					&ALICE,
+					vec![(NATIVE_CURRENCY_ID, 50), (X_TOKEN_ID, -80), (X_TOKEN_ID, -30)]
+				),
+				tokens::Error::<Runtime>::BalanceTooLow,
+			);
+			assert_noop!(
+				Currencies::apply_deltas(&ALICE, vec![(X_TOKEN_ID, 1); 5]),
+				Error::<Runtime>::TooManyDeltas,
+			);
+		});
+}
+
+#[test]
+fn multi_update_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Currencies::multi_update_balance(
+					Some(ALICE).into(),
+					vec![(ALICE, X_TOKEN_ID, 10)],
+					BatchFailurePolicy::AbortAll
+				),
+				BadOrigin
+			);
+			assert_noop!(
+				Currencies::multi_update_balance(
+					Origin::ROOT,
+					vec![(ALICE, X_TOKEN_ID, 10); 5],
+					BatchFailurePolicy::AbortAll
+				),
+				Error::<Runtime>::TooManyUpdates
+			);
+
+			let updates = vec![
+				(ALICE, X_TOKEN_ID, 50),
+				(BOB, NATIVE_CURRENCY_ID, -30),
+				(ALICE, X_TOKEN_ID, -120),
+			];
+			assert_ok!(Currencies::multi_update_balance(
+				Origin::ROOT,
+				updates.clone(),
+				BatchFailurePolicy::AbortAll
+			));
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &BOB), 70);
+
+			for (who, currency_id, amount) in updates {
+				let update_balance_event = TestEvent::currencies(RawEvent::BalanceUpdated(currency_id, who, amount));
+				assert!(System::events()
+					.iter()
+					.any(|record| record.event == update_balance_event));
+			}
+		});
+}
+
+#[test]
+fn multi_update_balance_should_be_all_or_nothing() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// the withdrawal precedes the deposit covering it
+			assert_err!(
+				Currencies::multi_update_balance(
+					Origin::ROOT,
+					vec![
+						(BOB, X_TOKEN_ID, 10),
+						(ALICE, X_TOKEN_ID, -150),
+						(ALICE, X_TOKEN_ID, 100),
+					],
+					BatchFailurePolicy::AbortAll
+				),
+				tokens::Error::<Runtime>::BalanceTooLow
+			);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 100);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Currencies::total_issuance(X_TOKEN_ID), 200);
+			let applied_event = TestEvent::currencies(RawEvent::BalanceUpdated(X_TOKEN_ID, BOB, 10));
+			assert!(!System::events().iter().any(|record| record.event == applied_event));
+
+			assert!(Currencies::multi_update_balance(
+				Origin::ROOT,
+				vec![(BOB, X_TOKEN_ID, 10), (ALICE, X_TOKEN_ID, -150)],
+				BatchFailurePolicy::AbortAll
+			)
+			.is_err());
+			let failed_event = TestEvent::currencies(RawEvent::BatchUpdateFailed(
+				1,
+				tokens::Error::<Runtime>::BalanceTooLow.into(),
+			));
+			assert!(System::events().iter().any(|record| record.event == failed_event));
+		});
+}
+
+#[test]
+fn multi_update_balance_should_skip_invalid_updates() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			let updates = vec![
+				(BOB, X_TOKEN_ID, 10),
+				(ALICE, X_TOKEN_ID, -150),
+				(ALICE, NATIVE_CURRENCY_ID, -30),
+			];
+			assert_err!(
+				Currencies::multi_update_balance(Origin::ROOT, updates.clone(), BatchFailurePolicy::AbortAll),
+				tokens::Error::<Runtime>::BalanceTooLow
+			);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 100);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &ALICE), 100);
+
+			assert_ok!(Currencies::multi_update_balance(
+				Origin::ROOT,
+				updates,
+				BatchFailurePolicy::SkipInvalid
+			));
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 110);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Currencies::free_balance(NATIVE_CURRENCY_ID, &ALICE), 70);
+
+			let partial_event = TestEvent::currencies(RawEvent::BatchPartial(2, vec![1]));
+			assert!(System::events().iter().any(|record| record.event == partial_event));
+		});
+}
+
+#[test]
+fn transferable_keep_alive_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Currencies::transferable_keep_alive(NATIVE_CURRENCY_ID, &ALICE), 99);
+			assert_eq!(Currencies::transferable_keep_alive(X_TOKEN_ID, &ALICE), 99);
+			assert_eq!(NativeCurrency::transferable_keep_alive(&ALICE), 99);
+
+			// at the existential deposit boundary
+			assert_ok!(Currencies::transfer(Some(ALICE).into(), BOB, NATIVE_CURRENCY_ID, 99));
+			assert_ok!(Currencies::transfer(Some(ALICE).into(), BOB, X_TOKEN_ID, 99));
+			assert_eq!(Currencies::transferable_keep_alive(NATIVE_CURRENCY_ID, &ALICE), 0);
+			assert_eq!(Currencies::transferable_keep_alive(X_TOKEN_ID, &ALICE), 0);
+
+			Currencies::set_lock(ID_1, X_TOKEN_ID, &BOB, 50);
+			assert_eq!(Currencies::transferable_keep_alive(X_TOKEN_ID, &BOB), 148);
+			Currencies::set_lock(ID_1, X_TOKEN_ID, &BOB, 150);
+			assert_eq!(Currencies::transferable_keep_alive(X_TOKEN_ID, &BOB), 48);
+		});
+}
+
+#[test]
+fn basic_currency_adapter_transfer_with_reason_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// locks of the adapter restrict transfers and reserves only
+			AdaptedBasicCurrency::set_lock(ID_1, &ALICE, 80);
+			assert!(AdaptedBasicCurrency::transfer_with_reason(
+				&ALICE,
+				&BOB,
+				50,
+				WithdrawReason::Transfer.into(),
+				ExistenceRequirement::AllowDeath
+			)
+			.is_err());
+			assert_ok!(AdaptedBasicCurrency::transfer_with_reason(
+				&ALICE,
+				&BOB,
+				50,
+				WithdrawReason::Fee.into(),
+				ExistenceRequirement::AllowDeath
+			));
+			assert_eq!(PalletBalances::free_balance(&ALICE), 50);
+			assert_eq!(PalletBalances::free_balance(&BOB), 150);
+			assert_eq!(PalletBalances::total_issuance(), 200);
+
+			assert!(AdaptedBasicCurrency::transfer_with_reason(
+				&BOB,
+				&EVA,
+				150,
+				WithdrawReason::Fee.into(),
+				ExistenceRequirement::KeepAlive
+			)
+			.is_err());
+			assert_ok!(AdaptedBasicCurrency::transfer_with_reason(
+				&BOB,
+				&EVA,
+				150,
+				WithdrawReason::Fee.into(),
+				ExistenceRequirement::AllowDeath
+			));
+			assert_eq!(PalletBalances::free_balance(&BOB), 0);
+			assert_eq!(PalletBalances::free_balance(&EVA), 150);
+		});
+}
+
+#[test]
+fn stored_map_should_work_for_native_currency() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(NativeCurrency::reserve(&ALICE, 30));
+			assert_eq!(
+				<Currencies as StoredMap<_, _>>::get(&ALICE),
+				AccountData {
+					free: 70,
+					reserved: 30,
+					frozen: 0,
+				}
+			);
+			assert!(<Currencies as StoredMap<_, _>>::is_explicit(&ALICE));
+			assert!(!<Currencies as StoredMap<_, _>>::is_explicit(&EVA));
+
+			<Currencies as StoredMap<_, _>>::insert(
+				&ALICE,
+				AccountData {
+					free: 50,
+					reserved: 60,
+					frozen: 0,
+				},
+			);
+			assert_eq!(NativeCurrency::free_balance(&ALICE), 50);
+			assert_eq!(NativeCurrency::reserved_balance(&ALICE), 60);
+			assert_eq!(NativeCurrency::total_issuance(), 210);
+
+			<Currencies as StoredMap<_, _>>::mutate(&ALICE, |account| account.reserved = 10);
+			assert_eq!(NativeCurrency::free_balance(&ALICE), 50);
+			assert_eq!(NativeCurrency::reserved_balance(&ALICE), 10);
+
+			<Currencies as StoredMap<_, _>>::remove(&ALICE);
+			assert_eq!(NativeCurrency::total_balance(&ALICE), 0);
+			assert!(!<Currencies as StoredMap<_, _>>::is_explicit(&ALICE));
+			assert_eq!(NativeCurrency::total_issuance(), 100);
+		});
+}
+
+#[test]
+fn stored_map_should_not_partially_apply_rejected_change() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(NativeCurrency::reserve(&ALICE, 30));
+			<PalletBalances as PalletLockableCurrency<_>>::set_lock(ID_1, &ALICE, 80, WithdrawReasons::all());
+
+			// unreserving succeeds, but withdrawing below the lock fails, so neither is applied
+			<Currencies as StoredMap<_, _>>::mutate(&ALICE, |account| {
+				account.free = 70;
+				account.reserved = 0;
+			});
+			assert_eq!(NativeCurrency::free_balance(&ALICE), 70);
+			assert_eq!(NativeCurrency::reserved_balance(&ALICE), 30);
+			assert_eq!(NativeCurrency::total_issuance(), 200);
+		});
+}
+
+#[test]
+fn asset_ids_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Currencies::asset_ids(), vec![NATIVE_CURRENCY_ID, X_TOKEN_ID]);
+
+			assert_ok!(Currencies::deposit(3, &ALICE, 50));
+			let asset_ids = Currencies::asset_ids();
+			assert_eq!(asset_ids[0], NATIVE_CURRENCY_ID);
+			let mut token_ids = asset_ids[1..].to_vec();
+			token_ids.sort();
+			assert_eq!(token_ids, vec![X_TOKEN_ID, 3]);
+
+			// token types routed to the native currency are not listed again
+			assert_ok!(<Tokens as MultiCurrency<_>>::deposit(WRAPPED_NATIVE_ID, &ALICE, 50));
+			assert_eq!(Currencies::asset_ids().len(), 3);
+			assert!(!Currencies::asset_ids().contains(&WRAPPED_NATIVE_ID));
+		});
+}
+
+#[test]
+fn proxy_transfer_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Currencies::proxy_transfer(Some(EVA).into(), ALICE, BOB, X_TOKEN_ID, 10),
+				Error::<Runtime>::NotProxy
+			);
+
+			assert_ok!(Currencies::set_proxy(Some(ALICE).into(), X_TOKEN_ID, Some(EVA)));
+			assert_eq!(Currencies::proxy_account((X_TOKEN_ID, ALICE)), Some(EVA));
+			let proxy_set_event = TestEvent::currencies(RawEvent::ProxySet(X_TOKEN_ID, ALICE, Some(EVA)));
+			assert!(System::events().iter().any(|record| record.event == proxy_set_event));
+
+			assert_ok!(Currencies::proxy_transfer(Some(EVA).into(), ALICE, BOB, X_TOKEN_ID, 10));
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 90);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &BOB), 110);
+			let proxy_transferred_event =
+				TestEvent::currencies(RawEvent::ProxyTransferred(X_TOKEN_ID, EVA, ALICE, BOB, 10));
+			assert!(System::events()
+				.iter()
+				.any(|record| record.event == proxy_transferred_event));
+
+			// the proxy is per currency
+			assert_noop!(
+				Currencies::proxy_transfer(Some(EVA).into(), ALICE, BOB, NATIVE_CURRENCY_ID, 10),
+				Error::<Runtime>::NotProxy
+			);
+
+			assert_ok!(Currencies::set_proxy(Some(ALICE).into(), X_TOKEN_ID, None));
+			assert_eq!(Currencies::proxy_account((X_TOKEN_ID, ALICE)), None);
+			assert_noop!(
+				Currencies::proxy_transfer(Some(EVA).into(), ALICE, BOB, X_TOKEN_ID, 10),
+				Error::<Runtime>::NotProxy
+			);
+		});
+}
+
+#[test]
+fn currency_as_frame_currency_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(XTokenCurrency::free_balance(&ALICE), 100);
+			assert_eq!(XTokenCurrency::total_issuance(), 200);
+			assert_eq!(XTokenCurrency::minimum_balance(), 1);
+
+			assert_ok!(XTokenCurrency::transfer(
+				&ALICE,
+				&BOB,
+				10,
+				ExistenceRequirement::AllowDeath
+			));
+			assert_eq!(Tokens::free_balance(X_TOKEN_ID, &ALICE), 90);
+			assert_eq!(Tokens::free_balance(X_TOKEN_ID, &BOB), 110);
+			assert_noop!(
+				XTokenCurrency::transfer(&ALICE, &BOB, 90, ExistenceRequirement::KeepAlive),
+				Error::<Runtime>::ExistentialDeposit
+			);
+
+			// balance changes and their imbalances, the total issuance changing when they are dropped
+			assert_eq!(XTokenCurrency::deposit_creating(&EVA, 20).peek(), 20);
+			assert_eq!(XTokenCurrency::total_issuance(), 220);
+			assert_eq!(XTokenCurrency::deposit_into_existing(&EVA, 5).map(|i| i.peek()), Ok(5));
+			assert_noop!(
+				XTokenCurrency::deposit_into_existing(&3, 5).map(|i| i.peek()),
+				Error::<Runtime>::DeadAccount
+			);
+			assert_eq!(
+				XTokenCurrency::withdraw(&EVA, 15, WithdrawReasons::all(), ExistenceRequirement::AllowDeath)
+					.map(|i| i.peek()),
+				Ok(15)
+			);
+			let (imbalance, gap) = XTokenCurrency::slash(&EVA, 15);
+			assert_eq!((imbalance.peek(), gap), (10, 5));
+			assert_eq!(XTokenCurrency::total_issuance(), 210);
+			drop(imbalance);
+			assert_eq!(XTokenCurrency::total_issuance(), 200);
+
+			match XTokenCurrency::make_free_balance_be(&ALICE, 100) {
+				SignedImbalance::Positive(imbalance) => assert_eq!(imbalance.peek(), 10),
+				SignedImbalance::Negative(_) => panic!("expected a positive imbalance"),
+			}
+			match XTokenCurrency::make_free_balance_be(&ALICE, 70) {
+				SignedImbalance::Negative(imbalance) => assert_eq!(imbalance.peek(), 30),
+				SignedImbalance::Positive(_) => panic!("expected a negative imbalance"),
+			}
+			assert_eq!(Tokens::free_balance(X_TOKEN_ID, &ALICE), 70);
+
+			// a rejected change gives a zero imbalance of its sign
+			match XTokenCurrency::make_free_balance_be(&ALICE, u64::max_value()) {
+				SignedImbalance::Positive(imbalance) => assert_eq!(imbalance.peek(), 0),
+				SignedImbalance::Negative(_) => panic!("expected a positive imbalance"),
+			}
+			XTokenCurrency::set_lock(ID_1, &ALICE, 70, WithdrawReasons::all());
+			match XTokenCurrency::make_free_balance_be(&ALICE, 50) {
+				SignedImbalance::Negative(imbalance) => assert_eq!(imbalance.peek(), 0),
+				SignedImbalance::Positive(_) => panic!("expected a negative imbalance"),
+			}
+			assert_eq!(Tokens::free_balance(X_TOKEN_ID, &ALICE), 70);
+			assert_eq!(XTokenCurrency::total_issuance(), 180);
+
+			// burning and issuing change the total issuance until their imbalances are dropped
+			let burned = XTokenCurrency::burn(10);
+			assert_eq!(XTokenCurrency::total_issuance(), 170);
+			drop(burned);
+			assert_eq!(XTokenCurrency::total_issuance(), 180);
+			assert_eq!(XTokenCurrency::burn(1000).peek(), 180);
+
+			// imbalances offset each other
+			let offset = XTokenCurrency::burn(10).offset(XTokenCurrency::issue(4));
+			assert_eq!(offset.map(|i| i.peek()), Ok(6));
+			assert_eq!(XTokenCurrency::total_issuance(), 180);
+		});
+}
+
+#[test]
+fn currency_as_frame_currency_reservable_and_lockable_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert!(XTokenCurrency::can_reserve(&ALICE, 100));
+			assert_ok!(XTokenCurrency::reserve(&ALICE, 30));
+			assert_eq!(XTokenCurrency::reserved_balance(&ALICE), 30);
+			assert_eq!(Tokens::reserved_balance(X_TOKEN_ID, &ALICE), 30);
+			assert_eq!(XTokenCurrency::unreserve(&ALICE, 10), 0);
+			assert_eq!(
+				XTokenCurrency::repatriate_reserved(&ALICE, &BOB, 10, BalanceStatus::Free),
+				Ok(0)
+			);
+			assert_eq!(Tokens::free_balance(X_TOKEN_ID, &BOB), 110);
+			let (imbalance, gap) = XTokenCurrency::slash_reserved(&ALICE, 15);
+			assert_eq!((imbalance.peek(), gap), (10, 5));
+
+			XTokenCurrency::set_lock(ID_1, &BOB, 100, WithdrawReasons::all());
+			assert_eq!(Tokens::locks(X_TOKEN_ID, BOB).len(), 1);
+			assert!(XTokenCurrency::ensure_can_withdraw(&BOB, 20, WithdrawReasons::all(), 90).is_err());
+			XTokenCurrency::remove_lock(ID_1, &BOB);
+			assert_ok!(XTokenCurrency::ensure_can_withdraw(
+				&BOB,
+				20,
+				WithdrawReasons::all(),
+				90
+			));
+		});
+}
+
+#[test]
+fn transfer_native_currency_keep_alive_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Currencies::transfer_native_currency_keep_alive(Some(ALICE).into(), BOB, 100),
+				pallet_balances::Error::<Runtime, pallet_balances::DefaultInstance>::KeepAlive
+			);
+
+			assert_ok!(Currencies::transfer_native_currency_keep_alive(
+				Some(ALICE).into(),
+				BOB,
+				99
+			));
+			assert_eq!(NativeCurrency::free_balance(&ALICE), 1);
+			assert_eq!(NativeCurrency::free_balance(&BOB), 199);
+
+			let transferred_event = TestEvent::currencies(RawEvent::Transferred(NATIVE_CURRENCY_ID, ALICE, BOB, 99));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+		});
+}
+
+#[test]
+fn eventful_basic_currency_should_emit_events() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(EventfulNativeCurrency::transfer(&ALICE, &BOB, 10));
+			assert_eq!(PalletBalances::free_balance(&BOB), 110);
+			let transferred_event = TestEvent::currencies(RawEvent::Transferred(NATIVE_CURRENCY_ID, ALICE, BOB, 10));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+
+			assert_ok!(EventfulNativeCurrency::deposit(&ALICE, 20));
+			let deposited_event = TestEvent::currencies(RawEvent::Deposited(NATIVE_CURRENCY_ID, ALICE, 20));
+			assert!(System::events().iter().any(|record| record.event == deposited_event));
+
+			assert_ok!(EventfulNativeCurrency::withdraw(&ALICE, 30));
+			assert_eq!(PalletBalances::free_balance(&ALICE), 80);
+			let withdrawn_event = TestEvent::currencies(RawEvent::Withdrawn(NATIVE_CURRENCY_ID, ALICE, 30));
+			assert!(System::events().iter().any(|record| record.event == withdrawn_event));
+
+			assert_ok!(EventfulNativeCurrency::update_balance(&BOB, -10));
+			assert_eq!(PalletBalances::free_balance(&BOB), 100);
+			let updated_event = TestEvent::currencies(RawEvent::BalanceUpdated(NATIVE_CURRENCY_ID, BOB, -10));
+			assert!(System::events().iter().any(|record| record.event == updated_event));
+		});
+}
+
+#[test]
+fn eventful_basic_currency_should_not_emit_events_on_failure() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert!(EventfulNativeCurrency::withdraw(&ALICE, 200).is_err());
+			let withdrawn_event = TestEvent::currencies(RawEvent::Withdrawn(NATIVE_CURRENCY_ID, ALICE, 200));
+			assert!(!System::events().iter().any(|record| record.event == withdrawn_event));
+		});
+}
+
+#[test]
+fn native_currency_id_router_should_work() {
+	assert_eq!(
+		NativeCurrencyIdRouter::<GetNativeCurrencyId>::convert(NATIVE_CURRENCY_ID),
+		CurrencyBackend::Native
+	);
+	assert_eq!(
+		NativeCurrencyIdRouter::<GetNativeCurrencyId>::convert(X_TOKEN_ID),
+		CurrencyBackend::Multi
+	);
+}
+
+#[test]
+fn currency_router_should_route_currencies_to_native_currency() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert!(Currencies::is_native(NATIVE_CURRENCY_ID));
+			assert!(Currencies::is_native(WRAPPED_NATIVE_ID));
+			assert!(!Currencies::is_native(X_TOKEN_ID));
+
+			assert_ok!(Currencies::transfer(Some(ALICE).into(), BOB, WRAPPED_NATIVE_ID, 10));
+			assert_ok!(Currencies::transfer(Some(ALICE).into(), BOB, NATIVE_CURRENCY_ID, 10));
+			assert_eq!(PalletBalances::free_balance(&ALICE), 80);
+			assert_eq!(PalletBalances::free_balance(&BOB), 120);
+
+			assert_ok!(Currencies::deposit(WRAPPED_NATIVE_ID, &ALICE, 20));
+			assert_ok!(Currencies::reserve(WRAPPED_NATIVE_ID, &ALICE, 30));
+			assert_ok!(Currencies::reserve(NATIVE_CURRENCY_ID, &ALICE, 10));
+			assert_eq!(PalletBalances::free_balance(&ALICE), 60);
+			assert_eq!(PalletBalances::reserved_balance(&ALICE), 40);
+			assert_eq!(Currencies::free_balance(WRAPPED_NATIVE_ID, &ALICE), 60);
+			assert_eq!(Currencies::total_issuance(WRAPPED_NATIVE_ID), 220);
+			assert_eq!(Currencies::total_issuance(NATIVE_CURRENCY_ID), 220);
+
+			// nothing goes through the multi-currency
+			assert_eq!(Tokens::total_balance(WRAPPED_NATIVE_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_balance(WRAPPED_NATIVE_ID, &BOB), 0);
+			assert_eq!(Tokens::total_issuance(WRAPPED_NATIVE_ID), 0);
+			assert_eq!(Currencies::free_balance(X_TOKEN_ID, &ALICE), 100);
+		});
+}
@@ -0,0 +1,17 @@
+//! Runtime API definition for schedule-update module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use orml_schedule_update::ScheduledDispatchInfo;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait ScheduleUpdateApi<BlockNumber, AccountId, Hash> where
+		BlockNumber: Codec,
+		AccountId: Codec,
+		Hash: Codec,
+	{
+		fn list_at(block: BlockNumber) -> Vec<ScheduledDispatchInfo<AccountId, Hash>>;
+	}
+}
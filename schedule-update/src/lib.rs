@@ -5,39 +5,158 @@ use frame_support::{
 	decl_error, decl_event, decl_module, decl_storage,
 	dispatch::Weight,
 	ensure,
+	storage::{
+		migration::{put_storage_value, StorageIterator},
+		IterableStorageMap,
+	},
 	traits::Get,
-	weights::{DispatchClass, GetDispatchInfo},
+	weights::{DispatchClass, FunctionOf, GetDispatchInfo},
 	Parameter,
 };
 use frame_system::{self as system, ensure_root, ensure_signed};
+use orml_traits::PendingWithdrawals;
 use sp_runtime::{
-	traits::{CheckedAdd, Dispatchable, One},
-	DispatchError, RuntimeDebug,
+	traits::{AtLeast32Bit, CheckedAdd, CheckedMul, CheckedSub, Dispatchable, Hash, One, Saturating, Zero},
+	DispatchError, DispatchResult, RuntimeDebug,
 };
 use sp_std::{prelude::*, result};
 
 mod mock;
 mod tests;
 
+/// A pending delayed dispatch.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ScheduledDispatchInfo<AccountId, Hash> {
+	/// The dispatch ID.
+	pub id: u32,
+	/// The account that scheduled the dispatch, `None` for root.
+	pub origin: Option<AccountId>,
+	/// The hash of the call.
+	pub call_hash: Hash,
+	/// The weight of the call.
+	pub weight: u32,
+}
+
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
 pub enum DelayedDispatchTime<BlockNumber> {
 	At(BlockNumber),
 	After(BlockNumber),
+	/// Dispatch at `start`, then every `period` blocks (start, period)
+	Recurring(BlockNumber, BlockNumber),
 }
 
 type DispatchId = u32;
+/// The priority of a delayed dispatch, dispatches of lower values are dispatched first within a block.
+pub type DispatchPriority = u8;
+/// The priority of delayed dispatches scheduled without one.
+pub const LOWEST_PRIORITY: DispatchPriority = 255;
 type CallOf<T> = <T as Trait>::Call;
+/// A delayed dispatch (who, call, id, recurring), where `recurring` is the original `(start, period)` of a recurring
+/// dispatch.
+type DelayedDispatchOf<T> = (
+	Option<<T as frame_system::Trait>::AccountId>,
+	CallOf<T>,
+	DispatchId,
+	Option<(
+		<T as frame_system::Trait>::BlockNumber,
+		<T as frame_system::Trait>::BlockNumber,
+	)>,
+);
+
+/// Storage releases of the schedule-update module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+	/// The original storage layout.
+	V1_0_0,
+	/// Delayed dispatches store the `(start, period)` of recurring dispatches.
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
+	}
+}
+
+/// Inspect the balance changes a call would make, for projections of pending dispatches.
+pub trait InspectBalanceChanges<AccountId, Call> {
+	/// The currency identifier.
+	type CurrencyId: Copy;
+	/// The balance of an account.
+	type Balance: AtLeast32Bit + Copy;
+
+	/// The free balance of `who` under `currency_id`.
+	fn free_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance;
+
+	/// The `(increase, decrease)` of the free balance of `who` under `currency_id`, if `call` is dispatched by `origin`,
+	/// `None` for root.
+	fn balance_change(
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		origin: &Option<AccountId>,
+		call: &Call,
+	) -> (Self::Balance, Self::Balance);
+
+	/// The total issuance of `currency_id`.
+	fn total_issuance(currency_id: Self::CurrencyId) -> Self::Balance;
+
+	/// The `(minted, burned)` amount of `currency_id`, if `call` is dispatched by `origin`, `None` for root.
+	fn issuance_change(
+		currency_id: Self::CurrencyId,
+		origin: &Option<AccountId>,
+		call: &Call,
+	) -> (Self::Balance, Self::Balance);
+}
+
+/// No balance changes are inspected, for runtimes which don't use `projected_balance` or `projected_issuance`.
+impl<AccountId, Call> InspectBalanceChanges<AccountId, Call> for () {
+	type CurrencyId = ();
+	type Balance = u128;
+
+	fn free_balance(_: (), _: &AccountId) -> u128 {
+		Zero::zero()
+	}
+
+	fn balance_change(_: (), _: &AccountId, _: &Option<AccountId>, _: &Call) -> (u128, u128) {
+		(Zero::zero(), Zero::zero())
+	}
+
+	fn total_issuance(_: ()) -> u128 {
+		Zero::zero()
+	}
+
+	fn issuance_change(_: (), _: &Option<AccountId>, _: &Call) -> (u128, u128) {
+		(Zero::zero(), Zero::zero())
+	}
+}
+
+type InspectorOf<T> = <T as Trait>::BalanceInspector;
+type InspectedCurrencyIdOf<T> =
+	<InspectorOf<T> as InspectBalanceChanges<<T as frame_system::Trait>::AccountId, CallOf<T>>>::CurrencyId;
+type InspectedBalanceOf<T> =
+	<InspectorOf<T> as InspectBalanceChanges<<T as frame_system::Trait>::AccountId, CallOf<T>>>::Balance;
 
 pub trait Trait: frame_system::Trait {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 	type Call: Parameter + Dispatchable<Origin = <Self as frame_system::Trait>::Origin> + GetDispatchInfo;
 	type MaxScheduleDispatchWeight: Get<Weight>;
+	/// The maximum number of dispatches a signed origin could schedule at the same block.
+	type MaxScheduledPerOrigin: Get<u32>;
+	/// The number of blocks the dispatch results of a block are kept for.
+	type ResultRetentionPeriod: Get<Self::BlockNumber>;
+	/// The maximum number of blocks ahead a dispatch could be scheduled `At` or `After`.
+	type MaxScheduleDelay: Get<Self::BlockNumber>;
+	/// The maximum number of dispatches that could be cancelled at once by `cancel_batch`.
+	type MaxCancelBatch: Get<u32>;
+	/// Inspect balance changes of scheduled calls for `projected_balance` and `projected_issuance`, `()` if unused.
+	type BalanceInspector: InspectBalanceChanges<Self::AccountId, CallOf<Self>>;
 }
 
 decl_event!(
 	/// Event for schedule-update module.
 	pub enum Event<T> where
 		<T as frame_system::Trait>::BlockNumber,
+		<T as frame_system::Trait>::Hash,
 	{
 		/// Add schedule dispatch success (BlockNumber, DispatchId)
 		ScheduleDispatch(BlockNumber, DispatchId),
@@ -47,6 +166,16 @@ decl_event!(
 		ScheduleDispatchSuccess(BlockNumber, DispatchId),
 		/// Schedule dispatch failed (DispatchId, DispatchError)
 		ScheduleDispatchFail(DispatchId, DispatchError),
+		/// Schedule dispatch skipped as its weight exceeds its max weight (BlockNumber, DispatchId)
+		ScheduleDispatchSkipped(BlockNumber, DispatchId),
+		/// Dispatch results before a block pruned (BlockNumber, count)
+		DispatchHistoryPruned(BlockNumber, u32),
+		/// Delayed dispatch call replaced (BlockNumber, DispatchId, old_call_hash, new_call_hash)
+		DispatchModified(BlockNumber, DispatchId, Hash, Hash),
+		/// Schedule dispatch deferred as the weight budget of its block was used up (DispatchId, from_block, to_block)
+		ScheduleDispatchDeferred(DispatchId, BlockNumber, BlockNumber),
+		/// Batch of delayed dispatches cancelled (cancelled, skipped)
+		BatchCancelled(u32, u32),
 	}
 );
 
@@ -60,6 +189,10 @@ decl_error! {
 		DispatchNotExisted,
 		BlockNumberOverflow,
 		ExceedMaxScheduleDispatchWeight,
+		OriginScheduleLimitExceeded,
+		DispatchClassMismatch,
+		DelayTooLong,
+		TooManyCancellations,
 	}
 }
 
@@ -67,9 +200,26 @@ decl_storage! {
 	trait Store for Module<T: Trait> as ScheduleUpdate {
 		pub NextId get(fn next_id): DispatchId;
 		pub DelayedNormalDispatches get(fn delayed_normal_dispatches):
-			double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) DispatchId => Option<(Option<T::AccountId>, CallOf<T>, DispatchId)>;
+			double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) DispatchId => Option<DelayedDispatchOf<T>>;
 		pub DelayedOperationalDispatches get(fn delayed_operational_dispatches):
-			double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) DispatchId => Option<(Option<T::AccountId>, CallOf<T>, DispatchId)>;
+			double_map hasher(twox_64_concat) T::BlockNumber, hasher(twox_64_concat) DispatchId => Option<DelayedDispatchOf<T>>;
+		/// The number of pending dispatches scheduled by a signed origin at a block.
+		pub ScheduledCountByOrigin get(fn scheduled_count_by_origin):
+			map hasher(blake2_128_concat) (T::BlockNumber, T::AccountId) => u32;
+		/// The number of pending dispatches at a block, indexing the blocks with any.
+		pub ScheduledCountByBlock get(fn scheduled_count_by_block):
+			map hasher(twox_64_concat) T::BlockNumber => u32;
+		/// The block number at which a recurring dispatch is next scheduled.
+		pub RecurringDispatches get(fn recurring_dispatches): map hasher(twox_64_concat) DispatchId => Option<T::BlockNumber>;
+		/// The maximum weight of a dispatch, above which it is skipped at execution.
+		pub DispatchMaxWeights get(fn dispatch_max_weights): map hasher(twox_64_concat) DispatchId => Option<Weight>;
+		/// The priority of a dispatch, if scheduled with one.
+		pub DispatchPriorities get(fn dispatch_priorities): map hasher(twox_64_concat) DispatchId => Option<DispatchPriority>;
+		/// The results of dispatches executed at a block, kept for `ResultRetentionPeriod` blocks.
+		pub BlockDispatchResults get(fn block_dispatch_results):
+			map hasher(twox_64_concat) T::BlockNumber => Vec<(DispatchId, DispatchResult)>;
+		/// The storage release of this module, by which migrations are run on runtime upgrade.
+		StorageVersion: Releases;
 	}
 }
 
@@ -80,9 +230,22 @@ decl_module! {
 		fn deposit_event() = default;
 
 		const MaxScheduleDispatchWeight: Weight = T::MaxScheduleDispatchWeight::get();
+		const MaxScheduledPerOrigin: u32 = T::MaxScheduledPerOrigin::get();
+		const ResultRetentionPeriod: T::BlockNumber = T::ResultRetentionPeriod::get();
+		const MaxScheduleDelay: T::BlockNumber = T::MaxScheduleDelay::get();
+		const MaxCancelBatch: u32 = T::MaxCancelBatch::get();
 
-		/// Add schedule_update at block_number
-		pub fn schedule_dispatch(origin, call: CallOf<T>, when: DelayedDispatchTime<T::BlockNumber>) {
+		/// Add schedule_update at block_number, to be skipped if its weight exceeds `max_weight` at execution
+		///
+		/// Dispatches of the same block and class are dispatched in ascending `priority`, then in the order they were
+		/// scheduled. Dispatches without a priority have `LOWEST_PRIORITY`.
+		pub fn schedule_dispatch(
+			origin,
+			call: CallOf<T>,
+			when: DelayedDispatchTime<T::BlockNumber>,
+			max_weight: Option<Weight>,
+			priority: Option<DispatchPriority>,
+		) {
 			let who = match origin.into() {
 				Ok(frame_system::RawOrigin::Root) => None,
 				Ok(frame_system::RawOrigin::Signed(t)) => Some(t),
@@ -90,26 +253,42 @@ decl_module! {
 			};
 
 			let now = <frame_system::Module<T>>::block_number();
+			let mut recurring = None;
 			let block_number = match when {
 				DelayedDispatchTime::At(block_number) => {
 					ensure!(block_number > now, Error::<T>::InvalidDelayedDispatchTime);
+					ensure!(
+						block_number <= now.saturating_add(T::MaxScheduleDelay::get()),
+						Error::<T>::DelayTooLong
+					);
 					block_number
 				},
 				DelayedDispatchTime::After(block_count) => {
+					ensure!(block_count <= T::MaxScheduleDelay::get(), Error::<T>::DelayTooLong);
 					now.checked_add(&block_count).ok_or(Error::<T>::BlockNumberOverflow)?
 				},
+				DelayedDispatchTime::Recurring(start, period) => {
+					ensure!(start > now && !period.is_zero(), Error::<T>::InvalidDelayedDispatchTime);
+					recurring = Some((start, period));
+					start
+				},
 			};
 
-			let id = Self::_get_next_id()?;
+			if let Some(w) = &who {
+				ensure!(
+					Self::scheduled_count_by_origin((block_number, w.clone())) < T::MaxScheduledPerOrigin::get(),
+					Error::<T>::OriginScheduleLimitExceeded
+				);
+			}
 
-			match call.get_dispatch_info().class {
-				DispatchClass::Normal => {
-					<DelayedNormalDispatches<T>>::insert(block_number, id, (who, call, id));
-				},
-				DispatchClass::Operational => {
-					<DelayedOperationalDispatches<T>>::insert(block_number, id, (who, call, id));
-				},
+			let id = Self::_get_next_id()?;
+			if let Some(max_weight) = max_weight {
+				DispatchMaxWeights::insert(id, max_weight);
+			}
+			if let Some(priority) = priority {
+				DispatchPriorities::insert(id, priority);
 			}
+			Self::_insert_delayed_dispatch(block_number, (who, call, id, recurring));
 			Self::deposit_event(RawEvent::ScheduleDispatch(block_number, id));
 		}
 
@@ -117,25 +296,180 @@ decl_module! {
 		pub fn cancel_deplayed_dispatch(origin, at: T::BlockNumber, id: DispatchId) {
 			let is_root = ensure_root(origin.clone()).is_ok();
 
-			if let Some((who, _, _)) = <DelayedNormalDispatches<T>>::get(at, id) {
+			if let Some((who, _, _, _)) = <DelayedNormalDispatches<T>>::get(at, id) {
 				if !is_root {
 					let w = ensure_signed(origin)?;
 					ensure!(Some(w) == who, Error::<T>::NoPermission);
 				}
 				<DelayedNormalDispatches<T>>::remove(at, id);
-			} else if let Some((who, _, _)) = <DelayedOperationalDispatches<T>>::get(at, id) {
+				Self::_dec_scheduled_count(at, &who);
+			} else if let Some((who, _, _, _)) = <DelayedOperationalDispatches<T>>::get(at, id) {
 				if !is_root {
 					let w = ensure_signed(origin)?;
 					ensure!(Some(w) == who, Error::<T>::NoPermission);
 				}
 				<DelayedOperationalDispatches<T>>::remove(at, id);
+				Self::_dec_scheduled_count(at, &who);
 			} else {
 				return Err(Error::<T>::DispatchNotExisted.into());
 			}
+			<RecurringDispatches<T>>::remove(id);
+			DispatchMaxWeights::remove(id);
+			DispatchPriorities::remove(id);
 			Self::deposit_event(RawEvent::CancelDeplayedDispatch(id));
 		}
 
+		/// Cancel a batch of delayed dispatches, as `(at, id)`, of at most `MaxCancelBatch`
+		///
+		/// Entries which don't exist or aren't owned by the origin are skipped rather than failing the call, and
+		/// reported in `BatchCancelled` along with the number cancelled.
+		pub fn cancel_batch(origin, cancellations: Vec<(T::BlockNumber, DispatchId)>) {
+			ensure!(
+				cancellations.len() <= T::MaxCancelBatch::get() as usize,
+				Error::<T>::TooManyCancellations
+			);
+
+			let mut cancelled = 0u32;
+			for (at, id) in cancellations.iter() {
+				if Self::cancel_deplayed_dispatch(origin.clone(), *at, *id).is_ok() {
+					cancelled += 1;
+				}
+			}
+			let skipped = cancellations.len() as u32 - cancelled;
+			Self::deposit_event(RawEvent::BatchCancelled(cancelled, skipped));
+		}
+
+		/// Replace the call of a pending dispatch, keeping its time slot, id and priority
+		///
+		/// Only the original scheduler could modify it, and the new call must be of the same dispatch class.
+		pub fn modify_dispatch(origin, at: T::BlockNumber, id: DispatchId, new_call: Box<CallOf<T>>) {
+			let who = match origin.into() {
+				Ok(frame_system::RawOrigin::Root) => None,
+				Ok(frame_system::RawOrigin::Signed(t)) => Some(t),
+				_ => return Err(Error::<T>::BadOrigin.into())
+			};
+
+			let (class, dispatch) = if let Some(dispatch) = <DelayedNormalDispatches<T>>::get(at, id) {
+				(DispatchClass::Normal, dispatch)
+			} else if let Some(dispatch) = <DelayedOperationalDispatches<T>>::get(at, id) {
+				(DispatchClass::Operational, dispatch)
+			} else {
+				return Err(Error::<T>::DispatchNotExisted.into());
+			};
+			let (scheduler, old_call, _, recurring) = dispatch;
+			ensure!(who == scheduler, Error::<T>::NoPermission);
+			ensure!(new_call.get_dispatch_info().class == class, Error::<T>::DispatchClassMismatch);
+
+			let old_call_hash = T::Hashing::hash_of(&old_call);
+			let new_call_hash = T::Hashing::hash_of(&new_call);
+			let dispatch = (scheduler, *new_call, id, recurring);
+			match class {
+				DispatchClass::Normal => <DelayedNormalDispatches<T>>::insert(at, id, dispatch),
+				DispatchClass::Operational => <DelayedOperationalDispatches<T>>::insert(at, id, dispatch),
+			}
+			Self::deposit_event(RawEvent::DispatchModified(at, id, old_call_hash, new_call_hash));
+		}
+
+		/// Cancel recurring dispatch
+		pub fn cancel_recurring_dispatch(origin, id: DispatchId) {
+			let at = Self::recurring_dispatches(id).ok_or(Error::<T>::DispatchNotExisted)?;
+			Self::cancel_deplayed_dispatch(origin, at, id)?;
+		}
+
+		/// Dispatch a pending dispatch immediately, ahead of its block, with the origin it was scheduled by
+		///
+		/// Only the original scheduler or root could execute it. A recurring dispatch executed successfully is
+		/// scheduled again as if it was executed at its block.
+		///
+		/// The weight of the call is charged, and as at its block, fails with `ExceedMaxScheduleDispatchWeight` if it
+		/// exceeds `MaxScheduleDispatchWeight`, and the dispatch is skipped if it exceeds its max weight.
+		#[weight = FunctionOf(
+			|(when, id): (&T::BlockNumber, &DispatchId)| Module::<T>::_scheduled_dispatch_weight(*when, *id),
+			DispatchClass::Normal,
+			true
+		)]
+		pub fn execute_now(origin, when: T::BlockNumber, id: DispatchId) {
+			let is_root = ensure_root(origin.clone()).is_ok();
+
+			let dispatch = if let Some(dispatch) = <DelayedNormalDispatches<T>>::get(when, id) {
+				dispatch
+			} else if let Some(dispatch) = <DelayedOperationalDispatches<T>>::get(when, id) {
+				dispatch
+			} else {
+				return Err(Error::<T>::DispatchNotExisted.into());
+			};
+			let (who, call, id, recurring) = dispatch;
+			if !is_root {
+				let w = ensure_signed(origin)?;
+				ensure!(Some(w) == who, Error::<T>::NoPermission);
+			}
+			let dispatch_weight = call.get_dispatch_info().weight;
+			ensure!(
+				dispatch_weight <= T::MaxScheduleDispatchWeight::get(),
+				Error::<T>::ExceedMaxScheduleDispatchWeight
+			);
+
+			<DelayedNormalDispatches<T>>::remove(when, id);
+			<DelayedOperationalDispatches<T>>::remove(when, id);
+			<RecurringDispatches<T>>::remove(id);
+			Self::_dec_scheduled_count(when, &who);
+
+			if Self::dispatch_max_weights(id).map_or(false, |max_weight| dispatch_weight > max_weight) {
+				DispatchMaxWeights::remove(id);
+				DispatchPriorities::remove(id);
+				Self::deposit_event(RawEvent::ScheduleDispatchSkipped(when, id));
+				return Ok(());
+			}
+
+			let dispatch_origin: T::Origin = match who.clone() {
+				Some(w) => frame_system::RawOrigin::Signed(w).into(),
+				None => frame_system::RawOrigin::Root.into(),
+			};
+			let recurring_call = recurring.map(|_| call.clone());
+			let result = call.dispatch(dispatch_origin);
+			let succeeded = result.is_ok();
+			if let Err(e) = result {
+				Self::deposit_event(RawEvent::ScheduleDispatchFail(id, e));
+			} else {
+				Self::deposit_event(RawEvent::ScheduleDispatchSuccess(<frame_system::Module<T>>::block_number(), id));
+			}
+			if let (true, Some(call)) = (succeeded, recurring_call) {
+				Self::_schedule_next_recurring(when, (who, call, id, recurring));
+			} else {
+				DispatchMaxWeights::remove(id);
+				DispatchPriorities::remove(id);
+			}
+		}
+
+		/// Prune dispatch results of up to `limit` blocks before `before_block`
+		pub fn prune_dispatch_history(origin, before_block: T::BlockNumber, limit: u32) {
+			ensure_root(origin)?;
+
+			let expired = <BlockDispatchResults<T> as IterableStorageMap<_, _>>::iter()
+				.filter(|(block_number, _)| *block_number < before_block)
+				.take(limit as usize)
+				.collect::<Vec<_>>();
+			let mut count = 0u32;
+			for (block_number, results) in expired {
+				count = count.saturating_add(results.len() as u32);
+				<BlockDispatchResults<T>>::remove(block_number);
+			}
+			Self::deposit_event(RawEvent::DispatchHistoryPruned(before_block, count));
+		}
+
+		fn on_runtime_upgrade() {
+			if StorageVersion::get() < Releases::V2_0_0 {
+				Self::migrate_to_v2();
+			}
+		}
+
 		fn on_initialize(now: T::BlockNumber) {
+			// Results of earlier blocks were pruned by earlier blocks
+			if let Some(expired) = now.checked_sub(&T::ResultRetentionPeriod::get()) {
+				<BlockDispatchResults<T>>::remove(expired);
+			}
+
+			let mut results = Vec::new();
 			let mut weight: Weight = 0;
 			let total_weight = T::MaxScheduleDispatchWeight::get();
 			let next_block_number = match now.checked_add(&One::one()) {
@@ -143,78 +477,370 @@ decl_module! {
 				_ => return
 			};
 
-			// Operational calls are dispatched first and then normal calls
-			// TODO: dispatches should be sorted
-			let mut operational_dispatches = <DelayedOperationalDispatches<T>>::iter_prefix(now);
-			let _ = operational_dispatches.try_for_each(|(who, call, id)| {
-				weight += call.get_dispatch_info().weight;
+			// Operational calls are dispatched first and then normal calls, each sorted by priority
+			let operational_dispatches = Self::_sorted_by_priority(<DelayedOperationalDispatches<T>>::iter_prefix(now));
+			let _ = operational_dispatches.into_iter().try_for_each(|(who, call, id, recurring)| {
+				let dispatch_weight = call.get_dispatch_info().weight;
+				if Self::dispatch_max_weights(id).map_or(false, |max_weight| dispatch_weight > max_weight) {
+					Self::_dec_scheduled_count(now, &who);
+					<DelayedOperationalDispatches<T>>::remove(now, id);
+					<RecurringDispatches<T>>::remove(id);
+					DispatchMaxWeights::remove(id);
+					DispatchPriorities::remove(id);
+					Self::deposit_event(RawEvent::ScheduleDispatchSkipped(now, id));
+					return Ok(());
+				}
+
+				weight += dispatch_weight;
 				if weight > total_weight {
 					return Err(Error::<T>::ExceedMaxScheduleDispatchWeight);
 				}
 
+				Self::_dec_scheduled_count(now, &who);
 				let origin: T::Origin;
-				if let Some(w) = who {
+				if let Some(w) = who.clone() {
 					origin = frame_system::RawOrigin::Signed(w).into();
 				} else {
 					origin = frame_system::RawOrigin::Root.into();
 				}
 
+				let recurring_call = recurring.map(|_| call.clone());
 				let result = call.dispatch(origin.clone());
+				let succeeded = result.is_ok();
+				results.push((id, result.clone()));
 				if let Err(e) = result {
 					 Self::deposit_event(RawEvent::ScheduleDispatchFail(id, e));
 				} else {
 					 Self::deposit_event(RawEvent::ScheduleDispatchSuccess(now, id));
 				}
 				<DelayedOperationalDispatches<T>>::remove(now, id);
+				<RecurringDispatches<T>>::remove(id);
+				if let (true, Some(call)) = (succeeded, recurring_call) {
+					Self::_schedule_next_recurring(now, (who, call, id, recurring));
+				} else {
+					DispatchMaxWeights::remove(id);
+					DispatchPriorities::remove(id);
+				}
 				Ok(())
 			});
 
-			let mut normal_dispatches = <DelayedNormalDispatches<T>>::iter_prefix(now);
-			let _ = normal_dispatches.try_for_each(|(who, call, id)| {
-				weight += call.get_dispatch_info().weight;
+			let normal_dispatches = Self::_sorted_by_priority(<DelayedNormalDispatches<T>>::iter_prefix(now));
+			let _ = normal_dispatches.into_iter().try_for_each(|(who, call, id, recurring)| {
+				let dispatch_weight = call.get_dispatch_info().weight;
+				if Self::dispatch_max_weights(id).map_or(false, |max_weight| dispatch_weight > max_weight) {
+					Self::_dec_scheduled_count(now, &who);
+					<DelayedNormalDispatches<T>>::remove(now, id);
+					<RecurringDispatches<T>>::remove(id);
+					DispatchMaxWeights::remove(id);
+					DispatchPriorities::remove(id);
+					Self::deposit_event(RawEvent::ScheduleDispatchSkipped(now, id));
+					return Ok(());
+				}
+
+				weight += dispatch_weight;
 				if weight > total_weight {
 					return Err(Error::<T>::ExceedMaxScheduleDispatchWeight);
 				}
 
+				Self::_dec_scheduled_count(now, &who);
 				let origin: T::Origin;
-				if let Some(w) = who {
+				if let Some(w) = who.clone() {
 					origin = frame_system::RawOrigin::Signed(w).into();
 				} else {
 					origin = frame_system::RawOrigin::Root.into();
 				}
 
+				let recurring_call = recurring.map(|_| call.clone());
 				let result = call.dispatch(origin.clone());
+				let succeeded = result.is_ok();
+				results.push((id, result.clone()));
 				if let Err(e) = result {
 					Self::deposit_event(RawEvent::ScheduleDispatchFail(id, e));
 				} else {
 					Self::deposit_event(RawEvent::ScheduleDispatchSuccess(now, id));
 				}
 				<DelayedNormalDispatches<T>>::remove(now, id);
+				<RecurringDispatches<T>>::remove(id);
+				if let (true, Some(call)) = (succeeded, recurring_call) {
+					Self::_schedule_next_recurring(now, (who, call, id, recurring));
+				} else {
+					DispatchMaxWeights::remove(id);
+					DispatchPriorities::remove(id);
+				}
 				Ok(())
 			});
 
+			if !results.is_empty() {
+				<BlockDispatchResults<T>>::insert(now, results);
+			}
+
 			// Check Call dispatch weight and ensure they don't exceed MaxScheduleDispatchWeight
 			// Extra ones are moved to next block
 			let operational_dispatches = <DelayedOperationalDispatches<T>>::iter_prefix(now);
-			operational_dispatches.for_each(|(who, call, id)| {
-				<DelayedOperationalDispatches<T>>::insert(next_block_number, id, (who, call, id));
+			operational_dispatches.for_each(|(who, call, id, recurring)| {
+				Self::_dec_scheduled_count(now, &who);
 				<DelayedOperationalDispatches<T>>::remove(now, id);
+				Self::_insert_delayed_dispatch(next_block_number, (who, call, id, recurring));
+				Self::deposit_event(RawEvent::ScheduleDispatchDeferred(id, now, next_block_number));
 			});
 
 			let normal_dispatches = <DelayedNormalDispatches<T>>::iter_prefix(now);
-			normal_dispatches.for_each(|(who, call, id)| {
-				<DelayedNormalDispatches<T>>::insert(next_block_number, id, (who, call, id));
+			normal_dispatches.for_each(|(who, call, id, recurring)| {
+				Self::_dec_scheduled_count(now, &who);
 				<DelayedNormalDispatches<T>>::remove(now, id);
+				Self::_insert_delayed_dispatch(next_block_number, (who, call, id, recurring));
+				Self::deposit_event(RawEvent::ScheduleDispatchDeferred(id, now, next_block_number));
 			});
 		}
 	}
 }
 
 impl<T: Trait> Module<T> {
+	/// Whether the dispatch `id` scheduled at `when` would be dispatched at that block given `remaining_weight`,
+	/// rather than being deferred or skipped.
+	///
+	/// Dispatches are ordered as in `on_initialize`, operational ones first, each sorted by priority, and once the
+	/// accumulated weight exceeds the budget all following dispatches are deferred.
+	pub fn will_dispatch_this_block(when: T::BlockNumber, id: DispatchId, remaining_weight: Weight) -> bool {
+		Self::plan_block_dispatches(when, remaining_weight).0.contains(&id)
+	}
+
+	/// Partition the dispatches due at `when` into those to be dispatched within `budget` and those to be deferred to
+	/// the next block, each in the order of `on_initialize`.
+	///
+	/// Dispatches whose weight exceeds their max weight are to be skipped, and are in neither.
+	pub fn plan_block_dispatches(when: T::BlockNumber, budget: Weight) -> (Vec<DispatchId>, Vec<DispatchId>) {
+		let mut to_run = Vec::new();
+		let mut to_defer = Vec::new();
+		let mut weight: Weight = 0;
+		let dispatches = Self::_sorted_by_priority(<DelayedOperationalDispatches<T>>::iter_prefix(when))
+			.into_iter()
+			.chain(Self::_sorted_by_priority(<DelayedNormalDispatches<T>>::iter_prefix(
+				when,
+			)));
+		for (_, call, id, _) in dispatches {
+			let dispatch_weight = call.get_dispatch_info().weight;
+			if Self::dispatch_max_weights(id).map_or(false, |max_weight| dispatch_weight > max_weight) {
+				continue;
+			}
+
+			weight = weight.saturating_add(dispatch_weight);
+			if weight > budget {
+				to_defer.push(id);
+			} else {
+				to_run.push(id);
+			}
+		}
+		(to_run, to_defer)
+	}
+
+	/// The IDs of all pending dispatches at `block`, operational ones first, each in ascending ID.
+	pub fn list_scheduled_at(block: T::BlockNumber) -> Vec<DispatchId> {
+		Self::scheduled_dispatch_info_at(block)
+			.into_iter()
+			.map(|info| info.id)
+			.collect()
+	}
+
+	/// The info of all pending dispatches at `block`, for the `ScheduleUpdateApi` runtime API, in the order of
+	/// `list_scheduled_at`.
+	pub fn scheduled_dispatch_info_at(block: T::BlockNumber) -> Vec<ScheduledDispatchInfo<T::AccountId, T::Hash>> {
+		let to_infos = |dispatches: Vec<DelayedDispatchOf<T>>| {
+			let mut infos = dispatches
+				.into_iter()
+				.map(|(origin, call, id, _)| ScheduledDispatchInfo {
+					id,
+					origin,
+					call_hash: T::Hashing::hash_of(&call),
+					weight: call.get_dispatch_info().weight,
+				})
+				.collect::<Vec<_>>();
+			// double map prefixes are iterated in the order of hashed keys
+			infos.sort_by_key(|info| info.id);
+			infos
+		};
+
+		let mut infos = to_infos(<DelayedOperationalDispatches<T>>::iter_prefix(block).collect());
+		infos.extend(to_infos(<DelayedNormalDispatches<T>>::iter_prefix(block).collect()));
+		infos
+	}
+
+	/// All pending dispatches as `(block, id, info)`, in ascending block, then in ascending id.
+	///
+	/// NOTE: This iterates the index of blocks with pending dispatches and reads every pending dispatch, so its cost
+	/// grows with the number of pending dispatches, and it's meant for off-chain use only.
+	pub fn all_pending_sorted() -> Vec<(T::BlockNumber, DispatchId, ScheduledDispatchInfo<T::AccountId, T::Hash>)> {
+		let mut blocks = <ScheduledCountByBlock<T> as IterableStorageMap<_, _>>::iter()
+			.map(|(block, _)| block)
+			.collect::<Vec<_>>();
+		blocks.sort();
+
+		let mut pending = Vec::new();
+		for block in blocks {
+			let mut infos = Self::scheduled_dispatch_info_at(block);
+			infos.sort_by_key(|info| info.id);
+			pending.extend(infos.into_iter().map(|info| (block, info.id, info)));
+		}
+		pending
+	}
+
+	/// The free balance of `who` under `currency_id` after all pending dispatches are dispatched, in ascending block,
+	/// by the balance changes inspected by `BalanceInspector`.
+	///
+	/// All of them are assumed to be dispatched successfully, including any to be deferred by the weight limit, and
+	/// recurring dispatches only once.
+	///
+	/// NOTE: This iterates over all pending dispatches, and should only be used off-chain, e.g. by runtime APIs.
+	pub fn projected_balance(currency_id: InspectedCurrencyIdOf<T>, who: &T::AccountId) -> InspectedBalanceOf<T> {
+		let free_balance = T::BalanceInspector::free_balance(currency_id, who);
+		let mut blocks = <ScheduledCountByBlock<T> as IterableStorageMap<_, _>>::iter()
+			.map(|(block, _)| block)
+			.collect::<Vec<_>>();
+		blocks.sort();
+
+		blocks
+			.into_iter()
+			.flat_map(|block| {
+				<DelayedOperationalDispatches<T>>::iter_prefix(block)
+					.chain(<DelayedNormalDispatches<T>>::iter_prefix(block))
+			})
+			.fold(free_balance, |balance, (origin, call, _, _)| {
+				let (increase, decrease) = T::BalanceInspector::balance_change(currency_id, who, &origin, &call);
+				balance.saturating_add(increase).saturating_sub(decrease)
+			})
+	}
+
+	/// The total issuance of `currency_id` after all pending dispatches are dispatched, by the issuance changes
+	/// inspected by `BalanceInspector`.
+	///
+	/// All of them are assumed to be dispatched successfully and in isolation, and recurring dispatches only once.
+	///
+	/// NOTE: This iterates over all pending dispatches, and should only be used off-chain, e.g. by runtime APIs.
+	pub fn projected_issuance(currency_id: InspectedCurrencyIdOf<T>) -> InspectedBalanceOf<T> {
+		let total_issuance = T::BalanceInspector::total_issuance(currency_id);
+		<ScheduledCountByBlock<T> as IterableStorageMap<_, _>>::iter()
+			.flat_map(|(block, _)| {
+				<DelayedOperationalDispatches<T>>::iter_prefix(block)
+					.chain(<DelayedNormalDispatches<T>>::iter_prefix(block))
+			})
+			.fold(total_issuance, |issuance, (origin, call, _, _)| {
+				let (minted, burned) = T::BalanceInspector::issuance_change(currency_id, &origin, &call);
+				issuance.saturating_add(minted).saturating_sub(burned)
+			})
+	}
+
+	/// Migrate the delayed dispatches stored as `(who, call, id)` to `(who, call, id, recurring)`, none of them being
+	/// recurring, and backfill `ScheduledCountByBlock` from them.
+	fn migrate_to_v2() {
+		for item in &[&b"DelayedNormalDispatches"[..], &b"DelayedOperationalDispatches"[..]] {
+			let dispatches =
+				StorageIterator::<(Option<T::AccountId>, CallOf<T>, DispatchId)>::new(b"ScheduleUpdate", item)
+					.collect::<Vec<_>>();
+			for (key, (who, call, id)) in dispatches {
+				let dispatch: DelayedDispatchOf<T> = (who, call, id, None);
+				put_storage_value(b"ScheduleUpdate", item, &key, dispatch);
+			}
+		}
+
+		for item in &[&b"DelayedNormalDispatches"[..], &b"DelayedOperationalDispatches"[..]] {
+			let keys = StorageIterator::<DelayedDispatchOf<T>>::new(b"ScheduleUpdate", item)
+				.map(|(key, _)| key)
+				.collect::<Vec<_>>();
+			for key in keys {
+				// the first key is hashed with `twox_64_concat`, so the block number follows its 8 byte hash
+				if let Ok(at) = T::BlockNumber::decode(&mut key.get(8..).unwrap_or_default()) {
+					<ScheduledCountByBlock<T>>::mutate(at, |count| *count = count.saturating_add(1));
+				}
+			}
+		}
+
+		StorageVersion::put(Releases::V2_0_0);
+	}
+
+	/// The weight of the call of the pending dispatch `id` at `when`, or zero if there is none.
+	fn _scheduled_dispatch_weight(when: T::BlockNumber, id: DispatchId) -> Weight {
+		Self::delayed_normal_dispatches(when, id)
+			.or_else(|| Self::delayed_operational_dispatches(when, id))
+			.map_or(0, |(_, call, _, _)| call.get_dispatch_info().weight)
+	}
+
+	/// Collect `dispatches` in ascending priority, then in ascending id, i.e. the order they were scheduled.
+	fn _sorted_by_priority(dispatches: impl Iterator<Item = DelayedDispatchOf<T>>) -> Vec<DelayedDispatchOf<T>> {
+		let mut dispatches = dispatches.collect::<Vec<_>>();
+		dispatches.sort_by_key(|(_, _, id, _)| (Self::dispatch_priorities(id).unwrap_or(LOWEST_PRIORITY), *id));
+		dispatches
+	}
+
 	fn _get_next_id() -> result::Result<DispatchId, Error<T>> {
 		let id = Self::next_id();
 		let next_id = id.checked_add(One::one()).ok_or(Error::<T>::CannotGetNextId)?;
 		NextId::put(next_id);
 		Ok(id)
 	}
+
+	fn _insert_delayed_dispatch(at: T::BlockNumber, dispatch: DelayedDispatchOf<T>) {
+		let (who, call, id, recurring) = dispatch;
+		Self::_inc_scheduled_count(at, &who);
+		if recurring.is_some() {
+			<RecurringDispatches<T>>::insert(id, at);
+		}
+
+		match call.get_dispatch_info().class {
+			DispatchClass::Normal => {
+				<DelayedNormalDispatches<T>>::insert(at, id, (who, call, id, recurring));
+			}
+			DispatchClass::Operational => {
+				<DelayedOperationalDispatches<T>>::insert(at, id, (who, call, id, recurring));
+			}
+		}
+	}
+
+	/// Schedule a recurring dispatch executed at `now` again, at the next `start + k * period` after `now`.
+	fn _schedule_next_recurring(now: T::BlockNumber, dispatch: DelayedDispatchOf<T>) {
+		if let Some((start, period)) = dispatch.3 {
+			let next = now
+				.checked_sub(&start)
+				.and_then(|elapsed| (elapsed / period).checked_add(&One::one()))
+				.and_then(|count| count.checked_mul(&period))
+				.and_then(|offset| start.checked_add(&offset));
+			if let Some(next) = next {
+				Self::_insert_delayed_dispatch(next, dispatch);
+			}
+		}
+	}
+
+	fn _inc_scheduled_count(at: T::BlockNumber, who: &Option<T::AccountId>) {
+		<ScheduledCountByBlock<T>>::mutate(at, |count| *count = count.saturating_add(1));
+		if let Some(w) = who {
+			<ScheduledCountByOrigin<T>>::mutate((at, w.clone()), |count| *count = count.saturating_add(1));
+		}
+	}
+
+	fn _dec_scheduled_count(at: T::BlockNumber, who: &Option<T::AccountId>) {
+		let block_count = Self::scheduled_count_by_block(at).saturating_sub(1);
+		if block_count == 0 {
+			<ScheduledCountByBlock<T>>::remove(at);
+		} else {
+			<ScheduledCountByBlock<T>>::insert(at, block_count);
+		}
+		if let Some(w) = who {
+			let key = (at, w.clone());
+			let count = Self::scheduled_count_by_origin(&key).saturating_sub(1);
+			if count == 0 {
+				<ScheduledCountByOrigin<T>>::remove(&key);
+			} else {
+				<ScheduledCountByOrigin<T>>::insert(&key, count);
+			}
+		}
+	}
+}
+
+/// The balance committed to pending dispatches is how much `projected_balance` is below the free balance, so pending
+/// dispatches crediting the account offset those debiting it.
+///
+/// NOTE: This iterates over all pending dispatches, as `projected_balance` does.
+impl<T: Trait> PendingWithdrawals<InspectedCurrencyIdOf<T>, T::AccountId, InspectedBalanceOf<T>> for Module<T> {
+	fn pending_withdrawals(currency_id: InspectedCurrencyIdOf<T>, who: &T::AccountId) -> InspectedBalanceOf<T> {
+		T::BalanceInspector::free_balance(currency_id, who).saturating_sub(Self::projected_balance(currency_id, who))
+	}
 }
@@ -81,12 +81,57 @@ impl pallet_balances::Trait for Runtime {
 
 parameter_types! {
 	pub const MaxScheduleDispatchWeight: Weight = 2_000_000;
+	pub const MaxScheduledPerOrigin: u32 = 3;
+	pub const ResultRetentionPeriod: BlockNumber = 10;
+	pub const MaxScheduleDelay: BlockNumber = 20;
+	pub const MaxCancelBatch: u32 = 3;
+}
+
+pub struct BalancesInspector;
+impl InspectBalanceChanges<AccountId, Call> for BalancesInspector {
+	type CurrencyId = ();
+	type Balance = u128;
+
+	fn free_balance(_: (), who: &AccountId) -> u128 {
+		Balances::free_balance(who)
+	}
+
+	fn balance_change(_: (), who: &AccountId, origin: &Option<AccountId>, call: &Call) -> (u128, u128) {
+		match (origin, call) {
+			(Some(from), Call::Balances(BalancesCall::transfer(dest, value))) => {
+				let increase = if dest == who { *value } else { 0 };
+				let decrease = if from == who { *value } else { 0 };
+				(increase, decrease)
+			}
+			_ => (0, 0),
+		}
+	}
+
+	fn total_issuance(_: ()) -> u128 {
+		Balances::total_issuance()
+	}
+
+	fn issuance_change(_: (), origin: &Option<AccountId>, call: &Call) -> (u128, u128) {
+		match (origin, call) {
+			(None, Call::Balances(BalancesCall::set_balance(who, new_free, new_reserved))) => {
+				let old_total = Balances::free_balance(who) + Balances::reserved_balance(who);
+				let new_total = new_free + new_reserved;
+				(new_total.saturating_sub(old_total), old_total.saturating_sub(new_total))
+			}
+			_ => (0, 0),
+		}
+	}
 }
 
 impl Trait for Runtime {
 	type Event = TestEvent;
 	type Call = Call;
 	type MaxScheduleDispatchWeight = MaxScheduleDispatchWeight;
+	type MaxScheduledPerOrigin = MaxScheduledPerOrigin;
+	type ResultRetentionPeriod = ResultRetentionPeriod;
+	type MaxScheduleDelay = MaxScheduleDelay;
+	type MaxCancelBatch = MaxCancelBatch;
+	type BalanceInspector = BalancesInspector;
 }
 pub type ScheduleUpdateModule = Module<Runtime>;
 
@@ -3,9 +3,9 @@
 #![cfg(test)]
 
 use super::*;
-use frame_support::{assert_noop, assert_ok};
-use mock::{BalancesCall, Call, ExtBuilder, Origin, Runtime, ScheduleUpdateModule, System, TestEvent};
-use sp_runtime::traits::OnInitialize;
+use frame_support::{assert_noop, assert_ok, storage::unhashed};
+use mock::{Balances, BalancesCall, Call, ExtBuilder, Origin, Runtime, ScheduleUpdateModule, System, TestEvent};
+use sp_runtime::traits::{OnInitialize, OnRuntimeUpgrade};
 
 #[test]
 fn schedule_dispatch_should_work() {
@@ -15,7 +15,9 @@ fn schedule_dispatch_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(2, 0));
@@ -28,7 +30,9 @@ fn schedule_dispatch_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::ROOT,
 			call,
-			DelayedDispatchTime::After(3)
+			DelayedDispatchTime::After(3),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(4, 1));
@@ -43,12 +47,110 @@ fn schedule_dispatch_should_fail() {
 	ExtBuilder::default().build().execute_with(|| {
 		let call = Call::Balances(BalancesCall::transfer(2, 11));
 		assert_noop!(
-			ScheduleUpdateModule::schedule_dispatch(Origin::signed(1), call, DelayedDispatchTime::At(0)),
+			ScheduleUpdateModule::schedule_dispatch(Origin::signed(1), call, DelayedDispatchTime::At(0), None, None),
 			Error::<Runtime>::InvalidDelayedDispatchTime
 		);
 	});
 }
 
+#[test]
+fn schedule_dispatch_should_respect_max_schedule_delay() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let call = Call::Balances(BalancesCall::transfer(2, 11));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call.clone(),
+			DelayedDispatchTime::At(21),
+			None,
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call.clone(),
+			DelayedDispatchTime::After(20),
+			None,
+			None
+		));
+
+		assert_noop!(
+			ScheduleUpdateModule::schedule_dispatch(
+				Origin::ROOT,
+				call.clone(),
+				DelayedDispatchTime::At(22),
+				None,
+				None
+			),
+			Error::<Runtime>::DelayTooLong
+		);
+		assert_noop!(
+			ScheduleUpdateModule::schedule_dispatch(Origin::ROOT, call, DelayedDispatchTime::After(21), None, None),
+			Error::<Runtime>::DelayTooLong
+		);
+	});
+}
+
+#[test]
+fn schedule_dispatch_should_respect_max_scheduled_per_origin() {
+	ExtBuilder::default().build().execute_with(|| {
+		for i in 0..3 {
+			let call = Call::Balances(BalancesCall::transfer(2, 10 + i));
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call,
+				DelayedDispatchTime::At(2),
+				None,
+				None
+			));
+		}
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 1)), 3);
+
+		let call = Call::Balances(BalancesCall::transfer(2, 13));
+		assert_noop!(
+			ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call.clone(),
+				DelayedDispatchTime::At(2),
+				None,
+				None
+			),
+			Error::<Runtime>::OriginScheduleLimitExceeded
+		);
+
+		// other blocks and other origins are not affected
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call.clone(),
+			DelayedDispatchTime::At(3),
+			None,
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(2),
+			call.clone(),
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+
+		// cancelling frees a slot
+		assert_ok!(ScheduleUpdateModule::cancel_deplayed_dispatch(Origin::signed(1), 2, 0));
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 1)), 2);
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+
+		// executing frees all slots
+		ScheduleUpdateModule::on_initialize(2);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 1)), 0);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 2)), 0);
+	});
+}
+
 #[test]
 fn cancel_deplayed_dispatch_should_work() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -57,7 +159,9 @@ fn cancel_deplayed_dispatch_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(2, 0));
@@ -77,7 +181,9 @@ fn cancel_deplayed_dispatch_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::After(3)
+			DelayedDispatchTime::After(3),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(4, 1));
@@ -97,7 +203,9 @@ fn cancel_deplayed_dispatch_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::ROOT,
 			call,
-			DelayedDispatchTime::At(5)
+			DelayedDispatchTime::At(5),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(5, 2));
@@ -114,6 +222,68 @@ fn cancel_deplayed_dispatch_should_work() {
 	});
 }
 
+#[test]
+fn cancel_batch_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		for i in 0..3 {
+			let call = Call::Balances(BalancesCall::transfer(3, 10 + i));
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call,
+				DelayedDispatchTime::At(2),
+				None,
+				None
+			));
+		}
+		let call = Call::Balances(BalancesCall::transfer(3, 20));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(2),
+			call,
+			DelayedDispatchTime::At(3),
+			None,
+			None
+		));
+
+		assert_noop!(
+			ScheduleUpdateModule::cancel_batch(Origin::signed(1), vec![(2, 0), (2, 1), (2, 2), (3, 3)]),
+			Error::<Runtime>::TooManyCancellations
+		);
+
+		// the dispatch of another account is skipped
+		assert_ok!(ScheduleUpdateModule::cancel_batch(
+			Origin::signed(1),
+			vec![(2, 0), (2, 1), (3, 3)]
+		));
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 0).is_none());
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 1).is_none());
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 2).is_some());
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(3, 3).is_some());
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 1)), 1);
+		for id in 0..2 {
+			let cancel_event = TestEvent::schedule_update(RawEvent::CancelDeplayedDispatch(id));
+			assert!(System::events().iter().any(|record| record.event == cancel_event));
+		}
+		let batch_event = TestEvent::schedule_update(RawEvent::BatchCancelled(2, 1));
+		assert!(System::events().iter().any(|record| record.event == batch_event));
+
+		// root could cancel any dispatch, and already cancelled ones are skipped
+		assert_ok!(ScheduleUpdateModule::cancel_batch(
+			Origin::ROOT,
+			vec![(2, 0), (2, 2), (3, 3)]
+		));
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 2).is_none());
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(3, 3).is_none());
+		let batch_event = TestEvent::schedule_update(RawEvent::BatchCancelled(2, 1));
+		assert_eq!(
+			System::events()
+				.iter()
+				.filter(|record| record.event == batch_event)
+				.count(),
+			2
+		);
+	});
+}
+
 #[test]
 fn cancel_deplayed_dispatch_should_fail() {
 	ExtBuilder::default().build().execute_with(|| {
@@ -127,7 +297,9 @@ fn cancel_deplayed_dispatch_should_fail() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(2, 0));
@@ -145,7 +317,9 @@ fn cancel_deplayed_dispatch_should_fail() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::ROOT,
 			call,
-			DelayedDispatchTime::At(5)
+			DelayedDispatchTime::At(5),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(5, 1));
@@ -168,14 +342,18 @@ fn on_initialize_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		let call = Call::Balances(BalancesCall::transfer(2, 12));
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(3)
+			DelayedDispatchTime::At(3),
+			None,
+			None
 		));
 
 		assert_eq!(System::events().len(), 7);
@@ -202,7 +380,9 @@ fn on_initialize_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::ROOT,
 			call,
-			DelayedDispatchTime::After(10)
+			DelayedDispatchTime::After(10),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(11, 2));
@@ -214,7 +394,9 @@ fn on_initialize_should_work() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::ROOT,
 			call,
-			DelayedDispatchTime::After(12)
+			DelayedDispatchTime::After(12),
+			None,
+			None
 		));
 
 		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatch(13, 3));
@@ -251,7 +433,9 @@ fn on_initialize_should_fail() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		assert_eq!(System::events().len(), 6);
@@ -279,7 +463,9 @@ fn on_initialize_should_fail() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::After(10)
+			DelayedDispatchTime::After(10),
+			None,
+			None
 		));
 
 		assert_eq!(System::events().len(), 8);
@@ -305,21 +491,27 @@ fn on_initialize_weight_exceed() {
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		let call = Call::Balances(BalancesCall::transfer(2, 12));
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		let call = Call::Balances(BalancesCall::transfer(2, 13));
 		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
 			Origin::signed(1),
 			call,
-			DelayedDispatchTime::At(2)
+			DelayedDispatchTime::At(2),
+			None,
+			None
 		));
 
 		assert_eq!(System::events().len(), 8);
@@ -328,17 +520,758 @@ fn on_initialize_weight_exceed() {
 
 		ScheduleUpdateModule::on_initialize(2);
 		println!("{:?}", System::events());
-		assert_eq!(System::events().len(), 12);
-		// TODO on_initialize should be sorted
-		//let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(0, 2));
-		//assert!(System::events().iter().any(|record| record.event == schedule_dispatch_event));
+		assert_eq!(System::events().len(), 13);
+		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(2, 0));
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == schedule_dispatch_event));
+
+		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(2, 1));
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == schedule_dispatch_event));
+
+		ScheduleUpdateModule::on_initialize(3);
+		assert_eq!(System::events().len(), 15);
+		let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(3, 2));
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == schedule_dispatch_event));
+	});
+}
+
+#[test]
+fn deferred_dispatch_should_emit_event() {
+	ExtBuilder::default().build().execute_with(|| {
+		for i in 0..3 {
+			let call = Call::Balances(BalancesCall::transfer(2, 10 + i));
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call,
+				DelayedDispatchTime::At(2),
+				None,
+				None
+			));
+		}
+
+		ScheduleUpdateModule::on_initialize(2);
+		assert_eq!(Balances::free_balance(2), 121);
+		let deferred_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchDeferred(2, 2, 3));
+		assert!(System::events().iter().any(|record| record.event == deferred_event));
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 2).is_none());
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(3, 2).is_some());
+
+		ScheduleUpdateModule::on_initialize(3);
+		assert_eq!(Balances::free_balance(2), 133);
+		let success_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(3, 2));
+		assert!(System::events().iter().any(|record| record.event == success_event));
+	});
+}
+
+#[test]
+fn recurring_dispatch_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 10));
+		assert_noop!(
+			ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call.clone(),
+				DelayedDispatchTime::Recurring(2, 0),
+				None,
+				None
+			),
+			Error::<Runtime>::InvalidDelayedDispatchTime
+		);
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::Recurring(2, 3),
+			None,
+			None
+		));
+		assert_eq!(ScheduleUpdateModule::recurring_dispatches(0), Some(2));
 
-		//let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(2, 2));
-		//assert!(System::events().iter().any(|record| record.event == schedule_dispatch_event));
+		ScheduleUpdateModule::on_initialize(2);
+		assert_eq!(Balances::free_balance(2), 110);
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 0).is_none());
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(5, 0).is_some());
+		assert_eq!(ScheduleUpdateModule::recurring_dispatches(0), Some(5));
+
+		ScheduleUpdateModule::on_initialize(5);
+		assert_eq!(Balances::free_balance(2), 120);
+		assert_eq!(ScheduleUpdateModule::recurring_dispatches(0), Some(8));
+
+		assert_noop!(
+			ScheduleUpdateModule::cancel_recurring_dispatch(Origin::signed(2), 0),
+			Error::<Runtime>::NoPermission
+		);
+		assert_ok!(ScheduleUpdateModule::cancel_recurring_dispatch(Origin::signed(1), 0));
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(8, 0).is_none());
+		assert_eq!(ScheduleUpdateModule::recurring_dispatches(0), None);
+
+		let cancel_event = TestEvent::schedule_update(RawEvent::CancelDeplayedDispatch(0));
+		assert!(System::events().iter().any(|record| record.event == cancel_event));
+
+		assert_noop!(
+			ScheduleUpdateModule::cancel_recurring_dispatch(Origin::signed(1), 0),
+			Error::<Runtime>::DispatchNotExisted
+		);
+	});
+}
+
+#[test]
+fn failed_recurring_dispatch_should_stop() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 110));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::Recurring(2, 3),
+			None,
+			None
+		));
+
+		ScheduleUpdateModule::on_initialize(2);
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(5, 0).is_none());
+		assert_eq!(ScheduleUpdateModule::recurring_dispatches(0), None);
+	});
+}
+
+#[test]
+fn dispatch_over_max_weight_should_be_skipped() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 10));
+		let call_weight = call.get_dispatch_info().weight;
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call.clone(),
+			DelayedDispatchTime::At(2),
+			Some(call_weight),
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			Some(call_weight - 1),
+			None
+		));
+		assert_eq!(ScheduleUpdateModule::dispatch_max_weights(1), Some(call_weight - 1));
+
+		ScheduleUpdateModule::on_initialize(2);
+		assert_eq!(Balances::free_balance(2), 110);
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(2, 1).is_none());
+		assert_eq!(ScheduleUpdateModule::dispatch_max_weights(0), None);
+		assert_eq!(ScheduleUpdateModule::dispatch_max_weights(1), None);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 1)), 0);
+
+		let success_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(2, 0));
+		assert!(System::events().iter().any(|record| record.event == success_event));
+		let skipped_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSkipped(2, 1));
+		assert!(System::events().iter().any(|record| record.event == skipped_event));
+	});
+}
+
+#[test]
+fn dispatch_results_should_be_recorded_and_pruned() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 10));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call.clone(),
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(3),
+			None,
+			None
+		));
 
+		ScheduleUpdateModule::on_initialize(2);
 		ScheduleUpdateModule::on_initialize(3);
-		assert_eq!(System::events().len(), 14);
-		//let schedule_dispatch_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(1, 3));
-		//assert!(System::events().iter().any(|record| record.event == schedule_dispatch_event));
+		assert_eq!(ScheduleUpdateModule::block_dispatch_results(2), vec![(0, Ok(()))]);
+		assert_eq!(ScheduleUpdateModule::block_dispatch_results(3), vec![(1, Ok(()))]);
+
+		assert_noop!(
+			ScheduleUpdateModule::prune_dispatch_history(Origin::signed(1), 3, 10),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(ScheduleUpdateModule::prune_dispatch_history(Origin::ROOT, 3, 0));
+		assert_eq!(ScheduleUpdateModule::block_dispatch_results(2), vec![(0, Ok(()))]);
+
+		assert_ok!(ScheduleUpdateModule::prune_dispatch_history(Origin::ROOT, 3, 10));
+		assert!(ScheduleUpdateModule::block_dispatch_results(2).is_empty());
+		assert_eq!(ScheduleUpdateModule::block_dispatch_results(3), vec![(1, Ok(()))]);
+
+		let pruned_event = TestEvent::schedule_update(RawEvent::DispatchHistoryPruned(3, 1));
+		assert!(System::events().iter().any(|record| record.event == pruned_event));
+
+		// automatically pruned after the retention period
+		ScheduleUpdateModule::on_initialize(13);
+		assert!(ScheduleUpdateModule::block_dispatch_results(3).is_empty());
+	});
+}
+
+#[test]
+fn prune_dispatch_history_should_respect_limit() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 10));
+		for block in 2..=4 {
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call.clone(),
+				DelayedDispatchTime::At(block),
+				None,
+				None
+			));
+		}
+		for block in 2..=4 {
+			ScheduleUpdateModule::on_initialize(block);
+		}
+		let recorded = || {
+			(2..=4)
+				.filter(|block| !ScheduleUpdateModule::block_dispatch_results(block).is_empty())
+				.count()
+		};
+
+		assert_ok!(ScheduleUpdateModule::prune_dispatch_history(Origin::ROOT, 5, 2));
+		assert_eq!(recorded(), 1);
+		let pruned_event = TestEvent::schedule_update(RawEvent::DispatchHistoryPruned(5, 2));
+		assert!(System::events().iter().any(|record| record.event == pruned_event));
+
+		assert_ok!(ScheduleUpdateModule::prune_dispatch_history(Origin::ROOT, 5, 2));
+		assert_eq!(recorded(), 0);
+	});
+}
+
+#[test]
+fn will_dispatch_this_block_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		// OperationalDispatches are ordered before NormalDispatches
+		let call = Call::Balances(BalancesCall::transfer(2, 10));
+		let normal_weight = call.get_dispatch_info().weight;
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		let call = Call::Balances(BalancesCall::set_balance(3, 10, 11));
+		let operational_weight = call.get_dispatch_info().weight;
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+
+		let budget = operational_weight + normal_weight;
+		assert!(ScheduleUpdateModule::will_dispatch_this_block(2, 1, budget));
+		assert!(ScheduleUpdateModule::will_dispatch_this_block(2, 0, budget));
+
+		assert!(ScheduleUpdateModule::will_dispatch_this_block(2, 1, budget - 1));
+		assert!(!ScheduleUpdateModule::will_dispatch_this_block(2, 0, budget - 1));
+
+		assert!(!ScheduleUpdateModule::will_dispatch_this_block(
+			2,
+			1,
+			operational_weight - 1
+		));
+		assert!(!ScheduleUpdateModule::will_dispatch_this_block(2, 2, budget));
+		assert!(!ScheduleUpdateModule::will_dispatch_this_block(3, 0, budget));
+	});
+}
+
+#[test]
+fn dispatches_should_be_sorted_by_priority() {
+	ExtBuilder::default().build().execute_with(|| {
+		for i in 0..2 {
+			let call = Call::Balances(BalancesCall::transfer(2, 10 + i));
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::signed(1),
+				call,
+				DelayedDispatchTime::At(2),
+				None,
+				None
+			));
+		}
+		let call = Call::Balances(BalancesCall::transfer(2, 12));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			Some(0)
+		));
+		assert_eq!(ScheduleUpdateModule::dispatch_priorities(2), Some(0));
+
+		// the budget allows two of them
+		assert!(ScheduleUpdateModule::will_dispatch_this_block(2, 2, 2_000_000));
+		assert!(ScheduleUpdateModule::will_dispatch_this_block(2, 0, 2_000_000));
+		assert!(!ScheduleUpdateModule::will_dispatch_this_block(2, 1, 2_000_000));
+
+		ScheduleUpdateModule::on_initialize(2);
+		let high_priority_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(2, 2));
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == high_priority_event));
+		let default_priority_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(2, 0));
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event == default_priority_event));
+		assert_eq!(ScheduleUpdateModule::dispatch_priorities(2), None);
+
+		// the last one is deferred
+		assert!(ScheduleUpdateModule::delayed_normal_dispatches(3, 1).is_some());
+		ScheduleUpdateModule::on_initialize(3);
+		let deferred_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(3, 1));
+		assert!(System::events().iter().any(|record| record.event == deferred_event));
+	});
+}
+
+#[test]
+fn projected_issuance_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_eq!(ScheduleUpdateModule::projected_issuance(()), 500);
+
+		// mint
+		let call = Call::Balances(BalancesCall::set_balance(1, 150, 0));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		// burn
+		let call = Call::Balances(BalancesCall::set_balance(2, 70, 0));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call,
+			DelayedDispatchTime::At(5),
+			None,
+			None
+		));
+		// transfers don't change the issuance
+		let call = Call::Balances(BalancesCall::transfer(3, 20));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+
+		assert_eq!(ScheduleUpdateModule::projected_issuance(()), 520);
+		assert_eq!(Balances::total_issuance(), 500);
+
+		ScheduleUpdateModule::on_initialize(2);
+		ScheduleUpdateModule::on_initialize(5);
+		assert_eq!(Balances::total_issuance(), 520);
+		assert_eq!(ScheduleUpdateModule::projected_issuance(()), 520);
+	});
+}
+
+#[test]
+fn pending_withdrawals_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let call = Call::Balances(BalancesCall::transfer(3, 30));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+
+		assert_eq!(ScheduleUpdateModule::pending_withdrawals((), &1), 30);
+		assert_eq!(ScheduleUpdateModule::pending_withdrawals((), &2), 0);
+		assert_eq!(ScheduleUpdateModule::pending_withdrawals((), &3), 0);
+
+		ScheduleUpdateModule::on_initialize(2);
+		assert_eq!(Balances::free_balance(1), 70);
+		assert_eq!(ScheduleUpdateModule::pending_withdrawals((), &1), 0);
+	});
+}
+
+#[test]
+fn projected_balance_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &1), 100);
+
+		// incoming
+		let call = Call::Balances(BalancesCall::transfer(1, 30));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(2),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		// outgoing
+		let call = Call::Balances(BalancesCall::transfer(3, 10));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		// due at a later block
+		let call = Call::Balances(BalancesCall::transfer(3, 20));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(3),
+			None,
+			None
+		));
+
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &1), 100);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &2), 70);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &3), 130);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &4), 100);
+
+		ScheduleUpdateModule::on_initialize(2);
+		assert_eq!(Balances::free_balance(1), 120);
+		System::set_block_number(2);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &1), 100);
+
+		ScheduleUpdateModule::on_initialize(3);
+		assert_eq!(Balances::free_balance(1), 100);
+		System::set_block_number(3);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &1), 100);
+		assert_eq!(ScheduleUpdateModule::projected_balance((), &3), 130);
+	});
+}
+
+#[test]
+fn modify_dispatch_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 11));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call.clone(),
+			DelayedDispatchTime::At(2),
+			None,
+			Some(1)
+		));
+
+		let new_call = Call::Balances(BalancesCall::transfer(2, 12));
+		assert_noop!(
+			ScheduleUpdateModule::modify_dispatch(Origin::signed(2), 2, 0, Box::new(new_call.clone())),
+			Error::<Runtime>::NoPermission
+		);
+		assert_noop!(
+			ScheduleUpdateModule::modify_dispatch(Origin::ROOT, 2, 0, Box::new(new_call.clone())),
+			Error::<Runtime>::NoPermission
+		);
+		assert_noop!(
+			ScheduleUpdateModule::modify_dispatch(Origin::signed(1), 3, 0, Box::new(new_call.clone())),
+			Error::<Runtime>::DispatchNotExisted
+		);
+
+		// Normal can't be escalated to Operational
+		let operational_call = Call::Balances(BalancesCall::set_balance(1, 10, 11));
+		assert_noop!(
+			ScheduleUpdateModule::modify_dispatch(Origin::signed(1), 2, 0, Box::new(operational_call)),
+			Error::<Runtime>::DispatchClassMismatch
+		);
+
+		assert_ok!(ScheduleUpdateModule::modify_dispatch(
+			Origin::signed(1),
+			2,
+			0,
+			Box::new(new_call.clone())
+		));
+		assert_eq!(
+			ScheduleUpdateModule::delayed_normal_dispatches(2, 0),
+			Some((Some(1), new_call.clone(), 0, None))
+		);
+		assert_eq!(ScheduleUpdateModule::dispatch_priorities(0), Some(1));
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((2, 1)), 1);
+
+		let modified_event = TestEvent::schedule_update(RawEvent::DispatchModified(
+			2,
+			0,
+			<Runtime as frame_system::Trait>::Hashing::hash_of(&call),
+			<Runtime as frame_system::Trait>::Hashing::hash_of(&new_call),
+		));
+		assert!(System::events().iter().any(|record| record.event == modified_event));
+	});
+}
+
+#[test]
+fn plan_block_dispatches_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let call = Call::Balances(BalancesCall::transfer(2, 10));
+		let normal_weight = call.get_dispatch_info().weight;
+		for _ in 0..3 {
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::ROOT,
+				call.clone(),
+				DelayedDispatchTime::At(2),
+				None,
+				None
+			));
+		}
+		// skipped as its weight exceeds its max weight
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call,
+			DelayedDispatchTime::At(2),
+			Some(normal_weight - 1),
+			None
+		));
+		let call = Call::Balances(BalancesCall::set_balance(3, 10, 11));
+		let operational_weight = call.get_dispatch_info().weight;
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			call,
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+
+		let budget = operational_weight + normal_weight * 2;
+		assert_eq!(
+			ScheduleUpdateModule::plan_block_dispatches(2, budget),
+			(vec![4, 0, 1], vec![2])
+		);
+		assert_eq!(
+			ScheduleUpdateModule::plan_block_dispatches(2, budget - 1),
+			(vec![4, 0], vec![1, 2])
+		);
+		assert_eq!(
+			ScheduleUpdateModule::plan_block_dispatches(2, operational_weight - 1),
+			(vec![], vec![4, 0, 1, 2])
+		);
+		assert_eq!(ScheduleUpdateModule::plan_block_dispatches(3, budget), (vec![], vec![]));
+	});
+}
+
+#[test]
+fn list_scheduled_at_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let transfer_call = Call::Balances(BalancesCall::transfer(2, 10));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			transfer_call.clone(),
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			transfer_call.clone(),
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		let set_balance_call = Call::Balances(BalancesCall::set_balance(3, 10, 11));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			set_balance_call.clone(),
+			DelayedDispatchTime::At(2),
+			None,
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::ROOT,
+			transfer_call.clone(),
+			DelayedDispatchTime::At(3),
+			None,
+			None
+		));
+
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(2), vec![2, 0, 1]);
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(3), vec![3]);
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(4), vec![]);
+
+		let infos = ScheduleUpdateModule::scheduled_dispatch_info_at(2);
+		assert_eq!(
+			infos[0],
+			ScheduledDispatchInfo {
+				id: 2,
+				origin: None,
+				call_hash: <Runtime as frame_system::Trait>::Hashing::hash_of(&set_balance_call),
+				weight: set_balance_call.get_dispatch_info().weight,
+			}
+		);
+		assert_eq!(
+			infos[1],
+			ScheduledDispatchInfo {
+				id: 0,
+				origin: Some(1),
+				call_hash: <Runtime as frame_system::Trait>::Hashing::hash_of(&transfer_call),
+				weight: transfer_call.get_dispatch_info().weight,
+			}
+		);
+		assert_eq!(infos[2].origin, None);
+
+		assert_ok!(ScheduleUpdateModule::cancel_deplayed_dispatch(Origin::ROOT, 2, 0));
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(2), vec![2, 1]);
+	});
+}
+
+#[test]
+fn all_pending_sorted_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let transfer_call = Call::Balances(BalancesCall::transfer(2, 10));
+		let set_balance_call = Call::Balances(BalancesCall::set_balance(3, 10, 11));
+		for (call, block) in vec![
+			(transfer_call.clone(), 8),
+			(transfer_call.clone(), 3),
+			(set_balance_call.clone(), 3),
+			(transfer_call.clone(), 5),
+		] {
+			assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+				Origin::ROOT,
+				call,
+				DelayedDispatchTime::At(block),
+				None,
+				None
+			));
+		}
+		// operational dispatches come first within a block
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(3), vec![2, 1]);
+
+		let pending = ScheduleUpdateModule::all_pending_sorted()
+			.into_iter()
+			.map(|(block, id, info)| {
+				assert_eq!(info.id, id);
+				(block, id)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(pending, vec![(3, 1), (3, 2), (5, 3), (8, 0)]);
+
+		assert_ok!(ScheduleUpdateModule::cancel_deplayed_dispatch(Origin::ROOT, 5, 3));
+		ScheduleUpdateModule::on_initialize(3);
+		let pending = ScheduleUpdateModule::all_pending_sorted()
+			.into_iter()
+			.map(|(block, id, _)| (block, id))
+			.collect::<Vec<_>>();
+		assert_eq!(pending, vec![(8, 0)]);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_block(3), 0);
+	});
+}
+
+#[test]
+fn execute_now_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		assert_noop!(
+			ScheduleUpdateModule::execute_now(Origin::signed(1), 5, 0),
+			Error::<Runtime>::DispatchNotExisted
+		);
+
+		let call = Call::Balances(BalancesCall::transfer(2, 11));
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call.clone(),
+			DelayedDispatchTime::At(5),
+			None,
+			None
+		));
+		assert_noop!(
+			ScheduleUpdateModule::execute_now(Origin::signed(2), 5, 0),
+			Error::<Runtime>::NoPermission
+		);
+
+		assert_ok!(ScheduleUpdateModule::execute_now(Origin::signed(1), 5, 0));
+		assert_eq!(Balances::free_balance(2), 111);
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(5), vec![]);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_origin((5, 1)), 0);
+		let success_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSuccess(1, 0));
+		assert!(System::events().iter().any(|record| record.event == success_event));
+
+		// nothing left to dispatch at the original block
+		ScheduleUpdateModule::on_initialize(5);
+		assert_eq!(Balances::free_balance(2), 111);
+
+		// root could execute any dispatch, with the origin it was scheduled by
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(6),
+			None,
+			None
+		));
+		assert_ok!(ScheduleUpdateModule::execute_now(Origin::ROOT, 6, 1));
+		assert_eq!(Balances::free_balance(1), 78);
+		assert_eq!(Balances::free_balance(2), 122);
+	});
+}
+
+#[test]
+fn execute_now_should_respect_max_weight() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(1);
+		let call = Call::Balances(BalancesCall::transfer(2, 11));
+		let call_weight = call.get_dispatch_info().weight;
+		assert_ok!(ScheduleUpdateModule::schedule_dispatch(
+			Origin::signed(1),
+			call,
+			DelayedDispatchTime::At(5),
+			Some(call_weight - 1),
+			None
+		));
+		assert_eq!(
+			super::Call::<Runtime>::execute_now(5, 0).get_dispatch_info().weight,
+			call_weight
+		);
+		assert_eq!(super::Call::<Runtime>::execute_now(5, 1).get_dispatch_info().weight, 0);
+
+		assert_ok!(ScheduleUpdateModule::execute_now(Origin::signed(1), 5, 0));
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(ScheduleUpdateModule::list_scheduled_at(5), vec![]);
+		assert_eq!(ScheduleUpdateModule::dispatch_max_weights(0), None);
+		let skipped_event = TestEvent::schedule_update(RawEvent::ScheduleDispatchSkipped(5, 0));
+		assert!(System::events().iter().any(|record| record.event == skipped_event));
+	});
+}
+
+#[test]
+fn migrate_to_v2_should_add_recurring_to_dispatches() {
+	ExtBuilder::default().build().execute_with(|| {
+		let normal_call = Call::Balances(BalancesCall::transfer(2, 11));
+		let operational_call = Call::Balances(BalancesCall::set_balance(1, 10, 11));
+		unhashed::put(
+			&<DelayedNormalDispatches<Runtime>>::hashed_key_for(2, 0),
+			&(Some(1u64), normal_call.clone(), 0u32),
+		);
+		unhashed::put(
+			&<DelayedOperationalDispatches<Runtime>>::hashed_key_for(3, 1),
+			&(None::<u64>, operational_call.clone(), 1u32),
+		);
+
+		ScheduleUpdateModule::on_runtime_upgrade();
+		assert_eq!(
+			ScheduleUpdateModule::delayed_normal_dispatches(2, 0),
+			Some((Some(1), normal_call, 0, None))
+		);
+		assert_eq!(
+			ScheduleUpdateModule::delayed_operational_dispatches(3, 1),
+			Some((None, operational_call, 1, None))
+		);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_block(2), 1);
+		assert_eq!(ScheduleUpdateModule::scheduled_count_by_block(3), 1);
+		assert_eq!(
+			ScheduleUpdateModule::all_pending_sorted()
+				.into_iter()
+				.map(|(block, id, _)| (block, id))
+				.collect::<Vec<_>>(),
+			vec![(2, 0), (3, 1)]
+		);
+		assert_eq!(StorageVersion::get(), Releases::V2_0_0);
 	});
 }
@@ -0,0 +1,23 @@
+//! Runtime API definition for tokens module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use orml_traits::AccountData;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait TokensApi<AccountId, CurrencyId, Balance> where
+		AccountId: Codec,
+		CurrencyId: Codec,
+		Balance: Codec,
+	{
+		/// The account data of `who` under all token types it holds, e.g. by
+		/// `MultiCurrencyAccountData::currencies_of`.
+		fn account_info_all(who: AccountId) -> Vec<(CurrencyId, AccountData<Balance>)>;
+
+		/// The account data of each of `accounts` under `currency_id`, e.g. by `batch_account_data` of the tokens
+		/// module.
+		fn batch_account_data(currency_id: CurrencyId, accounts: Vec<AccountId>) -> Vec<(AccountId, AccountData<Balance>)>;
+	}
+}
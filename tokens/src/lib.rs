@@ -12,6 +12,10 @@
 //! - Depositing and withdrawing balance.
 //! - Slashing an account balance.
 //!
+//! If `Trait::NativeCurrencyId` is set, `MultiCurrency` operations and transfers on the native currency ID are routed
+//! to `Trait::NativeCurrency`, so the tokens module could be used directly for all currency IDs. Reserves and locks
+//! are not routed.
+//!
 //! ### Implementations
 //!
 //! The tokens module provides implementations for following traits.
@@ -19,6 +23,8 @@
 //! - `MultiCurrency` - Abstraction over a fungible multi-currency system.
 //! - `MultiCurrencyExtended` - Extended `MultiCurrency` with additional helper types and methods, like updating balance
 //! by a given signed integer amount.
+//! - `NamedMultiReservableCurrency` - Reserve balance under named identifiers.
+//! - `MultiCurrencyHistory` - Query free balances as of a past block.
 //!
 //! ## Interface
 //!
@@ -26,6 +32,63 @@
 //!
 //! - `transfer` - Transfer some balance to another account.
 //! - `transfer_all` - Transfer all balance to another account.
+//! - `transfer_with_nonce` - Transfer some balance to another account, with a sequential nonce against replay.
+//! - `submit_transfer_intent` - Submit a transfer signed off-chain by its sender, as a relayer paying the transfer
+//! fee.
+//! - `transfer_batch_lenient` - Transfer balances to multiple accounts, with failed transfers reported by events
+//! instead of failing the call.
+//! - `transfer_split` - Split some balance among multiple accounts in proportion to their weights.
+//! - `transfer_all_matching` - Transfer the liquid balances of all token types selected by a runtime-defined filter
+//! to another account.
+//! - `transfer_and_reserve_named` - Transfer some balance to another account and reserve it under a named identifier.
+//! - `teleport_out` - Burn some balance of an account which is teleported to another chain, `TeleportOrigin` required.
+//! - `teleport_in` - Mint some balance to an account which is teleported from another chain, `TeleportOrigin`
+//! required.
+//! - `set_target_balance` - Bring the free balance of an account to a target, `TargetBalanceOrigin` required.
+//! - `set_reserved_to` - Bring the reserved balance of an account to a target, `TargetBalanceOrigin` required.
+//! - `update_balance_allow_death` - Update the balance of an account, crediting it even below the existential deposit,
+//! root required.
+//! - `rebalance_total_issuance` - Reconcile total issuance with the sum of account balances, root required.
+//! - `freeze_currency` - Freeze a token type, disabling all operations of it, root required.
+//! - `unfreeze_currency` - Unfreeze a token type, root required.
+//! - `activate_emergency_shutdown` - Disable all operations of all token types, root required.
+//! - `deactivate_emergency_shutdown` - Lift the emergency shutdown, root required.
+//! - `set_currency_metadata` - Set the metadata of a token type, `MetadataOrigin` required. Metadata from
+//!   `DefaultMetadata` is populated on the first mint of a token type without metadata.
+//! - `set_currency_admin` - Set the admin account of a token type, root required.
+//! - `accrue_interest` - Accrue interest to the free balance of given holders of a token type, root required.
+//! - `set_interest_schedule` - Set the interest periodically accrued to all accounts of a token type, root required.
+//! - `enforce_cap_on_existing` - Set the total issuance cap of a token type, slashing any excess from its admin, root
+//! required.
+//! - `migrate_my_balance` - Migrate the free and reserved balance of the caller to another token type, scaled by a
+//! ratio.
+//! - `submit_snapshot_root` - Store the root of a balance snapshot exported by the off-chain worker,
+//! `SnapshotOrigin` required.
+//! - `start_migration` - Start a migration over all accounts of a token type, processed over multiple blocks, root
+//! required.
+//! - `dedup_locks` - Merge locks of an account with the same lock ID, keeping the largest amount, root required.
+//! - `force_remove_lock` - Remove a lock of an account regardless of who set it, `ForceOrigin` required.
+//! - `register_lock_id` - Register a lock identifier to a purpose, so that only its owner could change locks with it,
+//! root required.
+//! - `set_exchange_rate` - Set the exchange rate of a token type to the native currency, root required.
+//! - `set_send_restricted` - Restrict transfers of a token type to allowlisted senders, root or currency admin
+//! required.
+//! - `set_send_allowed` - Add or remove a sender of the allowlist of a token type, root or currency admin required.
+//! - `set_minimum_reserve_requirement` - Set the reserved balance an account must maintain under a token type, root
+//! required.
+//!
+//! ### Off-chain Worker
+//!
+//! Every `SnapshotBlock` blocks, the off-chain worker exports the total issuance and account data of all token types
+//! to off-chain local storage, under the key `orml::tokens::snapshot::{block}`, for audit.
+//!
+//! ### Runtime APIs
+//!
+//! The tokens module provides helpers for the runtime APIs declared in `orml-tokens-rpc-runtime-api`:
+//!
+//! - `account_info_all` - The account data of an account under all token types it holds, by
+//! `MultiCurrencyAccountData::currencies_of`.
+//! - `batch_account_data` - The account data of multiple accounts under a token type.
 //!
 //! ### Genesis Config
 //!
@@ -34,26 +97,42 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, traits::Get, Parameter};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure,
+	storage::{
+		generator::StorageDoubleMap as StorageDoubleMapGenerator, unhashed, IterableStorageDoubleMap,
+		IterableStorageMap,
+	},
+	traits::{Contains, EnsureOrigin, Get, WithdrawReason, WithdrawReasons},
+	Parameter,
+};
 use rstd::convert::{TryFrom, TryInto};
 use rstd::prelude::*;
 use sp_runtime::{
-	traits::{AtLeast32Bit, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Member, Saturating, StaticLookup, Zero},
-	DispatchError, DispatchResult, RuntimeDebug,
+	helpers_128bit::multiply_by_rational,
+	offchain::StorageKind,
+	traits::{
+		AtLeast32Bit, CheckedAdd, CheckedSub, Convert, Hash, IdentifyAccount, MaybeSerializeDeserialize, Member, One,
+		SaturatedConversion, Saturating, StaticLookup, Verify, Zero,
+	},
+	DispatchError, DispatchResult, Permill, RuntimeDebug,
 };
 // FIXME: `pallet/frame-` prefix should be used for all pallet modules, but currently `frame_system`
 // would cause compiling error in `decl_module!` and `construct_runtime!`
 // #3295 https://github.com/paritytech/substrate/issues/3295
-use frame_system::{self as system, ensure_signed};
+use frame_system::{self as system, ensure_root, ensure_signed};
 
-#[cfg(feature = "std")]
 use rstd::collections::btree_map::BTreeMap;
 
 use orml_traits::{
-	arithmetic::{self, Signed},
-	BalanceStatus, LockIdentifier, MultiCurrency, MultiCurrencyExtended, MultiLockableCurrency,
-	MultiReservableCurrency, OnDustRemoval,
+	arithmetic::{self, SaturatingSigned, Signed},
+	BalanceStatus, BasicCurrency, BasicLockableCurrency, BasicReservableCurrency, CurrencyFilter, LockIdentifier,
+	MultiCurrency, MultiCurrencyAccountData, MultiCurrencyExtended, MultiCurrencyHistory, MultiCurrencyIssuance,
+	MultiLockableCurrency, MultiReservableCurrency, NamedMultiReservableCurrency, OnAccountRecreate, OnDustRemoval,
+	OnIssuanceChange, OnTransferRecord, PendingWithdrawals,
 };
+pub use orml_traits::{AccountData, AccountInfo, BalanceLock};
+use orml_utilities::{with_transaction_result, FixedU128};
 
 mod mock;
 mod tests;
@@ -62,6 +141,7 @@ pub trait Trait: frame_system::Trait {
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 	type Balance: Parameter + Member + AtLeast32Bit + Default + Copy + MaybeSerializeDeserialize;
 	type Amount: Signed
+		+ arithmetic::SaturatingSigned
 		+ TryInto<Self::Balance>
 		+ TryFrom<Self::Balance>
 		+ Parameter
@@ -73,48 +153,380 @@ pub trait Trait: frame_system::Trait {
 	type CurrencyId: Parameter + Member + Copy + MaybeSerializeDeserialize + Ord;
 	type ExistentialDeposit: Get<Self::Balance>;
 	type DustRemoval: OnDustRemoval<Self::Balance>;
+	/// The share of dust that is burned when an account is reaped, the remainder is credited to `DustReceiver`.
+	type DustSplit: Get<Permill>;
+	/// The account which receives the unburned share of dust.
+	type DustReceiver: Get<Self::AccountId>;
+	/// Whether the existential deposit is also enforced on reserved balance when unreserving. If set, a reserved
+	/// balance below the existential deposit left on an account with zero free balance after `unreserve` is removed
+	/// as dust, along with its named reserves.
+	type ReapReservedDust: Get<bool>;
+	/// Whether removed dust is reported per account, or consolidated by token type once per block.
+	type DustEventMode: Get<DustEventMode>;
+	/// Handler for every mint and burn of a token type, including of dust, transfer fees and migrations. Rebalancing
+	/// total issuance is not reported, as it corrects accounting rather than minting or burning.
+	type OnIssuanceChange: OnIssuanceChange<Self::CurrencyId, Self::Balance>;
+	/// Handler for every successful transfer of a token type, by dispatchables and `MultiCurrency` alike, with the
+	/// amount debited from the sender. Zero and self transfers, and transfers routed to `NativeCurrency`, are not
+	/// reported.
+	type OnTransferRecord: OnTransferRecord<Self::CurrencyId, Self::Balance>;
+	/// Handler for deposits to accounts with zero balance, which may charge a re-creation deposit or reject it.
+	///
+	/// Note a reaped account can't be told apart from one that never existed, so this is called for both.
+	type OnAccountRecreate: OnAccountRecreate<Self::CurrencyId, Self::AccountId, Self::Balance>;
+	/// The location of another chain that balances could be teleported to or from.
+	type MultiLocation: Parameter + Member;
+	/// The origin which may teleport balances in and out.
+	type TeleportOrigin: EnsureOrigin<Self::Origin>;
+	/// The origin which may set the free balance of an account to a target by `set_target_balance`, or its reserved
+	/// balance by `set_reserved_to`.
+	type TargetBalanceOrigin: EnsureOrigin<Self::Origin>;
+	/// The origin which may forcibly remove any lock by `force_remove_lock`.
+	type ForceOrigin: EnsureOrigin<Self::Origin>;
+	/// The fee rate charged on `transfer` and `transfer_all`, taken out of the transferred amount.
+	///
+	/// Set to `()` for no transfer fee, so that `transfer` and `transfer_all` move the full amount.
+	type TransferFee: Get<Permill>;
+	/// Accounts whose transfers, as sender or recipient, are not charged the transfer fee, e.g. treasury payouts.
+	type FeeExemptAccounts: Contains<Self::AccountId>;
+	/// Token types whose transfers are not charged the transfer fee.
+	type FeeExemptCurrencies: Contains<Self::CurrencyId>;
+	/// The share of transfer fees that goes to `Treasury`, the remainder is burned.
+	type TreasuryFeeShare: Get<Permill>;
+	/// The account which receives the treasury share of transfer fees.
+	type Treasury: Get<Self::AccountId>;
+	/// A flat fee charged to the sender, on top of the transferred amount, by the transfers charged the transfer fee,
+	/// e.g. `transfer` and `transfer_all`, and deposited to `Treasury`. Not charged on transfers exempt from the
+	/// transfer fee.
+	///
+	/// Set to `()` for no service fee.
+	type ServiceFee: Get<Self::Balance>;
+	/// The maximum number of named reserves an account could have under a token type.
+	type MaxReserves: Get<u32>;
+	/// The identifier of a named reserve. Each module reserving funds should use a distinct value, e.g. a variant
+	/// of an enum defined by the runtime.
+	type ReserveIdentifier: Parameter + Member + Copy;
+	/// The purpose a lock identifier could be registered to, e.g. a variant of an enum of modules defined by the
+	/// runtime, so that only its owner could set, extend or remove locks with it.
+	type LockPurpose: Parameter + Member + Copy;
+	/// The maximum length of a named reserve label.
+	type MaxLabelLen: Get<u32>;
+	/// The maximum length of currency metadata name, symbol and description.
+	type MaxNameLen: Get<u32>;
+	/// The maximum number of decimals in currency metadata.
+	type MaxDecimals: Get<u8>;
+	/// The origin which may set currency metadata.
+	type MetadataOrigin: EnsureOrigin<Self::Origin>;
+	/// The `(name, symbol, decimals)` metadata of a token type, populated on its first mint if it has no metadata.
+	/// `None` if not derivable.
+	type DefaultMetadata: Convert<Self::CurrencyId, Option<(Vec<u8>, Vec<u8>, u8)>>;
+	/// The maximum share of the free balance of an account that could be frozen by locks, enforced when a lock is
+	/// set or extended by `try_set_lock`, `try_extend_lock` and the other fallible lock functions. `set_lock` and
+	/// `extend_lock` are infallible, so aren't restricted by it.
+	type MaxLockRatio: Get<Permill>;
+	/// The maximum number of accounts a lock could be removed from at once by `remove_lock_all`.
+	type MaxLockRemovals: Get<u32>;
+	/// Which events are emitted.
+	type EventVerbosity: Get<EventVerbosity>;
+	/// Whether transfers are reported by `Transferred`, or by `Debited` and `Credited` per account.
+	type EventModel: Get<EventModel>;
+	/// The maximum number of holders interest could be accrued to at once by `accrue_interest`.
+	type MaxInterestHolders: Get<u32>;
+	/// Accounts, e.g. module owned pools, which are exempt from the existential deposit rule, so their balances are
+	/// never removed as dust.
+	type ProtectedAccounts: Contains<Self::AccountId>;
+	/// Protocol owned accounts, between which internal transfers may saturate instead of failing on overflow.
+	type ProtocolAccounts: Contains<Self::AccountId>;
+	/// The period in blocks of balance snapshots exported by the off-chain worker. Zero disables snapshots.
+	type SnapshotBlock: Get<Self::BlockNumber>;
+	/// The period in blocks of the per-account balance snapshots read by `balance_at`. Zero disables them.
+	type AccountSnapshotPeriod: Get<Self::BlockNumber>;
+	/// The maximum number of per-account balance snapshots retained for an account under a token type, the oldest
+	/// being removed first.
+	type MaxAccountSnapshots: Get<u32>;
+	/// The maximum number of balance changes retained in `BalanceHistory` for an account under a token type, the
+	/// oldest being removed first. Zero disables the history, so balance changes don't write it.
+	type MaxBalanceHistory: Get<u32>;
+	/// The origin which may submit the root of a balance snapshot by `submit_snapshot_root`.
+	type SnapshotOrigin: EnsureOrigin<Self::Origin>;
+	/// The number of blocks the zeroed account data of a reaped account is kept for, so that crediting it again
+	/// reuses the entry, before it's removed from storage.
+	type ReapGracePeriod: Get<Self::BlockNumber>;
+	/// The maximum number of reaped accounts whose account data is removed per block, the rest being carried over to
+	/// the next block.
+	type MaxReapsPerBlock: Get<u32>;
+	/// The maximum number of accounts processed by account migrations and scheduled interest accruals per block.
+	type MigrationBatchSize: Get<u32>;
+	/// The native currency ID, operations on which are routed to `NativeCurrency`. `None` disables the routing.
+	type NativeCurrencyId: Get<Option<Self::CurrencyId>>;
+	/// The native currency, e.g. `pallet-balances` adapted by `orml_currencies::BasicCurrencyAdapter`. Balances,
+	/// transfers, reserves and locks of `NativeCurrencyId` are all kept by it.
+	type NativeCurrency: BasicCurrency<Self::AccountId, Balance = Self::Balance>
+		+ BasicReservableCurrency<Self::AccountId, Balance = Self::Balance>
+		+ BasicLockableCurrency<Self::AccountId, Balance = Self::Balance>;
+	/// The maximum number of transfers in a batch of `transfer_batch_lenient`, or of currencies swept by
+	/// `transfer_all_matching`.
+	type MaxBatchTransfers: Get<u32>;
+	/// The balance of an account committed to pending withdrawals, e.g. scheduled transfers.
+	type PendingWithdrawals: PendingWithdrawals<Self::CurrencyId, Self::AccountId, Self::Balance>;
+	/// Whether `transfer_all` keeps back the balance committed to `PendingWithdrawals`.
+	type TransferAllPolicy: Get<TransferAllPolicy>;
+	/// The selections of token types `transfer_all_matching` could sweep.
+	type CurrencyFilter: Parameter + Member + CurrencyFilter<Self::CurrencyId>;
+	/// Accounts deposits to which are blocked, and handled as set by `DepositBounce`. Transfers to them fail.
+	type BlockedRecipients: Contains<Self::AccountId>;
+	/// How deposits to blocked recipients are handled.
+	type DepositBounce: Get<DepositBounce<Self::AccountId>>;
+	/// The maximum total balance of an account under a token type, enforced on deposits and transfers. `None` if
+	/// uncapped. Unreserving doesn't change the total balance, so is never restricted by it.
+	type MaxBalancePerAccount: Convert<Self::CurrencyId, Option<Self::Balance>>;
+	/// Whether locks exceeding the total balance of an account are kept or removed when its balance changes.
+	type StaleLockPolicy: Get<StaleLockPolicy>;
+	/// The smallest unit of a token type which may be transferred, transfer amounts having to be a multiple of it. A
+	/// granularity of zero or one imposes no restriction.
+	type Granularity: Convert<Self::CurrencyId, Self::Balance>;
+	/// Whether transfer amounts not a multiple of the granularity are rejected or rounded down.
+	type GranularityPolicy: Get<GranularityPolicy>;
+	/// How slashed balances are accounted for.
+	type SlashAccountingMode: Get<SlashAccountingMode<Self::AccountId>>;
+	/// The signature of a `SignedTransferIntent` by its `from` account.
+	type IntentSignature: Parameter + Verify<Signer = Self::IntentSigner>;
+	/// The signer of an `IntentSignature`, identifying an account.
+	type IntentSigner: IdentifyAccount<AccountId = Self::AccountId>;
+}
+
+/// A single named reserve on a balance. The reserved balance of an account is the sum of its named reserves and
+/// any unnamed reserve.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ReserveData<ReserveIdentifier, Balance> {
+	/// The identifier of this reserve. Only one reserve may be in existence for each identifier.
+	pub id: ReserveIdentifier,
+	/// The amount reserved under this identifier.
+	pub amount: Balance,
 }
 
-/// A single lock on a balance. There can be many of these on an account and they "overlap", so the
-/// same balance is frozen by multiple locks.
+/// The tag prefixing the signed payload of a `SignedTransferIntent`, so that signatures made for other purposes
+/// aren't valid intents.
+pub const TRANSFER_INTENT_TAG: &[u8] = b"orml-tokens:transfer-intent";
+
+/// A transfer built and signed off-chain by `from`, to be submitted by a relayer with `submit_transfer_intent`.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-pub struct BalanceLock<Balance> {
-	/// An identifier for this lock. Only one lock may be in existence for each identifier.
-	pub id: LockIdentifier,
-	/// The amount which the free balance may not drop below when this lock is in effect.
+pub struct SignedTransferIntent<AccountId, CurrencyId, Balance, BlockNumber> {
+	/// The account to transfer from, which signs the intent.
+	pub from: AccountId,
+	/// The account to transfer to.
+	pub to: AccountId,
+	/// The token type to transfer.
+	pub currency_id: CurrencyId,
+	/// The amount to transfer.
 	pub amount: Balance,
+	/// The nonce of `from` under `currency_id`, shared with `transfer_with_nonce`.
+	pub nonce: u64,
+	/// The last block the intent could be submitted in.
+	pub deadline: BlockNumber,
 }
 
-/// balance information for an account.
+/// Metadata of a token type.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
-pub struct AccountData<Balance> {
-	/// Non-reserved part of the balance. There may still be restrictions on this, but it is the
-	/// total pool what may in principle be transferred, reserved.
-	///
-	/// This is the only balance that matters in terms of most operations on tokens.
-	pub free: Balance,
-	/// Balance which is reserved and may not be used at all.
-	///
-	/// This can still get slashed, but gets slashed last of all.
-	///
-	/// This balance is a 'reserve' balance that other subsystems use in order to set aside tokens
-	/// that are still 'owned' by the account holder, but which are suspendable.
-	pub reserved: Balance,
-	/// The amount that `free` may not drop below when withdrawing.
-	pub frozen: Balance,
+pub struct TokenMetadata {
+	/// The name of the token.
+	pub name: Vec<u8>,
+	/// The symbol of the token.
+	pub symbol: Vec<u8>,
+	/// The number of decimals the token uses.
+	pub decimals: u8,
+	/// The description of the token.
+	pub description: Vec<u8>,
+}
+
+/// The position of an account's total balance of a token type relative to the existential deposit.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum AccountStatus {
+	/// The account has no balance.
+	Dead,
+	/// The account has some balance, but below the existential deposit.
+	Dust,
+	/// The account balance meets the existential deposit.
+	Alive,
+}
+
+/// Which events of the tokens module are emitted.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum EventVerbosity {
+	/// All events are emitted.
+	Full,
+	/// Only transfer events, `Transferred`, `TransferredExactOut`, `TeleportSent` and `TeleportReceived`, and
+	/// `Debited` and `Credited` under `EventModel::DoubleEntry`, are emitted.
+	TransfersOnly,
+	/// No event is emitted.
+	Silent,
+}
+
+impl Default for EventVerbosity {
+	fn default() -> Self {
+		EventVerbosity::Full
+	}
+}
+
+/// How transfers are reported by events.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum EventModel {
+	/// `Transferred` is deposited for each transfer.
+	Transfer,
+	/// `Debited` for the sender and `Credited` for the recipient are deposited in place of each `Transferred`.
+	DoubleEntry,
+}
+
+impl Default for EventModel {
+	fn default() -> Self {
+		EventModel::Transfer
+	}
+}
+
+/// How much of the free balance `transfer_all` transfers.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum TransferAllPolicy {
+	/// All of the free balance is transferred.
+	SweepAll,
+	/// The balance committed to `PendingWithdrawals` is kept back, so that the pending withdrawals could still be
+	/// made.
+	KeepPendingWithdrawals,
 }
 
-impl<Balance: Saturating + Copy + Ord> AccountData<Balance> {
-	/// The amount that this account's free balance may not be reduced beyond.
-	fn frozen(&self) -> Balance {
-		self.frozen
+impl Default for TransferAllPolicy {
+	fn default() -> Self {
+		TransferAllPolicy::SweepAll
 	}
-	/// The total balance in this account including any that is reserved and ignoring any frozen.
-	fn total(&self) -> Balance {
-		self.free.saturating_add(self.reserved)
+}
+
+/// How a deposit to a blocked recipient is handled.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum DepositBounce<AccountId> {
+	/// The deposit fails with `RecipientBlocked`.
+	Fail,
+	/// The deposit is made to the bounce account instead.
+	BounceTo(AccountId),
+}
+
+impl<AccountId> Default for DepositBounce<AccountId> {
+	fn default() -> Self {
+		DepositBounce::Fail
+	}
+}
+
+/// How locks exceeding the total balance of an account, which could never be enforced in full, are handled.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum StaleLockPolicy {
+	/// The locks are kept.
+	Retain,
+	/// The locks are removed when the free or reserved balance of the account changes.
+	Remove,
+}
+
+impl Default for StaleLockPolicy {
+	fn default() -> Self {
+		StaleLockPolicy::Retain
+	}
+}
+
+/// How transfer amounts which are not a multiple of the granularity of a token type are handled.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum GranularityPolicy {
+	/// The transfer fails with `InvalidGranularity`.
+	Reject,
+	/// The amount is rounded down to a multiple of the granularity.
+	RoundDown,
+}
+
+impl Default for GranularityPolicy {
+	fn default() -> Self {
+		GranularityPolicy::Reject
+	}
+}
+
+/// How the removal of dust is reported by events.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum DustEventMode {
+	/// `DustLost` is deposited for each account dust is removed from.
+	PerAccount,
+	/// `DustConsolidated` is deposited once at the end of each block with dust removed, with the total dust removed
+	/// in the block by token type.
+	Consolidated,
+}
+
+impl Default for DustEventMode {
+	fn default() -> Self {
+		DustEventMode::PerAccount
+	}
+}
+
+/// How balances slashed from accounts are accounted for.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum SlashAccountingMode<AccountId> {
+	/// The slashed balance is burned, reducing the total issuance.
+	ReduceIssuance,
+	/// The slashed balance is credited to the free balance of the accounting account, and the total issuance is
+	/// unchanged. The account should be one of `ProtectedAccounts`, so that slashes below the existential deposit
+	/// are not lost as dust.
+	TrackInAccount(AccountId),
+}
+
+impl<AccountId> Default for SlashAccountingMode<AccountId> {
+	fn default() -> Self {
+		SlashAccountingMode::ReduceIssuance
+	}
+}
+
+/// A planned operation on the free balance of an account, for `required_balance`.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum BalanceOp<Balance> {
+	/// Withdraw the amount from the free balance.
+	Withdraw(Balance),
+	/// Reserve the amount from the free balance.
+	Reserve(Balance),
+	/// Keep the account alive, so the existential deposit must remain in the free balance.
+	KeepAlive,
+}
+
+/// The identifier of a reserve released at a deadline unless claimed.
+pub type DeadlineReserveId = u32;
+
+/// Storage releases of the tokens module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+	/// The original storage layout.
+	V1_0_0,
+	/// Accounts are indexed by `AccountCurrencies`.
+	V2_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
 	}
 }
 
+/// The progress of backfilling the counters and indexes added by `Releases::V2_0_0` after a runtime upgrade, by the
+/// raw storage key of the last entry processed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum BackfillCursor {
+	/// Backfilling `GenesisIssuance`, `CreatedCurrencies`, `CurrencyCount` and `LiveCurrencyCount` from
+	/// `TotalIssuance`.
+	Currencies(Vec<u8>),
+	/// Backfilling `AccountCurrencies` and `HolderCount` from `Accounts`.
+	Accounts(Vec<u8>),
+}
+
+/// An operation over all accounts of a token type, processed over multiple blocks by `on_initialize`.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum AccountMigration {
+	/// Scale the free and reserved balances, named reserves and locks by `(numerator, denominator)`, rounded down.
+	/// The existential deposit is not enforced.
+	Redenominate(u32, u32),
+	/// Remove free balances below the existential deposit as dust.
+	SweepDust,
+}
+
 decl_storage! {
 	trait Store for Module<T: Trait> as Tokens {
 		/// The total issuance of a token type.
@@ -135,24 +547,173 @@ decl_storage! {
 				.collect::<Vec<_>>()
 		}): map hasher(twox_64_concat) T::CurrencyId => T::Balance;
 
+		/// The total issuance of a token type at genesis.
+		pub GenesisIssuance get(fn genesis_issuance): map hasher(twox_64_concat) T::CurrencyId => T::Balance;
+
 		/// Any liquidity locks of a token type under an account.
 		/// NOTE: Should only be accessed when setting, changing and freeing a lock.
 		pub Locks get(fn locks): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => Vec<BalanceLock<T::Balance>>;
 
+		/// The withdraw reasons of the locks of a token type under an account which don't apply to all reasons, as
+		/// set by `set_lock_with_reasons`.
+		pub LockReasons get(fn lock_reasons): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => Vec<(LockIdentifier, WithdrawReasons)>;
+
 		/// The balance of a token type under an account.
 		///
 		/// NOTE: If the total is ever zero, decrease account ref account.
 		///
 		/// NOTE: This is only used in the case that this module is used to store balances.
 		pub Accounts get(fn accounts): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => AccountData<T::Balance>;
+
+		/// The token types an account has a non-zero total balance of, as an index of `Accounts` by account.
+		pub AccountCurrencies get(fn account_currencies): double_map hasher(blake2_128_concat) T::AccountId, hasher(twox_64_concat) T::CurrencyId => bool;
+
+		/// The number of accounts with a non-zero total balance of a token type.
+		pub HolderCount get(fn holder_count): map hasher(twox_64_concat) T::CurrencyId => u32;
+
+		/// Whether a token type has ever had a non-zero total issuance.
+		pub CreatedCurrencies get(fn is_created_currency): map hasher(twox_64_concat) T::CurrencyId => bool;
+
+		/// The number of token types that have ever had a non-zero total issuance.
+		pub CurrencyCount get(fn currency_count): u32;
+
+		/// The number of token types with a non-zero total issuance.
+		pub LiveCurrencyCount get(fn live_currency_count): u32;
+
+		/// Named reserves of a token type under an account.
+		pub Reserves get(fn reserves): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => Vec<ReserveData<T::ReserveIdentifier, T::Balance>>;
+
+		/// The label of a named reserve of a token type under an account, describing what it's held for.
+		pub ReserveLabels get(fn reserve_label): double_map hasher(blake2_128_concat) (T::CurrencyId, T::AccountId), hasher(blake2_128_concat) T::ReserveIdentifier => Option<Vec<u8>>;
+
+		/// The reserved balance an account must maintain under a token type, e.g. a validator bond.
+		pub MinimumReserves get(fn minimum_reserve_requirement): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => T::Balance;
+
+		/// Whether an emergency shutdown is active, and all transfers, deposits, withdrawals, reserves and unreserves of
+		/// all token types are disabled.
+		pub ShutdownActive get(fn shutdown_active): bool;
+
+		/// Whether a token type is frozen, and all transfers, deposits, withdrawals and reserves of it are disabled.
+		pub FrozenCurrencies get(fn frozen_currencies): map hasher(twox_64_concat) T::CurrencyId => bool;
+
+		/// The progress of rebalancing total issuance of a token type, as the raw storage key of the last account
+		/// iterated and the sum of the balances of the accounts iterated.
+		pub RebalanceCursor get(fn rebalance_cursor): map hasher(twox_64_concat) T::CurrencyId => Option<(Vec<u8>, T::Balance)>;
+
+		/// The metadata of a token type.
+		pub CurrencyMetadata get(fn get_currency_metadata): map hasher(twox_64_concat) T::CurrencyId => Option<TokenMetadata>;
+
+		/// The maximum total issuance of a token type, enforced on every deposit.
+		pub TotalIssuanceCaps get(fn total_issuance_cap): map hasher(twox_64_concat) T::CurrencyId => Option<T::Balance>;
+
+		/// The admin account of a token type, from which any issuance above the cap is slashed.
+		pub CurrencyAdmins get(fn currency_admin): map hasher(twox_64_concat) T::CurrencyId => Option<T::AccountId>;
+
+		/// The root of the balance snapshot exported at a block.
+		pub SnapshotRoots get(fn snapshot_root): map hasher(twox_64_concat) T::BlockNumber => Option<T::Hash>;
+
+		/// The interest rate periodically accrued to all accounts of a token type, and the period in blocks.
+		pub InterestSchedules get(fn interest_schedule): map hasher(twox_64_concat) T::CurrencyId => Option<(Permill, T::BlockNumber)>;
+
+		/// The pending account migration of a token type, with the number of accounts processed.
+		pub PendingMigrations get(fn pending_migration): map hasher(twox_64_concat) T::CurrencyId => Option<(AccountMigration, u32)>;
+
+		/// The raw storage key of the last account processed by the pending account migration of a token type, to
+		/// resume from.
+		pub MigrationCursor get(fn migration_cursor): map hasher(twox_64_concat) T::CurrencyId => Option<Vec<u8>>;
+
+		/// The interest rate being accrued to all accounts of a token type by its interest schedule, with the number
+		/// of accounts processed.
+		pub InterestAccruals get(fn interest_accrual): map hasher(twox_64_concat) T::CurrencyId => Option<(Permill, u32)>;
+
+		/// The raw storage key of the last account processed by the interest accrual of a token type, to resume from.
+		pub InterestAccrualCursor get(fn interest_accrual_cursor): map hasher(twox_64_concat) T::CurrencyId => Option<Vec<u8>>;
+
+		/// The free balance of an account under a token type at each block it changed, in ascending order, at most
+		/// `MaxBalanceHistory` of them. Not recorded if `MaxBalanceHistory` is zero.
+		pub BalanceHistory get(fn balance_history): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => Vec<(T::BlockNumber, T::Balance)>;
+
+		/// The free balance of an account under a token type at snapshot blocks, every `AccountSnapshotPeriod`, in
+		/// ascending order, at most `MaxAccountSnapshots` of them. Snapshot blocks without changes of the balance since
+		/// the previous one are skipped.
+		pub AccountSnapshots get(fn account_snapshots): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => Vec<(T::BlockNumber, T::Balance)>;
+
+		/// The next deadline reserve ID.
+		pub NextDeadlineReserveId get(fn next_deadline_reserve_id): DeadlineReserveId;
+
+		/// A reserve released back to its owner at its deadline unless claimed before, as
+		/// `(currency_id, owner, amount, deadline)`.
+		pub DeadlineReserves get(fn deadline_reserve): map hasher(twox_64_concat) DeadlineReserveId => Option<(T::CurrencyId, T::AccountId, T::Balance, T::BlockNumber)>;
+
+		/// The deadline reserves released at a block unless claimed before.
+		pub ReserveDeadlines get(fn reserve_deadlines): map hasher(twox_64_concat) T::BlockNumber => Vec<DeadlineReserveId>;
+
+		/// The block at which a lock of a token type under an account expires and is removed, if set with one.
+		pub LockExpiries get(fn lock_expiry): double_map hasher(blake2_128_concat) (T::CurrencyId, T::AccountId), hasher(twox_64_concat) LockIdentifier => Option<T::BlockNumber>;
+
+		/// The accounts reaped under a token type whose account data is removed at a block, unless credited again.
+		pub ReapQueue get(fn reap_queue): map hasher(twox_64_concat) T::BlockNumber => Vec<(T::CurrencyId, T::AccountId)>;
+
+		/// The locks expiring at a block, as `(currency_id, who, lock_id)`.
+		pub ExpiringLocks get(fn expiring_locks): map hasher(twox_64_concat) T::BlockNumber => Vec<(T::CurrencyId, T::AccountId, LockIdentifier)>;
+
+		/// The exchange rate of a token type to the native currency. One if not set.
+		pub ExchangeRates get(fn exchange_rate): map hasher(twox_64_concat) T::CurrencyId => Option<FixedU128>;
+
+		/// The total balance of an account in native-equivalent units, with the block it was computed at.
+		///
+		/// NOTE: Removed when the balance of the account under any token type changes, and at the end of the block.
+		pub NativeEquivalentCache get(fn native_equivalent_cache): map hasher(blake2_128_concat) T::AccountId => Option<(T::BlockNumber, T::Balance)>;
+
+		/// The accounts with an entry in `NativeEquivalentCache`, to be removed at the end of the block.
+		pub NativeEquivalentCachedAccounts get(fn native_equivalent_cached_accounts): Vec<T::AccountId>;
+
+		/// The total dust removed in the current block by token type, under `DustEventMode::Consolidated`. Taken on
+		/// finalize to deposit `DustConsolidated`.
+		pub BlockDust get(fn block_dust): Vec<(T::CurrencyId, T::Balance)>;
+
+		/// The free balance of an account under a token type recorded by `checkpoint_balance`, with the block it was
+		/// recorded at.
+		pub BalanceCheckpoints get(fn balance_checkpoint): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => Option<(T::BlockNumber, T::Balance)>;
+
+		/// Whether transfers of a token type are restricted to senders in `SendAllowlist` and the currency admin.
+		pub SendRestrictedCurrencies get(fn send_restricted): map hasher(twox_64_concat) T::CurrencyId => bool;
+
+		/// The accounts allowed to send a token type, if it's restricted by `SendRestrictedCurrencies`.
+		pub SendAllowlist get(fn send_allowed): double_map hasher(twox_64_concat) T::CurrencyId, hasher(blake2_128_concat) T::AccountId => bool;
+
+		/// Whether a token type doesn't support reserves, as configured at genesis.
+		pub NonReservableCurrencies get(fn non_reservable_currencies): map hasher(twox_64_concat) T::CurrencyId => bool;
+
+		/// The purpose a lock identifier is registered to. Locks with a registered identifier could only be set,
+		/// extended or removed by `set_lock_as`, `extend_lock_as` and `remove_lock_as` with the purpose.
+		pub LockOwners get(fn lock_owner): map hasher(twox_64_concat) LockIdentifier => Option<T::LockPurpose>;
+
+		/// The last nonce of relayed transfers of a token type by an account.
+		pub Nonces get(fn nonce): map hasher(blake2_128_concat) (T::CurrencyId, T::AccountId) => u64;
+
+		/// The storage release of this module, to migrate storage from on runtime upgrade.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V2_0_0): Releases;
+
+		/// The progress of the backfill started by the runtime upgrade to `Releases::V2_0_0`, if not completed.
+		pub PendingBackfill get(fn pending_backfill): Option<BackfillCursor>;
 	}
 	add_extra_genesis {
 		config(endowed_accounts): Vec<(T::AccountId, T::CurrencyId, T::Balance)>;
+		config(non_reservable_currencies): Vec<T::CurrencyId>;
 
 		build(|config: &GenesisConfig<T>| {
 			config.endowed_accounts.iter().for_each(|(account_id, currency_id, initial_balance)| {
-				<Accounts<T>>::mutate(currency_id, account_id, |account_data| account_data.free = *initial_balance)
-			})
+				<Module<T>>::mutate_account(*currency_id, account_id, |account_data| account_data.free = *initial_balance);
+				<Module<T>>::record_free_balance(*currency_id, account_id);
+			});
+			<TotalIssuance<T> as IterableStorageMap<_, _>>::iter().for_each(|(currency_id, issuance)| {
+				<GenesisIssuance<T>>::insert(currency_id, issuance);
+				<Module<T>>::note_issuance_change(currency_id, Zero::zero(), issuance);
+			});
+			config.non_reservable_currencies.iter().for_each(|currency_id| {
+				<NonReservableCurrencies<T>>::insert(currency_id, true);
+			});
 		})
 	}
 }
@@ -161,10 +722,118 @@ decl_event!(
 	pub enum Event<T> where
 		<T as frame_system::Trait>::AccountId,
 		<T as Trait>::CurrencyId,
-		<T as Trait>::Balance
+		<T as Trait>::Balance,
+		<T as Trait>::Amount,
+		<T as Trait>::MultiLocation,
+		<T as Trait>::ReserveIdentifier,
+		<T as Trait>::LockPurpose,
+		<T as frame_system::Trait>::BlockNumber,
+		<T as frame_system::Trait>::Hash
 	{
 		/// Token transfer success (currency_id, from, to, amount)
 		Transferred(CurrencyId, AccountId, AccountId, Balance),
+		/// Transfer with the recipient receiving an exact amount success (currency_id, from, to, received, gross)
+		TransferredExactOut(CurrencyId, AccountId, AccountId, Balance, Balance),
+		/// Transfer fee charged (currency_id, from, burned, to_treasury)
+		TransferFeeCharged(CurrencyId, AccountId, Balance, Balance),
+		/// Some balance was reserved under a named identifier (currency_id, who, reserve_id, amount)
+		ReservedNamed(CurrencyId, AccountId, ReserveIdentifier, Balance),
+		/// Total issuance rebalanced to the sum of account balances (currency_id, old_value, new_value)
+		TotalIssuanceRebalanced(CurrencyId, Balance, Balance),
+		/// Reserved balance repatriated (currency_id, from, to, amount, status)
+		ReserveRepatriated(CurrencyId, AccountId, AccountId, Balance, BalanceStatus),
+		/// Some balance was reserved (currency_id, who, amount)
+		Reserved(CurrencyId, AccountId, Balance),
+		/// Some balance was unreserved (currency_id, who, amount)
+		Unreserved(CurrencyId, AccountId, Balance),
+		/// Token type frozen (currency_id)
+		CurrencyFrozen(CurrencyId),
+		/// Token type unfrozen (currency_id)
+		CurrencyUnfrozen(CurrencyId),
+		/// Emergency shutdown activated
+		EmergencyShutdownActivated,
+		/// Emergency shutdown deactivated
+		EmergencyShutdownDeactivated,
+		/// Currency metadata set (currency_id, metadata)
+		MetadataSet(CurrencyId, TokenMetadata),
+		/// Currency admin set (currency_id, admin)
+		CurrencyAdminSet(CurrencyId, Option<AccountId>),
+		/// Total issuance cap enforced, slashing the excess from the currency admin (currency_id, excess)
+		IssuanceCapEnforced(CurrencyId, Balance),
+		/// Interest accrued to holders, with the rounding dust credited to treasury (currency_id, rate, minted, dust)
+		InterestAccrued(CurrencyId, Permill, Balance, Balance),
+		/// Interest schedule set (currency_id, Option<(rate, period)>)
+		InterestScheduleSet(CurrencyId, Option<(Permill, BlockNumber)>),
+		/// Token teleported out to another chain (currency_id, from, amount, dest_chain)
+		TeleportSent(CurrencyId, AccountId, Balance, MultiLocation),
+		/// Token teleported in from another chain (currency_id, to, amount, origin_chain)
+		TeleportReceived(CurrencyId, AccountId, Balance, MultiLocation),
+		/// Free balance brought to a target by depositing or withdrawing the difference (currency_id, who, old, new)
+		TargetBalanceSet(CurrencyId, AccountId, Balance, Balance),
+		/// Reserved balance brought to a target by reserving or unreserving the difference (currency_id, who, old, new)
+		ReservedBalanceSet(CurrencyId, AccountId, Balance, Balance),
+		/// Balance updated by an administrative adjustment, allowing a balance below the existential deposit
+		/// (currency_id, who, amount)
+		BalanceUpdated(CurrencyId, AccountId, Amount),
+		/// Balance migrated to another token type (from_currency, to_currency, who, old_total, new_total)
+		BalanceMigrated(CurrencyId, CurrencyId, AccountId, Balance, Balance),
+		/// Balance snapshot root submitted (block, root_hash)
+		SnapshotRootSubmitted(BlockNumber, Hash),
+		/// Account migration started (currency_id, migration)
+		MigrationStarted(CurrencyId, AccountMigration),
+		/// Account migration progressed in a block (currency_id, processed)
+		MigrationProgress(CurrencyId, u32),
+		/// Account migration completed (currency_id)
+		MigrationCompleted(CurrencyId),
+		/// Locks with the same lock ID merged (currency_id, who, old_count, new_count)
+		LocksDeduplicated(CurrencyId, AccountId, u32, u32),
+		/// Reserve made with a deadline (id, currency_id, owner, amount, deadline)
+		DeadlineReserveCreated(DeadlineReserveId, CurrencyId, AccountId, Balance, BlockNumber),
+		/// Deadline reserve claimed before its deadline (id, beneficiary, amount)
+		DeadlineReserveClaimed(DeadlineReserveId, AccountId, Balance),
+		/// Deadline reserve released back to its owner at its deadline (id, owner, amount)
+		DeadlineReserveReleased(DeadlineReserveId, AccountId, Balance),
+		/// Lock removed at its expiry (currency_id, who, lock_id)
+		LockExpired(CurrencyId, AccountId, LockIdentifier),
+		/// Transfer of a lenient batch failed (index, error)
+		TransferFailed(u32, DispatchError),
+		/// Transfer of a token type swept by `transfer_all_matching` failed (currency_id, error)
+		SweepFailed(CurrencyId, DispatchError),
+		/// Deposit to a blocked recipient made to the bounce account instead (currency_id, recipient, bounce_account,
+		/// amount)
+		DepositBounced(CurrencyId, AccountId, AccountId, Balance),
+		/// Locks exceeding the total balance removed (currency_id, who, count)
+		StaleLocksRemoved(CurrencyId, AccountId, u32),
+		/// A lock was forcibly removed (currency_id, who, lock_id)
+		LockRemoved(CurrencyId, AccountId, LockIdentifier),
+		/// Lock identifier registered to a purpose, or unregistered if `None` (lock_id, purpose)
+		LockIdRegistered(LockIdentifier, Option<LockPurpose>),
+		/// Exchange rate to the native currency set (currency_id, rate)
+		ExchangeRateSet(CurrencyId, Option<FixedU128>),
+		/// Send restriction of a token type set (currency_id, restricted)
+		SendRestrictionSet(CurrencyId, bool),
+		/// Sender allowlist of a token type updated (currency_id, who, allowed)
+		SendAllowlistUpdated(CurrencyId, AccountId, bool),
+		/// Minimum reserve requirement of an account set (currency_id, who, amount)
+		MinimumReserveRequirementSet(CurrencyId, AccountId, Balance),
+		/// Transferred amount debited from the sender, under `EventModel::DoubleEntry` (currency_id, who, amount)
+		Debited(CurrencyId, AccountId, Balance),
+		/// Transferred amount credited to the recipient, under `EventModel::DoubleEntry` (currency_id, who, amount)
+		Credited(CurrencyId, AccountId, Balance),
+		/// Lock set on a token type by `set_lock_multi` (currency_id, who, lock_id, amount)
+		LockSet(CurrencyId, AccountId, LockIdentifier, Balance),
+		/// Dust removed from an account, under `DustEventMode::PerAccount` (currency_id, who, amount)
+		DustLost(CurrencyId, AccountId, Balance),
+		/// Dust removed in a block, under `DustEventMode::Consolidated` (total_by_currency)
+		DustConsolidated(Vec<(CurrencyId, Balance)>),
+		/// Service fee charged (currency_id, from, amount)
+		ServiceFeeCharged(CurrencyId, AccountId, Balance),
+		/// New balance deposited directly into the reserved balance (currency_id, who, amount)
+		ReservedDeposited(CurrencyId, AccountId, Balance),
+		/// Scheduled interest accrual to all accounts started (currency_id, rate)
+		InterestAccrualStarted(CurrencyId, Permill),
+		/// Scheduled interest accrual to all accounts completed (currency_id, processed)
+		InterestAccrualCompleted(CurrencyId, u32),
 	}
 );
 
@@ -174,7 +843,48 @@ decl_module! {
 
 		fn deposit_event() = default;
 
+		/// The maximum number of named reserves an account could have under a token type.
+		const MaxReserves: u32 = T::MaxReserves::get();
+
+		/// The maximum length of a named reserve label.
+		const MaxLabelLen: u32 = T::MaxLabelLen::get();
+
+		/// The maximum length of currency metadata name, symbol and description.
+		const MaxNameLen: u32 = T::MaxNameLen::get();
+
+		/// The maximum number of decimals in currency metadata.
+		const MaxDecimals: u8 = T::MaxDecimals::get();
+
+		/// The maximum number of accounts a lock could be removed from at once by `remove_lock_all`.
+		const MaxLockRemovals: u32 = T::MaxLockRemovals::get();
+
+		/// The maximum number of holders interest could be accrued to at once by `accrue_interest`.
+		const MaxInterestHolders: u32 = T::MaxInterestHolders::get();
+
+		/// The period in blocks of balance snapshots exported by the off-chain worker.
+		const SnapshotBlock: T::BlockNumber = T::SnapshotBlock::get();
+
+		/// The period in blocks of per-account balance snapshots.
+		const AccountSnapshotPeriod: T::BlockNumber = T::AccountSnapshotPeriod::get();
+
+		/// The maximum number of per-account balance snapshots retained for an account under a token type.
+		const MaxAccountSnapshots: u32 = T::MaxAccountSnapshots::get();
+
+		/// The maximum number of balance changes retained in `BalanceHistory` for an account under a token type.
+		const MaxBalanceHistory: u32 = T::MaxBalanceHistory::get();
+
+		/// The maximum number of reaped accounts whose account data is removed per block.
+		const MaxReapsPerBlock: u32 = T::MaxReapsPerBlock::get();
+
+		/// The maximum number of accounts processed by account migrations and scheduled interest accruals per block.
+		const MigrationBatchSize: u32 = T::MigrationBatchSize::get();
+
+		/// The maximum number of transfers in a batch of `transfer_batch_lenient`.
+		const MaxBatchTransfers: u32 = T::MaxBatchTransfers::get();
+
 		/// Transfer some balance to another account.
+		///
+		/// The transfer fee is charged out of `amount`.
 		pub fn transfer(
 			origin,
 			dest: <T::Lookup as StaticLookup>::Source,
@@ -183,12 +893,13 @@ decl_module! {
 		) {
 			let from = ensure_signed(origin)?;
 			let to = T::Lookup::lookup(dest)?;
-			<Self as MultiCurrency<_>>::transfer(currency_id, &from, &to, amount)?;
-
-			Self::deposit_event(RawEvent::Transferred(currency_id, from, to, amount));
+			Self::transfer_with_fee(currency_id, from, to, amount)?;
 		}
 
 		/// Transfer all remaining balance to the given account.
+		///
+		/// The transfer fee is charged out of the transferred balance. Under
+		/// `TransferAllPolicy::KeepPendingWithdrawals`, the balance committed to `PendingWithdrawals` is kept back.
 		pub fn transfer_all(
 			origin,
 			dest: <T::Lookup as StaticLookup>::Source,
@@ -196,72 +907,3031 @@ decl_module! {
 		) {
 			let from = ensure_signed(origin)?;
 			let to = T::Lookup::lookup(dest)?;
-			let balance = <Self as MultiCurrency<T::AccountId>>::free_balance(currency_id, &from);
-			<Self as MultiCurrency<T::AccountId>>::transfer(currency_id, &from, &to, balance)?;
+			let mut balance = <Self as MultiCurrency<T::AccountId>>::free_balance(currency_id, &from)
+				.saturating_sub(Self::service_fee(currency_id, &from, &to));
+			if T::TransferAllPolicy::get() == TransferAllPolicy::KeepPendingWithdrawals {
+				balance = balance.saturating_sub(T::PendingWithdrawals::pending_withdrawals(currency_id, &from));
+			}
+			Self::transfer_with_fee(currency_id, from, to, balance)?;
+		}
+
+		/// Transfer some balance to another account, as a relayed intent with `nonce`, which must be one above the
+		/// last nonce of the sender under `currency_id`.
+		///
+		/// The transfer fee is charged out of `amount`.
+		pub fn transfer_with_nonce(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			currency_id: T::CurrencyId,
+			#[compact] amount: T::Balance,
+			nonce: u64,
+		) {
+			let from = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(dest)?;
+			let key = (currency_id, from.clone());
+			ensure!(Some(nonce) == Self::nonce(&key).checked_add(1), Error::<T>::InvalidNonce);
 
-			Self::deposit_event(RawEvent::Transferred(currency_id, from, to, balance));
+			Self::transfer_with_fee(currency_id, from, to, amount)?;
+			<Nonces<T>>::insert(&key, nonce);
 		}
-	}
-}
 
-decl_error! {
-	/// Error for token module.
-	pub enum Error for Module<T: Trait> {
-		BalanceTooLow,
-		TotalIssuanceOverflow,
-		AmountIntoBalanceFailed,
-		ExistentialDeposit,
-		LiquidityRestrictions,
-	}
-}
+		/// Submit a transfer intent signed off-chain by its `from` account, as a relayer.
+		///
+		/// The signature is checked against `intent_signing_payload`, its nonce must be the next one of `from` as in
+		/// `transfer_with_nonce`, and it's rejected after its deadline block. The full amount is transferred, and the
+		/// transfer fee is charged to the relayer.
+		pub fn submit_transfer_intent(
+			origin,
+			intent: SignedTransferIntent<T::AccountId, T::CurrencyId, T::Balance, T::BlockNumber>,
+			signature: T::IntentSignature,
+		) {
+			let relayer = ensure_signed(origin)?;
+			ensure!(
+				signature.verify(&Self::intent_signing_payload(&intent)[..], &intent.from),
+				Error::<T>::BadSignature
+			);
+			ensure!(<system::Module<T>>::block_number() <= intent.deadline, Error::<T>::IntentExpired);
+			let key = (intent.currency_id, intent.from.clone());
+			ensure!(Some(intent.nonce) == Self::nonce(&key).checked_add(1), Error::<T>::InvalidNonce);
 
-impl<T: Trait> Module<T> {
-	/// Set free balance of `who` to a new value, meanwhile enforce existential rule.
-	///
-	/// Note this will not maintain total issuance except balance is less to ExistentialDeposit,
-	/// and the caller is expected to do it.
-	fn set_free_balance(currency_id: T::CurrencyId, who: &T::AccountId, balance: T::Balance) {
-		if balance < T::ExistentialDeposit::get() {
-			<Accounts<T>>::mutate(currency_id, who, |account_data| account_data.free = Zero::zero());
-			T::DustRemoval::on_dust_removal(balance);
-			<TotalIssuance<T>>::mutate(currency_id, |v| *v -= balance);
+			with_transaction_result(|| {
+				<Nonces<T>>::insert(&key, intent.nonce);
+				Self::transfer_with_relayer_fee(intent.currency_id, intent.from, intent.to, intent.amount, &relayer)
+			})?;
+		}
+
+		/// Transfer balances to multiple accounts, never failing on a single transfer.
+		///
+		/// Each transfer is attempted in turn, depositing `Transferred` on success, or `TransferFailed` with its
+		/// index on failure, in which case none of its changes are applied, and the other transfers are not affected.
+		/// The transfer fee is charged out of each amount. At most `MaxBatchTransfers` transfers could be made at once.
+		pub fn transfer_batch_lenient(
+			origin,
+			transfers: Vec<(<T::Lookup as StaticLookup>::Source, T::CurrencyId, T::Balance)>,
+		) {
+			let from = ensure_signed(origin)?;
+			ensure!(
+				transfers.len() <= T::MaxBatchTransfers::get() as usize,
+				Error::<T>::TooManyTransfers
+			);
+
+			for (index, (dest, currency_id, amount)) in transfers.into_iter().enumerate() {
+				let result = T::Lookup::lookup(dest)
+					.map_err(DispatchError::from)
+					.and_then(|to| {
+						with_transaction_result(|| Self::transfer_with_fee(currency_id, from.clone(), to, amount))
+					});
+				if let Err(e) = result {
+					Self::emit_event(RawEvent::TransferFailed(index as u32, e));
+				}
+			}
+		}
+
+		/// Split `total` among `recipients` in proportion to their weights, and transfer each share to its
+		/// recipient.
+		///
+		/// Shares are split by `split_by_weight`, and each one deposits `Transferred`. Either all shares are
+		/// transferred, or none if any of them fails. At most `MaxBatchTransfers` recipients could be paid at once.
+		/// The transfer fee is charged out of each share.
+		pub fn transfer_split(
+			origin,
+			currency_id: T::CurrencyId,
+			#[compact] total: T::Balance,
+			recipients: Vec<(<T::Lookup as StaticLookup>::Source, u32)>,
+		) {
+			let from = ensure_signed(origin)?;
+			ensure!(
+				recipients.len() <= T::MaxBatchTransfers::get() as usize,
+				Error::<T>::TooManyRecipients
+			);
+
+			let (dests, weights): (Vec<_>, Vec<_>) = recipients.into_iter().unzip();
+			let shares = Self::split_by_weight(total, &weights).ok_or(Error::<T>::ZeroTotalWeight)?;
+			let dests = dests
+				.into_iter()
+				.map(T::Lookup::lookup)
+				.collect::<rstd::result::Result<Vec<_>, _>>()?;
+			<Self as MultiCurrency<_>>::ensure_can_withdraw(currency_id, &from, total)?;
+
+			with_transaction_result(|| {
+				for (to, share) in dests.into_iter().zip(shares) {
+					if !share.is_zero() {
+						Self::transfer_with_fee(currency_id, from.clone(), to, share)?;
+					}
+				}
+				Ok(())
+			})?;
+		}
+
+		/// Transfer the liquid balance of each token type held by the caller and selected by `filter` to another
+		/// account, never failing on a single token type.
+		///
+		/// Each transfer is attempted in turn, depositing `Transferred` on success, or `SweepFailed` on failure. If
+		/// none succeeded, fails with the error of the first failed one. At most `MaxBatchTransfers` selected token
+		/// types held by the caller could be swept at once, including those with no liquid balance. The transfer fee
+		/// is charged out of each amount, and transfers routed to `NativeCurrency` are not included.
+		///
+		/// NOTE: This iterates over the token types held by the caller until more than `MaxBatchTransfers` are
+		/// selected.
+		pub fn transfer_all_matching(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			filter: T::CurrencyFilter,
+		) {
+			let from = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(dest)?;
+			let max_sweeps = T::MaxBatchTransfers::get() as usize;
+			let selected = <AccountCurrencies<T> as IterableStorageDoubleMap<_, _, _>>::iter(&from)
+				.map(|(currency_id, _)| currency_id)
+				.filter(|currency_id| filter.matches(currency_id) && !Self::is_native(*currency_id))
+				.take(max_sweeps + 1)
+				.collect::<Vec<_>>();
+			ensure!(selected.len() <= max_sweeps, Error::<T>::TooManyCurrencies);
+
+			let mut swept = false;
+			let mut first_error = None;
+			for currency_id in selected {
+				let amount = Self::accounts(currency_id, &from).spendable();
+				if amount.is_zero() {
+					continue;
+				}
+				match Self::transfer_with_fee(currency_id, from.clone(), to.clone(), amount) {
+					Ok(()) => swept = true,
+					Err(e) => {
+						first_error.get_or_insert(e);
+						Self::emit_event(RawEvent::SweepFailed(currency_id, e));
+					}
+				}
+			}
+			if let (false, Some(e)) = (swept, first_error) {
+				return Err(e);
+			}
+		}
+
+		/// Transfer some balance to another account, and reserve it at `dest` under `reserve_id`.
+		///
+		/// The transfer is made as by the `transfer` call, and the amount `dest` receives after the transfer fee is
+		/// then reserved, so the free balance of `dest` is not changed. Both are applied in a storage transaction,
+		/// which is rolled back if either fails.
+		pub fn transfer_and_reserve_named(
+			origin,
+			dest: <T::Lookup as StaticLookup>::Source,
+			currency_id: T::CurrencyId,
+			#[compact] amount: T::Balance,
+			reserve_id: T::ReserveIdentifier,
+		) {
+			let from = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(dest)?;
+			Self::ensure_not_frozen(currency_id)?;
+			Self::ensure_reservable(currency_id)?;
+			Self::ensure_can_add_reserve_named(&reserve_id, currency_id, &to)?;
+
+			let received = with_transaction_result(|| {
+				let to_balance = <Self as MultiCurrency<_>>::free_balance(currency_id, &to);
+				Self::transfer_with_fee(currency_id, from, to.clone(), amount)?;
+				let received = <Self as MultiCurrency<_>>::free_balance(currency_id, &to).saturating_sub(to_balance);
+				<Self as NamedMultiReservableCurrency<_>>::reserve_named(&reserve_id, currency_id, &to, received)?;
+				Ok(received)
+			})?;
+
+			Self::emit_event(RawEvent::ReservedNamed(currency_id, to, reserve_id, received));
+		}
+
+		/// Teleport some balance of `from` out to `dest_chain`, by burning it on this chain.
+		///
+		/// The dispatch origin of this call must be `TeleportOrigin`.
+		pub fn teleport_out(
+			origin,
+			currency_id: T::CurrencyId,
+			from: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance,
+			dest_chain: T::MultiLocation,
+		) {
+			T::TeleportOrigin::ensure_origin(origin)?;
+			let from = T::Lookup::lookup(from)?;
+			Self::ensure_not_migrating(currency_id)?;
+			<Self as MultiCurrency<_>>::withdraw(currency_id, &from, amount)?;
+
+			Self::emit_event(RawEvent::TeleportSent(currency_id, from, amount, dest_chain));
+		}
+
+		/// Teleport some balance in to `to` from `origin_chain`, by minting it on this chain.
+		///
+		/// The dispatch origin of this call must be `TeleportOrigin`.
+		pub fn teleport_in(
+			origin,
+			currency_id: T::CurrencyId,
+			to: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance,
+			origin_chain: T::MultiLocation,
+		) {
+			T::TeleportOrigin::ensure_origin(origin)?;
+			let to = T::Lookup::lookup(to)?;
+			Self::ensure_not_migrating(currency_id)?;
+			<Self as MultiCurrency<_>>::deposit(currency_id, &to, amount)?;
+
+			Self::emit_event(RawEvent::TeleportReceived(currency_id, to, amount, origin_chain));
+		}
+
+		/// Bring the free balance of `who` to `target`, by depositing or withdrawing the difference.
+		///
+		/// The deposit or withdrawal is subject to the same rules as `MultiCurrency::deposit` and
+		/// `MultiCurrency::withdraw`. Fails with `ExistentialDeposit` if `target` is non-zero and below the existential
+		/// deposit, and is a no-op if the free balance is already `target`. `TargetBalanceSet` is only deposited if the
+		/// free balance did reach `target`, which it doesn't if the deposit is bounced to `DepositBounce`.
+		///
+		/// The dispatch origin of this call must be `TargetBalanceOrigin`.
+		pub fn set_target_balance(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+			#[compact] target: T::Balance,
+		) {
+			T::TargetBalanceOrigin::ensure_origin(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			Self::ensure_not_migrating(currency_id)?;
+			ensure!(
+				target.is_zero() || target >= T::ExistentialDeposit::get() || T::ProtectedAccounts::contains(&who),
+				Error::<T>::ExistentialDeposit,
+			);
+
+			let current = <Self as MultiCurrency<_>>::free_balance(currency_id, &who);
+			if target > current {
+				<Self as MultiCurrency<_>>::deposit(currency_id, &who, target - current)?;
+			} else if target < current {
+				<Self as MultiCurrency<_>>::withdraw(currency_id, &who, current - target)?;
+			} else {
+				return Ok(());
+			}
+
+			if <Self as MultiCurrency<_>>::free_balance(currency_id, &who) == target {
+				Self::emit_event(RawEvent::TargetBalanceSet(currency_id, who, current, target));
+			}
+		}
+
+		/// Bring the reserved balance of `who` to `target`, by reserving or unreserving the difference, as given by
+		/// `reserve_delta_to`.
+		///
+		/// The reserve is subject to the same rules as `MultiReservableCurrency::reserve`, and the unreserve fails
+		/// while the token type is frozen. Is a no-op if the reserved balance is already `target`.
+		///
+		/// The dispatch origin of this call must be `TargetBalanceOrigin`.
+		pub fn set_reserved_to(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+			#[compact] target: T::Balance,
+		) {
+			T::TargetBalanceOrigin::ensure_origin(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			Self::ensure_not_migrating(currency_id)?;
+
+			let current = Self::reserved_balance(currency_id, &who);
+			if target > current {
+				<Self as MultiReservableCurrency<_>>::reserve(currency_id, &who, target - current)?;
+			} else if target < current {
+				Self::ensure_not_frozen(currency_id)?;
+				let _ = <Self as MultiReservableCurrency<_>>::unreserve(currency_id, &who, current - target);
+			} else {
+				return Ok(());
+			}
+
+			Self::emit_event(RawEvent::ReservedBalanceSet(currency_id, who, current, target));
+		}
+
+		/// Update the balance of `who` under `currency_id` by `amount` like `MultiCurrencyExtended::update_balance`,
+		/// except that a positive `amount` is credited even if the free balance stays below the existential deposit.
+		///
+		/// For deliberate administrative adjustments. A balance below the existential deposit is kept until the free
+		/// balance next changes, when it's removed as dust as usual. The native currency is updated as by
+		/// `update_balance`.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn update_balance_allow_death(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+			amount: T::Amount,
+		) {
+			ensure_root(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			if amount.is_positive() && !Self::is_native(currency_id) {
+				let by_balance = TryInto::<T::Balance>::try_into(amount)
+					.map_err(|_| Error::<T>::AmountIntoBalanceFailed)?;
+				Self::deposit_allow_death(currency_id, &who, by_balance)?;
+			} else {
+				<Self as MultiCurrencyExtended<_>>::update_balance(currency_id, &who, amount)?;
+			}
+
+			Self::emit_event(RawEvent::BalanceUpdated(currency_id, who, amount));
+		}
+
+		/// Reconcile the total issuance of `currency_id` with the sum of all account balances, iterating up to `limit`
+		/// accounts per call. The progress is kept in `RebalanceCursor`, and total issuance is updated once all
+		/// accounts are iterated, so this could run over multiple blocks.
+		///
+		/// Accounts are iterated in storage key order, so balance changes of accounts already iterated while
+		/// rebalancing is in progress are missed.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn rebalance_total_issuance(origin, currency_id: T::CurrencyId, limit: u32) {
+			ensure_root(origin)?;
+
+			let (cursor, mut sum) = match Self::rebalance_cursor(currency_id) {
+				Some((cursor, sum)) => (Some(cursor), sum),
+				None => (None, Zero::zero()),
+			};
+			let cursor = Self::iter_accounts_from(currency_id, cursor, limit, |_, account_data| {
+				sum = sum.saturating_add(account_data.total());
+			});
+			if let Some(cursor) = cursor {
+				<RebalanceCursor<T>>::insert(currency_id, (cursor, sum));
+				return Ok(());
+			}
+			<RebalanceCursor<T>>::remove(currency_id);
+
+			let old_value = Self::total_issuance(currency_id);
+			if old_value != sum {
+				Self::mutate_total_issuance(currency_id, |v| *v = sum);
+				Self::emit_event(RawEvent::TotalIssuanceRebalanced(currency_id, old_value, sum));
+			}
+		}
+
+		/// Freeze a token type, disabling all transfers, deposits, withdrawals and reserves of it.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn freeze_currency(origin, currency_id: T::CurrencyId) {
+			ensure_root(origin)?;
+			<FrozenCurrencies<T>>::insert(currency_id, true);
+
+			Self::emit_event(RawEvent::CurrencyFrozen(currency_id));
+		}
+
+		/// Unfreeze a token type.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn unfreeze_currency(origin, currency_id: T::CurrencyId) {
+			ensure_root(origin)?;
+			<FrozenCurrencies<T>>::remove(currency_id);
+
+			Self::emit_event(RawEvent::CurrencyUnfrozen(currency_id));
+		}
+
+		/// Disable all transfers, deposits, withdrawals, reserves and unreserves of all token types.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn activate_emergency_shutdown(origin) {
+			ensure_root(origin)?;
+			ShutdownActive::put(true);
+
+			Self::emit_event(RawEvent::EmergencyShutdownActivated);
+		}
+
+		/// Lift the emergency shutdown.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn deactivate_emergency_shutdown(origin) {
+			ensure_root(origin)?;
+			ShutdownActive::kill();
+
+			Self::emit_event(RawEvent::EmergencyShutdownDeactivated);
+		}
+
+		/// Set the exchange rate of a token type to the native currency, or reset it to one if `None`.
+		///
+		/// Cached native-equivalent balances are not recomputed until the next block.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn set_exchange_rate(origin, currency_id: T::CurrencyId, rate: Option<FixedU128>) {
+			ensure_root(origin)?;
+			match rate {
+				Some(rate) => <ExchangeRates<T>>::insert(currency_id, rate),
+				None => <ExchangeRates<T>>::remove(currency_id),
+			}
+
+			Self::emit_event(RawEvent::ExchangeRateSet(currency_id, rate));
+		}
+
+		/// Set the metadata of a token type.
+		///
+		/// The dispatch origin of this call must be `MetadataOrigin`.
+		pub fn set_currency_metadata(origin, currency_id: T::CurrencyId, metadata: TokenMetadata) {
+			T::MetadataOrigin::ensure_origin(origin)?;
+			let max_len = T::MaxNameLen::get() as usize;
+			ensure!(
+				metadata.name.len() <= max_len
+					&& metadata.symbol.len() <= max_len
+					&& metadata.description.len() <= max_len,
+				Error::<T>::MetadataTooLong,
+			);
+			ensure!(!metadata.symbol.is_empty(), Error::<T>::EmptySymbol);
+			ensure!(metadata.decimals <= T::MaxDecimals::get(), Error::<T>::InvalidDecimals);
+			<CurrencyMetadata<T>>::insert(currency_id, &metadata);
+
+			Self::emit_event(RawEvent::MetadataSet(currency_id, metadata));
+		}
+
+		/// Set the admin account of a token type, or remove it if `None`.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn set_currency_admin(origin, currency_id: T::CurrencyId, admin: Option<T::AccountId>) {
+			ensure_root(origin)?;
+			match &admin {
+				Some(who) => <CurrencyAdmins<T>>::insert(currency_id, who),
+				None => <CurrencyAdmins<T>>::remove(currency_id),
+			}
+
+			Self::emit_event(RawEvent::CurrencyAdminSet(currency_id, admin));
+		}
+
+		/// Restrict transfers of a token type to senders in the allowlist and the currency admin, or lift the
+		/// restriction.
+		///
+		/// The dispatch origin of this call must be _Root_ or the currency admin.
+		pub fn set_send_restricted(origin, currency_id: T::CurrencyId, restricted: bool) {
+			Self::ensure_root_or_currency_admin(origin, currency_id)?;
+			if restricted {
+				<SendRestrictedCurrencies<T>>::insert(currency_id, true);
+			} else {
+				<SendRestrictedCurrencies<T>>::remove(currency_id);
+			}
+
+			Self::emit_event(RawEvent::SendRestrictionSet(currency_id, restricted));
+		}
+
+		/// Add `who` to, or remove it from, the sender allowlist of a token type.
+		///
+		/// The dispatch origin of this call must be _Root_ or the currency admin.
+		pub fn set_send_allowed(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+			allowed: bool,
+		) {
+			Self::ensure_root_or_currency_admin(origin, currency_id)?;
+			let who = T::Lookup::lookup(who)?;
+			if allowed {
+				<SendAllowlist<T>>::insert(currency_id, &who, true);
+			} else {
+				<SendAllowlist<T>>::remove(currency_id, &who);
+			}
+
+			Self::emit_event(RawEvent::SendAllowlistUpdated(currency_id, who, allowed));
+		}
+
+		/// Accrue interest at `rate` to the free balance of each of `holders` under `currency_id`, minting it.
+		///
+		/// The interest of each holder is rounded down, and the aggregate rounding dust, i.e. the interest of the sum
+		/// of their balances minus the sum of their interests, is credited to `Treasury`. If the dust is not enough
+		/// to create the treasury account, it's not minted. Holders are given in batches of at most
+		/// `MaxInterestHolders`, see `set_interest_schedule` to accrue interest to all accounts periodically.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn accrue_interest(origin, currency_id: T::CurrencyId, rate: Permill, holders: Vec<T::AccountId>) {
+			ensure_root(origin)?;
+			ensure!(
+				holders.len() <= T::MaxInterestHolders::get() as usize,
+				Error::<T>::TooManyAccounts
+			);
+			Self::ensure_not_frozen(currency_id)?;
+
+			let mut interests: Vec<(T::AccountId, T::Balance)> = Vec::new();
+			let mut sum: T::Balance = Zero::zero();
+			for who in holders {
+				if interests.iter().any(|(w, _)| *w == who) {
+					continue;
+				}
+				let free_balance = Self::free_balance(currency_id, &who);
+				sum = sum.saturating_add(free_balance);
+				interests.push((who, Self::interest_of(rate, free_balance)));
+			}
+			let minted = Self::interest_of(rate, sum);
+			let credited = interests.iter().fold(Zero::zero(), |acc: T::Balance, (_, interest)| acc + *interest);
+			let dust = minted.saturating_sub(credited);
+
+			// validate the total mint first, so that no deposit below fails on total issuance
+			let new_total_issuance = Self::total_issuance(currency_id)
+				.checked_add(&minted)
+				.ok_or(Error::<T>::TotalIssuanceOverflow)?;
+			if let Some(cap) = Self::total_issuance_cap(currency_id) {
+				ensure!(new_total_issuance <= cap, Error::<T>::TotalIssuanceCapExceeded);
+			}
+
+			let total_issuance = Self::total_issuance(currency_id);
+			with_transaction_result(|| {
+				for (who, interest) in interests.iter() {
+					<Self as MultiCurrency<_>>::deposit(currency_id, who, *interest)?;
+				}
+				<Self as MultiCurrency<_>>::deposit(currency_id, &T::Treasury::get(), dust)
+			})?;
+
+			// deposits below the existential deposit to empty accounts change nothing, and are not counted
+			let minted = Self::total_issuance(currency_id).saturating_sub(total_issuance);
+			let dust = minted.saturating_sub(credited);
+			Self::emit_event(RawEvent::InterestAccrued(currency_id, rate, minted, dust));
+		}
+
+		/// Set the interest rate accrued to all accounts of `currency_id` every `period` blocks, or remove it if
+		/// `None`.
+		///
+		/// At each block which is a multiple of `period`, interest at `rate` is accrued to the free balance of all
+		/// accounts of the token type, rounded down, over multiple blocks within `MigrationBatchSize` with the progress
+		/// kept in `InterestAccruals`. Unlike a migration, it doesn't block transfers, so a balance moved to an account
+		/// not yet processed may accrue twice in a period, and one moved the other way not at all. The accrual of a
+		/// period is skipped if the one of the previous period, or a migration of the token type, is still in
+		/// progress.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn set_interest_schedule(
+			origin,
+			currency_id: T::CurrencyId,
+			schedule: Option<(Permill, T::BlockNumber)>,
+		) {
+			ensure_root(origin)?;
+
+			if let Some((rate, period)) = schedule {
+				ensure!(!period.is_zero(), Error::<T>::InvalidInterestSchedule);
+				<InterestSchedules<T>>::insert(currency_id, (rate, period));
+			} else {
+				<InterestSchedules<T>>::remove(currency_id);
+			}
+
+			Self::emit_event(RawEvent::InterestScheduleSet(currency_id, schedule));
+		}
+
+		/// Set the total issuance cap of a token type. If the total issuance is already above `cap`, the excess is
+		/// slashed from the admin account of the token type, as far as its balance allows.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn enforce_cap_on_existing(origin, currency_id: T::CurrencyId, #[compact] cap: T::Balance) {
+			ensure_root(origin)?;
+
+			let total_issuance = Self::total_issuance(currency_id);
+			let excess = if total_issuance > cap {
+				let admin = Self::currency_admin(currency_id).ok_or(Error::<T>::NoCurrencyAdmin)?;
+				let excess = total_issuance - cap;
+				Some(excess - <Self as MultiCurrency<_>>::slash(currency_id, &admin, excess))
+			} else {
+				None
+			};
+			<TotalIssuanceCaps<T>>::insert(currency_id, cap);
+
+			if let Some(excess) = excess {
+				Self::emit_event(RawEvent::IssuanceCapEnforced(currency_id, excess));
+			}
+		}
+
+		/// Migrate the free and reserved balance of the caller from `from_currency` to `to_currency`, scaled by
+		/// `ratio` as `(numerator, denominator)` and rounded down. Named reserves are carried over, scaled the same way.
+		///
+		/// The total issuance of `from_currency` is reduced by the migrated balance, and that of `to_currency` is
+		/// increased by the scaled balance. Fails with `AccountHasLocks` if the caller has any lock on `from_currency`,
+		/// and with `SameCurrency` if `from_currency` equals `to_currency`.
+		pub fn migrate_my_balance(
+			origin,
+			from_currency: T::CurrencyId,
+			to_currency: T::CurrencyId,
+			ratio: (u32, u32),
+		) {
+			let who = ensure_signed(origin)?;
+			let (numerator, denominator) = ratio;
+			// migrating to the same token type would burn and re-mint the balance for nothing
+			ensure!(from_currency != to_currency, Error::<T>::SameCurrency);
+			ensure!(denominator != 0, Error::<T>::InvalidMigration);
+			Self::ensure_not_frozen(from_currency)?;
+			Self::ensure_not_frozen(to_currency)?;
+			ensure!(!<Locks<T>>::contains_key(from_currency, &who), Error::<T>::AccountHasLocks);
+
+			let scale = |balance: T::Balance| -> rstd::result::Result<T::Balance, DispatchError> {
+				let scaled = multiply_by_rational(balance.saturated_into(), numerator.into(), denominator.into())
+					.map_err(|_| Error::<T>::TotalIssuanceOverflow)?;
+				TryInto::<T::Balance>::try_into(scaled).map_err(|_| Error::<T>::TotalIssuanceOverflow.into())
+			};
+
+			let from_account = Self::accounts(from_currency, &who);
+			let to_account = Self::accounts(to_currency, &who);
+			let old_total = from_account.total();
+			let free = scale(from_account.free)?;
+			let reserved = scale(from_account.reserved)?;
+			let new_total = free.checked_add(&reserved).ok_or(Error::<T>::TotalIssuanceOverflow)?;
+
+			let new_total_issuance = Self::total_issuance(to_currency)
+				.checked_add(&new_total)
+				.ok_or(Error::<T>::TotalIssuanceOverflow)?;
+			if let Some(cap) = Self::total_issuance_cap(to_currency) {
+				ensure!(new_total_issuance <= cap, Error::<T>::TotalIssuanceCapExceeded);
+			}
+			let new_free = to_account.free.checked_add(&free).ok_or(Error::<T>::TotalIssuanceOverflow)?;
+			let new_reserved = to_account.reserved.checked_add(&reserved).ok_or(Error::<T>::TotalIssuanceOverflow)?;
+
+			// the sum of rounded down named reserves never exceeds the rounded down reserved balance
+			let existing_reserves = Self::reserves(to_currency, &who);
+			let mut named_reserves = Vec::new();
+			let mut reserve_count = existing_reserves.len();
+			for reserve in Self::reserves(from_currency, &who) {
+				if !existing_reserves.iter().any(|existing| existing.id == reserve.id) {
+					reserve_count += 1;
+				}
+				named_reserves.push((reserve.id, scale(reserve.amount)?));
+			}
+			ensure!(reserve_count <= T::MaxReserves::get() as usize, Error::<T>::TooManyReserves);
+
+			Self::mutate_account(from_currency, &who, |account_data| {
+				account_data.free = Zero::zero();
+				account_data.reserved = Zero::zero();
+			});
+			Self::record_free_balance(from_currency, &who);
+			<Reserves<T>>::remove(from_currency, &who);
+			Self::burn_issuance(from_currency, old_total);
+
+			Self::mint_issuance(to_currency, new_total);
+			Self::set_reserved_balance(to_currency, &who, new_reserved);
+			Self::set_free_balance(to_currency, &who, new_free);
+			for (id, amount) in named_reserves {
+				Self::add_reserve_named(&id, to_currency, &who, amount);
+				if let Some(label) = <ReserveLabels<T>>::take((from_currency, who.clone()), id) {
+					if !amount.is_zero() {
+						<ReserveLabels<T>>::insert((to_currency, who.clone()), id, label);
+					}
+				}
+			}
+
+			Self::emit_event(RawEvent::BalanceMigrated(from_currency, to_currency, who, old_total, new_total));
+		}
+
+		/// Store `root_hash`, the root of the balance snapshot exported by the off-chain worker at `block`.
+		///
+		/// The dispatch origin of this call must be `SnapshotOrigin`. A submitted root is never overwritten.
+		pub fn submit_snapshot_root(origin, block: T::BlockNumber, root_hash: T::Hash) {
+			T::SnapshotOrigin::ensure_origin(origin)?;
+			ensure!(Self::is_snapshot_block(block), Error::<T>::InvalidSnapshotBlock);
+			ensure!(!<SnapshotRoots<T>>::contains_key(block), Error::<T>::SnapshotRootExists);
+			<SnapshotRoots<T>>::insert(block, root_hash);
+
+			Self::emit_event(RawEvent::SnapshotRootSubmitted(block, root_hash));
+		}
+
+		/// Start `migration` over all accounts of `currency_id`. Accounts are processed from the next block on, at most
+		/// `MigrationBatchSize` per block shared by all token types, and the progress is kept in `PendingMigrations`.
+		///
+		/// Transfers of `currency_id`, and the dispatchable calls withdrawing, depositing or reserving it, fail with
+		/// `MigrationInProgress` until the migration completes.
+		///
+		/// Accounts created while the migration is in progress may be missed. Only one migration of a token type could
+		/// be in progress.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn start_migration(origin, currency_id: T::CurrencyId, migration: AccountMigration) {
+			ensure_root(origin)?;
+			Self::ensure_not_migrating(currency_id)?;
+			if let AccountMigration::Redenominate(_, denominator) = migration {
+				ensure!(denominator != 0, Error::<T>::InvalidMigration);
+			}
+
+			Self::begin_migration(currency_id, migration);
+		}
+
+		/// Merge the locks of `who` under `currency_id` with the same lock ID into one, keeping the largest amount.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn dedup_locks(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+		) {
+			ensure_root(origin)?;
+			let who = T::Lookup::lookup(who)?;
+
+			let locks = Self::locks(currency_id, &who);
+			let mut deduped: Vec<BalanceLock<T::Balance>> = Vec::with_capacity(locks.len());
+			for lock in locks.iter() {
+				match deduped.iter_mut().find(|l| l.id == lock.id) {
+					Some(existing) => existing.amount = existing.amount.max(lock.amount),
+					None => deduped.push(lock.clone()),
+				}
+			}
+			Self::update_locks(currency_id, &who, &deduped[..]);
+
+			Self::emit_event(RawEvent::LocksDeduplicated(currency_id, who, locks.len() as u32, deduped.len() as u32));
+		}
+
+		/// Remove the lock `lock_id` of `who` under `currency_id`, regardless of the module which set it.
+		///
+		/// The dispatch origin of this call must be `ForceOrigin`.
+		pub fn force_remove_lock(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+			lock_id: LockIdentifier,
+		) {
+			T::ForceOrigin::ensure_origin(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			ensure!(
+				Self::locks(currency_id, &who).iter().any(|lock| lock.id == lock_id),
+				Error::<T>::LockNotFound
+			);
+
+			Self::do_remove_lock(lock_id, currency_id, &who);
+
+			Self::emit_event(RawEvent::LockRemoved(currency_id, who, lock_id));
+		}
+
+		/// Register the lock identifier `lock_id` to `purpose`, or unregister it if `None`.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn register_lock_id(origin, lock_id: LockIdentifier, purpose: Option<T::LockPurpose>) {
+			ensure_root(origin)?;
+			match &purpose {
+				Some(purpose) => <LockOwners<T>>::insert(lock_id, purpose),
+				None => <LockOwners<T>>::remove(lock_id),
+			}
+
+			Self::emit_event(RawEvent::LockIdRegistered(lock_id, purpose));
+		}
+
+		/// Set the reserved balance `who` must maintain under `currency_id`, or remove the requirement if zero.
+		///
+		/// The dispatch origin of this call must be _Root_.
+		pub fn set_minimum_reserve_requirement(
+			origin,
+			currency_id: T::CurrencyId,
+			who: <T::Lookup as StaticLookup>::Source,
+			#[compact] amount: T::Balance,
+		) {
+			ensure_root(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			if amount.is_zero() {
+				<MinimumReserves<T>>::remove(currency_id, &who);
+			} else {
+				<MinimumReserves<T>>::insert(currency_id, &who, amount);
+			}
+
+			Self::emit_event(RawEvent::MinimumReserveRequirementSet(currency_id, who, amount));
+		}
+
+		fn on_runtime_upgrade() {
+			if <StorageVersion>::get() < Releases::V2_0_0 {
+				Self::migrate_to_v2();
+			}
+		}
+
+		fn on_initialize(now: T::BlockNumber) {
+			Self::release_deadline_reserves(now);
+			Self::remove_expired_locks(now);
+			Self::remove_reaped_accounts(now);
+			Self::start_scheduled_interest(now);
+			let budget = Self::process_backfill(T::MigrationBatchSize::get());
+			let budget = Self::process_migrations(budget);
+			Self::process_interest_accruals(budget);
+		}
+
+		fn on_finalize(_now: T::BlockNumber) {
+			Self::consolidate_dust();
+			Self::clear_native_equivalent_cache();
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			if Self::is_snapshot_block(now) {
+				Self::snapshot_balances(now);
+			}
+		}
+	}
+}
+
+decl_error! {
+	/// Error for token module.
+	pub enum Error for Module<T: Trait> {
+		BalanceTooLow,
+		TotalIssuanceOverflow,
+		AmountIntoBalanceFailed,
+		AmountMagnitudeOverflow,
+		ExistentialDeposit,
+		LiquidityRestrictions,
+		MetadataTooLong,
+		EmptySymbol,
+		InvalidDecimals,
+		CannotCoverFee,
+		CurrencyFrozen,
+		EmergencyShutdown,
+		TooManyReserves,
+		TooManyAccounts,
+		TotalIssuanceCapExceeded,
+		NoCurrencyAdmin,
+		InvalidMigration,
+		AccountHasLocks,
+		InvalidSnapshotBlock,
+		SnapshotRootExists,
+		MigrationInProgress,
+		SameCurrency,
+		InvalidDeadline,
+		DeadlineReserveNotFound,
+		NoAvailableDeadlineReserveId,
+		UnexpectedReservedBalance,
+		BelowMinimumReceived,
+		RecipientBlocked,
+		BalanceCapExceeded,
+		SenderNotAllowed,
+		LockRatioExceeded,
+		NotCurrencyAdmin,
+		InvalidNonce,
+		ReservesNotSupported,
+		LockNotFound,
+		LabelTooLong,
+		BadSignature,
+		IntentExpired,
+		InvalidGranularity,
+		KeepAlive,
+		LockNotOwned,
+		TooManyCurrencies,
+		ZeroTotalWeight,
+		InvalidInterestSchedule,
+		TooManyTransfers,
+		TooManyRecipients,
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Set free balance of `who` to a new value, meanwhile enforce existential rule.
+	///
+	/// Note this will not maintain total issuance except balance is less to ExistentialDeposit,
+	/// and the caller is expected to do it.
+	fn set_free_balance(currency_id: T::CurrencyId, who: &T::AccountId, balance: T::Balance) {
+		if balance < T::ExistentialDeposit::get() && !T::ProtectedAccounts::contains(who) {
+			Self::mutate_account(currency_id, who, |account_data| account_data.free = Zero::zero());
+			Self::remove_dust(currency_id, who, balance);
+		} else {
+			Self::mutate_account(currency_id, who, |account_data| account_data.free = balance);
+		}
+		Self::record_free_balance(currency_id, who);
+		Self::remove_stale_locks(currency_id, who);
+	}
+
+	/// Mutate the account data of `who` under `currency_id`, and update `HolderCount` if its total balance changes
+	/// from or to zero. All changes of free and reserved balances go through it, so an account reaching zero by any
+	/// path is counted out exactly once. The cached native-equivalent total balance of `who` is removed, if any.
+	fn mutate_account<R>(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		f: impl FnOnce(&mut AccountData<T::Balance>) -> R,
+	) -> R {
+		if <NativeEquivalentCache<T>>::contains_key(who) {
+			<NativeEquivalentCache<T>>::remove(who);
+		}
+		<Accounts<T>>::mutate(currency_id, who, |account_data| {
+			let was_holder = !account_data.total().is_zero();
+			let result = f(account_data);
+			let is_holder = !account_data.total().is_zero();
+			if !was_holder && is_holder {
+				<HolderCount<T>>::mutate(currency_id, |count| *count = count.saturating_add(1));
+				<AccountCurrencies<T>>::insert(who, currency_id, true);
+			} else if was_holder && !is_holder {
+				// an account not yet indexed by the backfill after a runtime upgrade is not counted yet
+				if <AccountCurrencies<T>>::take(who, currency_id) {
+					<HolderCount<T>>::mutate(currency_id, |count| *count = count.saturating_sub(1));
+				}
+				let remove_at = <frame_system::Module<T>>::block_number() + T::ReapGracePeriod::get().max(One::one());
+				<ReapQueue<T>>::mutate(remove_at, |accounts| accounts.push((currency_id, who.clone())));
+			}
+			result
+		})
+	}
+
+	/// Start backfilling the counters and indexes of existing token types and accounts by `process_backfill`.
+	fn migrate_to_v2() {
+		<PendingBackfill>::put(BackfillCursor::Currencies(Self::storage_prefix(b"TotalIssuance")));
+		<StorageVersion>::put(Releases::V2_0_0);
+	}
+
+	/// Remove the reserved balance of `who` under `currency_id` as dust, along with its named reserves, if it's below
+	/// the existential deposit and the free balance is zero.
+	fn reap_reserved_dust(currency_id: T::CurrencyId, who: &T::AccountId) {
+		let account = Self::accounts(currency_id, who);
+		if !account.free.is_zero()
+			|| account.reserved.is_zero()
+			|| account.reserved >= T::ExistentialDeposit::get()
+			|| T::ProtectedAccounts::contains(who)
+		{
+			return;
+		}
+		Self::set_reserved_balance(currency_id, who, Zero::zero());
+		<Reserves<T>>::remove(currency_id, who);
+		<ReserveLabels<T>>::remove_prefix((currency_id, who.clone()));
+		Self::remove_dust(currency_id, who, account.reserved);
+	}
+
+	/// Burn the `DustSplit` share of `dust` removed from `who` and credit the rest to `DustReceiver`.
+	///
+	/// If the credited share would leave `DustReceiver` below the existential deposit, it's burned too.
+	fn remove_dust(currency_id: T::CurrencyId, who: &T::AccountId, dust: T::Balance) {
+		Self::report_dust(currency_id, who, dust);
+		let (mut burned, credited) = Self::split_dust(dust);
+		if !credited.is_zero() {
+			let receiver = T::DustReceiver::get();
+			let receiver_balance = Self::free_balance(currency_id, &receiver).saturating_add(credited);
+			if receiver_balance >= T::ExistentialDeposit::get() || T::ProtectedAccounts::contains(&receiver) {
+				Self::mutate_account(currency_id, &receiver, |account_data| {
+					account_data.free = receiver_balance
+				});
+				Self::record_free_balance(currency_id, &receiver);
+			} else {
+				burned = dust;
+			}
+		}
+		T::DustRemoval::on_dust_removal(burned);
+		Self::burn_issuance(currency_id, burned);
+	}
+
+	/// Report `dust` removed from `who` as set by `DustEventMode`.
+	fn report_dust(currency_id: T::CurrencyId, who: &T::AccountId, dust: T::Balance) {
+		if dust.is_zero() {
+			return;
+		}
+		match T::DustEventMode::get() {
+			DustEventMode::PerAccount => Self::emit_event(RawEvent::DustLost(currency_id, who.clone(), dust)),
+			DustEventMode::Consolidated => {
+				<BlockDust<T>>::mutate(
+					|block_dust| match block_dust.iter_mut().find(|(id, _)| *id == currency_id) {
+						Some((_, total)) => *total = total.saturating_add(dust),
+						None => block_dust.push((currency_id, dust)),
+					},
+				)
+			}
+		}
+	}
+
+	/// Deposit `DustConsolidated` with the dust removed in this block, if any.
+	fn consolidate_dust() {
+		let block_dust = <BlockDust<T>>::take();
+		if !block_dust.is_empty() {
+			Self::emit_event(RawEvent::DustConsolidated(block_dust));
+		}
+	}
+
+	/// Increase the total issuance of `currency_id` by `amount`, and notify `OnIssuanceChange`.
+	fn mint_issuance(currency_id: T::CurrencyId, amount: T::Balance) {
+		if amount.is_zero() {
+			return;
+		}
+		Self::mutate_total_issuance(currency_id, |v| *v += amount);
+		T::OnIssuanceChange::on_mint(currency_id, amount);
+	}
+
+	/// Populate the metadata of `currency_id` from `DefaultMetadata`, if it has none.
+	fn init_default_metadata(currency_id: T::CurrencyId) {
+		if <CurrencyMetadata<T>>::contains_key(currency_id) {
+			return;
+		}
+		if let Some((name, symbol, decimals)) = T::DefaultMetadata::convert(currency_id) {
+			let metadata = TokenMetadata {
+				name,
+				symbol,
+				decimals,
+				description: Vec::new(),
+			};
+			<CurrencyMetadata<T>>::insert(currency_id, &metadata);
+			Self::emit_event(RawEvent::MetadataSet(currency_id, metadata));
+		}
+	}
+
+	/// Decrease the total issuance of `currency_id` by `amount`, and notify `OnIssuanceChange`.
+	fn burn_issuance(currency_id: T::CurrencyId, amount: T::Balance) {
+		if amount.is_zero() {
+			return;
+		}
+		Self::mutate_total_issuance(currency_id, |v| *v -= amount);
+		T::OnIssuanceChange::on_burn(currency_id, amount);
+	}
+
+	/// Mutate the total issuance of `currency_id`, and update the currency counts if it changes from or to zero.
+	fn mutate_total_issuance(currency_id: T::CurrencyId, f: impl FnOnce(&mut T::Balance)) {
+		<TotalIssuance<T>>::mutate(currency_id, |issuance| {
+			let old_value = *issuance;
+			f(issuance);
+			Self::note_issuance_change(currency_id, old_value, *issuance);
+		});
+	}
+
+	/// Update `CurrencyCount` and `LiveCurrencyCount` for the total issuance of `currency_id` changing from
+	/// `old_value` to `new_value`. A token type is created the first time its issuance becomes non-zero, and stays
+	/// counted in `CurrencyCount` after its issuance returns to zero, as token types are never destroyed. A token type
+	/// not yet counted by the backfill after a runtime upgrade is not counted out.
+	fn note_issuance_change(currency_id: T::CurrencyId, old_value: T::Balance, new_value: T::Balance) {
+		if old_value.is_zero() && !new_value.is_zero() {
+			<LiveCurrencyCount>::mutate(|count| *count = count.saturating_add(1));
+			if !<CreatedCurrencies<T>>::get(currency_id) {
+				<CreatedCurrencies<T>>::insert(currency_id, true);
+				<CurrencyCount>::mutate(|count| *count = count.saturating_add(1));
+			}
+		} else if !old_value.is_zero() && new_value.is_zero() && <CreatedCurrencies<T>>::get(currency_id) {
+			<LiveCurrencyCount>::mutate(|count| *count = count.saturating_sub(1));
+		}
+	}
+
+	/// Account for `amount` slashed under `currency_id` according to `SlashAccountingMode`.
+	fn settle_slashed(currency_id: T::CurrencyId, amount: T::Balance) {
+		match T::SlashAccountingMode::get() {
+			SlashAccountingMode::ReduceIssuance => Self::burn_issuance(currency_id, amount),
+			SlashAccountingMode::TrackInAccount(account) => {
+				if amount.is_zero() {
+					return;
+				}
+				let balance = Self::free_balance(currency_id, &account);
+				Self::set_free_balance(currency_id, &account, balance + amount);
+			}
+		}
+	}
+
+	/// Deposit `amount` to `who` under `currency_id` and increase total issuance, even if the free balance stays below
+	/// the existential deposit.
+	fn deposit_allow_death(currency_id: T::CurrencyId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		Self::ensure_not_frozen(currency_id)?;
+		let new_total_issuance = Self::total_issuance(currency_id)
+			.checked_add(&amount)
+			.ok_or(Error::<T>::TotalIssuanceOverflow)?;
+		if let Some(cap) = Self::total_issuance_cap(currency_id) {
+			ensure!(new_total_issuance <= cap, Error::<T>::TotalIssuanceCapExceeded);
+		}
+		Self::ensure_within_balance_cap(currency_id, who, amount)?;
+
+		Self::mint_issuance(currency_id, amount);
+		Self::mutate_account(currency_id, who, |account_data| account_data.free += amount);
+		Self::record_free_balance(currency_id, who);
+		Ok(())
+	}
+
+	/// Record the free balance of `who` under `currency_id` at the current block in `BalanceHistory`, if changed
+	/// since the latest record, keeping at most `MaxBalanceHistory` of them, and snapshot it.
+	///
+	/// Nothing is read or written if both the history and snapshots are disabled.
+	fn record_free_balance(currency_id: T::CurrencyId, who: &T::AccountId) {
+		let max_history = T::MaxBalanceHistory::get() as usize;
+		if max_history == 0 && T::AccountSnapshotPeriod::get().is_zero() {
+			return;
+		}
+		let now = <frame_system::Module<T>>::block_number();
+		let free = Self::accounts(currency_id, who).free;
+		if max_history > 0 {
+			<BalanceHistory<T>>::mutate(currency_id, who, |history| {
+				match history.last_mut() {
+					Some((_, balance)) if *balance == free => {}
+					Some((block, balance)) if *block == now => *balance = free,
+					_ => history.push((now, free)),
+				}
+				if history.len() > max_history {
+					let excess = history.len() - max_history;
+					history.drain(..excess);
+				}
+			});
+		}
+		Self::snapshot_free_balance(currency_id, who, now, free);
+	}
+
+	/// Record `free` as the free balance of `who` under `currency_id` at the first snapshot block at or after `now`,
+	/// if snapshots are enabled by `AccountSnapshotPeriod`, keeping at most `MaxAccountSnapshots` of them.
+	fn snapshot_free_balance(currency_id: T::CurrencyId, who: &T::AccountId, now: T::BlockNumber, free: T::Balance) {
+		let period = T::AccountSnapshotPeriod::get();
+		if period.is_zero() {
+			return;
+		}
+		let remainder = now % period;
+		let snapshot_block = if remainder.is_zero() {
+			now
+		} else {
+			now.saturating_add(period - remainder)
+		};
+		<AccountSnapshots<T>>::mutate(currency_id, who, |snapshots| {
+			match snapshots.last_mut() {
+				Some((block, balance)) if *block == snapshot_block => *balance = free,
+				_ => snapshots.push((snapshot_block, free)),
+			}
+			let max_snapshots = T::MaxAccountSnapshots::get() as usize;
+			if snapshots.len() > max_snapshots {
+				let excess = snapshots.len() - max_snapshots;
+				snapshots.drain(..excess);
+			}
+		});
+	}
+
+	/// Ensure the total balance of `who` under `currency_id` would not exceed `MaxBalancePerAccount` after increasing
+	/// by `amount`.
+	fn ensure_within_balance_cap(currency_id: T::CurrencyId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		if let Some(cap) = T::MaxBalancePerAccount::convert(currency_id) {
+			let new_total = Self::total_balance(currency_id, who).saturating_add(amount);
+			ensure!(new_total <= cap, Error::<T>::BalanceCapExceeded);
+		}
+		Ok(())
+	}
+
+	/// Whether `currency_id` is the native currency, routed to `NativeCurrency`.
+	fn is_native(currency_id: T::CurrencyId) -> bool {
+		T::NativeCurrencyId::get() == Some(currency_id)
+	}
+
+	/// Deposit `event` if allowed by `EventVerbosity`, replacing `Transferred` by `Debited` and `Credited` under
+	/// `EventModel::DoubleEntry`. Both are of the transferred amount, any transfer fee being reported by
+	/// `TransferFeeCharged`.
+	fn emit_event(event: Event<T>) {
+		if let (EventModel::DoubleEntry, RawEvent::Transferred(currency_id, from, to, amount)) =
+			(T::EventModel::get(), &event)
+		{
+			Self::emit_allowed_event(RawEvent::Debited(*currency_id, from.clone(), *amount));
+			Self::emit_allowed_event(RawEvent::Credited(*currency_id, to.clone(), *amount));
+		} else {
+			Self::emit_allowed_event(event);
+		}
+	}
+
+	/// Deposit `event` if allowed by `EventVerbosity`.
+	fn emit_allowed_event(event: Event<T>) {
+		let allowed = match T::EventVerbosity::get() {
+			EventVerbosity::Full => true,
+			EventVerbosity::TransfersOnly => match event {
+				RawEvent::Transferred(..)
+				| RawEvent::TransferredExactOut(..)
+				| RawEvent::TeleportSent(..)
+				| RawEvent::TeleportReceived(..)
+				| RawEvent::Debited(..)
+				| RawEvent::Credited(..) => true,
+				_ => false,
+			},
+			EventVerbosity::Silent => false,
+		};
+		if allowed {
+			Self::deposit_event(event);
+		}
+	}
+
+	/// Set reserved balance of `who` to a new value, meanwhile enforce existential rule.
+	///
+	/// Note this will not maintain total issuance, and the caller is expected to do it.
+	fn set_reserved_balance(currency_id: T::CurrencyId, who: &T::AccountId, balance: T::Balance) {
+		Self::mutate_account(currency_id, who, |account_data| account_data.reserved = balance);
+		Self::remove_stale_locks(currency_id, who);
+	}
+
+	/// Remove the locks of `who` under `currency_id` exceeding its total balance, if `StaleLockPolicy` is `Remove`.
+	fn remove_stale_locks(currency_id: T::CurrencyId, who: &T::AccountId) {
+		if T::StaleLockPolicy::get() != StaleLockPolicy::Remove {
+			return;
+		}
+		let account = Self::accounts(currency_id, who);
+		let total = account.free.saturating_add(account.reserved);
+		let (stale, kept): (Vec<_>, Vec<_>) = Self::locks(currency_id, who)
+			.into_iter()
+			.partition(|lock| lock.amount > total);
+		if stale.is_empty() {
+			return;
+		}
+
+		for lock in stale.iter() {
+			Self::clear_lock_expiry(currency_id, who, lock.id);
+		}
+		Self::update_locks(currency_id, who, &kept[..]);
+		Self::emit_event(RawEvent::StaleLocksRemoved(
+			currency_id,
+			who.clone(),
+			stale.len() as u32,
+		));
+	}
+
+	/// Ensure the balance frozen by `locks` is within `MaxLockRatio` of the free balance of `who` under `currency_id`,
+	/// or isn't increased by them.
+	fn ensure_lock_ratio(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		locks: &[BalanceLock<T::Balance>],
+	) -> DispatchResult {
+		let ratio = T::MaxLockRatio::get();
+		let account = Self::accounts(currency_id, who);
+		let frozen = locks
+			.iter()
+			.fold(Zero::zero(), |acc: T::Balance, lock| acc.max(lock.amount));
+		ensure!(
+			frozen <= account.frozen || frozen <= ratio * account.free,
+			Error::<T>::LockRatioExceeded
+		);
+		Ok(())
+	}
+
+	/// The largest of `locks` applying to `reason`, by their withdraw `reasons`, where locks without reasons apply to
+	/// all of them.
+	fn max_lock_for(
+		locks: &[BalanceLock<T::Balance>],
+		reasons: &[(LockIdentifier, WithdrawReasons)],
+		reason: WithdrawReason,
+	) -> T::Balance {
+		locks
+			.iter()
+			.filter(|lock| {
+				reasons
+					.iter()
+					.find(|(id, _)| *id == lock.id)
+					.map_or(true, |(_, lock_reasons)| lock_reasons.contains(reason))
+			})
+			.fold(Zero::zero(), |acc: T::Balance, lock| acc.max(lock.amount))
+	}
+
+	/// The amount of the free balance of `who` under `currency_id` frozen by locks for `reason`.
+	pub fn frozen_for_reason(currency_id: T::CurrencyId, who: &T::AccountId, reason: WithdrawReason) -> T::Balance {
+		Self::max_lock_for(
+			&Self::locks(currency_id, who)[..],
+			&Self::lock_reasons(currency_id, who)[..],
+			reason,
+		)
+	}
+
+	/// Set the withdraw reasons of the lock `lock_id` of `who` under `currency_id`, applied by the next
+	/// `update_locks`.
+	fn put_lock_reasons(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		lock_id: LockIdentifier,
+		reasons: WithdrawReasons,
+	) {
+		<LockReasons<T>>::mutate(currency_id, who, |lock_reasons| {
+			lock_reasons.retain(|(id, _)| *id != lock_id);
+			if reasons != WithdrawReasons::all() {
+				lock_reasons.push((lock_id, reasons));
+			}
+		});
+	}
+
+	/// Update the account entry for `who` under `currency_id`, given the locks.
+	///
+	/// The frozen balance is the largest lock applying to `Transfer`, which all transfers and withdrawals are checked
+	/// against. Reserves are checked against `frozen_for_reason` of `Reserve` instead.
+	fn update_locks(currency_id: T::CurrencyId, who: &T::AccountId, locks: &[BalanceLock<T::Balance>]) {
+		// drop the reasons of removed locks
+		let mut reasons = Self::lock_reasons(currency_id, who);
+		reasons.retain(|(id, _)| locks.iter().any(|lock| lock.id == *id));
+		if reasons.is_empty() {
+			<LockReasons<T>>::remove(currency_id, who);
+		} else {
+			<LockReasons<T>>::insert(currency_id, who, &reasons);
+		}
+
+		// update account data
+		<Accounts<T>>::mutate(currency_id, who, |account_data| {
+			account_data.frozen = Self::max_lock_for(locks, &reasons[..], WithdrawReason::Transfer);
+		});
+
+		// update locks
+		let existed = <Locks<T>>::contains_key(currency_id, who);
+		if locks.is_empty() {
+			<Locks<T>>::remove(currency_id, who);
+			if existed {
+				// decrease account ref count when destruct lock
+				system::Module::<T>::dec_ref(who);
+			}
+		} else {
+			<Locks<T>>::insert(currency_id, who, locks);
+			if !existed {
+				// increase account ref count when initialize lock
+				system::Module::<T>::inc_ref(who);
+			}
+		}
+	}
+
+	/// The interest of `balance` at `rate`, rounded down.
+	fn interest_of(rate: Permill, balance: T::Balance) -> T::Balance {
+		multiply_by_rational(
+			balance.saturated_into(),
+			rate.deconstruct().into(),
+			Permill::one().deconstruct().into(),
+		)
+		.map(|interest| interest.saturated_into())
+		.unwrap_or_else(|_| Zero::zero())
+	}
+
+	/// Ensure no emergency shutdown is active, and `currency_id` is not frozen.
+	fn ensure_not_frozen(currency_id: T::CurrencyId) -> DispatchResult {
+		ensure!(!Self::shutdown_active(), Error::<T>::EmergencyShutdown);
+		ensure!(!Self::frozen_currencies(currency_id), Error::<T>::CurrencyFrozen);
+		Ok(())
+	}
+
+	/// Ensure no account migration of `currency_id` is in progress, or fail with `MigrationInProgress`, so that
+	/// balance movements don't race with the migration.
+	fn ensure_not_migrating(currency_id: T::CurrencyId) -> DispatchResult {
+		ensure!(
+			!<PendingMigrations<T>>::contains_key(currency_id),
+			Error::<T>::MigrationInProgress
+		);
+		Ok(())
+	}
+
+	/// Ensure `from` may send `currency_id`, which is when the token type is not send restricted, or `from` is in its
+	/// `SendAllowlist` or is its currency admin.
+	fn ensure_sender_allowed(currency_id: T::CurrencyId, from: &T::AccountId) -> DispatchResult {
+		if !Self::send_restricted(currency_id) || Self::send_allowed(currency_id, from) {
+			return Ok(());
+		}
+		ensure!(
+			Self::currency_admin(currency_id).as_ref() == Some(from),
+			Error::<T>::SenderNotAllowed
+		);
+		Ok(())
+	}
+
+	/// Ensure `origin` is _Root_ or the currency admin of `currency_id`.
+	fn ensure_root_or_currency_admin(origin: T::Origin, currency_id: T::CurrencyId) -> DispatchResult {
+		if ensure_root(origin.clone()).is_ok() {
+			return Ok(());
+		}
+		let who = ensure_signed(origin)?;
+		ensure!(
+			Self::currency_admin(currency_id) == Some(who),
+			Error::<T>::NotCurrencyAdmin
+		);
+		Ok(())
+	}
+
+	/// Ensure `currency_id` supports reserves, or fail with `ReservesNotSupported`.
+	fn ensure_reservable(currency_id: T::CurrencyId) -> DispatchResult {
+		ensure!(
+			<Self as MultiReservableCurrency<_>>::reservable(currency_id),
+			Error::<T>::ReservesNotSupported
+		);
+		Ok(())
+	}
+
+	/// Ensure the named reserve `id` could be added to `who`, which is either an existing one or the number of named
+	/// reserves is below `MaxReserves`.
+	fn ensure_can_add_reserve_named(
+		id: &T::ReserveIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+	) -> DispatchResult {
+		let reserves = Self::reserves(currency_id, who);
+		ensure!(
+			reserves.iter().any(|reserve| reserve.id == *id) || reserves.len() < T::MaxReserves::get() as usize,
+			Error::<T>::TooManyReserves,
+		);
+		Ok(())
+	}
+
+	/// Add `value` to the named reserve `id` of `who`. The reserved balance is not changed, and the caller is
+	/// expected to do it.
+	fn add_reserve_named(id: &T::ReserveIdentifier, currency_id: T::CurrencyId, who: &T::AccountId, value: T::Balance) {
+		if value.is_zero() {
+			return;
+		}
+		<Reserves<T>>::mutate(currency_id, who, |reserves| {
+			if let Some(reserve) = reserves.iter_mut().find(|reserve| reserve.id == *id) {
+				reserve.amount += value;
+			} else {
+				reserves.push(ReserveData { id: *id, amount: value });
+			}
+		});
+	}
+
+	/// Remove up to `value` from the named reserve `id` of `who`, returning the actual amount removed. The reserved
+	/// balance is not changed, and the caller is expected to do it.
+	fn remove_reserve_named(
+		id: &T::ReserveIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		value: T::Balance,
+	) -> T::Balance {
+		let mut reserves = Self::reserves(currency_id, who);
+		let mut actual = Zero::zero();
+		if let Some(index) = reserves.iter().position(|reserve| reserve.id == *id) {
+			actual = reserves[index].amount.min(value);
+			reserves[index].amount -= actual;
+			if reserves[index].amount.is_zero() {
+				reserves.remove(index);
+				<ReserveLabels<T>>::remove((currency_id, who.clone()), id);
+			}
+		}
+
+		if reserves.is_empty() {
+			<Reserves<T>>::remove(currency_id, who);
+		} else {
+			<Reserves<T>>::insert(currency_id, who, reserves);
+		}
+		actual
+	}
+
+	/// Reserve `amount` of `who` under `currency_id`, released back to `who` at `deadline` unless claimed before by
+	/// `claim_reserve`. Returns the ID of the reserve.
+	pub fn reserve_with_deadline(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		deadline: T::BlockNumber,
+	) -> rstd::result::Result<DeadlineReserveId, DispatchError> {
+		ensure!(
+			deadline > <frame_system::Module<T>>::block_number(),
+			Error::<T>::InvalidDeadline
+		);
+		let id = Self::next_deadline_reserve_id();
+		let next_id = id.checked_add(1).ok_or(Error::<T>::NoAvailableDeadlineReserveId)?;
+		<Self as MultiReservableCurrency<_>>::reserve(currency_id, who, amount)?;
+
+		NextDeadlineReserveId::put(next_id);
+		<DeadlineReserves<T>>::insert(id, (currency_id, who.clone(), amount, deadline));
+		<ReserveDeadlines<T>>::mutate(deadline, |ids| ids.push(id));
+
+		Self::emit_event(RawEvent::DeadlineReserveCreated(
+			id,
+			currency_id,
+			who.clone(),
+			amount,
+			deadline,
+		));
+		Ok(id)
+	}
+
+	/// Claim the deadline reserve `id` before its deadline, moving it to the free balance of `beneficiary`.
+	pub fn claim_reserve(id: DeadlineReserveId, beneficiary: &T::AccountId) -> DispatchResult {
+		let (currency_id, owner, amount, deadline) =
+			Self::deadline_reserve(id).ok_or(Error::<T>::DeadlineReserveNotFound)?;
+		let remaining = <Self as MultiReservableCurrency<_>>::repatriate_reserved(
+			currency_id,
+			&owner,
+			beneficiary,
+			amount,
+			BalanceStatus::Free,
+		)?;
+
+		<DeadlineReserves<T>>::remove(id);
+		<ReserveDeadlines<T>>::mutate(deadline, |ids| ids.retain(|i| *i != id));
+
+		Self::emit_event(RawEvent::DeadlineReserveClaimed(
+			id,
+			beneficiary.clone(),
+			amount.saturating_sub(remaining),
+		));
+		Ok(())
+	}
+
+	/// Release the deadline reserves unclaimed at `now` back to their owners.
+	fn release_deadline_reserves(now: T::BlockNumber) {
+		for id in <ReserveDeadlines<T>>::take(now) {
+			if let Some((currency_id, owner, amount, _)) = <DeadlineReserves<T>>::take(id) {
+				let remaining = <Self as MultiReservableCurrency<_>>::unreserve(currency_id, &owner, amount);
+				Self::emit_event(RawEvent::DeadlineReserveReleased(
+					id,
+					owner,
+					amount.saturating_sub(remaining),
+				));
+			}
+		}
+	}
+
+	/// Set a lock on the balance of `who` under `currency_id` like `try_set_lock`, removed at `expiry`.
+	///
+	/// Setting or extending the lock again without an expiry, or removing it, cancels the expiry.
+	pub fn set_lock_with_expiry(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		expiry: T::BlockNumber,
+	) -> DispatchResult {
+		ensure!(
+			expiry > <frame_system::Module<T>>::block_number(),
+			Error::<T>::InvalidDeadline
+		);
+		<Self as MultiLockableCurrency<_>>::try_set_lock(lock_id, currency_id, who, amount)?;
+		if amount.is_zero() {
+			return Ok(());
+		}
+
+		<LockExpiries<T>>::insert((currency_id, who.clone()), lock_id, expiry);
+		<ExpiringLocks<T>>::mutate(expiry, |locks| locks.push((currency_id, who.clone(), lock_id)));
+		Ok(())
+	}
+
+	/// The lock of `who` under `currency_id` expiring first, as `(lock_id, expiry, amount)`, or `None` if it has no
+	/// expiring locks. Locks expiring at the same block are ordered by ID.
+	pub fn next_lock_expiry(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+	) -> Option<(LockIdentifier, T::BlockNumber, T::Balance)> {
+		let (lock_id, expiry) =
+			<LockExpiries<T> as IterableStorageDoubleMap<_, _, _>>::iter((currency_id, who.clone()))
+				.min_by_key(|(lock_id, expiry)| (*expiry, *lock_id))?;
+		let amount = Self::locks(currency_id, who)
+			.into_iter()
+			.find(|lock| lock.id == lock_id)
+			.map_or_else(Zero::zero, |lock| lock.amount);
+		Some((lock_id, expiry, amount))
+	}
+
+	/// The largest number of locks on any account under `currency_id`, to size a cap on locks by.
+	///
+	/// NOTE: Iterates all accounts with locks of `currency_id`, so should only be called off-chain, e.g. by runtime
+	/// APIs.
+	pub fn max_locks_observed(currency_id: T::CurrencyId) -> u32 {
+		<Locks<T> as IterableStorageDoubleMap<_, _, _>>::iter(currency_id)
+			.map(|(_, locks)| locks.len() as u32)
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Set a lock with `lock_id`, registered to `purpose` or unregistered, as `set_lock`. Fails with `LockNotOwned` if
+	/// it's registered to another purpose.
+	pub fn set_lock_as(
+		purpose: &T::LockPurpose,
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, Some(purpose))?;
+		Self::do_set_lock(lock_id, currency_id, who, amount, WithdrawReasons::all())
+	}
+
+	/// Extend a lock with `lock_id`, registered to `purpose` or unregistered, as `extend_lock`. Fails with
+	/// `LockNotOwned` if it's registered to another purpose.
+	pub fn extend_lock_as(
+		purpose: &T::LockPurpose,
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, Some(purpose))?;
+		Self::do_extend_lock(lock_id, currency_id, who, amount)
+	}
+
+	/// Remove a lock with `lock_id`, registered to `purpose` or unregistered, as `remove_lock`. Fails with
+	/// `LockNotOwned` if it's registered to another purpose.
+	pub fn remove_lock_as(
+		purpose: &T::LockPurpose,
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, Some(purpose))?;
+		Self::do_remove_lock(lock_id, currency_id, who);
+		Ok(())
+	}
+
+	/// Set a lock with `lock_id` on the balance of `who` under each token type of `locks`, as `try_set_lock`,
+	/// depositing `LockSet` for each. Either all of the locks are set, or none if any amount exceeds the free balance
+	/// under its token type, or isn't allowed by `MaxLockRatio`.
+	pub fn set_lock_multi(
+		lock_id: LockIdentifier,
+		who: &T::AccountId,
+		locks: Vec<(T::CurrencyId, T::Balance)>,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, None)?;
+		for (currency_id, amount) in locks.iter() {
+			ensure!(
+				*amount <= Self::free_balance(*currency_id, who),
+				Error::<T>::BalanceTooLow
+			);
+			let new_locks = Self::locks_with(lock_id, *currency_id, who, *amount);
+			Self::ensure_lock_ratio(*currency_id, who, &new_locks[..])?;
+		}
+
+		for (currency_id, amount) in locks {
+			if amount.is_zero() {
+				continue;
+			}
+			Self::do_set_lock(lock_id, currency_id, who, amount, WithdrawReasons::all())?;
+			Self::emit_event(RawEvent::LockSet(currency_id, who.clone(), lock_id, amount));
+		}
+		Ok(())
+	}
+
+	/// Ensure `lock_id` is either unregistered or registered to `purpose`.
+	fn ensure_lock_owner(lock_id: LockIdentifier, purpose: Option<&T::LockPurpose>) -> DispatchResult {
+		if let Some(owner) = Self::lock_owner(lock_id) {
+			ensure!(purpose == Some(&owner), Error::<T>::LockNotOwned);
+		}
+		Ok(())
+	}
+
+	/// Cancel the expiry of the lock `lock_id` of `who` under `currency_id`, if any.
+	fn clear_lock_expiry(currency_id: T::CurrencyId, who: &T::AccountId, lock_id: LockIdentifier) {
+		if let Some(expiry) = <LockExpiries<T>>::take((currency_id, who.clone()), lock_id) {
+			<ExpiringLocks<T>>::mutate(expiry, |locks| {
+				locks.retain(|(c, w, id)| !(*c == currency_id && w == who && *id == lock_id))
+			});
+		}
+	}
+
+	/// Remove the account data of the accounts reaped a `ReapGracePeriod` ago, unless they were credited again or have
+	/// locks.
+	///
+	/// At most `MaxReapsPerBlock` accounts are processed, and the rest are queued before those of the next block.
+	fn remove_reaped_accounts(now: T::BlockNumber) {
+		let mut reaped = <ReapQueue<T>>::take(now);
+		let limit = T::MaxReapsPerBlock::get() as usize;
+		if reaped.len() > limit {
+			let mut carried = reaped.split_off(limit);
+			<ReapQueue<T>>::mutate(now + One::one(), |next| {
+				carried.append(next);
+				*next = carried;
+			});
+		}
+		for (currency_id, who) in reaped {
+			if Self::accounts(currency_id, &who).total().is_zero() && !<Locks<T>>::contains_key(currency_id, &who) {
+				<Accounts<T>>::remove(currency_id, &who);
+			}
+		}
+	}
+
+	/// Call `f` on up to `limit` accounts of `currency_id` in storage key order, starting after the raw storage key
+	/// `cursor`, or from the first account if `None`.
+	///
+	/// Returns the raw storage key of the last account iterated to resume from, or `None` if all accounts are
+	/// iterated. `f` may mutate or remove the account it's called on.
+	fn iter_accounts_from(
+		currency_id: T::CurrencyId,
+		cursor: Option<Vec<u8>>,
+		limit: u32,
+		mut f: impl FnMut(T::AccountId, AccountData<T::Balance>),
+	) -> Option<Vec<u8>> {
+		let prefix = <Accounts<T> as StorageDoubleMapGenerator<_, _, _>>::storage_double_map_final_key1(currency_id);
+		let start = cursor.unwrap_or_else(|| prefix.clone());
+		Self::iter_keys_from(&prefix, start, limit, |key| {
+			// the account ID is at the end of the key, after its `blake2_128_concat` hash
+			let who = T::AccountId::decode(&mut &key[prefix.len() + 16..]);
+			if let (Ok(who), Some(account_data)) = (who, unhashed::get::<AccountData<T::Balance>>(key)) {
+				f(who, account_data);
+			}
+		})
+	}
+
+	/// Call `f` on up to `limit` raw storage keys with `prefix` in order, starting after the raw storage key `start`.
+	///
+	/// Returns the last key iterated to resume from, or `None` if all keys are iterated.
+	fn iter_keys_from(prefix: &[u8], start: Vec<u8>, limit: u32, mut f: impl FnMut(&[u8])) -> Option<Vec<u8>> {
+		let next_key = |key: &[u8]| runtime_io::storage::next_key(key).filter(|next| next.starts_with(prefix));
+
+		let mut key = start;
+		let mut iterated = 0u32;
+		while let Some(next) = next_key(&key) {
+			if iterated == limit {
+				return Some(key);
+			}
+			key = next;
+			iterated += 1;
+			f(&key);
+		}
+		None
+	}
+
+	/// The raw storage key prefix of the storage item `item` of this module.
+	fn storage_prefix(item: &[u8]) -> Vec<u8> {
+		let mut prefix = runtime_io::hashing::twox_128(b"Tokens").to_vec();
+		prefix.extend_from_slice(&runtime_io::hashing::twox_128(item));
+		prefix
+	}
+
+	/// Backfill the counters and indexes added by `Releases::V2_0_0` for up to `limit` existing token types and
+	/// accounts in total, returning the number of them left to process in this block.
+	///
+	/// `GenesisIssuance` of an existing token type is its total issuance when backfilled. Token types and accounts
+	/// counted since the upgrade are not counted again.
+	fn process_backfill(limit: u32) -> u32 {
+		let mut budget = limit;
+		let mut cursor = Self::pending_backfill();
+		if cursor.is_none() {
+			return budget;
+		}
+
+		if let Some(BackfillCursor::Currencies(start)) = cursor.clone() {
+			let prefix = Self::storage_prefix(b"TotalIssuance");
+			let mut count = 0u32;
+			let next = Self::iter_keys_from(&prefix, start, budget, |key| {
+				count += 1;
+				// the token type is at the end of the key, after its `twox_64_concat` hash
+				let currency_id = T::CurrencyId::decode(&mut &key[prefix.len() + 8..]);
+				if let (Ok(currency_id), Some(issuance)) = (currency_id, unhashed::get::<T::Balance>(key)) {
+					if !<GenesisIssuance<T>>::contains_key(currency_id) {
+						<GenesisIssuance<T>>::insert(currency_id, issuance);
+					}
+					if !<CreatedCurrencies<T>>::get(currency_id) {
+						Self::note_issuance_change(currency_id, Zero::zero(), issuance);
+					}
+				}
+			});
+			budget = budget.saturating_sub(count);
+			cursor = Some(match next {
+				Some(key) => BackfillCursor::Currencies(key),
+				None => BackfillCursor::Accounts(Self::storage_prefix(b"Accounts")),
+			});
+		}
+
+		if let Some(BackfillCursor::Accounts(start)) = cursor.clone() {
+			let prefix = Self::storage_prefix(b"Accounts");
+			let mut count = 0u32;
+			let next = Self::iter_keys_from(&prefix, start, budget, |key| {
+				count += 1;
+				// the token type follows its `twox_64_concat` hash, and the account ID its `blake2_128_concat` hash
+				let mut input = &key[prefix.len() + 8..];
+				let currency_id = T::CurrencyId::decode(&mut input);
+				let account_data = unhashed::get::<AccountData<T::Balance>>(key);
+				if let (Ok(currency_id), Some(account_data)) = (currency_id, account_data) {
+					if let Ok(who) = T::AccountId::decode(&mut &input[16..]) {
+						if !account_data.total().is_zero() && !<AccountCurrencies<T>>::contains_key(&who, currency_id) {
+							<AccountCurrencies<T>>::insert(&who, currency_id, true);
+							<HolderCount<T>>::mutate(currency_id, |holders| *holders = holders.saturating_add(1));
+						}
+					}
+				}
+			});
+			budget = budget.saturating_sub(count);
+			cursor = next.map(BackfillCursor::Accounts);
+		}
+
+		match cursor {
+			Some(cursor) => <PendingBackfill>::put(cursor),
+			None => <PendingBackfill>::kill(),
+		}
+		budget
+	}
+
+	/// Remove the locks expiring at `now`.
+	fn remove_expired_locks(now: T::BlockNumber) {
+		for (currency_id, who, lock_id) in <ExpiringLocks<T>>::take(now) {
+			Self::do_remove_lock(lock_id, currency_id, &who);
+			Self::emit_event(RawEvent::LockExpired(currency_id, who, lock_id));
+		}
+	}
+
+	/// Start `migration` over all accounts of `currency_id`, to be processed from the next `on_initialize` on.
+	fn begin_migration(currency_id: T::CurrencyId, migration: AccountMigration) {
+		<PendingMigrations<T>>::insert(currency_id, (migration, 0));
+		<MigrationCursor<T>>::remove(currency_id);
+
+		Self::emit_event(RawEvent::MigrationStarted(currency_id, migration));
+	}
+
+	/// Start accruing interest to all accounts of the token types whose interest schedule is due at `now`, skipping
+	/// those with an interest accrual or a migration in progress.
+	fn start_scheduled_interest(now: T::BlockNumber) {
+		let due = <InterestSchedules<T> as IterableStorageMap<_, _>>::iter()
+			.filter(|(currency_id, (_, period))| {
+				(now % *period).is_zero()
+					&& !<InterestAccruals<T>>::contains_key(currency_id)
+					&& !<PendingMigrations<T>>::contains_key(currency_id)
+			})
+			.collect::<Vec<_>>();
+		for (currency_id, (rate, _)) in due {
+			<InterestAccruals<T>>::insert(currency_id, (rate, 0));
+			<InterestAccrualCursor<T>>::remove(currency_id);
+			Self::emit_event(RawEvent::InterestAccrualStarted(currency_id, rate));
+		}
+	}
+
+	/// Process pending interest accruals, up to `limit` accounts in total.
+	fn process_interest_accruals(limit: u32) {
+		let mut budget = limit;
+		let pending = <InterestAccruals<T> as IterableStorageMap<_, _>>::iter().collect::<Vec<_>>();
+		for (currency_id, (rate, processed)) in pending {
+			if budget == 0 {
+				break;
+			}
+
+			let mut count = 0u32;
+			let cursor = Self::iter_accounts_from(
+				currency_id,
+				Self::interest_accrual_cursor(currency_id),
+				budget,
+				|who, account_data| {
+					let interest = Self::interest_of(rate, account_data.free);
+					// a failed deposit, e.g. above the total issuance cap, should not stop the accrual
+					let _ = <Self as MultiCurrency<_>>::deposit(currency_id, &who, interest);
+					count += 1;
+				},
+			);
+
+			budget = budget.saturating_sub(count);
+			let processed = processed.saturating_add(count);
+			if let Some(cursor) = cursor {
+				<InterestAccrualCursor<T>>::insert(currency_id, cursor);
+				<InterestAccruals<T>>::insert(currency_id, (rate, processed));
+			} else {
+				<InterestAccruals<T>>::remove(currency_id);
+				<InterestAccrualCursor<T>>::remove(currency_id);
+				Self::emit_event(RawEvent::InterestAccrualCompleted(currency_id, processed));
+			}
+		}
+	}
+
+	/// Process pending account migrations, up to `limit` accounts in total, returning the number of accounts left
+	/// to process in this block.
+	fn process_migrations(limit: u32) -> u32 {
+		let mut budget = limit;
+		let pending = <PendingMigrations<T> as IterableStorageMap<_, _>>::iter().collect::<Vec<_>>();
+		for (currency_id, (migration, processed)) in pending {
+			if budget == 0 {
+				break;
+			}
+
+			let mut count = 0u32;
+			let cursor =
+				Self::iter_accounts_from(currency_id, Self::migration_cursor(currency_id), budget, |who, _| {
+					Self::migrate_account(currency_id, &who, migration);
+					count += 1;
+				});
+
+			budget = budget.saturating_sub(count);
+			let processed = processed.saturating_add(count);
+			if let Some(cursor) = cursor {
+				<MigrationCursor<T>>::insert(currency_id, cursor);
+				<PendingMigrations<T>>::insert(currency_id, (migration, processed));
+				Self::emit_event(RawEvent::MigrationProgress(currency_id, processed));
+			} else {
+				<PendingMigrations<T>>::remove(currency_id);
+				<MigrationCursor<T>>::remove(currency_id);
+				Self::emit_event(RawEvent::MigrationProgress(currency_id, processed));
+				Self::emit_event(RawEvent::MigrationCompleted(currency_id));
+			}
+		}
+		budget
+	}
+
+	/// Apply `migration` to `who` under `currency_id`.
+	fn migrate_account(currency_id: T::CurrencyId, who: &T::AccountId, migration: AccountMigration) {
+		match migration {
+			AccountMigration::Redenominate(numerator, denominator) => {
+				let scale = |balance: T::Balance| -> T::Balance {
+					multiply_by_rational(balance.saturated_into(), numerator.into(), denominator.into())
+						.map(|scaled| scaled.saturated_into())
+						.unwrap_or_else(|_| Zero::zero())
+				};
+
+				let account = Self::accounts(currency_id, who);
+				let free = scale(account.free);
+				let reserved = scale(account.reserved);
+				let old_total = account.total();
+				let new_total = free.saturating_add(reserved);
+				if new_total >= old_total {
+					Self::mint_issuance(currency_id, new_total - old_total);
+				} else {
+					Self::burn_issuance(currency_id, old_total - new_total);
+				}
+				Self::mutate_account(currency_id, who, |account_data| {
+					account_data.free = free;
+					account_data.reserved = reserved;
+				});
+				Self::record_free_balance(currency_id, who);
+				if <Reserves<T>>::contains_key(currency_id, who) {
+					<Reserves<T>>::mutate(currency_id, who, |reserves| {
+						reserves
+							.iter_mut()
+							.for_each(|reserve| reserve.amount = scale(reserve.amount))
+					});
+				}
+				let locks = Self::locks(currency_id, who)
+					.into_iter()
+					.map(|lock| BalanceLock {
+						id: lock.id,
+						amount: scale(lock.amount),
+					})
+					.collect::<Vec<_>>();
+				if !locks.is_empty() {
+					Self::update_locks(currency_id, who, &locks[..]);
+				}
+			}
+			AccountMigration::SweepDust => {
+				let free_balance = Self::free_balance(currency_id, who);
+				if !free_balance.is_zero() {
+					// removed as dust if below the existential deposit
+					Self::set_free_balance(currency_id, who, free_balance);
+				}
+			}
+		}
+	}
+
+	/// Whether a balance snapshot is exported at `block`.
+	fn is_snapshot_block(block: T::BlockNumber) -> bool {
+		let period = T::SnapshotBlock::get();
+		!period.is_zero() && (block % period).is_zero()
+	}
+
+	/// The off-chain local storage key of the balance snapshot exported at `block`, i.e.
+	/// `orml::tokens::snapshot::{block}`.
+	pub fn snapshot_key(block: T::BlockNumber) -> Vec<u8> {
+		let mut digits = Vec::new();
+		let mut n: u128 = block.saturated_into();
+		loop {
+			digits.push(b'0' + (n % 10) as u8);
+			n /= 10;
+			if n == 0 {
+				break;
+			}
+		}
+		digits.reverse();
+
+		let mut key = b"orml::tokens::snapshot::".to_vec();
+		key.extend(digits);
+		key
+	}
+
+	/// The account data of all accounts under `currency_id`, sorted by account, for chains forking or snapshotting
+	/// balances. The free balances may be re-imported as `endowed_accounts` of the genesis config.
+	///
+	/// NOTE: Iterates all accounts of `currency_id`, so should only be called off-chain or by a migration.
+	pub fn export_balances(currency_id: T::CurrencyId) -> Vec<(T::AccountId, AccountData<T::Balance>)> {
+		let mut balances = <Accounts<T> as IterableStorageDoubleMap<_, _, _>>::iter(currency_id).collect::<Vec<_>>();
+		balances.sort_by(|(a, _), (b, _)| a.cmp(b));
+		balances
+	}
+
+	/// Export the total issuance and account data of all token types to off-chain local storage under
+	/// `snapshot_key(block)`, returning the root of the snapshot.
+	///
+	/// The snapshot is SCALE encoded as `(Vec<(CurrencyId, Balance)>, Vec<(CurrencyId, AccountId, AccountData)>)`,
+	/// and its root is the ordered trie root of the encoded entries, total issuances first.
+	///
+	/// NOTE: This iterates over all accounts, and must only be called by the off-chain worker.
+	pub fn snapshot_balances(block: T::BlockNumber) -> T::Hash {
+		let total_issuances = <TotalIssuance<T> as IterableStorageMap<_, _>>::iter().collect::<Vec<_>>();
+		let mut accounts = Vec::new();
+		for (currency_id, _) in total_issuances.iter() {
+			for (who, account_data) in <Accounts<T> as IterableStorageDoubleMap<_, _, _>>::iter(currency_id) {
+				accounts.push((*currency_id, who, account_data));
+			}
+		}
+
+		let entries = total_issuances
+			.iter()
+			.map(Encode::encode)
+			.chain(accounts.iter().map(Encode::encode))
+			.collect::<Vec<_>>();
+		let root = T::Hashing::ordered_trie_root(entries);
+
+		runtime_io::offchain::local_storage_set(
+			StorageKind::PERSISTENT,
+			&Self::snapshot_key(block),
+			&(total_issuances, accounts).encode(),
+		);
+		root
+	}
+
+	/// The amount that has to be deposited to `who` under `currency_id` to keep it alive, which is the gap between
+	/// its free balance and the existential deposit.
+	///
+	/// Returns zero if the free balance already meets the existential deposit.
+	pub fn topup_needed(currency_id: T::CurrencyId, who: &T::AccountId) -> T::Balance {
+		T::ExistentialDeposit::get().saturating_sub(Self::free_balance(currency_id, who))
+	}
+
+	/// The number of decimals of `currency_id`, if its metadata is set.
+	pub fn currency_decimals(currency_id: T::CurrencyId) -> Option<u8> {
+		Self::get_currency_metadata(currency_id).map(|metadata| metadata.decimals)
+	}
+
+	/// The symbol of `currency_id`, if its metadata is set.
+	pub fn token_symbol(currency_id: T::CurrencyId) -> Option<Vec<u8>> {
+		Self::get_currency_metadata(currency_id).map(|metadata| metadata.symbol)
+	}
+
+	/// Remove the lock `lock_id` under `currency_id` from each of `accounts`, returning the number of accounts which
+	/// had the lock.
+	///
+	/// At most `MaxLockRemovals` accounts could be given at once.
+	pub fn remove_lock_all(
+		currency_id: T::CurrencyId,
+		lock_id: LockIdentifier,
+		accounts: &[T::AccountId],
+	) -> Result<u32, DispatchError> {
+		ensure!(
+			accounts.len() <= T::MaxLockRemovals::get() as usize,
+			Error::<T>::TooManyAccounts
+		);
+		Self::ensure_lock_owner(lock_id, None)?;
+
+		let mut affected = 0u32;
+		for who in accounts {
+			if Self::locks(currency_id, who).iter().any(|lock| lock.id == lock_id) {
+				<Self as MultiLockableCurrency<_>>::remove_lock(lock_id, currency_id, who);
+				affected += 1;
+			}
+		}
+		Ok(affected)
+	}
+
+	/// The maximum amount `slash` could deduct from `who` under `currency_id`, which is `free + reserved`.
+	pub fn slashable_balance(currency_id: T::CurrencyId, who: &T::AccountId) -> T::Balance {
+		Self::accounts(currency_id, who).total()
+	}
+
+	/// Deduct up to `amount` from the free balance of `who`, returning the amount unable to be slashed. The reserved
+	/// balance is never touched.
+	pub fn slash_free_only(currency_id: T::CurrencyId, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		if amount.is_zero() {
+			return amount;
+		}
+
+		let free_balance = Self::free_balance(currency_id, who);
+		let actual = free_balance.min(amount);
+		Self::set_free_balance(currency_id, who, free_balance - actual);
+		Self::settle_slashed(currency_id, actual);
+		amount - actual
+	}
+
+	/// Add `amount` to the reserved balance of `who` under `currency_id` and increase total issuance, depositing
+	/// `ReservedDeposited`.
+	///
+	/// The existential deposit is not enforced on reserved balance, so an account could be created by a deposit of
+	/// any amount. Deposits to blocked recipients always fail, and the native currency has no reserves.
+	pub fn deposit_reserved(currency_id: T::CurrencyId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		ensure!(!Self::is_native(currency_id), Error::<T>::ReservesNotSupported);
+		Self::ensure_not_frozen(currency_id)?;
+		Self::ensure_reservable(currency_id)?;
+		if amount.is_zero() {
+			return Ok(());
+		}
+		ensure!(!T::BlockedRecipients::contains(who), Error::<T>::RecipientBlocked);
+
+		let new_total_issuance = Self::total_issuance(currency_id)
+			.checked_add(&amount)
+			.ok_or(Error::<T>::TotalIssuanceOverflow)?;
+		if let Some(cap) = Self::total_issuance_cap(currency_id) {
+			ensure!(new_total_issuance <= cap, Error::<T>::TotalIssuanceCapExceeded);
+		}
+		Self::ensure_within_balance_cap(currency_id, who, amount)?;
+
+		Self::mint_issuance(currency_id, amount);
+		Self::set_reserved_balance(currency_id, who, Self::reserved_balance(currency_id, who) + amount);
+		Self::emit_event(RawEvent::ReservedDeposited(currency_id, who.clone(), amount));
+		Ok(())
+	}
+
+	/// Deduct up to `amount` from the combined balance of `who`, drawing from the reserved balance before the free
+	/// balance, returning the amount unable to be slashed.
+	///
+	/// This is the inverse ordering of `MultiCurrency::slash`, for slashing policies which punish bonded funds first.
+	pub fn slash_reserved_first(currency_id: T::CurrencyId, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+		if amount.is_zero() {
+			return amount;
+		}
+
+		let account = Self::accounts(currency_id, who);
+		let reserved_slashed_amount = account.reserved.min(amount);
+		let mut remaining_slash = amount - reserved_slashed_amount;
+
+		// slash reserved balance
+		if !reserved_slashed_amount.is_zero() {
+			Self::set_reserved_balance(currency_id, who, account.reserved - reserved_slashed_amount);
+		}
+
+		// slash free balance
+		if !remaining_slash.is_zero() {
+			let free_slashed_amount = account.free.min(remaining_slash);
+			remaining_slash -= free_slashed_amount;
+			Self::set_free_balance(currency_id, who, account.free - free_slashed_amount);
+		}
+
+		Self::settle_slashed(currency_id, amount - remaining_slash);
+		remaining_slash
+	}
+
+	/// Move up to `amount` from the reserved balance of `from` to the reserved balance of `to`, returning the amount
+	/// unable to be moved.
+	pub fn transfer_reserved(
+		currency_id: T::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> rstd::result::Result<T::Balance, DispatchError> {
+		Self::repatriate_reserved(currency_id, from, to, amount, BalanceStatus::Reserved)
+	}
+
+	/// The liquid balance of `who` under `currency_id`, which is the free balance minus the frozen balance, saturating
+	/// at zero. The reserved balance is never included, and unlike `transferable_keep_alive`, the existential deposit
+	/// is not kept back.
+	pub fn liquid_balance(currency_id: T::CurrencyId, who: &T::AccountId) -> T::Balance {
+		Self::accounts(currency_id, who).spendable()
+	}
+
+	/// The liquid balance of `who` under `currency_id`, less any shortfall of the reserved balance against its
+	/// `minimum_reserve_requirement`, which would have to be reserved out of the free balance to meet it.
+	pub fn withdrawable_respecting_min_reserve(currency_id: T::CurrencyId, who: &T::AccountId) -> T::Balance {
+		let shortfall = Self::minimum_reserve_requirement(currency_id, who)
+			.saturating_sub(Self::accounts(currency_id, who).reserved);
+		Self::liquid_balance(currency_id, who).saturating_sub(shortfall)
+	}
+
+	/// The free balance of `who` under `currency_id` at the latest snapshot block at or before `block`, or `None` if
+	/// snapshots are disabled, or there is no retained snapshot by then, e.g. if `block` is before the snapshots of
+	/// the account retained by `MaxAccountSnapshots`.
+	pub fn balance_at(currency_id: T::CurrencyId, who: &T::AccountId, block: T::BlockNumber) -> Option<T::Balance> {
+		Self::account_snapshots(currency_id, who)
+			.into_iter()
+			.rev()
+			.find(|(snapshot_block, _)| *snapshot_block <= block)
+			.map(|(_, balance)| balance)
+	}
+
+	/// Record the current free balance of `who` under `currency_id` as its checkpoint, replacing any previous one.
+	pub fn checkpoint_balance(currency_id: T::CurrencyId, who: &T::AccountId) {
+		let now = <frame_system::Module<T>>::block_number();
+		<BalanceCheckpoints<T>>::insert(currency_id, who, (now, Self::free_balance(currency_id, who)));
+	}
+
+	/// Whether the free balance of `who` under `currency_id` differs from its checkpoint. Always `false` if no
+	/// checkpoint was recorded.
+	///
+	/// NOTE: Only the net change is detected, a balance changed and changed back is the same as the checkpoint.
+	pub fn balance_changed_since_checkpoint(currency_id: T::CurrencyId, who: &T::AccountId) -> bool {
+		Self::balance_checkpoint(currency_id, who)
+			.map_or(false, |(_, balance)| balance != Self::free_balance(currency_id, who))
+	}
+
+	/// The amount to reserve, if positive, or unreserve, if negative, for the reserved balance of `who` under
+	/// `currency_id` to be `target_reserved`, saturating at the bounds of `Amount`.
+	pub fn reserve_delta_to(currency_id: T::CurrencyId, who: &T::AccountId, target_reserved: T::Balance) -> T::Amount {
+		let reserved = Self::reserved_balance(currency_id, who);
+		let magnitude = |balance: T::Balance| {
+			TryInto::<T::Amount>::try_into(balance).unwrap_or_else(|_| <T::Amount as arithmetic::Bounded>::max_value())
+		};
+		if target_reserved >= reserved {
+			magnitude(target_reserved - reserved)
+		} else {
+			magnitude(reserved - target_reserved).saturating_neg()
+		}
+	}
+
+	/// The liquid balance of `who` under `currency_id` net of `obligations` tracked elsewhere, negative if the
+	/// obligations exceed it, saturating at the bounds of `Amount`.
+	pub fn net_position(currency_id: T::CurrencyId, who: &T::AccountId, obligations: T::Balance) -> T::Amount {
+		let available = Self::liquid_balance(currency_id, who);
+		let magnitude = |balance: T::Balance| {
+			TryInto::<T::Amount>::try_into(balance).unwrap_or_else(|_| <T::Amount as arithmetic::Bounded>::max_value())
+		};
+		if available >= obligations {
+			magnitude(available - obligations)
+		} else {
+			magnitude(obligations - available).saturating_neg()
+		}
+	}
+
+	/// The spendable balance of `who` under `currency_id` for each of the withdraw reasons `Transfer`, `Fee` and
+	/// `Reserve`, i.e. the free balance minus the largest lock applying to the reason.
+	///
+	/// NOTE: Transfer fees are charged out of transfers, so locks only for `Fee` don't restrict them.
+	pub fn spendable_by_reason(currency_id: T::CurrencyId, who: &T::AccountId) -> Vec<(WithdrawReason, T::Balance)> {
+		let free = Self::accounts(currency_id, who).free;
+		let locks = Self::locks(currency_id, who);
+		let reasons = Self::lock_reasons(currency_id, who);
+		[WithdrawReason::Transfer, WithdrawReason::Fee, WithdrawReason::Reserve]
+			.iter()
+			.map(|reason| {
+				let frozen = Self::max_lock_for(&locks[..], &reasons[..], *reason);
+				(*reason, free.saturating_sub(frozen))
+			})
+			.collect()
+	}
+
+	/// Set a lock with `lock_id` on the balance of `who` under `currency_id` that only applies to withdrawals for
+	/// `reasons`, as `set_lock` otherwise. Setting or extending the lock by `set_lock` or `extend_lock` makes it apply
+	/// to all reasons again.
+	///
+	/// Locks of the native currency always apply to all reasons.
+	pub fn set_lock_with_reasons(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reasons: WithdrawReasons,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, None)?;
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::set_lock(lock_id, who, amount);
+			return Ok(());
+		}
+		Self::do_set_lock(lock_id, currency_id, who, amount, reasons)
+	}
+
+	/// Ensure the reserved balance of `who` under `currency_id` is exactly `expected`, or fail with
+	/// `UnexpectedReservedBalance`.
+	pub fn ensure_reserved_balance(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		expected: T::Balance,
+	) -> DispatchResult {
+		ensure!(
+			<Self as MultiReservableCurrency<_>>::reserved_balance(currency_id, who) == expected,
+			Error::<T>::UnexpectedReservedBalance
+		);
+		Ok(())
+	}
+
+	/// The account data of each of `accounts` under `currency_id`, paired with the account.
+	///
+	/// NOTE: This is intended to be used off-chain, e.g. by runtime APIs.
+	pub fn batch_account_data(
+		currency_id: T::CurrencyId,
+		accounts: Vec<T::AccountId>,
+	) -> Vec<(T::AccountId, AccountData<T::Balance>)> {
+		let balances = <Self as MultiCurrency<_>>::balances_batch(currency_id, &accounts);
+		accounts.into_iter().zip(balances).collect()
+	}
+
+	/// The sum of the total balances of `accounts` under `currency_id`, saturating at the maximum balance.
+	pub fn total_balance_of(currency_id: T::CurrencyId, accounts: &[T::AccountId]) -> T::Balance {
+		accounts.iter().fold(Zero::zero(), |acc: T::Balance, who| {
+			acc.saturating_add(<Self as MultiCurrency<_>>::total_balance(currency_id, who))
+		})
+	}
+
+	/// All token types with a non-zero total issuance.
+	///
+	/// NOTE: This iterates over all token types, and should only be used off-chain, e.g. by runtime APIs.
+	pub fn active_currencies() -> Vec<T::CurrencyId> {
+		<TotalIssuance<T> as IterableStorageMap<_, _>>::iter()
+			.filter(|(_, total_issuance)| !total_issuance.is_zero())
+			.map(|(currency_id, _)| currency_id)
+			.collect()
+	}
+
+	/// The circulating supply of `currency_id`, which is the total issuance excluding the total balances of
+	/// `excluded` accounts, like treasury and module accounts.
+	pub fn circulating_excluding(currency_id: T::CurrencyId, excluded: &[T::AccountId]) -> T::Balance {
+		excluded
+			.iter()
+			.fold(Self::total_issuance(currency_id), |circulating, who| {
+				circulating.saturating_sub(Self::total_balance(currency_id, who))
+			})
+	}
+
+	/// The share of `who` in the total issuance of `currency_id`, by total balance, rounded to the nearest. Zero if
+	/// the total issuance is zero.
+	pub fn issuance_share(currency_id: T::CurrencyId, who: &T::AccountId) -> Permill {
+		let total_issuance = Self::total_issuance(currency_id);
+		if total_issuance.is_zero() {
+			return Permill::zero();
+		}
+		Permill::from_rational_approximation(
+			Self::total_balance(currency_id, who).saturated_into::<u128>(),
+			total_issuance.saturated_into::<u128>(),
+		)
+	}
+
+	/// Move `value` from the free balance of `who` to their reserved balance under `id`, as `reserve_named`, and
+	/// label the named reserve with `label`, e.g. to be displayed as what it's held for. The label is replaced if
+	/// the named reserve exists, and removed with it.
+	///
+	/// Fails with `LabelTooLong` if `label` is longer than `MaxLabelLen`.
+	pub fn reserve_named_with_label(
+		id: &T::ReserveIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		value: T::Balance,
+		label: Vec<u8>,
+	) -> DispatchResult {
+		ensure!(label.len() <= T::MaxLabelLen::get() as usize, Error::<T>::LabelTooLong);
+		<Self as NamedMultiReservableCurrency<_>>::reserve_named(id, currency_id, who, value)?;
+		if Self::reserves(currency_id, who).iter().any(|reserve| reserve.id == *id) {
+			<ReserveLabels<T>>::insert((currency_id, who.clone()), id, label);
+		}
+		Ok(())
+	}
+
+	/// Reserve `value` from the free balance of `who`, as `reserve`, but fail with `KeepAlive` if the free balance
+	/// left would be below the existential deposit, as reserved balances don't keep an account alive.
+	pub fn reserve_keep_alive(currency_id: T::CurrencyId, who: &T::AccountId, value: T::Balance) -> DispatchResult {
+		if let Some(free_left) = Self::free_balance(currency_id, who).checked_sub(&value) {
+			ensure!(
+				value.is_zero() || free_left >= T::ExistentialDeposit::get(),
+				Error::<T>::KeepAlive
+			);
+		}
+		<Self as MultiReservableCurrency<_>>::reserve(currency_id, who, value)
+	}
+
+	/// The named reserves of `who` under `currency_id`, as `(id, amount, label)`. The label is empty if not set.
+	pub fn reserves_with_labels(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+	) -> Vec<(T::ReserveIdentifier, T::Balance, Vec<u8>)> {
+		Self::reserves(currency_id, who)
+			.into_iter()
+			.map(|reserve| {
+				let label = Self::reserve_label((currency_id, who.clone()), reserve.id).unwrap_or_default();
+				(reserve.id, reserve.amount, label)
+			})
+			.collect()
+	}
+
+	/// The amount `unreserve_named` would currently move from the named reserve `reserve_id` of `who` to their free
+	/// balance, which is less than originally reserved if the reserve was partially slashed. Zero while an emergency
+	/// shutdown is active.
+	pub fn releasable_named(
+		currency_id: T::CurrencyId,
+		reserve_id: &T::ReserveIdentifier,
+		who: &T::AccountId,
+	) -> T::Balance {
+		if Self::shutdown_active() {
+			return Zero::zero();
+		}
+		<Self as NamedMultiReservableCurrency<_>>::reserved_balance_named(reserve_id, currency_id, who)
+	}
+
+	/// The sum of the balances reserved under `reserve_id` by all accounts under `currency_id`, saturating at the
+	/// maximum balance.
+	///
+	/// NOTE: Iterates all accounts with named reserves of `currency_id`, so should only be called off-chain, e.g. by
+	/// runtime APIs.
+	pub fn total_reserved_named(currency_id: T::CurrencyId, reserve_id: &T::ReserveIdentifier) -> T::Balance {
+		<Reserves<T> as IterableStorageDoubleMap<_, _, _>>::iter(currency_id)
+			.flat_map(|(_, reserves)| reserves.into_iter())
+			.filter(|reserve| reserve.id == *reserve_id)
+			.fold(Zero::zero(), |acc: T::Balance, reserve| {
+				acc.saturating_add(reserve.amount)
+			})
+	}
+
+	/// All accounts with a non-zero named reserve `reserve_id` under `currency_id`, with the amounts reserved.
+	///
+	/// NOTE: Iterates all accounts with named reserves of `currency_id`, so should only be called off-chain, e.g. by
+	/// runtime APIs.
+	pub fn accounts_with_reserve(
+		currency_id: T::CurrencyId,
+		reserve_id: &T::ReserveIdentifier,
+	) -> Vec<(T::AccountId, T::Balance)> {
+		<Reserves<T> as IterableStorageDoubleMap<_, _, _>>::iter(currency_id)
+			.filter_map(|(who, reserves)| {
+				reserves
+					.into_iter()
+					.find(|reserve| reserve.id == *reserve_id && !reserve.amount.is_zero())
+					.map(|reserve| (who, reserve.amount))
+			})
+			.collect()
+	}
+
+	/// The net issuance of `currency_id` minted since genesis, negative if burned, saturating at the bounds of
+	/// `Amount`.
+	pub fn issuance_since_genesis(currency_id: T::CurrencyId) -> T::Amount {
+		let current = Self::total_issuance(currency_id);
+		let genesis = Self::genesis_issuance(currency_id);
+		let magnitude = |balance: T::Balance| {
+			TryInto::<T::Amount>::try_into(balance).unwrap_or_else(|_| <T::Amount as arithmetic::Bounded>::max_value())
+		};
+		if current >= genesis {
+			magnitude(current - genesis)
+		} else {
+			magnitude(genesis - current).saturating_neg()
+		}
+	}
+
+	/// The total balance of `who` under all token types in native-equivalent units, by `ExchangeRates`, saturating at
+	/// the maximum balance. Cached for the current block.
+	pub fn total_balance_native_equivalent(who: &T::AccountId) -> T::Balance {
+		let now = <frame_system::Module<T>>::block_number();
+		if let Some((at, cached)) = Self::native_equivalent_cache(who) {
+			if at == now {
+				return cached;
+			}
+		}
+
+		let total =
+			Self::currencies_of(who)
+				.into_iter()
+				.fold(Zero::zero(), |acc: T::Balance, (currency_id, account_data)| {
+					let rate = Self::exchange_rate(currency_id).unwrap_or_else(FixedU128::one);
+					acc.saturating_add(rate.saturating_mul_int(&account_data.total()))
+				});
+		<NativeEquivalentCache<T>>::insert(who, (now, total));
+		<NativeEquivalentCachedAccounts<T>>::mutate(|accounts| accounts.push(who.clone()));
+		total
+	}
+
+	/// Remove all entries of `NativeEquivalentCache`, so that it only lives within a block.
+	fn clear_native_equivalent_cache() {
+		for who in <NativeEquivalentCachedAccounts<T>>::take() {
+			<NativeEquivalentCache<T>>::remove(who);
+		}
+	}
+
+	/// The minimum free balance needed to perform `ops` under `currency_id`, which is the sum of all withdrawals and
+	/// reserves, plus the existential deposit if any op is `KeepAlive`. Saturates at the maximum balance.
+	pub fn required_balance(_currency_id: T::CurrencyId, ops: &[BalanceOp<T::Balance>]) -> T::Balance {
+		let keep_alive = ops.iter().any(|op| *op == BalanceOp::KeepAlive);
+		let existential_deposit = if keep_alive {
+			T::ExistentialDeposit::get()
+		} else {
+			Zero::zero()
+		};
+		ops.iter().fold(existential_deposit, |required, op| match op {
+			BalanceOp::Withdraw(amount) | BalanceOp::Reserve(amount) => required.saturating_add(*amount),
+			BalanceOp::KeepAlive => required,
+		})
+	}
+
+	/// Whether the free balance of `who` under `currency_id` would meet `threshold` after depositing `incoming`.
+	pub fn would_be_sufficient(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		incoming: T::Balance,
+		threshold: T::Balance,
+	) -> bool {
+		Self::free_balance(currency_id, who).saturating_add(incoming) >= threshold
+	}
+
+	/// Reserve as much as possible of `max` from the free balance of `who`, excluding any frozen, returning the amount
+	/// actually reserved.
+	///
+	/// Unlike `reserve`, this never fails on insufficient balance. Nothing is reserved if the token type is frozen.
+	pub fn reserve_up_to(currency_id: T::CurrencyId, who: &T::AccountId, max: T::Balance) -> T::Balance {
+		let amount = max.min(Self::accounts(currency_id, who).spendable());
+		match <Self as MultiReservableCurrency<_>>::reserve(currency_id, who, amount) {
+			Ok(()) => amount,
+			Err(_) => Zero::zero(),
+		}
+	}
+
+	/// Whether `required_free` could be withdrawn from and `required_reserve` could be reserved by `who` under
+	/// `currency_id` together, i.e. their sum is within the free balance excluding any frozen, from a single read.
+	///
+	/// Meant for collateral checks of other modules about to do both. Not sufficient if the sum overflows.
+	pub fn has_sufficient_balance(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		required_free: T::Balance,
+		required_reserve: T::Balance,
+	) -> bool {
+		required_free.checked_add(&required_reserve).map_or(false, |required| {
+			Self::accounts(currency_id, who).spendable() >= required
+		})
+	}
+
+	/// The balance `who` could still spend under `currency_id` after reserving `reserve` and then transferring
+	/// `transfer`, or `None` if the two together are not affordable. The transfer fee is charged out of `transfer`.
+	pub fn net_available_after(
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		reserve: T::Balance,
+		transfer: T::Balance,
+	) -> Option<T::Balance> {
+		let required = reserve.checked_add(&transfer)?;
+		Self::accounts(currency_id, who).spendable().checked_sub(&required)
+	}
+
+	/// The status of `who` under `currency_id`, by comparing its total balance with the existential deposit.
+	///
+	/// Note the existential deposit is shared by all token types.
+	pub fn account_status(currency_id: T::CurrencyId, who: &T::AccountId) -> AccountStatus {
+		let total = Self::total_balance(currency_id, who);
+		if total.is_zero() {
+			AccountStatus::Dead
+		} else if total < T::ExistentialDeposit::get() {
+			AccountStatus::Dust
+		} else {
+			AccountStatus::Alive
+		}
+	}
+
+	/// Check a transfer `amount` of `currency_id` against its `Granularity`, returning the amount to transfer, which
+	/// is rounded down to a multiple of the granularity, or rejected with `InvalidGranularity`, by
+	/// `GranularityPolicy`.
+	fn apply_granularity(
+		currency_id: T::CurrencyId,
+		amount: T::Balance,
+	) -> rstd::result::Result<T::Balance, DispatchError> {
+		let granularity = T::Granularity::convert(currency_id);
+		if granularity <= One::one() {
+			return Ok(amount);
+		}
+		let remainder = amount % granularity;
+		if remainder.is_zero() {
+			return Ok(amount);
+		}
+		match T::GranularityPolicy::get() {
+			GranularityPolicy::Reject => Err(Error::<T>::InvalidGranularity.into()),
+			GranularityPolicy::RoundDown => Ok(amount - remainder),
+		}
+	}
+
+	/// Split `fee` into `(burned, to_treasury)` by `TreasuryFeeShare`.
+	///
+	/// The treasury share is computed first and any rounding remainder is burned, so `burned + to_treasury`
+	/// always equals `fee`.
+	pub fn split_fee(fee: T::Balance) -> (T::Balance, T::Balance) {
+		let to_treasury = T::TreasuryFeeShare::get() * fee;
+		(fee.saturating_sub(to_treasury), to_treasury)
+	}
+
+	/// Split `total` into shares in proportion to `weights`, in the same order. Shares are rounded down, and the
+	/// remainder goes to the share of the largest weight, the first one if tied, so the shares always sum to `total`.
+	/// Returns `None` if the weights sum to zero.
+	pub fn split_by_weight(total: T::Balance, weights: &[u32]) -> Option<Vec<T::Balance>> {
+		let total_weight = weights.iter().map(|weight| u128::from(*weight)).sum::<u128>();
+		if total_weight.is_zero() {
+			return None;
+		}
+
+		let mut shares = weights
+			.iter()
+			.map(|weight| {
+				// a share never exceeds `total`, so neither the multiplication nor the conversion could fail
+				multiply_by_rational(total.saturated_into(), u128::from(*weight), total_weight)
+					.map(|share| share.saturated_into::<T::Balance>())
+					.unwrap_or_else(|_| Zero::zero())
+			})
+			.collect::<Vec<_>>();
+		let remainder = shares
+			.iter()
+			.fold(total, |remainder, share| remainder.saturating_sub(*share));
+		let largest = weights.iter().enumerate().fold(
+			0,
+			|largest, (index, weight)| if *weight > weights[largest] { index } else { largest },
+		);
+		shares[largest] = shares[largest].saturating_add(remainder);
+		Some(shares)
+	}
+
+	/// Split `dust` into the `(burned, credited)` shares by `DustSplit`, the credited one being the exact remainder.
+	pub fn split_dust(dust: T::Balance) -> (T::Balance, T::Balance) {
+		let burned = T::DustSplit::get() * dust;
+		(burned, dust.saturating_sub(burned))
+	}
+
+	/// The amount `to` receives from a `transfer` of `gross` by `from`, after the transfer fee is deducted.
+	///
+	/// It's `gross` if the transfer is exempt, or to `from` itself. The burned and treasury parts of the fee are both
+	/// split from the fee itself, so they don't change the net amount. Transfers via `MultiCurrency` are not charged,
+	/// and their recipients receive `gross`.
+	pub fn net_transfer_amount(
+		currency_id: T::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		gross: T::Balance,
+	) -> T::Balance {
+		if from == to || Self::is_fee_exempt(currency_id, from, to) {
+			return gross;
+		}
+		gross - T::TransferFee::get() * gross
+	}
+
+	/// Whether a transfer of `currency_id` from `from` to `to` is not charged the transfer fee, by
+	/// `FeeExemptCurrencies` or `FeeExemptAccounts`.
+	pub fn is_fee_exempt(currency_id: T::CurrencyId, from: &T::AccountId, to: &T::AccountId) -> bool {
+		T::FeeExemptCurrencies::contains(&currency_id)
+			|| T::FeeExemptAccounts::contains(from)
+			|| T::FeeExemptAccounts::contains(to)
+	}
+
+	/// The total transfer fee `transfer` would charge on a transfer of `amount` of `currency_id` from `from` to `to`,
+	/// including both its burned and treasury parts, for clients to preview.
+	///
+	/// It's zero if the transfer is exempt, or to `from` itself. The fee is computed on `amount` as rounded down by
+	/// `GranularityPolicy`, if it is, except for the native currency.
+	pub fn compute_transfer_fee(
+		currency_id: T::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+	) -> T::Balance {
+		if from == to || Self::is_fee_exempt(currency_id, from, to) {
+			return Zero::zero();
+		}
+		let amount = if Self::is_native(currency_id) {
+			amount
+		} else {
+			Self::apply_granularity(currency_id, amount).unwrap_or(amount)
+		};
+		T::TransferFee::get() * amount
+	}
+
+	/// The service fee `transfer` would charge `from` on top of a transfer of `currency_id` to `to`, zero if the
+	/// transfer is exempt from the transfer fee, or to `from` itself.
+	pub fn service_fee(currency_id: T::CurrencyId, from: &T::AccountId, to: &T::AccountId) -> T::Balance {
+		if from == to || Self::is_fee_exempt(currency_id, from, to) {
+			return Zero::zero();
+		}
+		T::ServiceFee::get()
+	}
+
+	/// The amount to send so that the recipient receives exactly `receive_amount` after the transfer fee, or `None`
+	/// if no such amount exists.
+	pub fn gross_transfer_amount(receive_amount: T::Balance) -> Option<T::Balance> {
+		let rate = T::TransferFee::get();
+		let keep = Permill::one().deconstruct() - rate.deconstruct();
+		if keep == 0 {
+			return None;
+		}
+		let net = |gross: T::Balance| gross - rate * gross;
+
+		// start from the rounded down exact solution, then correct the rounding of fee calculation
+		let mut gross: T::Balance = multiply_by_rational(
+			receive_amount.saturated_into(),
+			Permill::one().deconstruct().into(),
+			keep.into(),
+		)
+		.ok()?
+		.saturated_into();
+		while net(gross) < receive_amount {
+			gross = gross.checked_add(&One::one())?;
+		}
+		while !gross.is_zero() && net(gross - One::one()) >= receive_amount {
+			gross -= One::one();
+		}
+
+		if net(gross) == receive_amount {
+			Some(gross)
+		} else {
+			None
+		}
+	}
+
+	/// Whether `who` can afford a transfer of `amount`.
+	///
+	/// The gross cost is `amount` plus the transfer fee on it, including both its burned and treasury parts, plus the
+	/// service fee, and is compared against the balance `who` can spend for transfers. It is not affordable if the
+	/// gross cost overflows. As the recipient is not known, only `who` is checked for fee exemption.
+	pub fn can_afford(currency_id: T::CurrencyId, who: &T::AccountId, amount: T::Balance) -> bool {
+		let gross = if T::FeeExemptCurrencies::contains(&currency_id) || T::FeeExemptAccounts::contains(who) {
+			Some(amount)
+		} else {
+			amount
+				.checked_add(&(T::TransferFee::get() * amount))
+				.and_then(|gross| gross.checked_add(&T::ServiceFee::get()))
+		};
+		gross.map_or(false, |gross| {
+			<Self as MultiCurrency<_>>::ensure_can_withdraw(currency_id, who, gross).is_ok()
+		})
+	}
+
+	/// The largest amount `from` could send to `to` with `transfer`, keeping `from` alive, or zero if what `to`
+	/// would receive after the transfer fee couldn't create it when it doesn't exist.
+	///
+	/// The service fee `from` would be charged on top of the amount is left out of it.
+	/// Both fees are only counted if the transfer is not exempt.
+	pub fn max_keepalive_transfer(currency_id: T::CurrencyId, from: &T::AccountId, to: &T::AccountId) -> T::Balance {
+		let max = <Self as MultiCurrency<_>>::transferable_keep_alive(currency_id, from)
+			.saturating_sub(Self::service_fee(currency_id, from, to));
+		let creates_recipient =
+			<Self as MultiCurrency<_>>::total_balance(currency_id, to).is_zero() && !T::ProtectedAccounts::contains(to);
+		if creates_recipient && Self::net_transfer_amount(currency_id, from, to, max) < T::ExistentialDeposit::get() {
+			return Zero::zero();
+		}
+		max
+	}
+
+	/// Transfer `amount` from `from` to `to`.
+	///
+	/// If `allow_overflow` is set and both `from` and `to` are `ProtocolAccounts`, the balance of `to` saturates
+	/// instead of failing with `TotalIssuanceOverflow`, so that internal protocol movements never fail on it. The
+	/// amount lost by saturating is removed from total issuance.
+	pub(crate) fn transfer_internal(
+		currency_id: T::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+		allow_overflow: bool,
+	) -> DispatchResult {
+		let new_to_balance = Self::ensure_can_transfer(currency_id, from, to, amount, allow_overflow)?;
+		if amount.is_zero() || from == to {
+			return Ok(());
+		}
+
+		let from_balance = Self::free_balance(currency_id, from);
+		let received = new_to_balance - Self::free_balance(currency_id, to);
+		Self::set_free_balance(currency_id, from, from_balance - amount);
+		Self::set_free_balance(currency_id, to, new_to_balance);
+		Self::burn_issuance(currency_id, amount - received);
+		T::OnTransferRecord::on_transfer_record(currency_id, amount);
+
+		Ok(())
+	}
+
+	/// A dry-run of `transfer_internal`, returning the free balance of `to` after the transfer.
+	///
+	/// Transfers to blocked recipients always fail, as they are not deposits which could bounce.
+	fn ensure_can_transfer(
+		currency_id: T::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: T::Balance,
+		allow_overflow: bool,
+	) -> rstd::result::Result<T::Balance, DispatchError> {
+		Self::ensure_not_frozen(currency_id)?;
+		Self::ensure_sender_allowed(currency_id, from)?;
+		let to_balance = Self::free_balance(currency_id, to);
+		if amount.is_zero() || from == to {
+			return Ok(to_balance);
+		}
+		ensure!(!T::BlockedRecipients::contains(to), Error::<T>::RecipientBlocked);
+		Self::ensure_can_withdraw(currency_id, from, amount)?;
+
+		let new_to_balance =
+			if allow_overflow && T::ProtocolAccounts::contains(from) && T::ProtocolAccounts::contains(to) {
+				to_balance.saturating_add(amount)
+			} else {
+				to_balance
+					.checked_add(&amount)
+					.ok_or(Error::<T>::TotalIssuanceOverflow)?
+			};
+		ensure!(
+			new_to_balance >= T::ExistentialDeposit::get() || T::ProtectedAccounts::contains(to),
+			Error::<T>::ExistentialDeposit,
+		);
+		Self::ensure_within_balance_cap(currency_id, to, amount)?;
+		Ok(new_to_balance)
+	}
+
+	/// Check whether `from` could transfer `amount` of `currency_id` to an account with zero balance, without
+	/// writing anything.
+	///
+	/// Fails with `BalanceTooLow` or `LiquidityRestrictions` if `from` can't withdraw `amount`, or with
+	/// `ExistentialDeposit` if `amount` is below the existential deposit.
+	pub fn pre_flight_transfer(currency_id: T::CurrencyId, from: &T::AccountId, amount: T::Balance) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		<Self as MultiCurrency<_>>::ensure_can_withdraw(currency_id, from, amount)?;
+		ensure!(amount >= T::ExistentialDeposit::get(), Error::<T>::ExistentialDeposit);
+		Ok(())
+	}
+
+	/// Swap `amount_a` of `currency_a` from `from_a` to `from_b` with `amount_b` of `currency_b` from `from_b` to
+	/// `from_a`, all or nothing, and deposit a `Transferred` event per leg.
+	///
+	/// Both legs are applied in sequence in a storage transaction, which is rolled back if either fails.
+	pub fn atomic_double_transfer(
+		currency_a: T::CurrencyId,
+		from_a: &T::AccountId,
+		amount_a: T::Balance,
+		currency_b: T::CurrencyId,
+		from_b: &T::AccountId,
+		amount_b: T::Balance,
+	) -> DispatchResult {
+		with_transaction_result(|| {
+			Self::transfer_internal(currency_a, from_a, from_b, amount_a, false)?;
+			Self::transfer_internal(currency_b, from_b, from_a, amount_b, false)
+		})?;
+
+		Self::emit_event(RawEvent::Transferred(
+			currency_a,
+			from_a.clone(),
+			from_b.clone(),
+			amount_a,
+		));
+		Self::emit_event(RawEvent::Transferred(
+			currency_b,
+			from_b.clone(),
+			from_a.clone(),
+			amount_b,
+		));
+		Ok(())
+	}
+
+	/// Transfer some balance of `currency_id` from `from` to each of `recipients`, all or nothing, and deposit a
+	/// `Transferred` event per recipient. No transfer fee is charged, and transfers to `from` itself are no-ops.
+	///
+	/// Meant for large one-to-many distributions: the amounts to a recipient listed more than once are netted, the
+	/// account of `from` is read once and validated against the sum of all amounts, then written once, and each
+	/// recipient is validated against its netted amount, then written once. As transfers don't change total
+	/// issuance, it's only updated if `from` is left with dust. Transfers of the native currency are routed to
+	/// `NativeCurrency`, one per recipient, in a transaction.
+	pub fn transfer_batch_same_currency(
+		currency_id: T::CurrencyId,
+		from: &T::AccountId,
+		recipients: Vec<(T::AccountId, T::Balance)>,
+	) -> DispatchResult {
+		let mut netted: Vec<(T::AccountId, T::Balance)> = Vec::new();
+		let mut positions = BTreeMap::<T::AccountId, usize>::new();
+		for (to, amount) in recipients {
+			if to == *from || amount.is_zero() {
+				continue;
+			}
+			match positions.get(&to) {
+				Some(&position) => {
+					netted[position].1 = netted[position]
+						.1
+						.checked_add(&amount)
+						.ok_or(Error::<T>::BalanceTooLow)?
+				}
+				None => {
+					positions.insert(to.clone(), netted.len());
+					netted.push((to, amount));
+				}
+			}
+		}
+		let recipients = netted;
+		let total = recipients
+			.iter()
+			.try_fold(T::Balance::zero(), |acc, (_, amount)| acc.checked_add(amount))
+			.ok_or(Error::<T>::BalanceTooLow)?;
+
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::ensure_can_withdraw(from, total)?;
+			with_transaction_result(|| {
+				for (to, amount) in recipients.iter() {
+					T::NativeCurrency::transfer(from, to, *amount)?;
+				}
+				Ok(())
+			})?;
+			for (to, amount) in recipients {
+				Self::emit_event(RawEvent::Transferred(currency_id, from.clone(), to, amount));
+			}
+			return Ok(());
+		}
+		Self::ensure_not_frozen(currency_id)?;
+		Self::ensure_not_migrating(currency_id)?;
+		Self::ensure_sender_allowed(currency_id, from)?;
+
+		let from_account = Self::accounts(currency_id, from);
+		let new_from_balance = from_account.free.checked_sub(&total).ok_or(Error::<T>::BalanceTooLow)?;
+		ensure!(
+			new_from_balance >= from_account.frozen(),
+			Error::<T>::LiquidityRestrictions
+		);
+		for (to, amount) in recipients.iter() {
+			ensure!(
+				Self::free_balance(currency_id, to).saturating_add(*amount) >= T::ExistentialDeposit::get()
+					|| T::ProtectedAccounts::contains(to),
+				Error::<T>::ExistentialDeposit,
+			);
+			Self::ensure_within_balance_cap(currency_id, to, *amount)?;
+		}
+
+		Self::set_free_balance(currency_id, from, new_from_balance);
+		for (to, amount) in recipients {
+			// the sum of all balances is bounded by total issuance, so this cannot overflow
+			Self::set_free_balance(currency_id, &to, Self::free_balance(currency_id, &to) + amount);
+			T::OnTransferRecord::on_transfer_record(currency_id, amount);
+			Self::emit_event(RawEvent::Transferred(currency_id, from.clone(), to, amount));
+		}
+
+		Ok(())
+	}
+
+	/// Transfer `amount` from `from` to `to`, charging the transfer fee out of `amount`, and deposit events.
+	///
+	/// `to` receives `amount` minus the fee. The fee is split by `split_fee`, the burned part is removed from total
+	/// issuance and the rest is deposited to `Treasury`. If the treasury share is not enough to create the treasury
+	/// account, it's burned too. The service fee is charged to `from` on top of `amount`, and deposited to `Treasury`,
+	/// or burned the same way.
+	fn transfer_with_fee(
+		currency_id: T::CurrencyId,
+		from: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			return Self::native_transfer_with_fee(currency_id, from, to, amount);
+		}
+		Self::ensure_not_frozen(currency_id)?;
+		Self::ensure_not_migrating(currency_id)?;
+		Self::ensure_sender_allowed(currency_id, &from)?;
+		let amount = Self::apply_granularity(currency_id, amount)?;
+		if amount.is_zero() || from == to {
+			Self::emit_event(RawEvent::Transferred(currency_id, from, to, amount));
+			return Ok(());
+		}
+		ensure!(!T::BlockedRecipients::contains(&to), Error::<T>::RecipientBlocked);
+		let service_fee = Self::service_fee(currency_id, &from, &to);
+		let cost = amount.checked_add(&service_fee).ok_or(Error::<T>::BalanceTooLow)?;
+		Self::ensure_can_withdraw(currency_id, &from, cost)?;
+
+		let fee = Self::compute_transfer_fee(currency_id, &from, &to, amount);
+		let received = amount - fee;
+		let from_balance = Self::free_balance(currency_id, &from);
+		let to_balance = Self::free_balance(currency_id, &to);
+		ensure!(
+			to_balance + received >= T::ExistentialDeposit::get() || T::ProtectedAccounts::contains(&to),
+			Error::<T>::ExistentialDeposit,
+		);
+		Self::ensure_within_balance_cap(currency_id, &to, received)?;
+
+		with_transaction_result(|| {
+			Self::set_free_balance(currency_id, &from, from_balance - cost);
+			Self::set_free_balance(currency_id, &to, to_balance + received);
+			T::OnTransferRecord::on_transfer_record(currency_id, amount);
+			Self::emit_event(RawEvent::Transferred(currency_id, from.clone(), to.clone(), received));
+
+			if !fee.is_zero() {
+				let (burned, to_treasury) = Self::settle_fee(currency_id, fee)?;
+				Self::emit_event(RawEvent::TransferFeeCharged(
+					currency_id,
+					from.clone(),
+					burned,
+					to_treasury,
+				));
+			}
+			if !service_fee.is_zero() {
+				Self::burn_issuance(currency_id, service_fee);
+				// total issuance was just reduced by `service_fee`, re-issuing it cannot overflow
+				<Self as MultiCurrency<_>>::deposit(currency_id, &T::Treasury::get(), service_fee)?;
+				Self::emit_event(RawEvent::ServiceFeeCharged(currency_id, from.clone(), service_fee));
+			}
+			Ok(())
+		})
+	}
+
+	/// `transfer_with_fee` of the native currency, by `NativeCurrency`. The fee is withdrawn from `from` and its
+	/// treasury share by `split_fee` is deposited to `Treasury`, and the service fee is transferred to `Treasury`.
+	fn native_transfer_with_fee(
+		currency_id: T::CurrencyId,
+		from: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
+		Self::ensure_not_frozen(currency_id)?;
+		Self::ensure_sender_allowed(currency_id, &from)?;
+		if amount.is_zero() || from == to {
+			Self::emit_event(RawEvent::Transferred(currency_id, from, to, amount));
+			return Ok(());
+		}
+		let service_fee = Self::service_fee(currency_id, &from, &to);
+		let cost = amount.checked_add(&service_fee).ok_or(Error::<T>::BalanceTooLow)?;
+		T::NativeCurrency::ensure_can_withdraw(&from, cost)?;
+
+		let fee = Self::compute_transfer_fee(currency_id, &from, &to, amount);
+		let received = amount - fee;
+		with_transaction_result(|| {
+			T::NativeCurrency::transfer(&from, &to, received)?;
+			Self::emit_event(RawEvent::Transferred(currency_id, from.clone(), to.clone(), received));
+
+			if !fee.is_zero() {
+				T::NativeCurrency::withdraw(&from, fee)?;
+				let (burned, to_treasury) = Self::split_fee(fee);
+				T::NativeCurrency::deposit(&T::Treasury::get(), to_treasury)?;
+				Self::emit_event(RawEvent::TransferFeeCharged(
+					currency_id,
+					from.clone(),
+					burned,
+					to_treasury,
+				));
+			}
+			if !service_fee.is_zero() {
+				T::NativeCurrency::transfer(&from, &T::Treasury::get(), service_fee)?;
+				Self::emit_event(RawEvent::ServiceFeeCharged(currency_id, from.clone(), service_fee));
+			}
+			Ok(())
+		})
+	}
+
+	/// Burn `fee` from total issuance and deposit its treasury share by `split_fee` to `Treasury`, returning the
+	/// burned and deposited amounts. If the treasury share is not enough to create the treasury account, it's burned
+	/// too.
+	fn settle_fee(
+		currency_id: T::CurrencyId,
+		fee: T::Balance,
+	) -> rstd::result::Result<(T::Balance, T::Balance), DispatchError> {
+		let treasury = T::Treasury::get();
+		let (burned, to_treasury) = Self::split_fee(fee);
+		let (burned, to_treasury) = if Self::free_balance(currency_id, &treasury).is_zero()
+			&& to_treasury < T::ExistentialDeposit::get()
+			&& !T::ProtectedAccounts::contains(&treasury)
+		{
+			(fee, Zero::zero())
 		} else {
-			<Accounts<T>>::mutate(currency_id, who, |account_data| account_data.free = balance);
-		}
+			(burned, to_treasury)
+		};
+
+		Self::burn_issuance(currency_id, fee);
+		// total issuance was just reduced by `fee`, re-issuing a part of it cannot overflow
+		<Self as MultiCurrency<_>>::deposit(currency_id, &treasury, to_treasury)?;
+		Ok((burned, to_treasury))
 	}
 
-	/// Set reserved balance of `who` to a new value, meanwhile enforce existential rule.
-	///
-	/// Note this will not maintain total issuance, and the caller is expected to do it.
-	fn set_reserved_balance(currency_id: T::CurrencyId, who: &T::AccountId, balance: T::Balance) {
-		<Accounts<T>>::mutate(currency_id, who, |account_data| account_data.reserved = balance);
+	/// The payload `from` signs for `intent` to be submitted by `submit_transfer_intent`: `TRANSFER_INTENT_TAG`, the
+	/// genesis hash of this chain and `intent`, SCALE encoded, so that it can't be replayed on other chains.
+	pub fn intent_signing_payload(
+		intent: &SignedTransferIntent<T::AccountId, T::CurrencyId, T::Balance, T::BlockNumber>,
+	) -> Vec<u8> {
+		let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+		(TRANSFER_INTENT_TAG, genesis_hash, intent).encode()
 	}
 
-	/// Update the account entry for `who` under `currency_id`, given the locks.
-	fn update_locks(currency_id: T::CurrencyId, who: &T::AccountId, locks: &[BalanceLock<T::Balance>]) {
-		// update account data
-		<Accounts<T>>::mutate(currency_id, who, |account_data| {
-			account_data.frozen = Zero::zero();
-			for lock in locks.iter() {
-				account_data.frozen = account_data.frozen.max(lock.amount);
-			}
-		});
+	/// Transfer the full `amount` from `from` to `to`, charging the transfer fee to `relayer` instead, which is
+	/// settled by `settle_fee` before `amount` is moved. No fee is charged on the native currency.
+	fn transfer_with_relayer_fee(
+		currency_id: T::CurrencyId,
+		from: T::AccountId,
+		to: T::AccountId,
+		amount: T::Balance,
+		relayer: &T::AccountId,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::transfer(&from, &to, amount)?;
+			Self::emit_event(RawEvent::Transferred(currency_id, from, to, amount));
+			return Ok(());
+		}
+		Self::ensure_not_migrating(currency_id)?;
+		let amount = Self::apply_granularity(currency_id, amount)?;
+		let fee = Self::compute_transfer_fee(currency_id, &from, &to, amount);
 
-		// update locks
-		let existed = <Locks<T>>::contains_key(currency_id, who);
-		if locks.is_empty() {
-			<Locks<T>>::remove(currency_id, who);
-			if existed {
-				// decrease account ref count when destruct lock
-				system::Module::<T>::dec_ref(who);
-			}
-		} else {
-			<Locks<T>>::insert(currency_id, who, locks);
-			if !existed {
-				// increase account ref count when initialize lock
-				system::Module::<T>::inc_ref(who);
+		with_transaction_result(|| {
+			if !fee.is_zero() {
+				Self::ensure_can_withdraw(currency_id, relayer, fee)?;
+				Self::set_free_balance(currency_id, relayer, Self::free_balance(currency_id, relayer) - fee);
+				let (burned, to_treasury) = Self::settle_fee(currency_id, fee)?;
+				Self::emit_event(RawEvent::TransferFeeCharged(
+					currency_id,
+					relayer.clone(),
+					burned,
+					to_treasury,
+				));
 			}
-		}
+
+			Self::transfer_internal(currency_id, &from, &to, amount, false)?;
+			Self::emit_event(RawEvent::Transferred(currency_id, from.clone(), to.clone(), amount));
+			Ok(())
+		})
 	}
 }
 
@@ -270,29 +3940,75 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 	type Balance = T::Balance;
 
 	fn total_issuance(currency_id: Self::CurrencyId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::total_issuance();
+		}
 		<TotalIssuance<T>>::get(currency_id)
 	}
 
 	fn total_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::total_balance(who);
+		}
 		Self::accounts(currency_id, who).total()
 	}
 
 	fn free_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::free_balance(who);
+		}
 		Self::accounts(currency_id, who).free
 	}
 
+	/// For the native currency, locks are not exposed by `BasicCurrency`, so this is the free balance.
+	fn effective_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::free_balance(who);
+		}
+		Self::accounts(currency_id, who).spendable()
+	}
+
+	fn balances_batch(currency_id: Self::CurrencyId, accounts: &[T::AccountId]) -> Vec<AccountData<Self::Balance>> {
+		if Self::is_native(currency_id) {
+			return accounts
+				.iter()
+				.map(|who| {
+					let free = T::NativeCurrency::free_balance(who);
+					AccountData {
+						free,
+						reserved: T::NativeCurrency::total_balance(who).saturating_sub(free),
+						frozen: Zero::zero(),
+					}
+				})
+				.collect();
+		}
+		accounts.iter().map(|who| Self::accounts(currency_id, who)).collect()
+	}
+
+	/// Protected accounts are exempt from the existential deposit, so all of their spendable balance is transferable.
+	fn transferable_keep_alive(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::transferable_keep_alive(who);
+		}
+		let spendable = Self::accounts(currency_id, who).spendable();
+		if T::ProtectedAccounts::contains(who) {
+			spendable
+		} else {
+			spendable.saturating_sub(T::ExistentialDeposit::get())
+		}
+	}
+
 	fn ensure_can_withdraw(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::ensure_can_withdraw(who, amount);
+		}
 		if amount.is_zero() {
 			return Ok(());
 		}
 
-		let new_balance = Self::free_balance(currency_id, who)
-			.checked_sub(&amount)
-			.ok_or(Error::<T>::BalanceTooLow)?;
-		ensure!(
-			new_balance >= Self::accounts(currency_id, who).frozen(),
-			Error::<T>::LiquidityRestrictions
-		);
+		let account = Self::accounts(currency_id, who);
+		let new_balance = account.free.checked_sub(&amount).ok_or(Error::<T>::BalanceTooLow)?;
+		ensure!(new_balance >= account.frozen(), Error::<T>::LiquidityRestrictions);
 		Ok(())
 	}
 
@@ -302,76 +4018,220 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 		to: &T::AccountId,
 		amount: Self::Balance,
 	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::transfer(from, to, amount);
+		}
+		Self::ensure_not_migrating(currency_id)?;
+		let amount = Self::apply_granularity(currency_id, amount)?;
+		Self::transfer_internal(currency_id, from, to, amount, false)
+	}
+
+	/// The transfer fee is charged out of `amount`, and deposits events as the `transfer` call. Fails with
+	/// `BelowMinimumReceived` if `to` would receive less than `min_received`.
+	fn transfer_min_received(
+		currency_id: Self::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: Self::Balance,
+		min_received: Self::Balance,
+	) -> DispatchResult {
+		let received = if Self::is_native(currency_id) {
+			amount - Self::compute_transfer_fee(currency_id, from, to, amount)
+		} else {
+			let amount = Self::apply_granularity(currency_id, amount)?;
+			amount - Self::compute_transfer_fee(currency_id, from, to, amount)
+		};
+		ensure!(received >= min_received, Error::<T>::BelowMinimumReceived);
+		Self::transfer_with_fee(currency_id, from.clone(), to.clone(), amount)
+	}
+
+	/// Only free balances are set, and no dust is removed, so the total issuance is never changed. Fails with
+	/// `ExistentialDeposit` if the balance left in `from` would be dust.
+	fn transfer_without_issuance_change(
+		currency_id: Self::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::transfer(from, to, amount);
+		}
+		let new_to_balance = Self::ensure_can_transfer(currency_id, from, to, amount, false)?;
 		if amount.is_zero() || from == to {
 			return Ok(());
 		}
-		Self::ensure_can_withdraw(currency_id, from, amount)?;
-
-		let from_balance = Self::free_balance(currency_id, from);
-		let to_balance = Self::free_balance(currency_id, to);
+		let new_from_balance = Self::free_balance(currency_id, from) - amount;
 		ensure!(
-			to_balance + amount >= T::ExistentialDeposit::get(),
-			Error::<T>::ExistentialDeposit,
+			new_from_balance.is_zero()
+				|| new_from_balance >= T::ExistentialDeposit::get()
+				|| T::ProtectedAccounts::contains(from),
+			Error::<T>::ExistentialDeposit
 		);
 
-		if from != to {
-			Self::set_free_balance(currency_id, from, from_balance - amount);
-			Self::set_free_balance(currency_id, to, to_balance + amount);
-		}
+		let total_issuance = Self::total_issuance(currency_id);
+		Self::set_free_balance(currency_id, from, new_from_balance);
+		Self::set_free_balance(currency_id, to, new_to_balance);
+		debug_assert!(Self::total_issuance(currency_id) == total_issuance);
+		T::OnTransferRecord::on_transfer_record(currency_id, amount);
+
+		Ok(())
+	}
+
+	/// Transfer so that `to` receives exactly `receive_amount` after the transfer fee, and deposit events.
+	///
+	/// Fails with `CannotCoverFee` if `from` cannot afford the gross amount.
+	fn transfer_exact_out(
+		currency_id: Self::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		receive_amount: Self::Balance,
+	) -> DispatchResult {
+		let gross = if Self::is_fee_exempt(currency_id, from, to) {
+			receive_amount
+		} else {
+			Self::gross_transfer_amount(receive_amount).ok_or(Error::<T>::CannotCoverFee)?
+		};
+		let cost = gross
+			.checked_add(&Self::service_fee(currency_id, from, to))
+			.ok_or(Error::<T>::CannotCoverFee)?;
+		Self::ensure_can_withdraw(currency_id, from, cost).map_err(|_| Error::<T>::CannotCoverFee)?;
+		Self::transfer_with_fee(currency_id, from.clone(), to.clone(), gross)?;
 
+		Self::emit_event(RawEvent::TransferredExactOut(
+			currency_id,
+			from.clone(),
+			to.clone(),
+			receive_amount,
+			gross,
+		));
 		Ok(())
 	}
 
+	/// A deposit to a blocked recipient fails, or is made to the bounce account instead, as set by `DepositBounce`.
 	fn deposit(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::deposit(who, amount);
+		}
+		Self::ensure_not_frozen(currency_id)?;
 		if amount.is_zero() {
 			return Ok(());
 		}
+		if T::BlockedRecipients::contains(who) {
+			return match T::DepositBounce::get() {
+				DepositBounce::Fail => Err(Error::<T>::RecipientBlocked.into()),
+				DepositBounce::BounceTo(bounce_account) => {
+					ensure!(
+						!T::BlockedRecipients::contains(&bounce_account),
+						Error::<T>::RecipientBlocked
+					);
+					Self::deposit(currency_id, &bounce_account, amount)?;
+					Self::emit_event(RawEvent::DepositBounced(
+						currency_id,
+						who.clone(),
+						bounce_account,
+						amount,
+					));
+					Ok(())
+				}
+			};
+		}
 
-		ensure!(
-			Self::total_issuance(currency_id).checked_add(&amount).is_some(),
-			Error::<T>::TotalIssuanceOverflow,
-		);
+		let new_total_issuance = Self::total_issuance(currency_id)
+			.checked_add(&amount)
+			.ok_or(Error::<T>::TotalIssuanceOverflow)?;
+		if let Some(cap) = Self::total_issuance_cap(currency_id) {
+			ensure!(new_total_issuance <= cap, Error::<T>::TotalIssuanceCapExceeded);
+		}
 
 		let balance = Self::free_balance(currency_id, who);
+		let protected = T::ProtectedAccounts::contains(who);
 		// Nothing happens if deposition doesn't meet existential deposit rule,
 		// consistent behavior with pallet-balances.
-		if balance.is_zero() && amount < T::ExistentialDeposit::get() {
+		if balance.is_zero() && amount < T::ExistentialDeposit::get() && !protected {
 			return Ok(());
 		}
+		Self::ensure_within_balance_cap(currency_id, who, amount)?;
+
+		// the re-creation deposit is withheld from `amount` and not issued, protected accounts are never re-created
+		let amount = if Self::total_balance(currency_id, who).is_zero() && !protected {
+			let recreate_deposit = T::OnAccountRecreate::on_account_recreate(currency_id, who, amount)?;
+			let amount = amount.saturating_sub(recreate_deposit);
+			ensure!(amount >= T::ExistentialDeposit::get(), Error::<T>::ExistentialDeposit);
+			amount
+		} else {
+			amount
+		};
 
-		<TotalIssuance<T>>::mutate(currency_id, |v| *v += amount);
+		if Self::total_issuance(currency_id).is_zero() {
+			Self::init_default_metadata(currency_id);
+		}
+		Self::mint_issuance(currency_id, amount);
 		Self::set_free_balance(currency_id, who, balance + amount);
 
 		Ok(())
 	}
 
 	fn withdraw(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::withdraw(who, amount);
+		}
+		Self::ensure_not_frozen(currency_id)?;
 		if amount.is_zero() {
 			return Ok(());
 		}
 		Self::ensure_can_withdraw(currency_id, who, amount)?;
 
-		<TotalIssuance<T>>::mutate(currency_id, |v| *v -= amount);
+		Self::burn_issuance(currency_id, amount);
 		Self::set_free_balance(currency_id, who, Self::free_balance(currency_id, who) - amount);
 
 		Ok(())
 	}
 
-	// Check if `value` amount of free balance can be slashed from `who`.
+	fn withdraw_in_order(
+		who: &T::AccountId,
+		currencies: &[(Self::CurrencyId, Self::Balance)],
+		total_needed: Self::Balance,
+	) -> rstd::result::Result<Vec<(Self::CurrencyId, Self::Balance)>, DispatchError> {
+		with_transaction_result(|| {
+			let mut withdrawals = Vec::new();
+			let mut remaining = total_needed;
+			for (currency_id, max_amount) in currencies.iter() {
+				if remaining.is_zero() {
+					break;
+				}
+				let amount = remaining
+					.min(*max_amount)
+					.min(Self::effective_balance(*currency_id, who));
+				if !amount.is_zero() {
+					Self::withdraw(*currency_id, who, amount)?;
+					withdrawals.push((*currency_id, amount));
+					remaining -= amount;
+				}
+			}
+			ensure!(remaining.is_zero(), Error::<T>::BalanceTooLow);
+			Ok(withdrawals)
+		})
+	}
+
+	// Check if `value` amount can be slashed from `who`, drawing from reserved balance as `slash()` does.
 	fn can_slash(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> bool {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::can_slash(who, value);
+		}
 		if value.is_zero() {
 			return true;
 		}
-		Self::free_balance(currency_id, who) >= value
+		Self::slashable_balance(currency_id, who) >= value
 	}
 
 	/// Is a no-op if `value` to be slashed is zero.
 	///
-	/// NOTE: `slash()` prefers free balance, but assumes that reserve balance can be drawn
-	/// from in extreme circumstances. `can_slash()` should be used prior to `slash()` to avoid having
-	/// to draw from reserved funds, however we err on the side of punishment if things are inconsistent
-	/// or `can_slash` wasn't used appropriately.
+	/// NOTE: `slash()` prefers free balance, but draws from reserved balance once free balance is
+	/// exhausted. `can_slash()` checks against `slashable_balance`, which counts both.
 	fn slash(currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::slash(who, amount);
+		}
 		if amount.is_zero() {
 			return amount;
 		}
@@ -392,7 +4252,7 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 			Self::set_reserved_balance(currency_id, who, account.reserved - reserved_slashed_amount);
 		}
 
-		<TotalIssuance<T>>::mutate(currency_id, |v| *v -= amount - remaining_slash);
+		Self::settle_slashed(currency_id, amount - remaining_slash);
 		remaining_slash
 	}
 }
@@ -400,13 +4260,19 @@ impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
 impl<T: Trait> MultiCurrencyExtended<T::AccountId> for Module<T> {
 	type Amount = T::Amount;
 
+	/// Update the balance of `who` under `currency_id` by `by_amount`.
+	///
+	/// The magnitude of `by_amount` must fit in `Balance`, i.e. `|by_amount| <= Balance::max_value()`, and
+	/// `Amount::min_value()` is never supported, otherwise fails with `AmountMagnitudeOverflow`. This only restricts
+	/// an `Amount` type wider than `Balance`, as no balance or total issuance could be changed by more anyway.
 	fn update_balance(currency_id: Self::CurrencyId, who: &T::AccountId, by_amount: Self::Amount) -> DispatchResult {
 		if by_amount.is_zero() {
 			return Ok(());
 		}
 
+		let magnitude = by_amount.checked_abs().ok_or(Error::<T>::AmountMagnitudeOverflow)?;
 		let by_balance =
-			TryInto::<Self::Balance>::try_into(by_amount.abs()).map_err(|_| Error::<T>::AmountIntoBalanceFailed)?;
+			TryInto::<Self::Balance>::try_into(magnitude).map_err(|_| Error::<T>::AmountMagnitudeOverflow)?;
 		if by_amount.is_positive() {
 			Self::deposit(currency_id, who, by_balance)
 		} else {
@@ -415,15 +4281,180 @@ impl<T: Trait> MultiCurrencyExtended<T::AccountId> for Module<T> {
 	}
 }
 
-impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
-	type Moment = T::BlockNumber;
+impl<T: Trait> MultiCurrencyIssuance<T::AccountId> for Module<T> {
+	/// The total issuance of the native currency is never changed.
+	fn increase_total_issuance(currency_id: Self::CurrencyId, amount: Self::Balance) {
+		if Self::is_native(currency_id) {
+			return;
+		}
+		let headroom = <T::Balance as arithmetic::Bounded>::max_value() - Self::total_issuance(currency_id);
+		Self::mint_issuance(currency_id, amount.min(headroom));
+	}
 
-	// Set a lock on the balance of `who` under `currency_id`.
-	// Is a no-op if lock amount is zero.
-	fn set_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) {
-		if amount.is_zero() {
+	/// The total issuance of the native currency is never changed.
+	fn decrease_total_issuance(currency_id: Self::CurrencyId, amount: Self::Balance) {
+		if Self::is_native(currency_id) {
 			return;
 		}
+		Self::burn_issuance(currency_id, amount.min(Self::total_issuance(currency_id)));
+	}
+}
+
+impl<T: Trait> NamedMultiReservableCurrency<T::AccountId> for Module<T> {
+	type ReserveIdentifier = T::ReserveIdentifier;
+
+	fn reserved_balance_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+	) -> Self::Balance {
+		Self::reserves(currency_id, who)
+			.iter()
+			.find(|reserve| reserve.id == *id)
+			.map_or_else(Zero::zero, |reserve| reserve.amount)
+	}
+
+	/// Move `value` from the free balance from `who` to their reserved balance under `id`.
+	///
+	/// Is a no-op if value to be reserved is zero.
+	fn reserve_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		value: Self::Balance,
+	) -> DispatchResult {
+		if value.is_zero() {
+			return Ok(());
+		}
+		Self::ensure_can_add_reserve_named(id, currency_id, who)?;
+		Self::reserve(currency_id, who, value)?;
+		Self::add_reserve_named(id, currency_id, who, value);
+		Ok(())
+	}
+
+	/// Unreserve some funds under `id`, returning any amount that was unable to be unreserved.
+	///
+	/// Is a no-op if the value to be unreserved is zero, or while an emergency shutdown is active.
+	fn unreserve_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		value: Self::Balance,
+	) -> Self::Balance {
+		if value.is_zero() {
+			return Zero::zero();
+		}
+		if Self::shutdown_active() {
+			return value;
+		}
+		let actual = Self::remove_reserve_named(id, currency_id, who, value);
+		Self::unreserve(currency_id, who, actual);
+		value - actual
+	}
+
+	/// Slash from reserved balance under `id`, returning any amount that was unable to be slashed.
+	///
+	/// Is a no-op if the value to be slashed is zero.
+	fn slash_reserved_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		value: Self::Balance,
+	) -> Self::Balance {
+		if value.is_zero() {
+			return Zero::zero();
+		}
+		let actual = Self::remove_reserve_named(id, currency_id, who, value);
+		Self::slash_reserved(currency_id, who, actual);
+		value - actual
+	}
+}
+
+impl<T: Trait> MultiCurrencyAccountData<T::AccountId> for Module<T> {
+	/// Read by the `AccountCurrencies` index, so only the token types held by `who` are iterated.
+	fn currencies_of(who: &T::AccountId) -> Vec<(Self::CurrencyId, AccountData<Self::Balance>)> {
+		<AccountCurrencies<T> as IterableStorageDoubleMap<_, _, _>>::iter(who)
+			.map(|(currency_id, _)| (currency_id, Self::accounts(currency_id, who)))
+			.collect()
+	}
+
+	/// All token types with a total issuance entry, including those whose total issuance dropped to zero.
+	fn currency_ids() -> Vec<Self::CurrencyId> {
+		<TotalIssuance<T> as IterableStorageMap<_, _>>::iter()
+			.map(|(currency_id, _)| currency_id)
+			.collect()
+	}
+
+	fn account_info(currency_id: Self::CurrencyId, who: &T::AccountId) -> AccountInfo<Self::Balance> {
+		let account_data = Self::accounts(currency_id, who);
+		AccountInfo {
+			free: account_data.free,
+			reserved: account_data.reserved,
+			frozen: account_data.frozen,
+			locks: Self::locks(currency_id, who),
+			total_balance: account_data.total(),
+		}
+	}
+}
+
+impl<T: Trait> MultiCurrencyHistory<T::AccountId, T::BlockNumber> for Module<T> {
+	/// The latest free balance recorded at or before `block`, or `None` if there is none, including if it was
+	/// pruned as older than the `MaxBalanceHistory` latest ones, or if the history is disabled.
+	fn free_balance_at(
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		block: T::BlockNumber,
+	) -> Option<Self::Balance> {
+		let history = Self::balance_history(currency_id, who);
+		match history.binary_search_by_key(&block, |(changed_at, _)| *changed_at) {
+			Ok(index) => Some(history[index].1),
+			Err(index) => index.checked_sub(1).map(|previous| history[previous].1),
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// Set a lock for `reasons` on the balance of `who` under `currency_id`, regardless of `LockOwners`. Fails with
+	/// `LockRatioExceeded` if not allowed by `MaxLockRatio`.
+	///
+	/// Is a no-op if lock amount is zero.
+	fn do_set_lock(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+		reasons: WithdrawReasons,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		let locks = Self::locks_with(lock_id, currency_id, who, amount);
+		Self::ensure_lock_ratio(currency_id, who, &locks[..])?;
+		Self::put_locks(lock_id, currency_id, who, reasons, &locks[..]);
+		Ok(())
+	}
+
+	/// Store `locks` of `who` under `currency_id`, where the lock `lock_id` was set or extended to apply to
+	/// `reasons`, and cancel its expiry.
+	fn put_locks(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		reasons: WithdrawReasons,
+		locks: &[BalanceLock<T::Balance>],
+	) {
+		Self::clear_lock_expiry(currency_id, who, lock_id);
+		Self::put_lock_reasons(currency_id, who, lock_id, reasons);
+		Self::update_locks(currency_id, who, locks);
+	}
+
+	/// The locks of `who` under `currency_id`, with the lock `lock_id` set to `amount`.
+	fn locks_with(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+	) -> Vec<BalanceLock<T::Balance>> {
 		let mut new_lock = Some(BalanceLock {
 			id: lock_id,
 			amount: amount,
@@ -441,15 +4472,36 @@ impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
 		if let Some(lock) = new_lock {
 			locks.push(lock)
 		}
-		Self::update_locks(currency_id, who, &locks[..]);
+		locks
 	}
 
-	// Extend a lock on the balance of `who` under `currency_id`.
-	// Is a no-op if lock amount is zero
-	fn extend_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) {
+	/// Extend a lock on the balance of `who` under `currency_id`, regardless of `LockOwners`. Fails with
+	/// `LockRatioExceeded` if not allowed by `MaxLockRatio`.
+	///
+	/// Is a no-op if lock amount is zero.
+	fn do_extend_lock(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+	) -> DispatchResult {
 		if amount.is_zero() {
-			return;
+			return Ok(());
 		}
+		let locks = Self::locks_extended_with(lock_id, currency_id, who, amount);
+		Self::ensure_lock_ratio(currency_id, who, &locks[..])?;
+		// an extended lock applies to all reasons
+		Self::put_locks(lock_id, currency_id, who, WithdrawReasons::all(), &locks[..]);
+		Ok(())
+	}
+
+	/// The locks of `who` under `currency_id`, with the lock `lock_id` extended to `amount`.
+	fn locks_extended_with(
+		lock_id: LockIdentifier,
+		currency_id: T::CurrencyId,
+		who: &T::AccountId,
+		amount: T::Balance,
+	) -> Vec<BalanceLock<T::Balance>> {
 		let mut new_lock = Some(BalanceLock {
 			id: lock_id,
 			amount: amount,
@@ -470,31 +4522,118 @@ impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
 		if let Some(lock) = new_lock {
 			locks.push(lock)
 		}
-		Self::update_locks(currency_id, who, &locks[..]);
+		locks
 	}
 
-	fn remove_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId) {
+	/// Remove a lock on the balance of `who` under `currency_id`, regardless of `LockOwners`.
+	fn do_remove_lock(lock_id: LockIdentifier, currency_id: T::CurrencyId, who: &T::AccountId) {
+		Self::clear_lock_expiry(currency_id, who, lock_id);
 		let mut locks = Self::locks(currency_id, who);
 		locks.retain(|lock| lock.id != lock_id);
 		Self::update_locks(currency_id, who, &locks[..]);
 	}
 }
 
+/// Locks with an identifier registered in `LockOwners` could only be changed by `set_lock_as`, `extend_lock_as` and
+/// `remove_lock_as`, so `try_set_lock` and `try_extend_lock` fail with `LockNotOwned`, and `set_lock`, `extend_lock`
+/// and `remove_lock` are no-ops for them. Only `try_set_lock` and `try_extend_lock` are restricted by `MaxLockRatio`.
+impl<T: Trait> MultiLockableCurrency<T::AccountId> for Module<T> {
+	type Moment = T::BlockNumber;
+
+	// Set a lock on the balance of `who` under `currency_id`.
+	// Is a no-op if lock amount is zero.
+	fn set_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) {
+		if Self::ensure_lock_owner(lock_id, None).is_err() {
+			return;
+		}
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::set_lock(lock_id, who, amount);
+		} else if !amount.is_zero() {
+			let locks = Self::locks_with(lock_id, currency_id, who, amount);
+			Self::put_locks(lock_id, currency_id, who, WithdrawReasons::all(), &locks[..]);
+		}
+	}
+
+	// Extend a lock on the balance of `who` under `currency_id`.
+	// Is a no-op if lock amount is zero
+	fn extend_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId, amount: Self::Balance) {
+		if Self::ensure_lock_owner(lock_id, None).is_err() {
+			return;
+		}
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::extend_lock(lock_id, who, amount);
+		} else if !amount.is_zero() {
+			let locks = Self::locks_extended_with(lock_id, currency_id, who, amount);
+			Self::put_locks(lock_id, currency_id, who, WithdrawReasons::all(), &locks[..]);
+		}
+	}
+
+	fn remove_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &T::AccountId) {
+		if Self::ensure_lock_owner(lock_id, None).is_err() {
+			return;
+		}
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::remove_lock(lock_id, who);
+		} else {
+			Self::do_remove_lock(lock_id, currency_id, who);
+		}
+	}
+
+	fn try_set_lock(
+		lock_id: LockIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, None)?;
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::set_lock(lock_id, who, amount);
+			return Ok(());
+		}
+		Self::do_set_lock(lock_id, currency_id, who, amount, WithdrawReasons::all())
+	}
+
+	fn try_extend_lock(
+		lock_id: LockIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Self::ensure_lock_owner(lock_id, None)?;
+		if Self::is_native(currency_id) {
+			T::NativeCurrency::extend_lock(lock_id, who, amount);
+			return Ok(());
+		}
+		Self::do_extend_lock(lock_id, currency_id, who, amount)
+	}
+}
+
 impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 	/// Check if `who` can reserve `value` from their free balance.
 	///
 	/// Always `true` if value to be reserved is zero.
 	fn can_reserve(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> bool {
-		if value.is_zero() {
-			return true;
+		if Self::is_native(currency_id) {
+			return !Self::frozen_currencies(currency_id) && T::NativeCurrency::can_reserve(who, value);
 		}
-		Self::ensure_can_withdraw(currency_id, who, value).is_ok()
+		if Self::frozen_currencies(currency_id) || !Self::reservable(currency_id) {
+			return false;
+		}
+		Self::accounts(currency_id, who)
+			.free
+			.checked_sub(&value)
+			.map_or(false, |new_free| {
+				new_free >= Self::frozen_for_reason(currency_id, who, WithdrawReason::Reserve)
+			})
 	}
 
 	/// Slash from reserved balance, returning any amount that was unable to be slashed.
 	///
 	/// Is a no-op if the value to be slashed is zero.
 	fn slash_reserved(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::slash_reserved(who, value);
+		}
 		if value.is_zero() {
 			return Zero::zero();
 		}
@@ -502,41 +4641,85 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 		let reserved_balance = Self::reserved_balance(currency_id, who);
 		let actual = reserved_balance.min(value);
 		Self::set_reserved_balance(currency_id, who, reserved_balance - actual);
-		<TotalIssuance<T>>::mutate(currency_id, |v| *v -= actual);
+		Self::settle_slashed(currency_id, actual);
 		value - actual
 	}
 
+	fn reservable(currency_id: Self::CurrencyId) -> bool {
+		!Self::non_reservable_currencies(currency_id)
+	}
+
 	fn reserved_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::reserved_balance(who);
+		}
 		Self::accounts(currency_id, who).reserved
 	}
 
 	/// Move `value` from the free balance from `who` to their reserved balance.
 	///
+	/// Locked free funds cannot be reserved, so fails with `LiquidityRestrictions` if the free balance would drop
+	/// below the locks applying to `Reserve`. Unreserving is not restricted by locks.
+	///
 	/// Is a no-op if value to be reserved is zero.
 	fn reserve(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> DispatchResult {
+		Self::ensure_not_frozen(currency_id)?;
+		if Self::is_native(currency_id) {
+			if value.is_zero() {
+				return Ok(());
+			}
+			T::NativeCurrency::reserve(who, value)?;
+			Self::emit_event(RawEvent::Reserved(currency_id, who.clone(), value));
+			return Ok(());
+		}
+		Self::ensure_reservable(currency_id)?;
 		if value.is_zero() {
 			return Ok(());
 		}
-		Self::ensure_can_withdraw(currency_id, who, value)?;
-
 		let account = Self::accounts(currency_id, who);
+		let new_free = account.free.checked_sub(&value).ok_or(Error::<T>::BalanceTooLow)?;
+		ensure!(
+			new_free >= Self::frozen_for_reason(currency_id, who, WithdrawReason::Reserve),
+			Error::<T>::LiquidityRestrictions
+		);
+
+		let new_reserved = account
+			.reserved
+			.checked_add(&value)
+			.ok_or(Error::<T>::TotalIssuanceOverflow)?;
+
+		// increase the reserved balance first, so that the total balance never drops in between
+		Self::set_reserved_balance(currency_id, who, new_reserved);
 		Self::set_free_balance(currency_id, who, account.free - value);
-		Self::set_reserved_balance(currency_id, who, account.reserved + value);
+		Self::emit_event(RawEvent::Reserved(currency_id, who.clone(), value));
 		Ok(())
 	}
 
 	/// Unreserve some funds, returning any amount that was unable to be unreserved.
 	///
 	/// Is a no-op if the value to be unreserved is zero.
+	/// Is a no-op while an emergency shutdown is active.
 	fn unreserve(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> Self::Balance {
 		if value.is_zero() {
 			return Zero::zero();
 		}
+		if Self::shutdown_active() {
+			return value;
+		}
+		if Self::is_native(currency_id) {
+			let remaining = T::NativeCurrency::unreserve(who, value);
+			Self::emit_event(RawEvent::Unreserved(currency_id, who.clone(), value - remaining));
+			return remaining;
+		}
 
 		let account = Self::accounts(currency_id, who);
 		let actual = account.reserved.min(value);
 		Self::set_reserved_balance(currency_id, who, account.reserved - actual);
 		Self::set_free_balance(currency_id, who, account.free + actual);
+		if T::ReapReservedDust::get() {
+			Self::reap_reserved_dust(currency_id, who);
+		}
+		Self::emit_event(RawEvent::Unreserved(currency_id, who.clone(), actual));
 		value - actual
 	}
 
@@ -552,6 +4735,9 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 		value: Self::Balance,
 		status: BalanceStatus,
 	) -> rstd::result::Result<Self::Balance, DispatchError> {
+		if Self::is_native(currency_id) {
+			return T::NativeCurrency::repatriate_reserved(slashed, beneficiary, value, status);
+		}
 		if value.is_zero() {
 			return Ok(Zero::zero());
 		}
@@ -575,6 +4761,13 @@ impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
 			}
 		}
 		Self::set_reserved_balance(currency_id, slashed, from_account.reserved - actual);
+		Self::emit_event(RawEvent::ReserveRepatriated(
+			currency_id,
+			slashed.clone(),
+			beneficiary.clone(),
+			actual,
+			status,
+		));
 		Ok(value - actual)
 	}
 }
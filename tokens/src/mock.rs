@@ -6,7 +6,11 @@ use frame_support::{impl_outer_event, impl_outer_origin, parameter_types};
 use frame_system as system;
 use primitives::H256;
 use rstd::{cell::RefCell, marker::PhantomData};
-use sp_runtime::{testing::Header, traits::IdentityLookup, Perbill};
+use sp_runtime::{
+	testing::{Header, TestSignature, UintAuthorityId},
+	traits::IdentityLookup,
+	Perbill, Permill,
+};
 
 use super::*;
 
@@ -68,6 +72,32 @@ parameter_types! {
 
 thread_local! {
 	static ACCUMULATED_DUST: RefCell<Balance> = RefCell::new(Zero::zero());
+	static RECREATE_DEPOSIT: RefCell<Balance> = RefCell::new(Zero::zero());
+	static RECREATED_ACCOUNTS: RefCell<u32> = RefCell::new(0);
+	static TRANSFER_FEE: RefCell<Permill> = RefCell::new(Permill::zero());
+	static TREASURY_FEE_SHARE: RefCell<Permill> = RefCell::new(Permill::zero());
+	static SERVICE_FEE: RefCell<Balance> = RefCell::new(Zero::zero());
+	static DUST_SPLIT: RefCell<Permill> = RefCell::new(Permill::one());
+	static MAX_LOCK_RATIO: RefCell<Permill> = RefCell::new(Permill::one());
+	static REAP_RESERVED_DUST: RefCell<bool> = RefCell::new(false);
+	static NET_ISSUANCE: RefCell<Vec<(CurrencyId, i128)>> = RefCell::new(Vec::new());
+	static TRANSFER_VOLUME: RefCell<BTreeMap<CurrencyId, Balance>> = RefCell::new(BTreeMap::new());
+	static EVENT_VERBOSITY: RefCell<EventVerbosity> = RefCell::new(EventVerbosity::Full);
+	static NATIVE_BALANCES: RefCell<BTreeMap<AccountId, Balance>> = RefCell::new(BTreeMap::new());
+	static NATIVE_RESERVED: RefCell<BTreeMap<AccountId, Balance>> = RefCell::new(BTreeMap::new());
+	static NATIVE_LOCKS: RefCell<BTreeMap<(AccountId, LockIdentifier), Balance>> = RefCell::new(BTreeMap::new());
+	static DEPOSIT_BOUNCE: RefCell<DepositBounce<AccountId>> = RefCell::new(DepositBounce::Fail);
+	static STALE_LOCK_POLICY: RefCell<StaleLockPolicy> = RefCell::new(StaleLockPolicy::Retain);
+	static SLASH_ACCOUNTING_MODE: RefCell<SlashAccountingMode<AccountId>> =
+		RefCell::new(SlashAccountingMode::ReduceIssuance);
+	static MAX_BALANCE: RefCell<Option<Balance>> = RefCell::new(None);
+	static MAX_BALANCE_HISTORY: RefCell<u32> = RefCell::new(3);
+	static GRANULARITY: RefCell<Balance> = RefCell::new(1);
+	static GRANULARITY_POLICY: RefCell<GranularityPolicy> = RefCell::new(GranularityPolicy::Reject);
+	static DUST_EVENT_MODE: RefCell<DustEventMode> = RefCell::new(DustEventMode::PerAccount);
+	static EVENT_MODEL: RefCell<EventModel> = RefCell::new(EventModel::Transfer);
+	static PENDING_WITHDRAWALS: RefCell<BTreeMap<(CurrencyId, AccountId), Balance>> = RefCell::new(BTreeMap::new());
+	static TRANSFER_ALL_POLICY: RefCell<TransferAllPolicy> = RefCell::new(TransferAllPolicy::SweepAll);
 }
 
 pub struct MockDustRemoval<Balance>(PhantomData<Balance>);
@@ -82,32 +112,583 @@ impl OnDustRemoval<Balance> for MockDustRemoval<Balance> {
 	}
 }
 
+pub struct MockAccountRecreate;
+impl MockAccountRecreate {
+	pub fn set_recreate_deposit(deposit: Balance) {
+		RECREATE_DEPOSIT.with(|v| *v.borrow_mut() = deposit);
+	}
+
+	pub fn recreated_accounts() -> u32 {
+		RECREATED_ACCOUNTS.with(|v| *v.borrow())
+	}
+}
+impl OnAccountRecreate<CurrencyId, AccountId, Balance> for MockAccountRecreate {
+	fn on_account_recreate(_: CurrencyId, _: &AccountId, amount: Balance) -> Result<Balance, DispatchError> {
+		let deposit = RECREATE_DEPOSIT.with(|v| *v.borrow());
+		if amount < deposit {
+			return Err(DispatchError::Other("cannot pay re-creation deposit"));
+		}
+		RECREATED_ACCOUNTS.with(|v| *v.borrow_mut() += 1);
+		Ok(deposit)
+	}
+}
+
+pub struct MockNativeCurrency;
+impl BasicCurrency<AccountId> for MockNativeCurrency {
+	type Balance = Balance;
+
+	fn total_issuance() -> Balance {
+		NATIVE_BALANCES.with(|v| v.borrow().values().sum())
+	}
+
+	fn total_balance(who: &AccountId) -> Balance {
+		Self::free_balance(who) + Self::reserved_balance(who)
+	}
+
+	fn free_balance(who: &AccountId) -> Balance {
+		NATIVE_BALANCES.with(|v| v.borrow().get(who).copied().unwrap_or_default())
+	}
+
+	fn ensure_can_withdraw(who: &AccountId, amount: Balance) -> DispatchResult {
+		if Self::free_balance(who) < amount {
+			return Err(DispatchError::Other("native balance too low"));
+		}
+		if Self::free_balance(who) - amount < Self::frozen_balance(who) {
+			return Err(DispatchError::Other("native balance locked"));
+		}
+		Ok(())
+	}
+
+	fn transfer(from: &AccountId, to: &AccountId, amount: Balance) -> DispatchResult {
+		Self::withdraw(from, amount)?;
+		Self::deposit(to, amount)
+	}
+
+	fn deposit(who: &AccountId, amount: Balance) -> DispatchResult {
+		NATIVE_BALANCES.with(|v| *v.borrow_mut().entry(*who).or_default() += amount);
+		Ok(())
+	}
+
+	fn withdraw(who: &AccountId, amount: Balance) -> DispatchResult {
+		Self::ensure_can_withdraw(who, amount)?;
+		NATIVE_BALANCES.with(|v| *v.borrow_mut().entry(*who).or_default() -= amount);
+		Ok(())
+	}
+
+	fn can_slash(who: &AccountId, value: Balance) -> bool {
+		Self::free_balance(who) >= value
+	}
+
+	fn slash(who: &AccountId, amount: Balance) -> Balance {
+		let slashed = Self::free_balance(who).min(amount);
+		NATIVE_BALANCES.with(|v| *v.borrow_mut().entry(*who).or_default() -= slashed);
+		amount - slashed
+	}
+}
+
+impl MockNativeCurrency {
+	/// The largest lock of `who`.
+	pub fn frozen_balance(who: &AccountId) -> Balance {
+		NATIVE_LOCKS.with(|v| {
+			v.borrow()
+				.iter()
+				.filter(|((account, _), _)| account == who)
+				.map(|(_, amount)| *amount)
+				.max()
+				.unwrap_or_default()
+		})
+	}
+}
+
+impl BasicReservableCurrency<AccountId> for MockNativeCurrency {
+	fn can_reserve(who: &AccountId, value: Balance) -> bool {
+		Self::ensure_can_withdraw(who, value).is_ok()
+	}
+
+	fn slash_reserved(who: &AccountId, value: Balance) -> Balance {
+		let slashed = Self::reserved_balance(who).min(value);
+		NATIVE_RESERVED.with(|v| *v.borrow_mut().entry(*who).or_default() -= slashed);
+		value - slashed
+	}
+
+	fn reserved_balance(who: &AccountId) -> Balance {
+		NATIVE_RESERVED.with(|v| v.borrow().get(who).copied().unwrap_or_default())
+	}
+
+	fn reserve(who: &AccountId, value: Balance) -> DispatchResult {
+		Self::ensure_can_withdraw(who, value)?;
+		NATIVE_BALANCES.with(|v| *v.borrow_mut().entry(*who).or_default() -= value);
+		NATIVE_RESERVED.with(|v| *v.borrow_mut().entry(*who).or_default() += value);
+		Ok(())
+	}
+
+	fn unreserve(who: &AccountId, value: Balance) -> Balance {
+		let actual = Self::reserved_balance(who).min(value);
+		NATIVE_RESERVED.with(|v| *v.borrow_mut().entry(*who).or_default() -= actual);
+		NATIVE_BALANCES.with(|v| *v.borrow_mut().entry(*who).or_default() += actual);
+		value - actual
+	}
+
+	fn repatriate_reserved(
+		slashed: &AccountId,
+		beneficiary: &AccountId,
+		value: Balance,
+		status: BalanceStatus,
+	) -> Result<Balance, DispatchError> {
+		let actual = Self::reserved_balance(slashed).min(value);
+		NATIVE_RESERVED.with(|v| *v.borrow_mut().entry(*slashed).or_default() -= actual);
+		match status {
+			BalanceStatus::Free => NATIVE_BALANCES.with(|v| *v.borrow_mut().entry(*beneficiary).or_default() += actual),
+			BalanceStatus::Reserved => {
+				NATIVE_RESERVED.with(|v| *v.borrow_mut().entry(*beneficiary).or_default() += actual)
+			}
+		}
+		Ok(value - actual)
+	}
+}
+
+impl BasicLockableCurrency<AccountId> for MockNativeCurrency {
+	type Moment = u64;
+
+	fn set_lock(lock_id: LockIdentifier, who: &AccountId, amount: Balance) {
+		NATIVE_LOCKS.with(|v| v.borrow_mut().insert((*who, lock_id), amount));
+	}
+
+	fn extend_lock(lock_id: LockIdentifier, who: &AccountId, amount: Balance) {
+		NATIVE_LOCKS.with(|v| {
+			let mut locks = v.borrow_mut();
+			let lock = locks.entry((*who, lock_id)).or_default();
+			*lock = (*lock).max(amount);
+		});
+	}
+
+	fn remove_lock(lock_id: LockIdentifier, who: &AccountId) {
+		NATIVE_LOCKS.with(|v| v.borrow_mut().remove(&(*who, lock_id)));
+	}
+}
+
+pub struct TransferFee;
+impl Get<Permill> for TransferFee {
+	fn get() -> Permill {
+		TRANSFER_FEE.with(|v| *v.borrow())
+	}
+}
+
+/// Records each mint as a positive change and each burn as a negative one.
+pub struct MockIssuanceChange;
+impl MockIssuanceChange {
+	pub fn changes() -> Vec<(CurrencyId, i128)> {
+		NET_ISSUANCE.with(|v| v.borrow().clone())
+	}
+
+	pub fn net_issuance(currency_id: CurrencyId) -> i128 {
+		Self::changes()
+			.iter()
+			.filter(|(id, _)| *id == currency_id)
+			.map(|(_, change)| change)
+			.sum()
+	}
+}
+impl OnIssuanceChange<CurrencyId, Balance> for MockIssuanceChange {
+	fn on_mint(currency_id: CurrencyId, amount: Balance) {
+		NET_ISSUANCE.with(|v| v.borrow_mut().push((currency_id, amount as i128)));
+	}
+
+	fn on_burn(currency_id: CurrencyId, amount: Balance) {
+		NET_ISSUANCE.with(|v| v.borrow_mut().push((currency_id, -(amount as i128))));
+	}
+}
+
+/// Accumulates the transfer volume of each currency.
+pub struct MockTransferRecord;
+impl MockTransferRecord {
+	pub fn volume(currency_id: CurrencyId) -> Balance {
+		TRANSFER_VOLUME.with(|v| v.borrow().get(&currency_id).copied().unwrap_or_default())
+	}
+}
+impl OnTransferRecord<CurrencyId, Balance> for MockTransferRecord {
+	fn on_transfer_record(currency_id: CurrencyId, amount: Balance) {
+		TRANSFER_VOLUME.with(|v| *v.borrow_mut().entry(currency_id).or_default() += amount);
+	}
+}
+
+pub struct ReapReservedDust;
+impl Get<bool> for ReapReservedDust {
+	fn get() -> bool {
+		REAP_RESERVED_DUST.with(|v| *v.borrow())
+	}
+}
+
+pub struct MaxLockRatio;
+impl Get<Permill> for MaxLockRatio {
+	fn get() -> Permill {
+		MAX_LOCK_RATIO.with(|v| *v.borrow())
+	}
+}
+
+pub struct DustSplit;
+impl Get<Permill> for DustSplit {
+	fn get() -> Permill {
+		DUST_SPLIT.with(|v| *v.borrow())
+	}
+}
+
+pub struct TreasuryFeeShare;
+impl Get<Permill> for TreasuryFeeShare {
+	fn get() -> Permill {
+		TREASURY_FEE_SHARE.with(|v| *v.borrow())
+	}
+}
+
+pub struct ServiceFee;
+impl Get<Balance> for ServiceFee {
+	fn get() -> Balance {
+		SERVICE_FEE.with(|v| *v.borrow())
+	}
+}
+
+pub struct ProtectedAccounts;
+impl Contains<AccountId> for ProtectedAccounts {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![POOL]
+	}
+}
+
+pub struct FeeExemptAccounts;
+impl Contains<AccountId> for FeeExemptAccounts {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![TREASURY]
+	}
+}
+
+pub struct FeeExemptCurrencies;
+impl Contains<CurrencyId> for FeeExemptCurrencies {
+	fn sorted_members() -> Vec<CurrencyId> {
+		vec![FEE_EXEMPT_TOKEN_ID]
+	}
+}
+
+pub struct ProtocolAccounts;
+impl Contains<AccountId> for ProtocolAccounts {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![TREASURY, POOL]
+	}
+}
+
+pub struct BlockedRecipients;
+impl Contains<AccountId> for BlockedRecipients {
+	fn sorted_members() -> Vec<AccountId> {
+		vec![BLOCKED]
+	}
+}
+
+pub struct MockDepositBounce;
+impl MockDepositBounce {
+	pub fn set(deposit_bounce: DepositBounce<AccountId>) {
+		DEPOSIT_BOUNCE.with(|v| *v.borrow_mut() = deposit_bounce);
+	}
+}
+impl Get<DepositBounce<AccountId>> for MockDepositBounce {
+	fn get() -> DepositBounce<AccountId> {
+		DEPOSIT_BOUNCE.with(|v| v.borrow().clone())
+	}
+}
+
+pub struct MockStaleLockPolicy;
+impl MockStaleLockPolicy {
+	pub fn set(policy: StaleLockPolicy) {
+		STALE_LOCK_POLICY.with(|v| *v.borrow_mut() = policy);
+	}
+}
+impl Get<StaleLockPolicy> for MockStaleLockPolicy {
+	fn get() -> StaleLockPolicy {
+		STALE_LOCK_POLICY.with(|v| *v.borrow())
+	}
+}
+
+pub struct MockPendingWithdrawals;
+impl MockPendingWithdrawals {
+	pub fn set(currency_id: CurrencyId, who: AccountId, amount: Balance) {
+		PENDING_WITHDRAWALS.with(|v| v.borrow_mut().insert((currency_id, who), amount));
+	}
+}
+impl PendingWithdrawals<CurrencyId, AccountId, Balance> for MockPendingWithdrawals {
+	fn pending_withdrawals(currency_id: CurrencyId, who: &AccountId) -> Balance {
+		PENDING_WITHDRAWALS.with(|v| v.borrow().get(&(currency_id, *who)).copied().unwrap_or_default())
+	}
+}
+
+pub struct MockTransferAllPolicy;
+impl MockTransferAllPolicy {
+	pub fn set(policy: TransferAllPolicy) {
+		TRANSFER_ALL_POLICY.with(|v| *v.borrow_mut() = policy);
+	}
+}
+impl Get<TransferAllPolicy> for MockTransferAllPolicy {
+	fn get() -> TransferAllPolicy {
+		TRANSFER_ALL_POLICY.with(|v| *v.borrow())
+	}
+}
+
+pub struct MockEventModel;
+impl MockEventModel {
+	pub fn set(model: EventModel) {
+		EVENT_MODEL.with(|v| *v.borrow_mut() = model);
+	}
+}
+impl Get<EventModel> for MockEventModel {
+	fn get() -> EventModel {
+		EVENT_MODEL.with(|v| *v.borrow())
+	}
+}
+
+pub struct MockDustEventMode;
+impl MockDustEventMode {
+	pub fn set(mode: DustEventMode) {
+		DUST_EVENT_MODE.with(|v| *v.borrow_mut() = mode);
+	}
+}
+impl Get<DustEventMode> for MockDustEventMode {
+	fn get() -> DustEventMode {
+		DUST_EVENT_MODE.with(|v| *v.borrow())
+	}
+}
+
+/// Sets the granularity of `TEST_TOKEN_ID` only.
+pub struct MockGranularity;
+impl MockGranularity {
+	pub fn set(granularity: Balance) {
+		GRANULARITY.with(|v| *v.borrow_mut() = granularity);
+	}
+}
+impl Convert<CurrencyId, Balance> for MockGranularity {
+	fn convert(currency_id: CurrencyId) -> Balance {
+		if currency_id == TEST_TOKEN_ID {
+			GRANULARITY.with(|v| *v.borrow())
+		} else {
+			1
+		}
+	}
+}
+
+pub struct MockGranularityPolicy;
+impl MockGranularityPolicy {
+	pub fn set(policy: GranularityPolicy) {
+		GRANULARITY_POLICY.with(|v| *v.borrow_mut() = policy);
+	}
+}
+impl Get<GranularityPolicy> for MockGranularityPolicy {
+	fn get() -> GranularityPolicy {
+		GRANULARITY_POLICY.with(|v| *v.borrow())
+	}
+}
+
+pub struct MockSlashAccountingMode;
+impl MockSlashAccountingMode {
+	pub fn set(mode: SlashAccountingMode<AccountId>) {
+		SLASH_ACCOUNTING_MODE.with(|v| *v.borrow_mut() = mode);
+	}
+}
+impl Get<SlashAccountingMode<AccountId>> for MockSlashAccountingMode {
+	fn get() -> SlashAccountingMode<AccountId> {
+		SLASH_ACCOUNTING_MODE.with(|v| v.borrow().clone())
+	}
+}
+
+pub struct MockMaxBalanceHistory;
+impl MockMaxBalanceHistory {
+	pub fn set(max_balance_history: u32) {
+		MAX_BALANCE_HISTORY.with(|v| *v.borrow_mut() = max_balance_history);
+	}
+}
+impl Get<u32> for MockMaxBalanceHistory {
+	fn get() -> u32 {
+		MAX_BALANCE_HISTORY.with(|v| *v.borrow())
+	}
+}
+
+/// Caps the total balance of accounts under `TEST_TOKEN_ID` only.
+pub struct MockMaxBalancePerAccount;
+impl MockMaxBalancePerAccount {
+	pub fn set(max_balance: Option<Balance>) {
+		MAX_BALANCE.with(|v| *v.borrow_mut() = max_balance);
+	}
+}
+impl Convert<CurrencyId, Option<Balance>> for MockMaxBalancePerAccount {
+	fn convert(currency_id: CurrencyId) -> Option<Balance> {
+		if currency_id == TEST_TOKEN_ID {
+			MAX_BALANCE.with(|v| *v.borrow())
+		} else {
+			None
+		}
+	}
+}
+
+/// Derives metadata for `AUTO_METADATA_TOKEN_ID` only.
+pub struct MockDefaultMetadata;
+impl Convert<CurrencyId, Option<(Vec<u8>, Vec<u8>, u8)>> for MockDefaultMetadata {
+	fn convert(currency_id: CurrencyId) -> Option<(Vec<u8>, Vec<u8>, u8)> {
+		if currency_id == AUTO_METADATA_TOKEN_ID {
+			Some((b"Auto Token".to_vec(), b"AUTO".to_vec(), 10))
+		} else {
+			None
+		}
+	}
+}
+
+pub struct MockEventVerbosity;
+impl Get<EventVerbosity> for MockEventVerbosity {
+	fn get() -> EventVerbosity {
+		EVENT_VERBOSITY.with(|v| *v.borrow())
+	}
+}
+
+parameter_types! {
+	pub const TreasuryAccount: AccountId = TREASURY;
+	pub const DustReceiver: AccountId = DUST_RECEIVER;
+	pub const MaxReserves: u32 = 2;
+	pub const MaxNameLen: u32 = 32;
+	pub const MaxLabelLen: u32 = 8;
+	pub const MaxDecimals: u8 = 18;
+	pub const MaxLockRemovals: u32 = 3;
+	pub const MaxInterestHolders: u32 = 3;
+	pub const MaxBatchTransfers: u32 = 3;
+	pub const SnapshotBlock: u64 = 10;
+	pub const AccountSnapshotPeriod: u64 = 10;
+	pub const MaxAccountSnapshots: u32 = 3;
+	pub const ReapGracePeriod: u64 = 3;
+	pub const MaxReapsPerBlock: u32 = 2;
+	pub const MigrationBatchSize: u32 = 2;
+	pub const NativeCurrencyId: Option<CurrencyId> = Some(NATIVE_CURRENCY_ID);
+}
+
 impl Trait for Runtime {
 	type Event = TestEvent;
 	type Balance = Balance;
-	type Amount = i64;
+	type Amount = i128;
 	type CurrencyId = CurrencyId;
 	type ExistentialDeposit = ExistentialDeposit;
 	type DustRemoval = MockDustRemoval<Balance>;
+	type DustSplit = DustSplit;
+	type DustReceiver = DustReceiver;
+	type ReapReservedDust = ReapReservedDust;
+	type DustEventMode = MockDustEventMode;
+	type OnIssuanceChange = MockIssuanceChange;
+	type OnTransferRecord = MockTransferRecord;
+	type OnAccountRecreate = MockAccountRecreate;
+	type MultiLocation = u32;
+	type TeleportOrigin = system::EnsureRoot<AccountId>;
+	type TargetBalanceOrigin = system::EnsureRoot<AccountId>;
+	type ForceOrigin = system::EnsureRoot<AccountId>;
+	type TransferFee = TransferFee;
+	type FeeExemptAccounts = FeeExemptAccounts;
+	type FeeExemptCurrencies = FeeExemptCurrencies;
+	type TreasuryFeeShare = TreasuryFeeShare;
+	type Treasury = TreasuryAccount;
+	type ServiceFee = ServiceFee;
+	type MaxReserves = MaxReserves;
+	type ReserveIdentifier = ReserveId;
+	type LockPurpose = LockPurpose;
+	type MaxLabelLen = MaxLabelLen;
+	type MaxNameLen = MaxNameLen;
+	type MaxDecimals = MaxDecimals;
+	type MetadataOrigin = system::EnsureRoot<AccountId>;
+	type DefaultMetadata = MockDefaultMetadata;
+	type MaxLockRatio = MaxLockRatio;
+	type MaxLockRemovals = MaxLockRemovals;
+	type EventVerbosity = MockEventVerbosity;
+	type EventModel = MockEventModel;
+	type MaxInterestHolders = MaxInterestHolders;
+	type MaxBatchTransfers = MaxBatchTransfers;
+	type PendingWithdrawals = MockPendingWithdrawals;
+	type TransferAllPolicy = MockTransferAllPolicy;
+	type CurrencyFilter = MockCurrencyFilter;
+	type BlockedRecipients = BlockedRecipients;
+	type DepositBounce = MockDepositBounce;
+	type MaxBalancePerAccount = MockMaxBalancePerAccount;
+	type StaleLockPolicy = MockStaleLockPolicy;
+	type Granularity = MockGranularity;
+	type GranularityPolicy = MockGranularityPolicy;
+	type SlashAccountingMode = MockSlashAccountingMode;
+	type IntentSignature = TestSignature;
+	type IntentSigner = UintAuthorityId;
+	type ProtectedAccounts = ProtectedAccounts;
+	type ProtocolAccounts = ProtocolAccounts;
+	type SnapshotBlock = SnapshotBlock;
+	type AccountSnapshotPeriod = AccountSnapshotPeriod;
+	type MaxAccountSnapshots = MaxAccountSnapshots;
+	type MaxBalanceHistory = MockMaxBalanceHistory;
+	type SnapshotOrigin = system::EnsureRoot<AccountId>;
+	type ReapGracePeriod = ReapGracePeriod;
+	type MaxReapsPerBlock = MaxReapsPerBlock;
+	type MigrationBatchSize = MigrationBatchSize;
+	type NativeCurrencyId = NativeCurrencyId;
+	type NativeCurrency = MockNativeCurrency;
 }
 
 pub type Tokens = Module<Runtime>;
 
+pub const NATIVE_CURRENCY_ID: CurrencyId = 0;
 pub const TEST_TOKEN_ID: CurrencyId = 1;
+pub const AUTO_METADATA_TOKEN_ID: CurrencyId = 8;
+pub const FEE_EXEMPT_TOKEN_ID: CurrencyId = 9;
+pub const PARA_CHAIN: u32 = 2000;
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
 pub const CHARLIE: AccountId = 3;
+pub const TREASURY: AccountId = 4;
+pub const POOL: AccountId = 5;
+pub const BLOCKED: AccountId = 6;
+pub const DUST_RECEIVER: AccountId = 7;
 pub const ID_1: LockIdentifier = *b"1       ";
 pub const ID_2: LockIdentifier = *b"2       ";
 
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReserveId {
+	Staking,
+	Auction,
+	Vesting,
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LockPurpose {
+	Staking,
+	Democracy,
+}
+
+/// Selects the listed currencies.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MockCurrencyFilter(pub Vec<CurrencyId>);
+impl CurrencyFilter<CurrencyId> for MockCurrencyFilter {
+	fn matches(&self, currency_id: &CurrencyId) -> bool {
+		self.0.contains(currency_id)
+	}
+}
+
 pub struct ExtBuilder {
 	endowed_accounts: Vec<(AccountId, CurrencyId, Balance)>,
+	transfer_fee: Permill,
+	treasury_fee_share: Permill,
+	service_fee: Balance,
+	dust_split: Permill,
+	reap_reserved_dust: bool,
+	max_lock_ratio: Permill,
+	event_verbosity: EventVerbosity,
+	non_reservable_currencies: Vec<CurrencyId>,
 }
 
 impl Default for ExtBuilder {
 	fn default() -> Self {
 		Self {
 			endowed_accounts: vec![],
+			transfer_fee: Permill::zero(),
+			treasury_fee_share: Permill::zero(),
+			service_fee: Zero::zero(),
+			dust_split: Permill::one(),
+			reap_reserved_dust: false,
+			max_lock_ratio: Permill::one(),
+			event_verbosity: EventVerbosity::Full,
+			non_reservable_currencies: vec![],
 		}
 	}
 }
@@ -122,6 +703,42 @@ impl ExtBuilder {
 		self.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, TEST_TOKEN_ID, 100)])
 	}
 
+	pub fn transfer_fee(mut self, transfer_fee: Permill, treasury_fee_share: Permill) -> Self {
+		self.transfer_fee = transfer_fee;
+		self.treasury_fee_share = treasury_fee_share;
+		self
+	}
+
+	pub fn service_fee(mut self, service_fee: Balance) -> Self {
+		self.service_fee = service_fee;
+		self
+	}
+
+	pub fn dust_split(mut self, dust_split: Permill) -> Self {
+		self.dust_split = dust_split;
+		self
+	}
+
+	pub fn reap_reserved_dust(mut self, reap_reserved_dust: bool) -> Self {
+		self.reap_reserved_dust = reap_reserved_dust;
+		self
+	}
+
+	pub fn max_lock_ratio(mut self, max_lock_ratio: Permill) -> Self {
+		self.max_lock_ratio = max_lock_ratio;
+		self
+	}
+
+	pub fn event_verbosity(mut self, event_verbosity: EventVerbosity) -> Self {
+		self.event_verbosity = event_verbosity;
+		self
+	}
+
+	pub fn non_reservable_currencies(mut self, non_reservable_currencies: Vec<CurrencyId>) -> Self {
+		self.non_reservable_currencies = non_reservable_currencies;
+		self
+	}
+
 	pub fn build(self) -> runtime_io::TestExternalities {
 		let mut t = frame_system::GenesisConfig::default()
 			.build_storage::<Runtime>()
@@ -129,10 +746,19 @@ impl ExtBuilder {
 
 		GenesisConfig::<Runtime> {
 			endowed_accounts: self.endowed_accounts,
+			non_reservable_currencies: self.non_reservable_currencies,
 		}
 		.assimilate_storage(&mut t)
 		.unwrap();
 
+		TRANSFER_FEE.with(|v| *v.borrow_mut() = self.transfer_fee);
+		TREASURY_FEE_SHARE.with(|v| *v.borrow_mut() = self.treasury_fee_share);
+		SERVICE_FEE.with(|v| *v.borrow_mut() = self.service_fee);
+		DUST_SPLIT.with(|v| *v.borrow_mut() = self.dust_split);
+		REAP_RESERVED_DUST.with(|v| *v.borrow_mut() = self.reap_reserved_dust);
+		MAX_LOCK_RATIO.with(|v| *v.borrow_mut() = self.max_lock_ratio);
+		EVENT_VERBOSITY.with(|v| *v.borrow_mut() = self.event_verbosity);
+
 		t.into()
 	}
 }
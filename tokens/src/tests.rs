@@ -5,9 +5,100 @@
 use super::*;
 use frame_support::{assert_noop, assert_ok};
 use mock::{
-	Balance, ExtBuilder, MockDustRemoval, Runtime, System, TestEvent, Tokens, ALICE, BOB, CHARLIE, ID_1, ID_2,
-	TEST_TOKEN_ID,
+	Balance, ExtBuilder, LockPurpose, MockAccountRecreate, MockCurrencyFilter, MockDepositBounce, MockDustEventMode,
+	MockDustRemoval, MockEventModel, MockGranularity, MockGranularityPolicy, MockIssuanceChange, MockMaxBalanceHistory,
+	MockMaxBalancePerAccount, MockNativeCurrency, MockPendingWithdrawals, MockSlashAccountingMode, MockStaleLockPolicy,
+	MockTransferAllPolicy, MockTransferRecord, Origin, ReserveId, Runtime, System, TestEvent, Tokens, ALICE,
+	AUTO_METADATA_TOKEN_ID, BLOCKED, BOB, CHARLIE, DUST_RECEIVER, FEE_EXEMPT_TOKEN_ID, ID_1, ID_2, NATIVE_CURRENCY_ID,
+	PARA_CHAIN, POOL, TEST_TOKEN_ID, TREASURY,
 };
+use orml_traits::ReserveMode;
+use primitives::{
+	offchain::{testing::TestOffchainExt, OffchainExt},
+	H256,
+};
+use sp_runtime::{
+	testing::TestSignature,
+	traits::{BadOrigin, Bounded, OnFinalize, OnInitialize, OnRuntimeUpgrade},
+};
+
+#[test]
+fn registered_lock_ids_should_be_changed_by_owner_only() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::register_lock_id(Some(ALICE).into(), ID_1, Some(LockPurpose::Staking)),
+				BadOrigin
+			);
+			assert_ok!(Tokens::register_lock_id(Origin::ROOT, ID_1, Some(LockPurpose::Staking)));
+			assert_eq!(Tokens::lock_owner(ID_1), Some(LockPurpose::Staking));
+
+			assert_ok!(Tokens::set_lock_as(
+				&LockPurpose::Staking,
+				ID_1,
+				TEST_TOKEN_ID,
+				&ALICE,
+				30
+			));
+			assert_ok!(Tokens::extend_lock_as(
+				&LockPurpose::Staking,
+				ID_1,
+				TEST_TOKEN_ID,
+				&ALICE,
+				40
+			));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 40);
+
+			// another purpose, or none, cannot change it
+			assert_noop!(
+				Tokens::set_lock_as(&LockPurpose::Democracy, ID_1, TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::LockNotOwned
+			);
+			assert_noop!(
+				Tokens::extend_lock_as(&LockPurpose::Democracy, ID_1, TEST_TOKEN_ID, &ALICE, 50),
+				Error::<Runtime>::LockNotOwned
+			);
+			assert_noop!(
+				Tokens::remove_lock_as(&LockPurpose::Democracy, ID_1, TEST_TOKEN_ID, &ALICE),
+				Error::<Runtime>::LockNotOwned
+			);
+			assert_noop!(
+				Tokens::try_set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::LockNotOwned
+			);
+			assert_noop!(
+				Tokens::try_extend_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50),
+				Error::<Runtime>::LockNotOwned
+			);
+
+			// the infallible trait methods leave it unchanged
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10);
+			Tokens::extend_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+			Tokens::remove_lock(ID_1, TEST_TOKEN_ID, &ALICE);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 40);
+			assert_eq!(Tokens::locks(TEST_TOKEN_ID, ALICE).len(), 1);
+
+			// unregistered ids could be used by any purpose
+			assert_ok!(Tokens::set_lock_as(
+				&LockPurpose::Democracy,
+				ID_2,
+				TEST_TOKEN_ID,
+				&ALICE,
+				20
+			));
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &BOB, 20);
+
+			assert_ok!(Tokens::remove_lock_as(
+				&LockPurpose::Staking,
+				ID_1,
+				TEST_TOKEN_ID,
+				&ALICE
+			));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 20);
+		});
+}
 
 #[test]
 fn set_lock_should_work() {
@@ -28,6 +119,61 @@ fn set_lock_should_work() {
 		});
 }
 
+#[test]
+fn remove_lock_all_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10);
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 5);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 20);
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &BOB, 30);
+
+			assert_eq!(
+				Tokens::remove_lock_all(TEST_TOKEN_ID, ID_1, &[ALICE, BOB, CHARLIE]),
+				Ok(2)
+			);
+			assert_eq!(
+				Tokens::locks(TEST_TOKEN_ID, ALICE),
+				vec![BalanceLock { id: ID_2, amount: 5 }]
+			);
+			assert_eq!(
+				Tokens::locks(TEST_TOKEN_ID, BOB),
+				vec![BalanceLock { id: ID_2, amount: 30 }]
+			);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 5);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &BOB).frozen, 30);
+
+			assert_eq!(Tokens::remove_lock_all(TEST_TOKEN_ID, ID_1, &[ALICE, BOB]), Ok(0));
+			assert_noop!(
+				Tokens::remove_lock_all(TEST_TOKEN_ID, ID_2, &[ALICE, BOB, CHARLIE, TREASURY]),
+				Error::<Runtime>::TooManyAccounts
+			);
+		});
+}
+
+#[test]
+fn encumbered_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::encumbered_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::available_balance(TEST_TOKEN_ID, &ALICE), 50);
+
+			// the lock exceeds the free balance
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &BOB, 150);
+			assert_eq!(Tokens::encumbered_balance(TEST_TOKEN_ID, &BOB), 150);
+			assert_eq!(Tokens::available_balance(TEST_TOKEN_ID, &BOB), 0);
+
+			assert_eq!(Tokens::encumbered_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+			assert_eq!(Tokens::available_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+		});
+}
+
 #[test]
 fn extend_lock_should_work() {
 	ExtBuilder::default()
@@ -60,6 +206,22 @@ fn remove_lock_should_work() {
 		});
 }
 
+#[test]
+fn effective_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::effective_balance(TEST_TOKEN_ID, &ALICE), 100);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert_eq!(Tokens::effective_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+			assert_eq!(Tokens::effective_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::effective_balance(TEST_TOKEN_ID, &ALICE), 0);
+		});
+}
+
 #[test]
 fn frozen_can_limit_liquidity() {
 	ExtBuilder::default()
@@ -77,364 +239,5306 @@ fn frozen_can_limit_liquidity() {
 }
 
 #[test]
-fn can_reserve_is_correct() {
+fn stale_locks_should_be_retained_by_default() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_eq!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 0), true);
-			assert_eq!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 101), false);
-			assert_eq!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 100), true);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 100), 0);
+			assert_eq!(Tokens::locks(TEST_TOKEN_ID, ALICE).len(), 1);
+			assert_eq!(System::refs(&ALICE), 1);
 		});
 }
 
 #[test]
-fn reserve_should_work() {
+fn stale_locks_should_be_removed_under_remove_policy() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_noop!(
-				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 101),
-				Error::<Runtime>::BalanceTooLow,
+			MockStaleLockPolicy::set(StaleLockPolicy::Remove);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 20);
+			assert_eq!(System::refs(&ALICE), 1);
+
+			// reserving doesn't change the total balance
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 10));
+			assert_eq!(Tokens::locks(TEST_TOKEN_ID, ALICE).len(), 2);
+
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 70), 0);
+			assert_eq!(
+				Tokens::locks(TEST_TOKEN_ID, ALICE),
+				vec![BalanceLock { id: ID_2, amount: 20 }]
 			);
-			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 0));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).frozen, 20);
+			let removed_event = TestEvent::tokens(RawEvent::StaleLocksRemoved(TEST_TOKEN_ID, ALICE, 1));
+			assert!(System::events().iter().any(|record| record.event == removed_event));
+
+			// draining the account removes all locks
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 30), 0);
+			assert!(Tokens::locks(TEST_TOKEN_ID, ALICE).is_empty());
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).frozen, 0);
+			assert_eq!(System::refs(&ALICE), 0);
 		});
 }
 
 #[test]
-fn unreserve_should_work() {
+fn max_locks_observed_should_work() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 0), 0);
-			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 50), 50);
-			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
-			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 15), 0);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 85);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 15);
-			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 30), 15);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::max_locks_observed(TEST_TOKEN_ID), 0);
+
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 10);
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &BOB, 20);
+			Tokens::set_lock(ID_1, FEE_EXEMPT_TOKEN_ID, &ALICE, 10);
+			assert_eq!(Tokens::max_locks_observed(TEST_TOKEN_ID), 2);
+			assert_eq!(Tokens::max_locks_observed(FEE_EXEMPT_TOKEN_ID), 1);
+
+			Tokens::remove_lock(ID_2, TEST_TOKEN_ID, &BOB);
+			assert_eq!(Tokens::max_locks_observed(TEST_TOKEN_ID), 1);
 		});
 }
 
 #[test]
-fn slash_reserved_should_work() {
+fn set_lock_multi_should_work() {
 	ExtBuilder::default()
-		.one_hundred_for_alice_n_bob()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, FEE_EXEMPT_TOKEN_ID, 50)])
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
-			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &ALICE, 0), 0);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
-			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &ALICE, 100), 50);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+			assert_ok!(Tokens::set_lock_multi(
+				ID_1,
+				&ALICE,
+				vec![(TEST_TOKEN_ID, 80), (FEE_EXEMPT_TOKEN_ID, 50)]
+			));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 80);
+			assert_eq!(Tokens::accounts(FEE_EXEMPT_TOKEN_ID, &ALICE).frozen, 50);
+
+			let lock_set_event = TestEvent::tokens(RawEvent::LockSet(FEE_EXEMPT_TOKEN_ID, ALICE, ID_1, 50));
+			assert!(System::events().iter().any(|record| record.event == lock_set_event));
 		});
 }
 
 #[test]
-fn repatriate_reserved_should_work() {
+fn set_lock_multi_should_set_no_lock_on_failure() {
 	ExtBuilder::default()
-		.one_hundred_for_alice_n_bob()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, FEE_EXEMPT_TOKEN_ID, 50)])
 		.build()
 		.execute_with(|| {
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(
-				Tokens::repatriate_reserved(TEST_TOKEN_ID, &ALICE, &ALICE, 0, BalanceStatus::Free),
-				Ok(0)
-			);
-			assert_eq!(
-				Tokens::repatriate_reserved(TEST_TOKEN_ID, &ALICE, &ALICE, 50, BalanceStatus::Free),
-				Ok(50)
+			assert_noop!(
+				Tokens::set_lock_multi(ID_1, &ALICE, vec![(TEST_TOKEN_ID, 80), (FEE_EXEMPT_TOKEN_ID, 51)]),
+				Error::<Runtime>::BalanceTooLow
 			);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert!(Tokens::locks(TEST_TOKEN_ID, &ALICE).is_empty());
+			assert!(Tokens::locks(FEE_EXEMPT_TOKEN_ID, &ALICE).is_empty());
+		});
+}
 
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 0);
-			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 50);
-			assert_eq!(
-				Tokens::repatriate_reserved(TEST_TOKEN_ID, &BOB, &BOB, 60, BalanceStatus::Reserved),
-				Ok(10)
+#[test]
+fn lock_ratio_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.max_lock_ratio(Permill::from_percent(80))
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::try_set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 80));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 80);
+			assert_noop!(
+				Tokens::try_set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 81),
+				Error::<Runtime>::LockRatioExceeded
 			);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 50);
-
-			assert_eq!(
-				Tokens::repatriate_reserved(TEST_TOKEN_ID, &BOB, &ALICE, 30, BalanceStatus::Reserved),
-				Ok(0)
+			assert_noop!(
+				Tokens::try_extend_lock(ID_1, TEST_TOKEN_ID, &ALICE, 81),
+				Error::<Runtime>::LockRatioExceeded
 			);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 20);
+			// locks not increasing the frozen balance are allowed
+			assert_ok!(Tokens::try_set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 50));
 
-			assert_eq!(
-				Tokens::repatriate_reserved(TEST_TOKEN_ID, &BOB, &ALICE, 30, BalanceStatus::Free),
-				Ok(10)
+			assert_noop!(
+				Tokens::try_set_lock(ID_1, TEST_TOKEN_ID, &BOB, 81),
+				Error::<Runtime>::LockRatioExceeded
 			);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 120);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 0);
+			assert_ok!(Tokens::try_extend_lock(ID_1, TEST_TOKEN_ID, &BOB, 80));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &BOB).frozen, 80);
+
+			// the infallible functions aren't restricted
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 90);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &BOB).frozen, 90);
 		});
 }
 
 #[test]
-fn slash_draw_reserved_correct() {
+fn lock_ratio_should_apply_at_one_hundred_percent() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+			assert_ok!(Tokens::try_set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 100));
+			assert_noop!(
+				Tokens::try_extend_lock(ID_1, TEST_TOKEN_ID, &ALICE, 101),
+				Error::<Runtime>::LockRatioExceeded
+			);
+			assert_noop!(
+				Tokens::try_set_lock(ID_1, TEST_TOKEN_ID, &BOB, 101),
+				Error::<Runtime>::LockRatioExceeded
+			);
 
-			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 80), 0);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 120);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 101);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &BOB).frozen, 101);
+		});
+}
 
-			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 50), 30);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+#[test]
+fn can_reserve_is_correct() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 0), true);
+			assert_eq!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 101), false);
+			assert_eq!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 100), true);
 		});
 }
 
 #[test]
-fn genesis_issuance_should_work() {
+fn holder_count_should_work() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 2);
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &CHARLIE, 100));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 3);
+
+			// reserving all of the free balance keeps the account a holder
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &CHARLIE, 100));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 3);
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &CHARLIE, 100), 0);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 3);
 		});
 }
 
 #[test]
-fn transfer_should_work() {
+fn currency_count_should_track_created_and_live_currencies() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 150);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+			assert_eq!(Tokens::currency_count(), 1);
+			assert_eq!(Tokens::live_currency_count(), 1);
 
-			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 50));
-			assert!(System::events().iter().any(|record| record.event == transferred_event));
+			// the first deposit creates a currency
+			assert_ok!(Tokens::deposit(FEE_EXEMPT_TOKEN_ID, &CHARLIE, 100));
+			assert!(Tokens::is_created_currency(FEE_EXEMPT_TOKEN_ID));
+			assert_eq!(Tokens::currency_count(), 2);
+			assert_eq!(Tokens::live_currency_count(), 2);
+			assert_ok!(Tokens::deposit(FEE_EXEMPT_TOKEN_ID, &ALICE, 100));
+			assert_eq!(Tokens::currency_count(), 2);
+			assert_eq!(Tokens::live_currency_count(), 2);
 
-			assert_noop!(
-				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 60),
-				Error::<Runtime>::BalanceTooLow,
-			);
+			// burning all of the issuance is no longer live, but stays created
+			assert_ok!(Tokens::withdraw(FEE_EXEMPT_TOKEN_ID, &CHARLIE, 100));
+			assert_ok!(Tokens::withdraw(FEE_EXEMPT_TOKEN_ID, &ALICE, 100));
+			assert_eq!(Tokens::total_issuance(FEE_EXEMPT_TOKEN_ID), 0);
+			assert_eq!(Tokens::currency_count(), 2);
+			assert_eq!(Tokens::live_currency_count(), 1);
+
+			// issuing it again doesn't create it twice
+			assert_ok!(Tokens::deposit(FEE_EXEMPT_TOKEN_ID, &CHARLIE, 100));
+			assert_eq!(Tokens::currency_count(), 2);
+			assert_eq!(Tokens::live_currency_count(), 2);
 		});
 }
 
 #[test]
-fn transfer_fails_if_below_existential_deposit() {
+fn holder_count_should_track_reserved_only_accounts() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_noop!(
-				Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 1),
-				Error::<Runtime>::ExistentialDeposit
+			assert_ok!(Tokens::deposit_reserved(TEST_TOKEN_ID, &CHARLIE, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &CHARLIE), 1);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 201);
+			let deposited_event = TestEvent::tokens(RawEvent::ReservedDeposited(TEST_TOKEN_ID, CHARLIE, 1));
+			assert!(System::events().iter().any(|record| record.event == deposited_event));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 3);
+
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &CHARLIE, 1), 0);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 2);
+
+			// repatriating into the reserved balance of an empty account counts it in
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+			assert_ok!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &ALICE, &CHARLIE, 50, BalanceStatus::Reserved),
+				0
+			);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 3);
+			assert_ok!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &CHARLIE, &BOB, 50, BalanceStatus::Free),
+				0
 			);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 2);
 		});
 }
 
 #[test]
-fn transfer_enforces_existential_rule() {
+fn holder_count_should_decrement_once_on_dust() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
 			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 1);
 		});
 }
 
 #[test]
-fn transfer_all_should_work() {
+fn holder_count_should_decrement_once_on_full_withdraw() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), BOB, TEST_TOKEN_ID));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 200);
-
-			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 100));
-			assert!(System::events().iter().any(|record| record.event == transferred_event));
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 100));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 1);
 		});
 }
 
 #[test]
-fn deposit_should_work() {
+fn holder_count_should_decrement_once_on_full_transfer() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 100));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 200);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 300);
-
-			assert_noop!(
-				Tokens::deposit(TEST_TOKEN_ID, &ALICE, Balance::max_value()),
-				Error::<Runtime>::TotalIssuanceOverflow,
-			);
+			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 2);
+			assert_ok!(Tokens::transfer_all(Some(CHARLIE).into(), BOB, TEST_TOKEN_ID));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 1);
 		});
 }
 
 #[test]
-fn deposit_enforces_existential_rule() {
+fn holder_count_should_decrement_once_on_slash_to_zero() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &CHARLIE, 1));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 0);
-			assert_eq!(MockDustRemoval::accumulated_dust(), 0);
+			// both free and reserved balances are slashed
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 100), 0);
+			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 1);
 		});
 }
 
 #[test]
-fn withdraw_should_work() {
+fn reaped_accounts_should_be_removed_after_grace_period() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+			System::set_block_number(1);
+			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID));
+			assert_ok!(Tokens::transfer_all(Some(BOB).into(), CHARLIE, TEST_TOKEN_ID));
+			assert_eq!(
+				Tokens::reap_queue(4),
+				vec![(TEST_TOKEN_ID, ALICE), (TEST_TOKEN_ID, BOB)]
+			);
+			assert!(<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, ALICE));
+
+			// crediting within the grace period reuses the entry
+			System::set_block_number(2);
+			assert_ok!(Tokens::transfer(Some(CHARLIE).into(), ALICE, TEST_TOKEN_ID, 10));
+
+			Tokens::on_initialize(4);
+			assert_eq!(Tokens::reap_queue(4), vec![]);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 10);
+			assert!(<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, ALICE));
+			assert!(!<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, BOB));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 2);
+		});
+}
+
+#[test]
+fn reaped_accounts_removal_should_be_bounded() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), TREASURY, TEST_TOKEN_ID));
+			assert_ok!(Tokens::transfer_all(Some(BOB).into(), TREASURY, TEST_TOKEN_ID));
+			assert_ok!(Tokens::transfer_all(Some(CHARLIE).into(), TREASURY, TEST_TOKEN_ID));
+
+			Tokens::on_initialize(4);
+			assert!(!<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, ALICE));
+			assert!(!<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, BOB));
+			assert!(<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, CHARLIE));
+			assert_eq!(Tokens::reap_queue(5), vec![(TEST_TOKEN_ID, CHARLIE)]);
+
+			Tokens::on_initialize(5);
+			assert_eq!(Tokens::reap_queue(5), vec![]);
+			assert!(!<Accounts<Runtime>>::contains_key(TEST_TOKEN_ID, CHARLIE));
+		});
+}
+
+#[test]
+fn reserve_keep_alive_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve_keep_alive(TEST_TOKEN_ID, &ALICE, 98));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 2);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 98);
 
 			assert_noop!(
-				Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 60),
+				Tokens::reserve_keep_alive(TEST_TOKEN_ID, &BOB, 99),
+				Error::<Runtime>::KeepAlive
+			);
+			assert_noop!(
+				Tokens::reserve_keep_alive(TEST_TOKEN_ID, &BOB, 101),
 				Error::<Runtime>::BalanceTooLow
 			);
+
+			// `reserve` still permits it
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 1);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 99);
 		});
 }
 
 #[test]
-fn withdraw_enforces_existential_rule() {
+fn reserve_should_work() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 99));
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 101),
+				Error::<Runtime>::BalanceTooLow,
+			);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 0));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 100);
+
+			let reserved_event = TestEvent::tokens(RawEvent::Reserved(TEST_TOKEN_ID, ALICE, 50));
+			assert!(System::events().iter().any(|record| record.event == reserved_event));
+		});
+}
+
+#[test]
+fn reserve_should_not_reserve_locked_funds() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// free == frozen
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 100);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 1),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+
+			// free slightly exceeds frozen
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 90);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 11),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 10));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 90);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 10);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 1),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+		});
+}
+
+#[test]
+fn reserve_should_fail_while_free_is_below_frozen() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// a lock set after a reserve leaves free below frozen
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 90);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 90);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 1),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+			assert_noop!(
+				Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 1),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+
+			// unreserving is not restricted by locks
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 25), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 95);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 5);
+
+			// only the part of free above frozen can be reserved again
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 6),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 5));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 90);
+		});
+}
+
+#[test]
+fn unreserve_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 0), 0);
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 50), 50);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 15), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 85);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 15);
+			let unreserved_event = TestEvent::tokens(RawEvent::Unreserved(TEST_TOKEN_ID, ALICE, 15));
+			assert!(System::events().iter().any(|record| record.event == unreserved_event));
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 30), 15);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+		});
+}
+
+#[test]
+fn unreserve_should_keep_reserved_dust_by_default() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 100));
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &ALICE, 98), 0);
+
+			// the unreserved balance is below the existential deposit and removed, the reserved dust is kept
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 1), 0);
 			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 1);
 			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+
+			// a full unreserve leaving free balance below the existential deposit reaps the account
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 1), 0);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE), Default::default());
+			assert_eq!(MockDustRemoval::accumulated_dust(), 2);
 			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
 		});
 }
 
 #[test]
-fn slash_should_work() {
+fn unreserve_should_reap_reserved_dust() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
+		.reap_reserved_dust(true)
 		.build()
 		.execute_with(|| {
-			// slashed_amount < amount
-			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 50), 0);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 100));
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &ALICE, 98), 0);
+
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 1), 0);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE), Default::default());
+			assert_eq!(MockDustRemoval::accumulated_dust(), 2);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+
+			// reserved balance at or above the existential deposit is kept
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 100));
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &BOB, 97), 0);
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &BOB, 1), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 2);
+		});
+}
+
+#[test]
+fn slash_reserved_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &ALICE, 0), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &ALICE, 100), 50);
 			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
 			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+		});
+}
 
-			// slashed_amount == amount
-			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 51), 1);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+#[test]
+fn reserve_named_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 101),
+				Error::<Runtime>::BalanceTooLow,
+			);
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 10));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE),
+				40
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE),
+				20
+			);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 60);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 40);
+			assert_eq!(Tokens::reserves(TEST_TOKEN_ID, &ALICE).len(), 2);
 		});
 }
 
 #[test]
-fn slash_enforces_existential_rule() {
+fn reserve_named_should_respect_max_reserves() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 99), 0);
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
-			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 10));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 10));
+			assert_noop!(
+				Tokens::reserve_named(&ReserveId::Vesting, TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::TooManyReserves,
+			);
+			assert_noop!(
+				Tokens::transfer_and_reserve_named(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 10, ReserveId::Vesting),
+				Error::<Runtime>::TooManyReserves,
+			);
+
+			// topping up existing named reserves always works
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 10));
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE),
+				20
+			);
+
+			// other accounts are not affected
+			assert_ok!(Tokens::reserve_named(&ReserveId::Vesting, TEST_TOKEN_ID, &BOB, 10));
 		});
 }
 
 #[test]
-fn update_balance_should_work() {
+fn unreserve_named_should_work() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
-			assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &ALICE, 50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 150);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(
+				Tokens::unreserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 10),
+				0
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE),
+				20
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 60);
 
-			assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &BOB, -50));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
-			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+			// cannot unreserve more than the named reserve
+			assert_eq!(
+				Tokens::unreserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30),
+				10
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE),
+				0
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE),
+				20
+			);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 80);
+			assert_eq!(Tokens::reserves(TEST_TOKEN_ID, &ALICE).len(), 1);
+		});
+}
 
-			assert_noop!(
-				Tokens::update_balance(TEST_TOKEN_ID, &BOB, -60),
-				Error::<Runtime>::BalanceTooLow
+#[test]
+fn slash_reserved_named_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(
+				Tokens::slash_reserved_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 40),
+				10
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE),
+				0
 			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE),
+				20
+			);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 170);
 		});
 }
 
 #[test]
-fn ensure_can_withdraw_should_work() {
+fn releasable_named_should_reflect_slashes() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::releasable_named(TEST_TOKEN_ID, &ReserveId::Staking, &ALICE), 0);
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_eq!(Tokens::releasable_named(TEST_TOKEN_ID, &ReserveId::Staking, &ALICE), 30);
+
+			assert_eq!(
+				Tokens::slash_reserved_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 12),
+				0
+			);
+			assert_eq!(Tokens::releasable_named(TEST_TOKEN_ID, &ReserveId::Staking, &ALICE), 18);
+
+			assert_eq!(
+				Tokens::unreserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 18),
+				0
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 88);
+			assert_eq!(Tokens::releasable_named(TEST_TOKEN_ID, &ReserveId::Staking, &ALICE), 0);
+		});
+}
+
+#[test]
+fn reserves_with_labels_should_work() {
 	ExtBuilder::default()
 		.one_hundred_for_alice_n_bob()
 		.build()
 		.execute_with(|| {
 			assert_noop!(
-				Tokens::ensure_can_withdraw(TEST_TOKEN_ID, &ALICE, 101),
-				Error::<Runtime>::BalanceTooLow
+				Tokens::reserve_named_with_label(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 10, b"too long!".to_vec()),
+				Error::<Runtime>::LabelTooLong
 			);
 
-			assert_ok!(Tokens::ensure_can_withdraw(TEST_TOKEN_ID, &ALICE, 1));
-			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_ok!(Tokens::reserve_named_with_label(
+				&ReserveId::Staking,
+				TEST_TOKEN_ID,
+				&ALICE,
+				30,
+				b"stake".to_vec()
+			));
+			assert_ok!(Tokens::reserve_named_with_label(
+				&ReserveId::Auction,
+				TEST_TOKEN_ID,
+				&ALICE,
+				20,
+				b"bid #1".to_vec()
+			));
+			assert_eq!(
+				Tokens::reserves_with_labels(TEST_TOKEN_ID, &ALICE),
+				vec![
+					(ReserveId::Staking, 30, b"stake".to_vec()),
+					(ReserveId::Auction, 20, b"bid #1".to_vec()),
+				]
+			);
+
+			assert_eq!(
+				Tokens::unreserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30),
+				0
+			);
+			assert_eq!(Tokens::reserve_label((TEST_TOKEN_ID, ALICE), ReserveId::Staking), None);
+			assert_eq!(
+				Tokens::reserves_with_labels(TEST_TOKEN_ID, &ALICE),
+				vec![(ReserveId::Auction, 20, b"bid #1".to_vec())]
+			);
 		});
 }
 
 #[test]
-fn no_op_if_amount_is_zero() {
-	ExtBuilder::default().build().execute_with(|| {
-		assert_ok!(Tokens::ensure_can_withdraw(TEST_TOKEN_ID, &ALICE, 0));
-		assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 0));
-		assert_ok!(Tokens::transfer(Some(ALICE).into(), ALICE, TEST_TOKEN_ID, 0));
-		assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 0));
-		assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 0));
-		assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 0), 0);
-		assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 1), 1);
-		assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &ALICE, 0));
-	});
+fn named_reserves_should_be_isolated_by_reserve_id() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 20));
+
+			// another module's reserve id can't release or slash the funds
+			assert_eq!(
+				Tokens::unreserve_named(&ReserveId::Vesting, TEST_TOKEN_ID, &ALICE, 30),
+				30
+			);
+			assert_eq!(
+				Tokens::slash_reserved_named(&ReserveId::Vesting, TEST_TOKEN_ID, &ALICE, 30),
+				30
+			);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+
+			assert_eq!(
+				Tokens::unreserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 30),
+				10
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE),
+				30
+			);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE),
+				0
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
+		});
+}
+
+#[test]
+fn repatriate_reserved_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &ALICE, &ALICE, 0, BalanceStatus::Free),
+				Ok(0)
+			);
+			assert_eq!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &ALICE, &ALICE, 50, BalanceStatus::Free),
+				Ok(50)
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 0);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &BOB, &BOB, 60, BalanceStatus::Reserved),
+				Ok(10)
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 50);
+
+			assert_eq!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &BOB, &ALICE, 30, BalanceStatus::Reserved),
+				Ok(0)
+			);
+			let repatriated_event = TestEvent::tokens(RawEvent::ReserveRepatriated(
+				TEST_TOKEN_ID,
+				BOB,
+				ALICE,
+				30,
+				BalanceStatus::Reserved,
+			));
+			assert!(System::events().iter().any(|record| record.event == repatriated_event));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 20);
+
+			assert_eq!(
+				Tokens::repatriate_reserved(TEST_TOKEN_ID, &BOB, &ALICE, 30, BalanceStatus::Free),
+				Ok(10)
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 120);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 0);
+		});
+}
+
+#[test]
+fn slash_draw_reserved_correct() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 80), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 120);
+
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 50), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+		});
+}
+
+#[test]
+fn genesis_issuance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+		});
+}
+
+#[test]
+fn transfer_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 150);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 50));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 60),
+				Error::<Runtime>::BalanceTooLow,
+			);
+		});
+}
+
+#[test]
+fn transfer_fails_if_below_existential_deposit() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 1),
+				Error::<Runtime>::ExistentialDeposit
+			);
+		});
+}
+
+#[test]
+fn transfer_enforces_existential_rule() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+		});
+}
+
+#[test]
+fn transfer_all_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), BOB, TEST_TOKEN_ID));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 200);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 100));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+		});
+}
+
+#[test]
+fn transfer_all_should_sweep_pending_withdrawals_by_default() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockPendingWithdrawals::set(TEST_TOKEN_ID, ALICE, 30);
+			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), BOB, TEST_TOKEN_ID));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 200);
+		});
+}
+
+#[test]
+fn transfer_all_should_keep_pending_withdrawals() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockTransferAllPolicy::set(TransferAllPolicy::KeepPendingWithdrawals);
+			MockPendingWithdrawals::set(TEST_TOKEN_ID, ALICE, 30);
+			assert_ok!(Tokens::transfer_all(Some(ALICE).into(), BOB, TEST_TOKEN_ID));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 170);
+
+			// the pending withdrawal could still be made
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 30));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 30);
+		});
+}
+
+#[test]
+fn transfer_with_nonce_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, ALICE)), 0);
+			assert_ok!(Tokens::transfer_with_nonce(
+				Some(ALICE).into(),
+				BOB,
+				TEST_TOKEN_ID,
+				10,
+				1
+			));
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, ALICE)), 1);
+			assert_ok!(Tokens::transfer_with_nonce(
+				Some(ALICE).into(),
+				BOB,
+				TEST_TOKEN_ID,
+				10,
+				2
+			));
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, ALICE)), 2);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 80);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 120);
+
+			// nonces are per sender and token type
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, BOB)), 0);
+			assert_eq!(Tokens::nonce((NATIVE_CURRENCY_ID, ALICE)), 0);
+		});
+}
+
+#[test]
+fn transfer_with_nonce_should_reject_invalid_nonce() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer_with_nonce(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10, 0),
+				Error::<Runtime>::InvalidNonce,
+			);
+			assert_noop!(
+				Tokens::transfer_with_nonce(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10, 2),
+				Error::<Runtime>::InvalidNonce,
+			);
+			assert_ok!(Tokens::transfer_with_nonce(
+				Some(ALICE).into(),
+				BOB,
+				TEST_TOKEN_ID,
+				10,
+				1
+			));
+
+			// replaying a used nonce fails
+			assert_noop!(
+				Tokens::transfer_with_nonce(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10, 1),
+				Error::<Runtime>::InvalidNonce,
+			);
+
+			// a failed transfer doesn't use up the nonce
+			assert_noop!(
+				Tokens::transfer_with_nonce(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 1000, 2),
+				Error::<Runtime>::BalanceTooLow,
+			);
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, ALICE)), 1);
+		});
+}
+
+#[test]
+fn split_fee_should_be_rounding_safe() {
+	ExtBuilder::default()
+		.transfer_fee(Permill::zero(), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::split_fee(0), (0, 0));
+			assert_eq!(Tokens::split_fee(1), (1, 0));
+			assert_eq!(Tokens::split_fee(7), (5, 2));
+
+			for fee in 0..1000 {
+				let (burned, to_treasury) = Tokens::split_fee(fee);
+				assert_eq!(burned + to_treasury, fee);
+				// within one unit of the exact 30% share
+				assert!((to_treasury * 10).max(fee * 3) - (to_treasury * 10).min(fee * 3) < 10);
+			}
+		});
+}
+
+#[test]
+fn submit_transfer_intent_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			let intent = SignedTransferIntent {
+				from: ALICE,
+				to: CHARLIE,
+				currency_id: TEST_TOKEN_ID,
+				amount: 50,
+				nonce: 1,
+				deadline: 5,
+			};
+			let signature = TestSignature(ALICE, Tokens::intent_signing_payload(&intent));
+			assert_ok!(Tokens::submit_transfer_intent(
+				Some(BOB).into(),
+				intent.clone(),
+				signature.clone()
+			));
+
+			// the full amount is transferred and the relayer pays the fee
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 95);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 195);
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, ALICE)), 1);
+
+			// replaying the intent fails on its stale nonce
+			assert_noop!(
+				Tokens::submit_transfer_intent(Some(BOB).into(), intent, signature),
+				Error::<Runtime>::InvalidNonce
+			);
+
+			// a failed transfer doesn't use the nonce or charge the relayer
+			let intent = SignedTransferIntent {
+				from: ALICE,
+				to: CHARLIE,
+				currency_id: TEST_TOKEN_ID,
+				amount: 60,
+				nonce: 2,
+				deadline: 5,
+			};
+			let signature = TestSignature(ALICE, Tokens::intent_signing_payload(&intent));
+			assert_noop!(
+				Tokens::submit_transfer_intent(Some(BOB).into(), intent, signature),
+				Error::<Runtime>::BalanceTooLow
+			);
+			assert_eq!(Tokens::nonce((TEST_TOKEN_ID, ALICE)), 1);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 95);
+		});
+}
+
+#[test]
+fn submit_transfer_intent_should_reject_bad_signature() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			let intent = SignedTransferIntent {
+				from: ALICE,
+				to: CHARLIE,
+				currency_id: TEST_TOKEN_ID,
+				amount: 50,
+				nonce: 1,
+				deadline: 5,
+			};
+			let signature = TestSignature(BOB, Tokens::intent_signing_payload(&intent));
+			assert_noop!(
+				Tokens::submit_transfer_intent(Some(BOB).into(), intent.clone(), signature),
+				Error::<Runtime>::BadSignature
+			);
+
+			// the payload is tagged and bound to the genesis hash of the chain
+			let signature = TestSignature(ALICE, intent.encode());
+			assert_noop!(
+				Tokens::submit_transfer_intent(Some(BOB).into(), intent.clone(), signature),
+				Error::<Runtime>::BadSignature
+			);
+
+			let signature = TestSignature(ALICE, Tokens::intent_signing_payload(&intent));
+			let tampered = SignedTransferIntent { amount: 100, ..intent };
+			assert_noop!(
+				Tokens::submit_transfer_intent(Some(BOB).into(), tampered, signature),
+				Error::<Runtime>::BadSignature
+			);
+		});
+}
+
+#[test]
+fn submit_transfer_intent_should_reject_expired_deadline() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			let intent = SignedTransferIntent {
+				from: ALICE,
+				to: CHARLIE,
+				currency_id: TEST_TOKEN_ID,
+				amount: 50,
+				nonce: 1,
+				deadline: 5,
+			};
+			let signature = TestSignature(ALICE, Tokens::intent_signing_payload(&intent));
+			System::set_block_number(6);
+			assert_noop!(
+				Tokens::submit_transfer_intent(Some(BOB).into(), intent.clone(), signature.clone()),
+				Error::<Runtime>::IntentExpired
+			);
+
+			System::set_block_number(5);
+			assert_ok!(Tokens::submit_transfer_intent(Some(BOB).into(), intent, signature));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 50);
+		});
+}
+
+#[test]
+fn fee_exempt_transfers_should_not_be_charged() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(TREASURY, TEST_TOKEN_ID, 100),
+			(ALICE, FEE_EXEMPT_TOKEN_ID, 100),
+		])
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			// a normal sender pays the fee
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 45);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 195);
+
+			// an exempt sender doesn't
+			assert_ok!(Tokens::transfer(Some(TREASURY).into(), BOB, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 95);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 195);
+
+			// nor a transfer to an exempt recipient
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), TREASURY, TEST_TOKEN_ID, 20));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 70);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 195);
+
+			// nor a transfer of an exempt currency
+			assert_eq!(Tokens::net_transfer_amount(FEE_EXEMPT_TOKEN_ID, &ALICE, &BOB, 50), 50);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, FEE_EXEMPT_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(FEE_EXEMPT_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::total_issuance(FEE_EXEMPT_TOKEN_ID), 100);
+		});
+}
+
+#[test]
+fn compute_transfer_fee_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (TREASURY, TEST_TOKEN_ID, 100)])
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::compute_transfer_fee(TEST_TOKEN_ID, &TREASURY, &BOB, 50), 0);
+			assert_eq!(Tokens::compute_transfer_fee(TEST_TOKEN_ID, &ALICE, &ALICE, 50), 0);
+			assert_eq!(Tokens::compute_transfer_fee(NATIVE_CURRENCY_ID, &ALICE, &BOB, 50), 5);
+
+			let fee = Tokens::compute_transfer_fee(TEST_TOKEN_ID, &ALICE, &BOB, 50);
+			assert_eq!(fee, 5);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50 - fee);
+			let (burned, to_treasury) = Tokens::split_fee(fee);
+			assert!(System::events().iter().any(|record| record.event
+				== TestEvent::tokens(RawEvent::TransferFeeCharged(TEST_TOKEN_ID, ALICE, burned, to_treasury))));
+
+			// an exempt sender is charged nothing
+			assert_ok!(Tokens::transfer(Some(TREASURY).into(), BOB, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 95);
+		});
+}
+
+#[test]
+fn split_by_weight_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(Tokens::split_by_weight(100, &[1, 2, 3]), Some(vec![16, 33, 51]));
+		assert_eq!(Tokens::split_by_weight(100, &[1, 1, 1]), Some(vec![34, 33, 33]));
+		assert_eq!(Tokens::split_by_weight(10, &[0, 1]), Some(vec![0, 10]));
+		assert_eq!(Tokens::split_by_weight(10, &[]), None);
+		assert_eq!(Tokens::split_by_weight(10, &[0, 0]), None);
+	});
+}
+
+#[test]
+fn transfer_split_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer_split(
+				Some(ALICE).into(),
+				TEST_TOKEN_ID,
+				100,
+				vec![(BOB, 1), (CHARLIE, 2), (DUST_RECEIVER, 3)]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 116);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 33);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &DUST_RECEIVER), 51);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, DUST_RECEIVER, 51));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+		});
+}
+
+#[test]
+fn transfer_split_should_fail_before_any_transfer() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer_split(Some(ALICE).into(), TEST_TOKEN_ID, 101, vec![(BOB, 1), (CHARLIE, 1)]),
+				Error::<Runtime>::BalanceTooLow
+			);
+			assert_noop!(
+				Tokens::transfer_split(Some(ALICE).into(), TEST_TOKEN_ID, 100, vec![(BOB, 0), (CHARLIE, 0)]),
+				Error::<Runtime>::ZeroTotalWeight
+			);
+			assert_noop!(
+				Tokens::transfer_split(
+					Some(ALICE).into(),
+					TEST_TOKEN_ID,
+					100,
+					vec![(BOB, 1), (CHARLIE, 1), (TREASURY, 1), (DUST_RECEIVER, 1)]
+				),
+				Error::<Runtime>::TooManyRecipients
+			);
+		});
+}
+
+#[test]
+fn transfer_split_should_be_all_or_nothing() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// the share of 1 to charlie couldn't create it, after the share of 99 to bob
+			assert_noop!(
+				Tokens::transfer_split(Some(ALICE).into(), TEST_TOKEN_ID, 100, vec![(BOB, 98), (CHARLIE, 1)]),
+				Error::<Runtime>::ExistentialDeposit
+			);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+		});
+}
+
+#[test]
+fn transfer_all_matching_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(ALICE, AUTO_METADATA_TOKEN_ID, 100),
+			(ALICE, FEE_EXEMPT_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert_ok!(Tokens::transfer_all_matching(
+				Some(ALICE).into(),
+				BOB,
+				MockCurrencyFilter(vec![TEST_TOKEN_ID, FEE_EXEMPT_TOKEN_ID, NATIVE_CURRENCY_ID])
+			));
+
+			// only the liquid balances of the selected token types are moved
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 70);
+			assert_eq!(Tokens::free_balance(FEE_EXEMPT_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(FEE_EXEMPT_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::free_balance(AUTO_METADATA_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::free_balance(AUTO_METADATA_TOKEN_ID, &BOB), 0);
+		});
+}
+
+#[test]
+fn transfer_all_matching_should_report_failed_sweeps() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, AUTO_METADATA_TOKEN_ID, 100)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::freeze_currency(Origin::ROOT, AUTO_METADATA_TOKEN_ID));
+			assert_ok!(Tokens::transfer_all_matching(
+				Some(ALICE).into(),
+				BOB,
+				MockCurrencyFilter(vec![TEST_TOKEN_ID, AUTO_METADATA_TOKEN_ID])
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::free_balance(AUTO_METADATA_TOKEN_ID, &ALICE), 100);
+			let failed_event = TestEvent::tokens(RawEvent::SweepFailed(
+				AUTO_METADATA_TOKEN_ID,
+				Error::<Runtime>::CurrencyFrozen.into(),
+			));
+			assert!(System::events().iter().any(|record| record.event == failed_event));
+
+			// fails if nothing could be swept
+			assert_eq!(
+				Tokens::transfer_all_matching(
+					Some(ALICE).into(),
+					BOB,
+					MockCurrencyFilter(vec![AUTO_METADATA_TOKEN_ID])
+				),
+				Err(Error::<Runtime>::CurrencyFrozen.into())
+			);
+		});
+}
+
+#[test]
+fn transfer_all_matching_should_be_bounded() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(ALICE, AUTO_METADATA_TOKEN_ID, 100),
+			(ALICE, FEE_EXEMPT_TOKEN_ID, 100),
+			(ALICE, 10, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer_all_matching(
+					Some(ALICE).into(),
+					BOB,
+					MockCurrencyFilter(vec![TEST_TOKEN_ID, AUTO_METADATA_TOKEN_ID, FEE_EXEMPT_TOKEN_ID, 10])
+				),
+				Error::<Runtime>::TooManyCurrencies
+			);
+		});
+}
+
+#[test]
+fn net_transfer_amount_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::net_transfer_amount(TEST_TOKEN_ID, &ALICE, &BOB, 0), 0);
+			assert_eq!(Tokens::net_transfer_amount(TEST_TOKEN_ID, &ALICE, &BOB, 100), 90);
+			assert_eq!(Tokens::net_transfer_amount(TEST_TOKEN_ID, &ALICE, &ALICE, 100), 100);
+			assert_eq!(Tokens::net_transfer_amount(TEST_TOKEN_ID, &TREASURY, &BOB, 100), 100);
+			assert_eq!(Tokens::net_transfer_amount(TEST_TOKEN_ID, &ALICE, &TREASURY, 100), 100);
+
+			let net = Tokens::net_transfer_amount(TEST_TOKEN_ID, &ALICE, &BOB, 73);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 73));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100 + net);
+		});
+}
+
+#[test]
+fn transfer_should_respect_granularity() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 1000)])
+		.build()
+		.execute_with(|| {
+			MockGranularity::set(100);
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 150),
+				Error::<Runtime>::InvalidGranularity
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 150),
+				Error::<Runtime>::InvalidGranularity
+			);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 200));
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 200));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 600);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 400);
+		});
+}
+
+#[test]
+fn transfer_should_round_down_to_granularity() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 1000)])
+		.build()
+		.execute_with(|| {
+			MockGranularity::set(100);
+			MockGranularityPolicy::set(GranularityPolicy::RoundDown);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 150));
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 250));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 700);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 300);
+			assert!(System::events()
+				.iter()
+				.any(|record| record.event == TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 100))));
+
+			// amounts below the granularity are rounded down to nothing
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 700);
+		});
+}
+
+#[test]
+fn transfer_with_fee_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 70));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 163);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 2);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 195);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 63));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+			let fee_event = TestEvent::tokens(RawEvent::TransferFeeCharged(TEST_TOKEN_ID, ALICE, 5, 2));
+			assert!(System::events().iter().any(|record| record.event == fee_event));
+		});
+}
+
+#[test]
+fn transfer_fee_treasury_share_below_existential_deposit_is_burned() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(20))
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 70));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 163);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 193);
+
+			let fee_event = TestEvent::tokens(RawEvent::TransferFeeCharged(TEST_TOKEN_ID, ALICE, 7, 0));
+			assert!(System::events().iter().any(|record| record.event == fee_event));
+		});
+}
+
+#[test]
+fn transfer_with_fee_should_fail_if_treasury_share_cannot_be_deposited() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (TREASURY, TEST_TOKEN_ID, 100)])
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			MockMaxBalancePerAccount::set(Some(100));
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 70),
+				Error::<Runtime>::BalanceCapExceeded
+			);
+			MockMaxBalancePerAccount::set(None);
+		});
+}
+
+#[test]
+fn gross_transfer_amount_should_work() {
+	ExtBuilder::default()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			for receive_amount in 0..1000 {
+				let gross = Tokens::gross_transfer_amount(receive_amount).unwrap();
+				assert_eq!(gross - Permill::from_percent(10) * gross, receive_amount);
+			}
+		});
+
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(Tokens::gross_transfer_amount(63), Some(63));
+	});
+
+	ExtBuilder::default()
+		.transfer_fee(Permill::one(), Permill::zero())
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::gross_transfer_amount(63), None);
+		});
+}
+
+#[test]
+fn transfer_exact_out_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			let gross = Tokens::gross_transfer_amount(63).unwrap();
+			assert_ok!(Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 63));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100 - gross);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 163);
+
+			let exact_out_event =
+				TestEvent::tokens(RawEvent::TransferredExactOut(TEST_TOKEN_ID, ALICE, BOB, 63, gross));
+			assert!(System::events().iter().any(|record| record.event == exact_out_event));
+
+			assert_noop!(
+				Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 30),
+				Error::<Runtime>::CannotCoverFee,
+			);
+		});
+}
+
+#[test]
+fn max_keepalive_transfer_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 3),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &ALICE, &BOB), 98);
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &ALICE, &TREASURY), 98);
+
+			// one is transferable, but too little to create the recipient
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &CHARLIE, &BOB), 1);
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &CHARLIE, &TREASURY), 0);
+
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), TREASURY, TEST_TOKEN_ID, 98));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 2);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 98);
+		});
+}
+
+#[test]
+fn max_keepalive_transfer_should_account_for_fee() {
+	ExtBuilder::default()
+		.balances(vec![
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 4),
+			(TREASURY, TEST_TOKEN_ID, 4),
+		])
+		.transfer_fee(Permill::from_percent(50), Permill::zero())
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &CHARLIE, &BOB), 2);
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &CHARLIE, &ALICE), 0);
+
+			// an exempt sender is not charged the fee, so its transfer can create the recipient
+			assert_eq!(Tokens::max_keepalive_transfer(TEST_TOKEN_ID, &TREASURY, &ALICE), 2);
+			assert_ok!(Tokens::transfer(Some(TREASURY).into(), ALICE, TEST_TOKEN_ID, 2));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 2);
+		});
+}
+
+#[test]
+fn can_afford_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			assert!(Tokens::can_afford(TEST_TOKEN_ID, &ALICE, 90));
+			assert!(!Tokens::can_afford(TEST_TOKEN_ID, &ALICE, 95));
+			assert!(!Tokens::can_afford(TEST_TOKEN_ID, &CHARLIE, 1));
+			assert!(!Tokens::can_afford(TEST_TOKEN_ID, &ALICE, Balance::max_value()));
+
+			assert_noop!(
+				Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 95),
+				Error::<Runtime>::CannotCoverFee,
+			);
+			assert_ok!(Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 90));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 190);
+		});
+}
+
+#[test]
+fn can_afford_should_include_service_fee() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.service_fee(5)
+		.build()
+		.execute_with(|| {
+			assert!(Tokens::can_afford(TEST_TOKEN_ID, &ALICE, 81));
+			assert!(!Tokens::can_afford(TEST_TOKEN_ID, &ALICE, 90));
+			// the treasury is exempt from fees
+			assert!(Tokens::can_afford(TEST_TOKEN_ID, &TREASURY, 0));
+
+			assert_noop!(
+				Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 90),
+				Error::<Runtime>::CannotCoverFee,
+			);
+			assert_ok!(Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 81));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 5);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 181);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 5);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 191);
+
+			let service_fee_event = TestEvent::tokens(RawEvent::ServiceFeeCharged(TEST_TOKEN_ID, ALICE, 5));
+			assert!(System::events().iter().any(|record| record.event == service_fee_event));
+
+			// `transfer_all` leaves the service fee
+			assert_ok!(Tokens::transfer_all(Some(BOB).into(), CHARLIE, TEST_TOKEN_ID));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 10);
+		});
+}
+
+#[test]
+fn can_afford_should_respect_locks() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+			assert!(Tokens::can_afford(TEST_TOKEN_ID, &ALICE, 45));
+			assert!(!Tokens::can_afford(TEST_TOKEN_ID, &ALICE, 46));
+			assert_ok!(Tokens::transfer_exact_out(TEST_TOKEN_ID, &ALICE, &BOB, 45));
+		});
+}
+
+#[test]
+fn transfer_and_reserve_named_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer_and_reserve_named(
+				Some(ALICE).into(),
+				BOB,
+				TEST_TOKEN_ID,
+				40,
+				ReserveId::Staking
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 60);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 40);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &BOB),
+				40
+			);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 40));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+			let reserved_event = TestEvent::tokens(RawEvent::ReservedNamed(TEST_TOKEN_ID, BOB, ReserveId::Staking, 40));
+			assert!(System::events().iter().any(|record| record.event == reserved_event));
+
+			assert_noop!(
+				Tokens::transfer_and_reserve_named(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 61, ReserveId::Staking),
+				Error::<Runtime>::BalanceTooLow,
+			);
+		});
+}
+
+#[test]
+fn transfer_and_reserve_named_should_charge_fees_and_reject_blocked_recipients() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.service_fee(1)
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer_and_reserve_named(
+				Some(ALICE).into(),
+				BOB,
+				TEST_TOKEN_ID,
+				40,
+				ReserveId::Staking
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 59);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 36);
+			assert_eq!(
+				Tokens::reserved_balance_named(&ReserveId::Staking, TEST_TOKEN_ID, &BOB),
+				36
+			);
+
+			assert_noop!(
+				Tokens::transfer_and_reserve_named(Some(ALICE).into(), BLOCKED, TEST_TOKEN_ID, 10, ReserveId::Staking),
+				Error::<Runtime>::RecipientBlocked,
+			);
+		});
+}
+
+#[test]
+fn deposit_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 100));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 200);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 300);
+
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &ALICE, Balance::max_value()),
+				Error::<Runtime>::TotalIssuanceOverflow,
+			);
+		});
+}
+
+#[test]
+fn deposit_to_reaped_account_calls_on_account_recreate() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockAccountRecreate::set_recreate_deposit(5);
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &BOB, 100));
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+
+			// re-create reaped account
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &BOB, 10));
+			assert_eq!(MockAccountRecreate::recreated_accounts(), 1);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 5);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 105);
+
+			// top up existing accounts
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &BOB, 10));
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 10));
+			assert_eq!(MockAccountRecreate::recreated_accounts(), 1);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 15);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 110);
+
+			// rejected re-creation
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &CHARLIE, 4),
+				DispatchError::Other("cannot pay re-creation deposit"),
+			);
+			// re-creation deposit leaving less than existential deposit
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &CHARLIE, 6),
+				Error::<Runtime>::ExistentialDeposit,
+			);
+		});
+}
+
+#[test]
+fn deposit_enforces_existential_rule() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &CHARLIE, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 0);
+		});
+}
+
+#[test]
+fn withdraw_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+
+			assert_noop!(
+				Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 60),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn burn_if_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::burn_if(TEST_TOKEN_ID, &ALICE, 50, || false));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			assert_ok!(Tokens::burn_if(TEST_TOKEN_ID, &ALICE, 50, || {
+				Tokens::free_balance(TEST_TOKEN_ID, &ALICE) == 100
+			}));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+
+			assert_noop!(
+				Tokens::burn_if(TEST_TOKEN_ID, &ALICE, 60, || true),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn withdraw_enforces_existential_rule() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+		});
+}
+
+#[test]
+fn slash_reserved_first_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 50));
+
+			// free first
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &BOB, 60), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 40);
+
+			// reserved first
+			assert_eq!(Tokens::slash_reserved_first(TEST_TOKEN_ID, &ALICE, 60), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 40);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 80);
+
+			assert_eq!(Tokens::slash_reserved_first(TEST_TOKEN_ID, &ALICE, 50), 10);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 40);
+		});
+}
+
+#[test]
+fn settle_matrix_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::settle_matrix(&[
+				(ALICE, BOB, TEST_TOKEN_ID, 60),
+				(BOB, CHARLIE, TEST_TOKEN_ID, 30),
+				(ALICE, CHARLIE, TEST_TOKEN_ID, 40),
+			]));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 130);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 70);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+		});
+}
+
+#[test]
+fn settle_matrix_is_all_or_nothing() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// amounts received within the settlement may not be spent in it
+			assert_noop!(
+				Tokens::settle_matrix(&[(ALICE, BOB, TEST_TOKEN_ID, 50), (BOB, CHARLIE, TEST_TOKEN_ID, 120)]),
+				Error::<Runtime>::BalanceTooLow
+			);
+			// a later transfer fails after an earlier one is applied
+			assert_noop!(
+				Tokens::settle_matrix(&[(ALICE, BOB, TEST_TOKEN_ID, 60), (ALICE, CHARLIE, TEST_TOKEN_ID, 41)]),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn slash_free_only_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+
+			assert_eq!(Tokens::slash_free_only(TEST_TOKEN_ID, &ALICE, 30), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 170);
+
+			assert_eq!(Tokens::slash_free_only(TEST_TOKEN_ID, &ALICE, 30), 10);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+		});
+}
+
+#[test]
+fn slash_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// slashed_amount < amount
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 50), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+
+			// slashed_amount == amount
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 51), 1);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+		});
+}
+
+#[test]
+fn slash_should_respect_slash_accounting_mode() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 10), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 190);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 0);
+
+			MockSlashAccountingMode::set(SlashAccountingMode::TrackInAccount(POOL));
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 10), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 80);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 10);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 190);
+
+			// slashes below the existential deposit are tracked too
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 30));
+			assert_eq!(Tokens::slash_reserved(TEST_TOKEN_ID, &BOB, 1), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 29);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 11);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 190);
+		});
+}
+
+#[test]
+fn slash_enforces_existential_rule() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 99), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+		});
+}
+
+#[test]
+fn update_balance_with_amount_wider_than_balance() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			let max_amount = Balance::max_value() as i128;
+			assert_noop!(
+				Tokens::update_balance(TEST_TOKEN_ID, &ALICE, max_amount + 1),
+				Error::<Runtime>::AmountMagnitudeOverflow
+			);
+			assert_noop!(
+				Tokens::update_balance(TEST_TOKEN_ID, &ALICE, -max_amount - 1),
+				Error::<Runtime>::AmountMagnitudeOverflow
+			);
+			assert_noop!(
+				Tokens::update_balance(TEST_TOKEN_ID, &ALICE, i128::min_value()),
+				Error::<Runtime>::AmountMagnitudeOverflow
+			);
+
+			// within range, but fails on the balance itself
+			assert_noop!(
+				Tokens::update_balance(TEST_TOKEN_ID, &ALICE, max_amount),
+				Error::<Runtime>::TotalIssuanceOverflow
+			);
+			assert_noop!(
+				Tokens::update_balance(TEST_TOKEN_ID, &ALICE, -max_amount),
+				Error::<Runtime>::BalanceTooLow
+			);
+
+			assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &ALICE, max_amount - 200));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), Balance::max_value() - 100);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), Balance::max_value());
+		});
+}
+
+#[test]
+fn update_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &ALICE, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 150);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+
+			assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &BOB, -50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			assert_noop!(
+				Tokens::update_balance(TEST_TOKEN_ID, &BOB, -60),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn ensure_can_withdraw_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::ensure_can_withdraw(TEST_TOKEN_ID, &ALICE, 101),
+				Error::<Runtime>::BalanceTooLow
+			);
+
+			assert_ok!(Tokens::ensure_can_withdraw(TEST_TOKEN_ID, &ALICE, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+		});
+}
+
+#[test]
+fn no_op_if_amount_is_zero() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(Tokens::ensure_can_withdraw(TEST_TOKEN_ID, &ALICE, 0));
+		assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 0));
+		assert_ok!(Tokens::transfer(Some(ALICE).into(), ALICE, TEST_TOKEN_ID, 0));
+		assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 0));
+		assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 0));
+		assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 0), 0);
+		assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 1), 1);
+		assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &ALICE, 0));
+	});
+}
+
+#[test]
+fn teleport_out_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::teleport_out(Origin::ROOT, TEST_TOKEN_ID, ALICE, 40, PARA_CHAIN));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 60);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 160);
+
+			let teleported_event = TestEvent::tokens(RawEvent::TeleportSent(TEST_TOKEN_ID, ALICE, 40, PARA_CHAIN));
+			assert!(System::events().iter().any(|record| record.event == teleported_event));
+
+			assert_noop!(
+				Tokens::teleport_out(Origin::ROOT, TEST_TOKEN_ID, ALICE, 61, PARA_CHAIN),
+				Error::<Runtime>::BalanceTooLow,
+			);
+		});
+}
+
+#[test]
+fn teleport_in_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::teleport_in(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				CHARLIE,
+				40,
+				PARA_CHAIN
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 40);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 240);
+
+			let teleported_event =
+				TestEvent::tokens(RawEvent::TeleportReceived(TEST_TOKEN_ID, CHARLIE, 40, PARA_CHAIN));
+			assert!(System::events().iter().any(|record| record.event == teleported_event));
+		});
+}
+
+#[test]
+fn update_balance_allow_death_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			// the default update drops a deposit below the existential deposit to an empty account
+			assert_ok!(Tokens::update_balance(TEST_TOKEN_ID, &CHARLIE, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			assert_noop!(
+				Tokens::update_balance_allow_death(Some(ALICE).into(), TEST_TOKEN_ID, CHARLIE, 1),
+				BadOrigin
+			);
+			assert_ok!(Tokens::update_balance_allow_death(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				CHARLIE,
+				1
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 1);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 201);
+			let updated_event = TestEvent::tokens(RawEvent::BalanceUpdated(TEST_TOKEN_ID, CHARLIE, 1));
+			assert!(System::events().iter().any(|record| record.event == updated_event));
+
+			assert_ok!(Tokens::update_balance_allow_death(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				ALICE,
+				-40
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 60);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 161);
+		});
+}
+
+#[test]
+fn reserve_delta_to_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert_eq!(Tokens::reserve_delta_to(TEST_TOKEN_ID, &ALICE, 50), 20);
+			assert_eq!(Tokens::reserve_delta_to(TEST_TOKEN_ID, &ALICE, 30), 0);
+			assert_eq!(Tokens::reserve_delta_to(TEST_TOKEN_ID, &ALICE, 10), -20);
+		});
+}
+
+#[test]
+fn set_reserved_to_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::set_reserved_to(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, 50),
+				BadOrigin
+			);
+
+			// reserve more to reach the target
+			assert_ok!(Tokens::set_reserved_to(Origin::ROOT, TEST_TOKEN_ID, ALICE, 50));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			let set_event = TestEvent::tokens(RawEvent::ReservedBalanceSet(TEST_TOKEN_ID, ALICE, 0, 50));
+			assert!(System::events().iter().any(|record| record.event == set_event));
+
+			// release to reach the target
+			assert_ok!(Tokens::set_reserved_to(Origin::ROOT, TEST_TOKEN_ID, ALICE, 20));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 80);
+
+			assert_noop!(
+				Tokens::set_reserved_to(Origin::ROOT, TEST_TOKEN_ID, ALICE, 101),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn set_target_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::set_target_balance(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, 150),
+				BadOrigin
+			);
+			assert_noop!(
+				Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, ALICE, 1),
+				Error::<Runtime>::ExistentialDeposit
+			);
+
+			assert_ok!(Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, ALICE, 150));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 150);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+			let top_up_event = TestEvent::tokens(RawEvent::TargetBalanceSet(TEST_TOKEN_ID, ALICE, 100, 150));
+			assert!(System::events().iter().any(|record| record.event == top_up_event));
+
+			assert_ok!(Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, BOB, 30));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 30);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 180);
+			let trim_event = TestEvent::tokens(RawEvent::TargetBalanceSet(TEST_TOKEN_ID, BOB, 100, 30));
+			assert!(System::events().iter().any(|record| record.event == trim_event));
+
+			// reaching the target is a no-op
+			let events = System::events().len();
+			assert_ok!(Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, BOB, 30));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 30);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 180);
+			assert_eq!(System::events().len(), events);
+
+			// withdrawing respects locks
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 20);
+			assert_noop!(
+				Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, BOB, 10),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+
+			assert_noop!(
+				Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, ALICE, Balance::max_value()),
+				Error::<Runtime>::TotalIssuanceOverflow
+			);
+
+			// a bounced deposit doesn't reach the target
+			MockDepositBounce::set(DepositBounce::BounceTo(TREASURY));
+			let events = System::events().len();
+			assert_ok!(Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, BLOCKED, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BLOCKED), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 50);
+			let bounced_event = TestEvent::tokens(RawEvent::DepositBounced(TEST_TOKEN_ID, BLOCKED, TREASURY, 50));
+			assert_eq!(System::events().len(), events + 1);
+			assert_eq!(
+				System::events().last().map(|record| &record.event),
+				Some(&bounced_event)
+			);
+		});
+}
+
+#[test]
+fn teleport_requires_teleport_origin() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::teleport_out(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, 40, PARA_CHAIN),
+				BadOrigin,
+			);
+			assert_noop!(
+				Tokens::teleport_in(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, 40, PARA_CHAIN),
+				BadOrigin,
+			);
+		});
+}
+
+#[test]
+fn topup_needed_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, TEST_TOKEN_ID, 1)])
+		.build()
+		.execute_with(|| {
+			// empty account
+			assert_eq!(Tokens::topup_needed(TEST_TOKEN_ID, &CHARLIE), 2);
+			// below existential deposit
+			assert_eq!(Tokens::topup_needed(TEST_TOKEN_ID, &BOB), 1);
+			// above existential deposit
+			assert_eq!(Tokens::topup_needed(TEST_TOKEN_ID, &ALICE), 0);
+		});
+}
+
+#[test]
+fn account_status_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, TEST_TOKEN_ID, 1)])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::account_status(TEST_TOKEN_ID, &CHARLIE), AccountStatus::Dead);
+			assert_eq!(Tokens::account_status(TEST_TOKEN_ID, &BOB), AccountStatus::Dust);
+			assert_eq!(Tokens::account_status(TEST_TOKEN_ID, &ALICE), AccountStatus::Alive);
+
+			// reserved balance counts towards the total
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 99));
+			assert_eq!(Tokens::account_status(TEST_TOKEN_ID, &ALICE), AccountStatus::Alive);
+		});
+}
+
+#[test]
+fn currencies_of_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 50), (BOB, 3, 100)])
+		.build()
+		.execute_with(|| {
+			let mut currencies = Tokens::currencies_of(&ALICE);
+			currencies.sort_by_key(|(currency_id, _)| *currency_id);
+			assert_eq!(
+				currencies,
+				vec![
+					(
+						TEST_TOKEN_ID,
+						AccountData {
+							free: 100,
+							reserved: 0,
+							frozen: 0,
+						}
+					),
+					(
+						2,
+						AccountData {
+							free: 50,
+							reserved: 0,
+							frozen: 0,
+						}
+					),
+				]
+			);
+			assert_eq!(Tokens::currencies_of(&CHARLIE), vec![]);
+		});
+}
+
+#[test]
+fn set_currency_metadata_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		let metadata = TokenMetadata {
+			name: b"Test Token".to_vec(),
+			symbol: b"TT".to_vec(),
+			decimals: 12,
+			description: b"A token for testing".to_vec(),
+		};
+		assert_eq!(Tokens::get_currency_metadata(TEST_TOKEN_ID), None);
+		assert_eq!(Tokens::currency_decimals(TEST_TOKEN_ID), None);
+
+		assert_noop!(
+			Tokens::set_currency_metadata(Some(ALICE).into(), TEST_TOKEN_ID, metadata.clone()),
+			BadOrigin,
+		);
+		assert_ok!(Tokens::set_currency_metadata(
+			Origin::ROOT,
+			TEST_TOKEN_ID,
+			metadata.clone()
+		));
+		assert_eq!(Tokens::get_currency_metadata(TEST_TOKEN_ID), Some(metadata.clone()));
+		assert_eq!(Tokens::currency_decimals(TEST_TOKEN_ID), Some(12));
+		assert_eq!(Tokens::token_symbol(TEST_TOKEN_ID), Some(b"TT".to_vec()));
+
+		let metadata_set_event = TestEvent::tokens(RawEvent::MetadataSet(TEST_TOKEN_ID, metadata));
+		assert!(System::events().iter().any(|record| record.event == metadata_set_event));
+	});
+}
+
+#[test]
+fn first_mint_should_populate_default_metadata() {
+	ExtBuilder::default().build().execute_with(|| {
+		let auto_metadata = TokenMetadata {
+			name: b"Auto Token".to_vec(),
+			symbol: b"AUTO".to_vec(),
+			decimals: 10,
+			description: vec![],
+		};
+		assert_eq!(Tokens::get_currency_metadata(AUTO_METADATA_TOKEN_ID), None);
+
+		assert_ok!(Tokens::deposit(AUTO_METADATA_TOKEN_ID, &ALICE, 100));
+		assert_eq!(
+			Tokens::get_currency_metadata(AUTO_METADATA_TOKEN_ID),
+			Some(auto_metadata.clone())
+		);
+		let metadata_set_event = TestEvent::tokens(RawEvent::MetadataSet(AUTO_METADATA_TOKEN_ID, auto_metadata));
+		assert!(System::events().iter().any(|record| record.event == metadata_set_event));
+
+		// token types without derivable metadata are not affected
+		assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 100));
+		assert_eq!(Tokens::get_currency_metadata(TEST_TOKEN_ID), None);
+
+		let metadata = TokenMetadata {
+			name: b"Test Token".to_vec(),
+			symbol: b"TT".to_vec(),
+			decimals: 12,
+			description: b"A token for testing".to_vec(),
+		};
+		assert_ok!(Tokens::set_currency_metadata(
+			Origin::ROOT,
+			AUTO_METADATA_TOKEN_ID,
+			metadata.clone()
+		));
+		assert_eq!(
+			Tokens::get_currency_metadata(AUTO_METADATA_TOKEN_ID),
+			Some(metadata.clone())
+		);
+
+		// later mints never overwrite existing metadata
+		assert_ok!(Tokens::withdraw(AUTO_METADATA_TOKEN_ID, &ALICE, 100));
+		assert_ok!(Tokens::deposit(AUTO_METADATA_TOKEN_ID, &BOB, 100));
+		assert_eq!(Tokens::get_currency_metadata(AUTO_METADATA_TOKEN_ID), Some(metadata));
+	});
+}
+
+#[test]
+fn set_currency_metadata_fails_if_too_long() {
+	ExtBuilder::default().build().execute_with(|| {
+		let metadata = TokenMetadata {
+			name: vec![b'a'; 33],
+			symbol: b"TT".to_vec(),
+			..Default::default()
+		};
+		assert_noop!(
+			Tokens::set_currency_metadata(Origin::ROOT, TEST_TOKEN_ID, metadata),
+			Error::<Runtime>::MetadataTooLong,
+		);
+	});
+}
+
+#[test]
+fn set_currency_metadata_should_validate_symbol_and_decimals() {
+	ExtBuilder::default().build().execute_with(|| {
+		let metadata = TokenMetadata {
+			symbol: b"TT".to_vec(),
+			decimals: 18,
+			..Default::default()
+		};
+		assert_ok!(Tokens::set_currency_metadata(
+			Origin::ROOT,
+			TEST_TOKEN_ID,
+			metadata.clone()
+		));
+
+		assert_noop!(
+			Tokens::set_currency_metadata(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				TokenMetadata {
+					decimals: 19,
+					..metadata.clone()
+				}
+			),
+			Error::<Runtime>::InvalidDecimals,
+		);
+		assert_noop!(
+			Tokens::set_currency_metadata(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				TokenMetadata {
+					symbol: vec![],
+					..metadata
+				}
+			),
+			Error::<Runtime>::EmptySymbol,
+		);
+	});
+}
+
+#[test]
+fn rebalance_total_issuance_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 50),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &CHARLIE, 20));
+			TotalIssuance::<Runtime>::insert(TEST_TOKEN_ID, 1000);
+
+			assert_noop!(
+				Tokens::rebalance_total_issuance(Some(ALICE).into(), TEST_TOKEN_ID, 10),
+				BadOrigin,
+			);
+
+			assert_ok!(Tokens::rebalance_total_issuance(Origin::ROOT, TEST_TOKEN_ID, 2));
+			assert!(Tokens::rebalance_cursor(TEST_TOKEN_ID).is_some());
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 1000);
+
+			assert_ok!(Tokens::rebalance_total_issuance(Origin::ROOT, TEST_TOKEN_ID, 2));
+			assert_eq!(Tokens::rebalance_cursor(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+
+			let rebalanced_event = TestEvent::tokens(RawEvent::TotalIssuanceRebalanced(TEST_TOKEN_ID, 1000, 250));
+			assert!(System::events().iter().any(|record| record.event == rebalanced_event));
+
+			// nothing changes if already balanced
+			assert_ok!(Tokens::rebalance_total_issuance(Origin::ROOT, TEST_TOKEN_ID, 10));
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+		});
+}
+
+#[test]
+fn rebalance_total_issuance_should_resume_after_iterated_account_removed() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 50),
+		])
+		.build()
+		.execute_with(|| {
+			TotalIssuance::<Runtime>::insert(TEST_TOKEN_ID, 1000);
+
+			assert_ok!(Tokens::rebalance_total_issuance(Origin::ROOT, TEST_TOKEN_ID, 1));
+			let (iterated, _) = <Accounts<Runtime> as IterableStorageDoubleMap<_, _, _>>::iter(TEST_TOKEN_ID)
+				.next()
+				.unwrap();
+			<Accounts<Runtime>>::remove(TEST_TOKEN_ID, iterated);
+
+			// the remaining accounts are neither skipped nor counted twice
+			assert_ok!(Tokens::rebalance_total_issuance(Origin::ROOT, TEST_TOKEN_ID, 10));
+			assert_eq!(Tokens::rebalance_cursor(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+		});
+}
+
+#[test]
+fn freeze_currency_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(Tokens::freeze_currency(Some(ALICE).into(), TEST_TOKEN_ID), BadOrigin);
+			assert_ok!(Tokens::freeze_currency(Origin::ROOT, TEST_TOKEN_ID));
+			assert!(Tokens::frozen_currencies(TEST_TOKEN_ID));
+
+			let frozen_event = TestEvent::tokens(RawEvent::CurrencyFrozen(TEST_TOKEN_ID));
+			assert!(System::events().iter().any(|record| record.event == frozen_event));
+
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10),
+				Error::<Runtime>::CurrencyFrozen,
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 10),
+				Error::<Runtime>::CurrencyFrozen,
+			);
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::CurrencyFrozen,
+			);
+			assert_noop!(
+				Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::CurrencyFrozen,
+			);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::CurrencyFrozen,
+			);
+
+			assert_noop!(Tokens::unfreeze_currency(Some(ALICE).into(), TEST_TOKEN_ID), BadOrigin);
+			assert_ok!(Tokens::unfreeze_currency(Origin::ROOT, TEST_TOKEN_ID));
+			assert!(!Tokens::frozen_currencies(TEST_TOKEN_ID));
+
+			let unfrozen_event = TestEvent::tokens(RawEvent::CurrencyUnfrozen(TEST_TOKEN_ID));
+			assert!(System::events().iter().any(|record| record.event == unfrozen_event));
+
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+		});
+}
+
+#[test]
+fn issuance_share_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, TEST_TOKEN_ID, 300)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::issuance_share(TEST_TOKEN_ID, &ALICE), Permill::from_percent(25));
+			assert_eq!(Tokens::issuance_share(TEST_TOKEN_ID, &BOB), Permill::from_percent(75));
+			assert_eq!(Tokens::issuance_share(TEST_TOKEN_ID, &CHARLIE), Permill::zero());
+
+			// zero issuance
+			assert_eq!(Tokens::total_issuance(2), 0);
+			assert_eq!(Tokens::issuance_share(2, &ALICE), Permill::zero());
+		});
+}
+
+#[test]
+fn required_balance_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_eq!(Tokens::required_balance(TEST_TOKEN_ID, &[]), 0);
+		assert_eq!(
+			Tokens::required_balance(TEST_TOKEN_ID, &[BalanceOp::Withdraw(30), BalanceOp::Reserve(20)]),
+			50
+		);
+		assert_eq!(
+			Tokens::required_balance(
+				TEST_TOKEN_ID,
+				&[
+					BalanceOp::Withdraw(30),
+					BalanceOp::KeepAlive,
+					BalanceOp::Reserve(20),
+					BalanceOp::KeepAlive,
+				]
+			),
+			52
+		);
+		assert_eq!(
+			Tokens::required_balance(
+				TEST_TOKEN_ID,
+				&[BalanceOp::Withdraw(Balance::max_value()), BalanceOp::KeepAlive]
+			),
+			Balance::max_value()
+		);
+	});
+}
+
+#[test]
+fn circulating_excluding_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 50),
+			(CHARLIE, TEST_TOKEN_ID, 30),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 20));
+			assert_eq!(Tokens::circulating_excluding(TEST_TOKEN_ID, &[]), 180);
+			assert_eq!(Tokens::circulating_excluding(TEST_TOKEN_ID, &[BOB, CHARLIE]), 100);
+			assert_eq!(Tokens::circulating_excluding(TEST_TOKEN_ID, &[ALICE, BOB, CHARLIE]), 0);
+		});
+}
+
+#[test]
+fn transfer_reserved_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 50));
+
+			assert_eq!(Tokens::transfer_reserved(TEST_TOKEN_ID, &ALICE, &BOB, 30), Ok(0));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 30);
+
+			let repatriated_event = TestEvent::tokens(RawEvent::ReserveRepatriated(
+				TEST_TOKEN_ID,
+				ALICE,
+				BOB,
+				30,
+				BalanceStatus::Reserved,
+			));
+			assert!(System::events().iter().any(|record| record.event == repatriated_event));
+
+			// partial move
+			assert_eq!(Tokens::transfer_reserved(TEST_TOKEN_ID, &ALICE, &BOB, 30), Ok(10));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 50);
+
+			// free balances are not changed
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 100);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+		});
+}
+
+#[test]
+fn account_currencies_should_index_holders() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 50), (BOB, 3, 10)])
+		.build()
+		.execute_with(|| {
+			assert!(Tokens::account_currencies(&ALICE, TEST_TOKEN_ID));
+			assert!(Tokens::account_currencies(&ALICE, 2));
+			assert!(!Tokens::account_currencies(&ALICE, 3));
+
+			assert_ok!(Tokens::reserve(2, &ALICE, 20));
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, 2, 30));
+			assert!(Tokens::account_currencies(&ALICE, 2));
+			assert!(Tokens::account_currencies(&BOB, 2));
+
+			assert_eq!(Tokens::unreserve(2, &ALICE, 20), 0);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, 2, 20));
+			assert!(!Tokens::account_currencies(&ALICE, 2));
+			assert_eq!(
+				Tokens::currencies_of(&ALICE),
+				vec![(
+					TEST_TOKEN_ID,
+					AccountData {
+						free: 100,
+						reserved: 0,
+						frozen: 0,
+					}
+				)]
+			);
+		});
+}
+
+#[test]
+fn migrate_to_v2_should_index_holders() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 50), (BOB, 3, 10)])
+		.build()
+		.execute_with(|| {
+			assert_eq!(<StorageVersion>::get(), Releases::V2_0_0);
+			for (who, currency_id) in vec![(ALICE, TEST_TOKEN_ID), (ALICE, 2), (BOB, 3)] {
+				<AccountCurrencies<Runtime>>::remove(&who, currency_id);
+				<HolderCount<Runtime>>::remove(currency_id);
+				<CreatedCurrencies<Runtime>>::remove(currency_id);
+				<GenesisIssuance<Runtime>>::remove(currency_id);
+			}
+			<CurrencyCount>::kill();
+			<LiveCurrencyCount>::kill();
+			<StorageVersion>::put(Releases::V1_0_0);
+			assert_eq!(Tokens::currencies_of(&ALICE), vec![]);
+
+			Tokens::on_runtime_upgrade();
+			assert_eq!(<StorageVersion>::get(), Releases::V2_0_0);
+			assert!(Tokens::pending_backfill().is_some());
+
+			// an account emptied before it's backfilled is not counted out, and its recipient is counted once
+			assert_ok!(Tokens::transfer_all(Some(BOB).into(), CHARLIE, 3));
+			assert_eq!(Tokens::holder_count(3), 1);
+
+			// at most `MigrationBatchSize` token types and accounts are backfilled per block
+			Tokens::on_initialize(1);
+			assert!(Tokens::pending_backfill().is_some());
+			for block in 2..=5 {
+				Tokens::on_initialize(block);
+			}
+			assert_eq!(Tokens::pending_backfill(), None);
+
+			assert!(Tokens::account_currencies(&ALICE, TEST_TOKEN_ID));
+			assert!(Tokens::account_currencies(&ALICE, 2));
+			assert!(Tokens::account_currencies(&CHARLIE, 3));
+			assert!(!Tokens::account_currencies(&BOB, 3));
+			assert!(!Tokens::account_currencies(&BOB, 2));
+			assert_eq!(Tokens::holder_count(TEST_TOKEN_ID), 1);
+			assert_eq!(Tokens::holder_count(2), 1);
+			assert_eq!(Tokens::holder_count(3), 1);
+			assert_eq!(Tokens::currency_count(), 3);
+			assert_eq!(Tokens::live_currency_count(), 3);
+			assert_eq!(Tokens::genesis_issuance(TEST_TOKEN_ID), 100);
+			assert_eq!(Tokens::genesis_issuance(3), 10);
+		});
+}
+
+#[test]
+fn reserve_multi_account_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(BOB, 2, 50),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(
+				Tokens::reserve_multi_account(
+					vec![(TEST_TOKEN_ID, ALICE, 30), (2, BOB, 20), (TEST_TOKEN_ID, ALICE, 10)],
+					ReserveMode::AllOrNothing
+				),
+				Ok(vec![Ok(()), Ok(()), Ok(())])
+			);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 40);
+			assert_eq!(Tokens::reserved_balance(2, &BOB), 20);
+		});
+}
+
+#[test]
+fn reserve_multi_account_all_or_nothing_should_not_reserve_any_on_failure() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(BOB, 2, 50),
+		])
+		.build()
+		.execute_with(|| {
+			// each entry alone could be reserved, but not in total
+			assert_noop!(
+				Tokens::reserve_multi_account(
+					vec![(TEST_TOKEN_ID, ALICE, 30), (2, BOB, 40), (2, BOB, 20)],
+					ReserveMode::AllOrNothing
+				),
+				Error::<Runtime>::BalanceTooLow,
+			);
+		});
+}
+
+#[test]
+fn reserve_multi_account_allow_partial_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(BOB, 2, 50),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(
+				Tokens::reserve_multi_account(
+					vec![(TEST_TOKEN_ID, ALICE, 30), (2, BOB, 60), (2, BOB, 20)],
+					ReserveMode::AllowPartial
+				),
+				Ok(vec![Ok(()), Err(Error::<Runtime>::BalanceTooLow.into()), Ok(())])
+			);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::reserved_balance(2, &BOB), 20);
+		});
+}
+
+#[test]
+fn would_be_sufficient_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert!(Tokens::would_be_sufficient(TEST_TOKEN_ID, &ALICE, 50, 150));
+			assert!(!Tokens::would_be_sufficient(TEST_TOKEN_ID, &ALICE, 49, 150));
+			assert!(Tokens::would_be_sufficient(TEST_TOKEN_ID, &CHARLIE, 10, 10));
+			assert!(Tokens::would_be_sufficient(
+				TEST_TOKEN_ID,
+				&ALICE,
+				Balance::max_value(),
+				Balance::max_value()
+			));
+		});
+}
+
+#[test]
+fn event_verbosity_should_work() {
+	let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 45));
+	let fee_event = TestEvent::tokens(RawEvent::TransferFeeCharged(TEST_TOKEN_ID, ALICE, 5, 0));
+	let frozen_event = TestEvent::tokens(RawEvent::CurrencyFrozen(TEST_TOKEN_ID));
+
+	for (verbosity, expected) in vec![
+		(
+			EventVerbosity::Full,
+			vec![transferred_event.clone(), fee_event.clone(), frozen_event.clone()],
+		),
+		(EventVerbosity::TransfersOnly, vec![transferred_event.clone()]),
+		(EventVerbosity::Silent, vec![]),
+	] {
+		ExtBuilder::default()
+			.one_hundred_for_alice_n_bob()
+			.transfer_fee(Permill::from_percent(10), Permill::zero())
+			.event_verbosity(verbosity)
+			.build()
+			.execute_with(|| {
+				assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
+				assert_ok!(Tokens::freeze_currency(Origin::ROOT, TEST_TOKEN_ID));
+
+				let events = System::events()
+					.into_iter()
+					.map(|record| record.event)
+					.filter(|event| match event {
+						TestEvent::tokens(_) => true,
+						_ => false,
+					})
+					.collect::<Vec<_>>();
+				assert_eq!(events, expected);
+			});
+	}
+}
+
+#[test]
+fn event_model_should_work() {
+	let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 50));
+	let debited_event = TestEvent::tokens(RawEvent::Debited(TEST_TOKEN_ID, ALICE, 50));
+	let credited_event = TestEvent::tokens(RawEvent::Credited(TEST_TOKEN_ID, BOB, 50));
+
+	for (model, expected) in vec![
+		(EventModel::Transfer, vec![transferred_event.clone()]),
+		(
+			EventModel::DoubleEntry,
+			vec![debited_event.clone(), credited_event.clone()],
+		),
+	] {
+		ExtBuilder::default()
+			.one_hundred_for_alice_n_bob()
+			.build()
+			.execute_with(|| {
+				MockEventModel::set(model);
+				assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
+
+				let events = System::events()
+					.into_iter()
+					.map(|record| record.event)
+					.filter(|event| match event {
+						TestEvent::tokens(_) => true,
+						_ => false,
+					})
+					.collect::<Vec<_>>();
+				assert_eq!(events, expected);
+			});
+	}
+}
+
+#[test]
+fn total_issuance_cap_should_be_enforced_on_deposit() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::enforce_cap_on_existing(Origin::ROOT, TEST_TOKEN_ID, 250));
+			assert_eq!(Tokens::total_issuance_cap(TEST_TOKEN_ID), Some(250));
+
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 50));
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &ALICE, 1),
+				Error::<Runtime>::TotalIssuanceCapExceeded
+			);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+		});
+}
+
+#[test]
+fn enforce_cap_on_existing_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::enforce_cap_on_existing(Some(ALICE).into(), TEST_TOKEN_ID, 150),
+				BadOrigin
+			);
+			assert_noop!(
+				Tokens::enforce_cap_on_existing(Origin::ROOT, TEST_TOKEN_ID, 150),
+				Error::<Runtime>::NoCurrencyAdmin
+			);
+
+			assert_ok!(Tokens::set_currency_admin(Origin::ROOT, TEST_TOKEN_ID, Some(BOB)));
+			assert_eq!(Tokens::currency_admin(TEST_TOKEN_ID), Some(BOB));
+
+			assert_ok!(Tokens::enforce_cap_on_existing(Origin::ROOT, TEST_TOKEN_ID, 150));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 150);
+			assert_eq!(Tokens::total_issuance_cap(TEST_TOKEN_ID), Some(150));
+
+			let enforced_event = TestEvent::tokens(RawEvent::IssuanceCapEnforced(TEST_TOKEN_ID, 50));
+			assert!(System::events().iter().any(|record| record.event == enforced_event));
+
+			// the excess is only slashed as far as the admin balance allows
+			assert_ok!(Tokens::enforce_cap_on_existing(Origin::ROOT, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+
+			assert_ok!(Tokens::set_currency_admin(Origin::ROOT, TEST_TOKEN_ID, None));
+			assert_eq!(Tokens::currency_admin(TEST_TOKEN_ID), None);
+		});
+}
+
+#[test]
+fn send_allowlist_management_should_work() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			Tokens::set_send_restricted(Some(ALICE).into(), TEST_TOKEN_ID, true),
+			Error::<Runtime>::NotCurrencyAdmin
+		);
+		assert_noop!(
+			Tokens::set_send_allowed(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, true),
+			Error::<Runtime>::NotCurrencyAdmin
+		);
+
+		assert_ok!(Tokens::set_send_restricted(Origin::ROOT, TEST_TOKEN_ID, true));
+		assert!(Tokens::send_restricted(TEST_TOKEN_ID));
+		let restricted_event = TestEvent::tokens(RawEvent::SendRestrictionSet(TEST_TOKEN_ID, true));
+		assert!(System::events().iter().any(|record| record.event == restricted_event));
+
+		assert_ok!(Tokens::set_currency_admin(Origin::ROOT, TEST_TOKEN_ID, Some(CHARLIE)));
+		assert_ok!(Tokens::set_send_allowed(
+			Some(CHARLIE).into(),
+			TEST_TOKEN_ID,
+			ALICE,
+			true
+		));
+		assert!(Tokens::send_allowed(TEST_TOKEN_ID, ALICE));
+		let allowed_event = TestEvent::tokens(RawEvent::SendAllowlistUpdated(TEST_TOKEN_ID, ALICE, true));
+		assert!(System::events().iter().any(|record| record.event == allowed_event));
+
+		assert_ok!(Tokens::set_send_allowed(Origin::ROOT, TEST_TOKEN_ID, ALICE, false));
+		assert!(!Tokens::send_allowed(TEST_TOKEN_ID, ALICE));
+		assert_ok!(Tokens::set_send_restricted(Some(CHARLIE).into(), TEST_TOKEN_ID, false));
+		assert!(!Tokens::send_restricted(TEST_TOKEN_ID));
+	});
+}
+
+#[test]
+fn transfer_should_respect_send_allowlist() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::set_currency_admin(Origin::ROOT, TEST_TOKEN_ID, Some(CHARLIE)));
+			assert_ok!(Tokens::set_send_restricted(Origin::ROOT, TEST_TOKEN_ID, true));
+			assert_ok!(Tokens::set_send_allowed(Origin::ROOT, TEST_TOKEN_ID, ALICE, true));
+
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 110);
+
+			assert_noop!(
+				Tokens::transfer(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 10),
+				Error::<Runtime>::SenderNotAllowed
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &BOB, &ALICE, 10),
+				Error::<Runtime>::SenderNotAllowed
+			);
+			assert_noop!(
+				Tokens::transfer_batch_same_currency(TEST_TOKEN_ID, &BOB, vec![(ALICE, 10)]),
+				Error::<Runtime>::SenderNotAllowed
+			);
+
+			// the currency admin bypasses the allowlist
+			assert_ok!(Tokens::transfer(Some(CHARLIE).into(), BOB, TEST_TOKEN_ID, 10));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 120);
+
+			assert_ok!(Tokens::set_send_restricted(Origin::ROOT, TEST_TOKEN_ID, false));
+			assert_ok!(Tokens::transfer(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 10));
+		});
+}
+
+#[test]
+fn accrue_interest_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 105),
+			(BOB, TEST_TOKEN_ID, 105),
+			(TREASURY, TEST_TOKEN_ID, 10),
+		])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::accrue_interest(
+					Some(ALICE).into(),
+					TEST_TOKEN_ID,
+					Permill::from_percent(10),
+					vec![ALICE]
+				),
+				BadOrigin
+			);
+			assert_noop!(
+				Tokens::accrue_interest(
+					Origin::ROOT,
+					TEST_TOKEN_ID,
+					Permill::from_percent(10),
+					vec![ALICE, BOB, CHARLIE, TREASURY]
+				),
+				Error::<Runtime>::TooManyAccounts
+			);
+
+			// 10.5 each is rounded down, and the aggregate dust of 1 is credited to treasury
+			assert_ok!(Tokens::accrue_interest(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				Permill::from_percent(10),
+				vec![ALICE, BOB, ALICE]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 115);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 115);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 11);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 241);
+
+			let accrued_event = TestEvent::tokens(RawEvent::InterestAccrued(
+				TEST_TOKEN_ID,
+				Permill::from_percent(10),
+				21,
+				1,
+			));
+			assert!(System::events().iter().any(|record| record.event == accrued_event));
+		});
+}
+
+#[test]
+fn accrue_interest_should_not_count_dust_not_minted() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 105), (BOB, TEST_TOKEN_ID, 105)])
+		.build()
+		.execute_with(|| {
+			// the aggregate dust of 1 is not enough to create the treasury account
+			assert_ok!(Tokens::accrue_interest(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				Permill::from_percent(10),
+				vec![ALICE, BOB]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 230);
+
+			let accrued_event = TestEvent::tokens(RawEvent::InterestAccrued(
+				TEST_TOKEN_ID,
+				Permill::from_percent(10),
+				20,
+				0,
+			));
+			assert!(System::events().iter().any(|record| record.event == accrued_event));
+		});
+}
+
+#[test]
+fn interest_schedule_should_accrue_periodically() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, TEST_TOKEN_ID, 200)])
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::set_interest_schedule(Some(ALICE).into(), TEST_TOKEN_ID, Some((Permill::from_percent(10), 5))),
+				BadOrigin
+			);
+			assert_noop!(
+				Tokens::set_interest_schedule(Origin::ROOT, TEST_TOKEN_ID, Some((Permill::from_percent(10), 0))),
+				Error::<Runtime>::InvalidInterestSchedule
+			);
+			assert_ok!(Tokens::set_interest_schedule(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				Some((Permill::from_percent(10), 5))
+			));
+
+			Tokens::on_initialize(4);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+
+			Tokens::on_initialize(5);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 110);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 220);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 330);
+			assert_eq!(Tokens::interest_accrual(TEST_TOKEN_ID), None);
+			let completed_event = TestEvent::tokens(RawEvent::InterestAccrualCompleted(TEST_TOKEN_ID, 2));
+			assert!(System::events().iter().any(|record| record.event == completed_event));
+
+			Tokens::on_initialize(10);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 121);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 242);
+
+			assert_ok!(Tokens::set_interest_schedule(Origin::ROOT, TEST_TOKEN_ID, None));
+			assert_eq!(Tokens::interest_schedule(TEST_TOKEN_ID), None);
+			Tokens::on_initialize(15);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 121);
+		});
+}
+
+#[test]
+fn interest_accrual_should_resume_across_blocks_without_blocking_transfers() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::set_interest_schedule(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				Some((Permill::from_percent(10), 5))
+			));
+
+			Tokens::on_initialize(5);
+			assert_eq!(
+				Tokens::interest_accrual(TEST_TOKEN_ID),
+				Some((Permill::from_percent(10), 2))
+			);
+			assert!(Tokens::interest_accrual_cursor(TEST_TOKEN_ID).is_some());
+			assert_eq!(Tokens::pending_migration(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 320);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+
+			Tokens::on_initialize(6);
+			assert_eq!(Tokens::interest_accrual(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::interest_accrual_cursor(TEST_TOKEN_ID), None);
+			// the remaining account accrues interest on its balance after the transfer
+			assert!((329..=331).contains(&Tokens::total_issuance(TEST_TOKEN_ID)));
+			let completed_event = TestEvent::tokens(RawEvent::InterestAccrualCompleted(TEST_TOKEN_ID, 3));
+			assert!(System::events().iter().any(|record| record.event == completed_event));
+		});
+}
+
+#[test]
+fn protected_account_should_not_be_reaped() {
+	ExtBuilder::default()
+		.balances(vec![(POOL, TEST_TOKEN_ID, 100), (ALICE, TEST_TOKEN_ID, 100)])
+		.build()
+		.execute_with(|| {
+			MockAccountRecreate::set_recreate_deposit(1);
+
+			assert_ok!(Tokens::transfer(Some(POOL).into(), ALICE, TEST_TOKEN_ID, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 1);
+			assert_ok!(Tokens::transfer(Some(POOL).into(), ALICE, TEST_TOKEN_ID, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			// receives funds below the existential deposit without re-creation
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &POOL, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 1);
+			assert_eq!(MockAccountRecreate::recreated_accounts(), 0);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), POOL, TEST_TOKEN_ID, 1));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 2);
+
+			// other accounts are still reaped
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 1),
+				Error::<Runtime>::ExistentialDeposit
+			);
+		});
+}
+
+#[test]
+fn has_sufficient_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert!(Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 70, 0));
+			assert!(Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 40, 30));
+			assert!(!Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 40, 31));
+			assert!(!Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 70, 70));
+			assert!(!Tokens::has_sufficient_balance(
+				TEST_TOKEN_ID,
+				&ALICE,
+				Balance::max_value(),
+				1
+			));
+			assert!(Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 0, 0));
+			assert!(!Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 71, 0));
+			assert!(!Tokens::has_sufficient_balance(TEST_TOKEN_ID, &ALICE, 0, 71));
+			assert!(!Tokens::has_sufficient_balance(TEST_TOKEN_ID, &CHARLIE, 1, 0));
+
+			assert!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 70));
+			assert!(!Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 71));
+		});
+}
+
+#[test]
+fn liquid_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::liquid_balance(TEST_TOKEN_ID, &ALICE), 100);
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::liquid_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &ALICE), 100);
+
+			// locks above the free balance leave nothing liquid
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 200);
+			assert_eq!(Tokens::liquid_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::liquid_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+		});
+}
+
+#[test]
+fn balance_at_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 0), Some(100));
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &CHARLIE, 0), None);
+
+			for (block, amount) in vec![(5, 10), (10, 10), (15, 10)] {
+				System::set_block_number(block);
+				assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, amount));
+			}
+			assert_eq!(
+				Tokens::account_snapshots(TEST_TOKEN_ID, &ALICE),
+				vec![(0, 100), (10, 80), (20, 70)]
+			);
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 9), Some(100));
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 10), Some(80));
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 19), Some(80));
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 25), Some(70));
+		});
+}
+
+#[test]
+fn balance_at_should_be_none_outside_window() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			for block in vec![5, 15, 35, 45] {
+				System::set_block_number(block);
+				assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+			}
+
+			// only the latest `MaxAccountSnapshots` snapshots are retained
+			assert_eq!(
+				Tokens::account_snapshots(TEST_TOKEN_ID, &ALICE),
+				vec![(20, 80), (40, 70), (50, 60)]
+			);
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 10), None);
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 20), Some(80));
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 39), Some(80));
+			assert_eq!(Tokens::balance_at(TEST_TOKEN_ID, &ALICE, 45), Some(70));
+		});
+}
+
+#[test]
+fn balance_changed_since_checkpoint_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert!(!Tokens::balance_changed_since_checkpoint(TEST_TOKEN_ID, &ALICE));
+
+			System::set_block_number(1);
+			Tokens::checkpoint_balance(TEST_TOKEN_ID, &ALICE);
+			assert_eq!(Tokens::balance_checkpoint(TEST_TOKEN_ID, &ALICE), Some((1, 100)));
+			assert!(!Tokens::balance_changed_since_checkpoint(TEST_TOKEN_ID, &ALICE));
+
+			// changes of other accounts are not detected
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &BOB, 10));
+			assert!(!Tokens::balance_changed_since_checkpoint(TEST_TOKEN_ID, &ALICE));
+
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+			assert!(Tokens::balance_changed_since_checkpoint(TEST_TOKEN_ID, &ALICE));
+
+			// a new checkpoint is taken from the current balance
+			System::set_block_number(2);
+			Tokens::checkpoint_balance(TEST_TOKEN_ID, &ALICE);
+			assert_eq!(Tokens::balance_checkpoint(TEST_TOKEN_ID, &ALICE), Some((2, 90)));
+			assert!(!Tokens::balance_changed_since_checkpoint(TEST_TOKEN_ID, &ALICE));
+		});
+}
+
+#[test]
+fn net_position_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::net_position(TEST_TOKEN_ID, &ALICE, 0), 50);
+			assert_eq!(Tokens::net_position(TEST_TOKEN_ID, &ALICE, 40), 10);
+			assert_eq!(Tokens::net_position(TEST_TOKEN_ID, &ALICE, 50), 0);
+
+			// obligations exceeding the liquid balance are a negative net position
+			assert_eq!(Tokens::net_position(TEST_TOKEN_ID, &ALICE, 80), -30);
+			assert_eq!(Tokens::net_position(TEST_TOKEN_ID, &CHARLIE, 10), -10);
+		});
+}
+
+#[test]
+fn withdrawable_respecting_min_reserve_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::set_minimum_reserve_requirement(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, 50),
+				BadOrigin
+			);
+			assert_ok!(Tokens::set_minimum_reserve_requirement(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				ALICE,
+				50
+			));
+			assert_eq!(Tokens::minimum_reserve_requirement(TEST_TOKEN_ID, &ALICE), 50);
+			assert!(System::events().iter().any(|record| record.event
+				== TestEvent::tokens(RawEvent::MinimumReserveRequirementSet(TEST_TOKEN_ID, ALICE, 50))));
+
+			// a top up of 30 is needed to meet the minimum reserve
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10);
+			assert_eq!(Tokens::liquid_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Tokens::withdrawable_respecting_min_reserve(TEST_TOKEN_ID, &ALICE), 40);
+
+			// the minimum reserve is met
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 40));
+			assert_eq!(Tokens::withdrawable_respecting_min_reserve(TEST_TOKEN_ID, &ALICE), 30);
+
+			// without a requirement, it's the liquid balance
+			assert_eq!(Tokens::withdrawable_respecting_min_reserve(TEST_TOKEN_ID, &BOB), 100);
+			assert_ok!(Tokens::set_minimum_reserve_requirement(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				ALICE,
+				0
+			));
+			assert!(!<MinimumReserves<Runtime>>::contains_key(TEST_TOKEN_ID, ALICE));
+		});
+}
+
+#[test]
+fn net_available_after_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 30);
+			assert_eq!(Tokens::net_available_after(TEST_TOKEN_ID, &ALICE, 40, 31), None);
+			assert_eq!(Tokens::net_available_after(TEST_TOKEN_ID, &ALICE, 70, 0), Some(0));
+			assert_eq!(
+				Tokens::net_available_after(TEST_TOKEN_ID, &ALICE, 0, Balance::max_value()),
+				None
+			);
+			assert_eq!(Tokens::net_available_after(TEST_TOKEN_ID, &ALICE, 40, 20), Some(10));
+
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 40));
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 20));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).spendable(), 10);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 11),
+				Error::<Runtime>::LiquidityRestrictions,
+			);
+		});
+}
+
+#[test]
+fn reserves_should_fail_for_non_reservable_currencies() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 100), (BOB, 2, 100)])
+		.non_reservable_currencies(vec![2])
+		.build()
+		.execute_with(|| {
+			assert!(!Tokens::reservable(2));
+			assert!(!Tokens::can_reserve(2, &ALICE, 10));
+			assert_noop!(Tokens::reserve(2, &ALICE, 10), Error::<Runtime>::ReservesNotSupported);
+			assert_noop!(
+				Tokens::reserve_named(&ReserveId::Staking, 2, &ALICE, 10),
+				Error::<Runtime>::ReservesNotSupported
+			);
+			assert_noop!(
+				Tokens::transfer_and_reserve_named(Some(BOB).into(), ALICE, 2, 10, ReserveId::Staking),
+				Error::<Runtime>::ReservesNotSupported
+			);
+
+			assert!(Tokens::reservable(TEST_TOKEN_ID));
+			assert!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 10));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 10));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 10));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+		});
+}
+
+#[test]
+fn reserve_up_to_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 40);
+			assert_eq!(Tokens::reserve_up_to(TEST_TOKEN_ID, &ALICE, 80), 60);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 40);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 60);
+			let reserved_event = TestEvent::tokens(RawEvent::Reserved(TEST_TOKEN_ID, ALICE, 60));
+			assert!(System::events().iter().any(|record| record.event == reserved_event));
+
+			assert_eq!(Tokens::reserve_up_to(TEST_TOKEN_ID, &BOB, 30), 30);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 70);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 30);
+
+			assert_eq!(Tokens::reserve_up_to(TEST_TOKEN_ID, &CHARLIE, 30), 0);
+		});
+}
+
+#[test]
+fn transfer_internal_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(POOL, TEST_TOKEN_ID, 100), (ALICE, TEST_TOKEN_ID, 100)])
+		.build()
+		.execute_with(|| {
+			<Accounts<Runtime>>::mutate(TEST_TOKEN_ID, TREASURY, |account_data| {
+				account_data.free = Balance::max_value() - 10
+			});
+			<Accounts<Runtime>>::mutate(TEST_TOKEN_ID, BOB, |account_data| {
+				account_data.free = Balance::max_value() - 10
+			});
+
+			assert_noop!(
+				Tokens::transfer_internal(TEST_TOKEN_ID, &POOL, &TREASURY, 20, false),
+				Error::<Runtime>::TotalIssuanceOverflow
+			);
+			// not protocol accounts
+			assert_noop!(
+				Tokens::transfer_internal(TEST_TOKEN_ID, &ALICE, &BOB, 20, true),
+				Error::<Runtime>::TotalIssuanceOverflow
+			);
+
+			assert_ok!(Tokens::transfer_internal(TEST_TOKEN_ID, &POOL, &TREASURY, 20, true));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 80);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), Balance::max_value());
+			// the 10 lost by saturating is burned
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 190);
+		});
+}
+
+#[test]
+fn migrate_my_balance_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(BOB, 2, 10),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::migrate_my_balance(Some(ALICE).into(), TEST_TOKEN_ID, 2, (3, 2)));
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE), Default::default());
+			assert_eq!(Tokens::reserves(TEST_TOKEN_ID, &ALICE), vec![]);
+			assert_eq!(Tokens::free_balance(2, &ALICE), 105);
+			assert_eq!(Tokens::reserved_balance(2, &ALICE), 45);
+			assert_eq!(Tokens::reserved_balance_named(&ReserveId::Staking, 2, &ALICE), 45);
+
+			let migrated_event = TestEvent::tokens(RawEvent::BalanceMigrated(TEST_TOKEN_ID, 2, ALICE, 100, 150));
+			assert!(System::events().iter().any(|record| record.event == migrated_event));
+		});
+}
+
+#[test]
+fn migrate_my_balance_should_adjust_total_issuance() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 101),
+			(BOB, TEST_TOKEN_ID, 100),
+			(BOB, 2, 10),
+		])
+		.build()
+		.execute_with(|| {
+			// 101 / 2 is rounded down
+			assert_ok!(Tokens::migrate_my_balance(Some(ALICE).into(), TEST_TOKEN_ID, 2, (1, 2)));
+			assert_eq!(Tokens::free_balance(2, &ALICE), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 100);
+			assert_eq!(Tokens::total_issuance(2), 60);
+
+			assert_noop!(
+				Tokens::migrate_my_balance(Some(BOB).into(), TEST_TOKEN_ID, 2, (1, 0)),
+				Error::<Runtime>::InvalidMigration
+			);
+			assert_noop!(
+				Tokens::migrate_my_balance(Some(BOB).into(), TEST_TOKEN_ID, TEST_TOKEN_ID, (1, 1)),
+				Error::<Runtime>::SameCurrency
+			);
+		});
+}
+
+#[test]
+fn migrate_my_balance_should_fail_with_locks() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10);
+			assert_noop!(
+				Tokens::migrate_my_balance(Some(ALICE).into(), TEST_TOKEN_ID, 2, (1, 1)),
+				Error::<Runtime>::AccountHasLocks
+			);
+
+			Tokens::remove_lock(ID_1, TEST_TOKEN_ID, &ALICE);
+			assert_ok!(Tokens::migrate_my_balance(Some(ALICE).into(), TEST_TOKEN_ID, 2, (1, 1)));
+			assert_eq!(Tokens::free_balance(2, &ALICE), 100);
+		});
+}
+
+#[test]
+fn export_balances_should_work() {
+	let exported = ExtBuilder::default()
+		.balances(vec![
+			(CHARLIE, TEST_TOKEN_ID, 30),
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 50),
+			(ALICE, 2, 70),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 20));
+
+			let exported = Tokens::export_balances(TEST_TOKEN_ID);
+			assert_eq!(
+				exported,
+				vec![
+					(
+						ALICE,
+						AccountData {
+							free: 100,
+							reserved: 0,
+							frozen: 0,
+						}
+					),
+					(
+						BOB,
+						AccountData {
+							free: 30,
+							reserved: 20,
+							frozen: 0,
+						}
+					),
+					(
+						CHARLIE,
+						AccountData {
+							free: 30,
+							reserved: 0,
+							frozen: 0,
+						}
+					),
+				]
+			);
+			assert_eq!(Tokens::export_balances(TEST_TOKEN_ID), exported);
+			assert_eq!(Tokens::export_balances(3), vec![]);
+			exported
+		});
+
+	// re-import into a fresh genesis
+	let endowed_accounts = exported
+		.iter()
+		.map(|(who, account_data)| (*who, TEST_TOKEN_ID, account_data.free))
+		.collect::<Vec<_>>();
+	ExtBuilder::default()
+		.balances(endowed_accounts)
+		.build()
+		.execute_with(|| {
+			let reimported = Tokens::export_balances(TEST_TOKEN_ID);
+			assert_eq!(
+				reimported.iter().map(|(who, _)| *who).collect::<Vec<_>>(),
+				vec![ALICE, BOB, CHARLIE]
+			);
+			assert_eq!(
+				reimported
+					.iter()
+					.map(|(who, account_data)| (*who, account_data.free))
+					.collect::<Vec<_>>(),
+				exported
+					.iter()
+					.map(|(who, account_data)| (*who, account_data.free))
+					.collect::<Vec<_>>()
+			);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 160);
+		});
+}
+
+#[test]
+fn snapshot_balances_should_work() {
+	let (offchain, _state) = TestOffchainExt::new();
+	let mut ext = ExtBuilder::default().one_hundred_for_alice_n_bob().build();
+	ext.register_extension(OffchainExt::new(offchain));
+	ext.execute_with(|| {
+		assert_eq!(Tokens::snapshot_key(120), b"orml::tokens::snapshot::120".to_vec());
+
+		let root = Tokens::snapshot_balances(120);
+		let snapshot = runtime_io::offchain::local_storage_get(StorageKind::PERSISTENT, &Tokens::snapshot_key(120))
+			.expect("snapshot is exported");
+		let (total_issuances, mut accounts) =
+			<(Vec<(u32, Balance)>, Vec<(u32, u64, AccountData<Balance>)>)>::decode(&mut &snapshot[..]).unwrap();
+		accounts.sort_by_key(|(_, who, _)| *who);
+		assert_eq!(total_issuances, vec![(TEST_TOKEN_ID, 200)]);
+		assert_eq!(
+			accounts,
+			vec![
+				(TEST_TOKEN_ID, ALICE, Tokens::accounts(TEST_TOKEN_ID, ALICE)),
+				(TEST_TOKEN_ID, BOB, Tokens::accounts(TEST_TOKEN_ID, BOB)),
+			]
+		);
+
+		// unchanged balances give the same root
+		assert_eq!(Tokens::snapshot_balances(130), root);
+		assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+		assert_ne!(Tokens::snapshot_balances(140), root);
+	});
+}
+
+#[test]
+fn submit_snapshot_root_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			let root = H256::repeat_byte(1);
+			assert_noop!(Tokens::submit_snapshot_root(Some(ALICE).into(), 10, root), BadOrigin);
+			assert_noop!(
+				Tokens::submit_snapshot_root(Origin::ROOT, 15, root),
+				Error::<Runtime>::InvalidSnapshotBlock
+			);
+
+			assert_noop!(Tokens::submit_snapshot_root(Origin::NONE, 10, root), BadOrigin);
+			assert_ok!(Tokens::submit_snapshot_root(Origin::ROOT, 10, root));
+			assert_eq!(Tokens::snapshot_root(10), Some(root));
+			let submitted_event = TestEvent::tokens(RawEvent::SnapshotRootSubmitted(10, root));
+			assert!(System::events().iter().any(|record| record.event == submitted_event));
+
+			assert_noop!(
+				Tokens::submit_snapshot_root(Origin::ROOT, 10, H256::repeat_byte(2)),
+				Error::<Runtime>::SnapshotRootExists
+			);
+		});
+}
+
+#[test]
+fn start_migration_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::start_migration(Some(ALICE).into(), TEST_TOKEN_ID, AccountMigration::SweepDust),
+				BadOrigin
+			);
+			assert_noop!(
+				Tokens::start_migration(Origin::ROOT, TEST_TOKEN_ID, AccountMigration::Redenominate(1, 0)),
+				Error::<Runtime>::InvalidMigration
+			);
+
+			assert_ok!(Tokens::start_migration(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				AccountMigration::SweepDust
+			));
+			assert_eq!(
+				Tokens::pending_migration(TEST_TOKEN_ID),
+				Some((AccountMigration::SweepDust, 0))
+			);
+			assert_noop!(
+				Tokens::start_migration(Origin::ROOT, TEST_TOKEN_ID, AccountMigration::SweepDust),
+				Error::<Runtime>::MigrationInProgress
+			);
+		});
+}
+
+#[test]
+fn migration_should_resume_across_blocks() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+			(TREASURY, TEST_TOKEN_ID, 100),
+			(POOL, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			let accounts = [ALICE, BOB, CHARLIE, TREASURY, POOL];
+			let migrated = || {
+				accounts
+					.iter()
+					.filter(|who| Tokens::free_balance(TEST_TOKEN_ID, who) == 10)
+					.count()
+			};
+			assert_ok!(Tokens::start_migration(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				AccountMigration::Redenominate(1, 10)
+			));
+
+			Tokens::on_initialize(1);
+			assert_eq!(migrated(), 2);
+			let progress_event = TestEvent::tokens(RawEvent::MigrationProgress(TEST_TOKEN_ID, 2));
+			assert!(System::events().iter().any(|record| record.event == progress_event));
+
+			Tokens::on_initialize(2);
+			assert_eq!(migrated(), 4);
+			assert_eq!(
+				Tokens::pending_migration(TEST_TOKEN_ID),
+				Some((AccountMigration::Redenominate(1, 10), 4))
+			);
+
+			Tokens::on_initialize(3);
+			assert_eq!(migrated(), 5);
+			assert_eq!(Tokens::pending_migration(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 50);
+			let completed_event = TestEvent::tokens(RawEvent::MigrationCompleted(TEST_TOKEN_ID));
+			assert!(System::events().iter().any(|record| record.event == completed_event));
+
+			// nothing left to process
+			Tokens::on_initialize(4);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 10);
+		});
+}
+
+#[test]
+fn migration_should_resume_after_migrated_account_removed() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::start_migration(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				AccountMigration::Redenominate(1, 10)
+			));
+
+			Tokens::on_initialize(1);
+			assert!(Tokens::migration_cursor(TEST_TOKEN_ID).is_some());
+			let (migrated, _) = <Accounts<Runtime> as IterableStorageDoubleMap<_, _, _>>::iter(TEST_TOKEN_ID)
+				.find(|(_, account_data)| account_data.free == 10)
+				.unwrap();
+			<Accounts<Runtime>>::remove(TEST_TOKEN_ID, migrated);
+
+			// the remaining account is neither skipped nor migrated twice
+			Tokens::on_initialize(2);
+			assert_eq!(Tokens::pending_migration(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::migration_cursor(TEST_TOKEN_ID), None);
+			assert!(
+				<Accounts<Runtime> as IterableStorageDoubleMap<_, _, _>>::iter(TEST_TOKEN_ID)
+					.all(|(_, account_data)| account_data.free == 10)
+			);
+		});
+}
+
+#[test]
+fn balance_movements_should_be_blocked_during_migration() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+			(ALICE, 2, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::start_migration(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				AccountMigration::SweepDust
+			));
+			Tokens::on_initialize(1);
+			assert!(Tokens::pending_migration(TEST_TOKEN_ID).is_some());
+
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 10),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			assert_noop!(
+				Tokens::transfer_batch_same_currency(TEST_TOKEN_ID, &ALICE, vec![(BOB, 10)]),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			assert_noop!(
+				Tokens::teleport_out(Origin::ROOT, TEST_TOKEN_ID, ALICE, 10, PARA_CHAIN),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			assert_noop!(
+				Tokens::teleport_in(Origin::ROOT, TEST_TOKEN_ID, ALICE, 10, PARA_CHAIN),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			assert_noop!(
+				Tokens::set_target_balance(Origin::ROOT, TEST_TOKEN_ID, ALICE, 90),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			assert_noop!(
+				Tokens::set_reserved_to(Origin::ROOT, TEST_TOKEN_ID, ALICE, 10),
+				Error::<Runtime>::MigrationInProgress,
+			);
+			// withdrawals and reserves by other modules are not blocked
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &CHARLIE, 10));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &CHARLIE, 10));
+			// other token types are not affected
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, 2, 10));
+
+			Tokens::on_initialize(2);
+			assert_eq!(Tokens::pending_migration(TEST_TOKEN_ID), None);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 10));
+			assert_ok!(Tokens::set_reserved_to(Origin::ROOT, TEST_TOKEN_ID, ALICE, 10));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 110);
+		});
+}
+
+#[test]
+fn migrations_should_share_batch_size() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+			(ALICE, 2, 100),
+			(BOB, 2, 100),
+		])
+		.build()
+		.execute_with(|| {
+			<Accounts<Runtime>>::mutate(TEST_TOKEN_ID, ALICE, |account_data| account_data.free = 1);
+			assert_ok!(Tokens::start_migration(
+				Origin::ROOT,
+				TEST_TOKEN_ID,
+				AccountMigration::SweepDust
+			));
+			assert_ok!(Tokens::set_interest_schedule(
+				Origin::ROOT,
+				2,
+				Some((Permill::from_percent(10), 1))
+			));
+
+			// the accrual started at block 1 is only completed at block 3, so it's not started again
+			for block in 1..=3 {
+				Tokens::on_initialize(block);
+			}
+			assert_eq!(Tokens::pending_migration(TEST_TOKEN_ID), None);
+			assert_eq!(Tokens::interest_accrual(2), None);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+			assert_eq!(Tokens::free_balance(2, &ALICE), 110);
+			assert_eq!(Tokens::free_balance(2, &BOB), 110);
+			assert_eq!(Tokens::total_issuance(2), 220);
+		});
+}
+
+#[test]
+fn slashable_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert_eq!(Tokens::slashable_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::slashable_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+
+			// consistent with `slash` drawing from reserved balance
+			assert!(Tokens::can_slash(TEST_TOKEN_ID, &ALICE, 100));
+			assert!(!Tokens::can_slash(TEST_TOKEN_ID, &ALICE, 101));
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &ALICE, 100), 0);
+			assert_eq!(Tokens::slashable_balance(TEST_TOKEN_ID, &ALICE), 0);
+		});
+}
+
+#[test]
+fn transferable_keep_alive_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 1),
+			(POOL, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::transferable_keep_alive(TEST_TOKEN_ID, &ALICE), 98);
+			assert_eq!(Tokens::transferable_keep_alive(TEST_TOKEN_ID, &BOB), 0);
+			assert_eq!(Tokens::transferable_keep_alive(TEST_TOKEN_ID, &POOL), 100);
+
+			// at the existential deposit boundary
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 98));
+			assert_eq!(Tokens::transferable_keep_alive(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::transferable_keep_alive(TEST_TOKEN_ID, &CHARLIE), 96);
+
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &CHARLIE, 50);
+			assert_eq!(Tokens::transferable_keep_alive(TEST_TOKEN_ID, &CHARLIE), 46);
+		});
+}
+
+#[test]
+fn atomic_double_transfer_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, 2, 100)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::atomic_double_transfer(TEST_TOKEN_ID, &ALICE, 30, 2, &BOB, 60));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 30);
+			assert_eq!(Tokens::free_balance(2, &ALICE), 60);
+			assert_eq!(Tokens::free_balance(2, &BOB), 40);
+
+			let transferred_a_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 30));
+			assert!(System::events()
+				.iter()
+				.any(|record| record.event == transferred_a_event));
+			let transferred_b_event = TestEvent::tokens(RawEvent::Transferred(2, BOB, ALICE, 60));
+			assert!(System::events()
+				.iter()
+				.any(|record| record.event == transferred_b_event));
+		});
+}
+
+#[test]
+fn atomic_double_transfer_should_be_all_or_nothing() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (BOB, 2, 100)])
+		.build()
+		.execute_with(|| {
+			// the second leg fails
+			assert_noop!(
+				Tokens::atomic_double_transfer(TEST_TOKEN_ID, &ALICE, 30, 2, &BOB, 101),
+				Error::<Runtime>::BalanceTooLow
+			);
+			// the second leg leaves the receiver below the existential deposit
+			assert_noop!(
+				Tokens::atomic_double_transfer(TEST_TOKEN_ID, &ALICE, 30, 2, &BOB, 1),
+				Error::<Runtime>::ExistentialDeposit
+			);
+			// the second leg spends the first
+			assert_noop!(
+				Tokens::atomic_double_transfer(TEST_TOKEN_ID, &ALICE, 30, TEST_TOKEN_ID, &BOB, 31),
+				Error::<Runtime>::BalanceTooLow
+			);
+		});
+}
+
+#[test]
+fn atomic_double_transfer_should_work_with_same_currency() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::atomic_double_transfer(
+				TEST_TOKEN_ID,
+				&ALICE,
+				30,
+				TEST_TOKEN_ID,
+				&BOB,
+				10
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 80);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 20);
+		});
+}
+
+#[test]
+fn batch_account_data_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 50);
+
+			assert_eq!(
+				Tokens::balances_batch(TEST_TOKEN_ID, &[BOB, CHARLIE, ALICE]),
+				vec![
+					AccountData {
+						free: 100,
+						reserved: 0,
+						frozen: 50,
+					},
+					AccountData::default(),
+					AccountData {
+						free: 70,
+						reserved: 30,
+						frozen: 0,
+					},
+				]
+			);
+			assert_eq!(
+				Tokens::batch_account_data(TEST_TOKEN_ID, vec![ALICE, BOB]),
+				vec![
+					(
+						ALICE,
+						AccountData {
+							free: 70,
+							reserved: 30,
+							frozen: 0,
+						}
+					),
+					(
+						BOB,
+						AccountData {
+							free: 100,
+							reserved: 0,
+							frozen: 50,
+						}
+					),
+				]
+			);
+			assert!(Tokens::batch_account_data(TEST_TOKEN_ID, vec![]).is_empty());
+		});
+}
+
+#[test]
+fn total_balance_of_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 50),
+			(CHARLIE, TEST_TOKEN_ID, 30),
+		])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 40));
+			assert_eq!(Tokens::total_balance_of(TEST_TOKEN_ID, &[ALICE, BOB, CHARLIE]), 180);
+			assert_eq!(Tokens::total_balance_of(TEST_TOKEN_ID, &[ALICE, TREASURY]), 100);
+			assert_eq!(Tokens::total_balance_of(TEST_TOKEN_ID, &[]), 0);
+
+			<Accounts<Runtime>>::mutate(TEST_TOKEN_ID, BOB, |account_data| {
+				account_data.free = Balance::max_value() - 10
+			});
+			assert_eq!(
+				Tokens::total_balance_of(TEST_TOKEN_ID, &[ALICE, BOB, CHARLIE]),
+				Balance::max_value()
+			);
+		});
+}
+
+#[test]
+fn dedup_locks_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 10);
+			let lock = |id, amount| BalanceLock { id, amount };
+			<Locks<Runtime>>::insert(
+				TEST_TOKEN_ID,
+				ALICE,
+				vec![lock(ID_1, 10), lock(ID_2, 20), lock(ID_1, 30)],
+			);
+
+			assert_noop!(Tokens::dedup_locks(Some(ALICE).into(), TEST_TOKEN_ID, ALICE), BadOrigin);
+
+			assert_ok!(Tokens::dedup_locks(Origin::ROOT, TEST_TOKEN_ID, ALICE));
+			assert_eq!(
+				Tokens::locks(TEST_TOKEN_ID, ALICE),
+				vec![lock(ID_1, 30), lock(ID_2, 20)]
+			);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).frozen, 30);
+
+			let deduped_event = TestEvent::tokens(RawEvent::LocksDeduplicated(TEST_TOKEN_ID, ALICE, 3, 2));
+			assert!(System::events().iter().any(|record| record.event == deduped_event));
+		});
+}
+
+#[test]
+fn force_remove_lock_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 30);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).frozen, 50);
+
+			assert_noop!(
+				Tokens::force_remove_lock(Some(ALICE).into(), TEST_TOKEN_ID, ALICE, ID_1),
+				BadOrigin,
+			);
+			assert_noop!(
+				Tokens::force_remove_lock(Origin::ROOT, TEST_TOKEN_ID, BOB, ID_1),
+				Error::<Runtime>::LockNotFound,
+			);
+
+			assert_ok!(Tokens::force_remove_lock(Origin::ROOT, TEST_TOKEN_ID, ALICE, ID_1));
+			assert_eq!(Tokens::locks(TEST_TOKEN_ID, ALICE).len(), 1);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).frozen, 30);
+			let removed_event = TestEvent::tokens(RawEvent::LockRemoved(TEST_TOKEN_ID, ALICE, ID_1));
+			assert!(System::events().iter().any(|record| record.event == removed_event));
+		});
+}
+
+#[test]
+fn next_lock_expiry_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_noop!(
+				Tokens::set_lock_with_expiry(ID_1, TEST_TOKEN_ID, &ALICE, 10, 1),
+				Error::<Runtime>::InvalidDeadline
+			);
+
+			assert_ok!(Tokens::set_lock_with_expiry(ID_1, TEST_TOKEN_ID, &ALICE, 10, 8));
+			assert_ok!(Tokens::set_lock_with_expiry(ID_2, TEST_TOKEN_ID, &ALICE, 20, 5));
+			assert_eq!(Tokens::next_lock_expiry(TEST_TOKEN_ID, &ALICE), Some((ID_2, 5, 20)));
+
+			// only permanent locks
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &BOB, 10);
+			assert_eq!(Tokens::next_lock_expiry(TEST_TOKEN_ID, &BOB), None);
+
+			// expired locks are removed
+			Tokens::on_initialize(5);
+			assert_eq!(
+				Tokens::locks(TEST_TOKEN_ID, ALICE),
+				vec![BalanceLock { id: ID_1, amount: 10 }]
+			);
+			assert_eq!(Tokens::next_lock_expiry(TEST_TOKEN_ID, &ALICE), Some((ID_1, 8, 10)));
+			let expired_event = TestEvent::tokens(RawEvent::LockExpired(TEST_TOKEN_ID, ALICE, ID_2));
+			assert!(System::events().iter().any(|record| record.event == expired_event));
+
+			// setting the lock again without an expiry makes it permanent
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 15);
+			assert_eq!(Tokens::next_lock_expiry(TEST_TOKEN_ID, &ALICE), None);
+			assert_eq!(Tokens::expiring_locks(8), vec![]);
+			Tokens::on_initialize(8);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, ALICE).frozen, 15);
+		});
+}
+
+#[test]
+fn claim_reserve_before_deadline_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_noop!(
+				Tokens::reserve_with_deadline(TEST_TOKEN_ID, &ALICE, 30, 1),
+				Error::<Runtime>::InvalidDeadline
+			);
+
+			assert_eq!(Tokens::reserve_with_deadline(TEST_TOKEN_ID, &ALICE, 30, 5), Ok(0));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
+			assert_eq!(Tokens::reserve_deadlines(5), vec![0]);
+
+			assert_ok!(Tokens::claim_reserve(0, &BOB));
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 130);
+			assert_eq!(Tokens::deadline_reserve(0), None);
+			assert_eq!(Tokens::reserve_deadlines(5), vec![]);
+			assert_noop!(
+				Tokens::claim_reserve(0, &BOB),
+				Error::<Runtime>::DeadlineReserveNotFound
+			);
+
+			let claimed_event = TestEvent::tokens(RawEvent::DeadlineReserveClaimed(0, BOB, 30));
+			assert!(System::events().iter().any(|record| record.event == claimed_event));
+
+			// nothing left to release at the deadline
+			Tokens::on_initialize(5);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 70);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 130);
+		});
+}
+
+#[test]
+fn unclaimed_reserve_should_be_released_at_deadline() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+			assert_eq!(Tokens::reserve_with_deadline(TEST_TOKEN_ID, &ALICE, 30, 3), Ok(0));
+			assert_eq!(Tokens::reserve_with_deadline(TEST_TOKEN_ID, &BOB, 20, 4), Ok(1));
+
+			Tokens::on_initialize(2);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 30);
+
+			Tokens::on_initialize(3);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::deadline_reserve(0), None);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &BOB), 20);
+
+			let released_event = TestEvent::tokens(RawEvent::DeadlineReserveReleased(0, ALICE, 30));
+			assert!(System::events().iter().any(|record| record.event == released_event));
+
+			// released reserves can't be claimed
+			assert_noop!(
+				Tokens::claim_reserve(0, &BOB),
+				Error::<Runtime>::DeadlineReserveNotFound
+			);
+		});
+}
+
+#[test]
+fn pre_flight_transfer_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+
+			assert_ok!(Tokens::pre_flight_transfer(TEST_TOKEN_ID, &ALICE, 50));
+			assert_ok!(Tokens::pre_flight_transfer(TEST_TOKEN_ID, &ALICE, 0));
+			assert_noop!(
+				Tokens::pre_flight_transfer(TEST_TOKEN_ID, &ALICE, 51),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+			assert_noop!(
+				Tokens::pre_flight_transfer(TEST_TOKEN_ID, &ALICE, 101),
+				Error::<Runtime>::BalanceTooLow
+			);
+			assert_noop!(
+				Tokens::pre_flight_transfer(TEST_TOKEN_ID, &ALICE, 1),
+				Error::<Runtime>::ExistentialDeposit
+			);
+		});
+}
+
+#[test]
+fn native_currency_should_be_routed() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(MockNativeCurrency::deposit(&ALICE, 100));
+			assert_eq!(Tokens::free_balance(NATIVE_CURRENCY_ID, &ALICE), 100);
+			assert_eq!(Tokens::total_balance(NATIVE_CURRENCY_ID, &ALICE), 100);
+			assert_eq!(Tokens::total_issuance(NATIVE_CURRENCY_ID), 100);
+			assert_eq!(Tokens::accounts(NATIVE_CURRENCY_ID, &ALICE), Default::default());
+
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(
+				NATIVE_CURRENCY_ID,
+				&ALICE,
+				&BOB,
+				30
+			));
+			assert_eq!(MockNativeCurrency::free_balance(&ALICE), 70);
+			assert_eq!(MockNativeCurrency::free_balance(&BOB), 30);
+
+			assert_ok!(Tokens::transfer(Some(BOB).into(), ALICE, NATIVE_CURRENCY_ID, 10));
+			assert_eq!(MockNativeCurrency::free_balance(&ALICE), 80);
+			assert_eq!(MockNativeCurrency::free_balance(&BOB), 20);
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(NATIVE_CURRENCY_ID, BOB, ALICE, 10));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+
+			assert_ok!(Tokens::withdraw(NATIVE_CURRENCY_ID, &ALICE, 80));
+			assert!(Tokens::withdraw(NATIVE_CURRENCY_ID, &ALICE, 1).is_err());
+			assert_eq!(Tokens::total_issuance(NATIVE_CURRENCY_ID), 20);
+
+			// other token types are unaffected
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+		});
+}
+
+#[test]
+fn native_currency_reserves_and_locks_should_be_routed() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(MockNativeCurrency::deposit(&ALICE, 100));
+
+		assert_ok!(Tokens::reserve(NATIVE_CURRENCY_ID, &ALICE, 30));
+		assert_eq!(MockNativeCurrency::reserved_balance(&ALICE), 30);
+		assert_eq!(Tokens::reserved_balance(NATIVE_CURRENCY_ID, &ALICE), 30);
+		assert_eq!(Tokens::free_balance(NATIVE_CURRENCY_ID, &ALICE), 70);
+		assert_eq!(Tokens::accounts(NATIVE_CURRENCY_ID, &ALICE), Default::default());
+		assert_eq!(Tokens::unreserve(NATIVE_CURRENCY_ID, &ALICE, 40), 10);
+		assert_eq!(MockNativeCurrency::reserved_balance(&ALICE), 0);
+
+		Tokens::set_lock(ID_1, NATIVE_CURRENCY_ID, &ALICE, 60);
+		assert_eq!(MockNativeCurrency::frozen_balance(&ALICE), 60);
+		assert_eq!(Tokens::locks(NATIVE_CURRENCY_ID, &ALICE), vec![]);
+		assert!(Tokens::transfer(Some(ALICE).into(), BOB, NATIVE_CURRENCY_ID, 50).is_err());
+		Tokens::remove_lock(ID_1, NATIVE_CURRENCY_ID, &ALICE);
+		assert_eq!(MockNativeCurrency::frozen_balance(&ALICE), 0);
+		assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, NATIVE_CURRENCY_ID, 50));
+	});
+}
+
+#[test]
+fn native_currency_transfer_should_charge_fee() {
+	ExtBuilder::default()
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			assert_ok!(MockNativeCurrency::deposit(&ALICE, 100));
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, NATIVE_CURRENCY_ID, 70));
+			assert_eq!(MockNativeCurrency::free_balance(&ALICE), 30);
+			assert_eq!(MockNativeCurrency::free_balance(&BOB), 63);
+			assert_eq!(MockNativeCurrency::free_balance(&TREASURY), 2);
+			assert_eq!(MockNativeCurrency::total_issuance(), 95);
+			let fee_event = TestEvent::tokens(RawEvent::TransferFeeCharged(NATIVE_CURRENCY_ID, ALICE, 5, 2));
+			assert!(System::events().iter().any(|record| record.event == fee_event));
+
+			assert_ok!(Tokens::freeze_currency(Origin::ROOT, NATIVE_CURRENCY_ID));
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, NATIVE_CURRENCY_ID, 10),
+				Error::<Runtime>::CurrencyFrozen
+			);
+		});
+}
+
+#[test]
+fn ensure_reserved_balance_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::ensure_reserved_balance(TEST_TOKEN_ID, &ALICE, 0));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::ensure_reserved_balance(TEST_TOKEN_ID, &ALICE, 30));
+			assert_noop!(
+				Tokens::ensure_reserved_balance(TEST_TOKEN_ID, &ALICE, 29),
+				Error::<Runtime>::UnexpectedReservedBalance
+			);
+			assert_noop!(
+				Tokens::ensure_reserved_balance(TEST_TOKEN_ID, &ALICE, 31),
+				Error::<Runtime>::UnexpectedReservedBalance
+			);
+		});
+}
+
+#[test]
+fn spendable_by_reason_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::set_lock_with_reasons(
+				ID_1,
+				TEST_TOKEN_ID,
+				&ALICE,
+				30,
+				WithdrawReason::Transfer.into()
+			));
+			assert_ok!(Tokens::set_lock_with_reasons(
+				ID_2,
+				TEST_TOKEN_ID,
+				&ALICE,
+				50,
+				WithdrawReason::Reserve.into()
+			));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+
+			// each reason is only restricted by the locks applying to it
+			assert_eq!(
+				Tokens::spendable_by_reason(TEST_TOKEN_ID, &ALICE),
+				vec![
+					(WithdrawReason::Transfer, 50),
+					(WithdrawReason::Fee, 80),
+					(WithdrawReason::Reserve, 30),
+				]
+			);
+			assert_eq!(Tokens::accounts(TEST_TOKEN_ID, &ALICE).frozen, 30);
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 51),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 31),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+			assert!(Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert!(!Tokens::can_reserve(TEST_TOKEN_ID, &ALICE, 31));
+
+			// setting a lock again makes it apply to all reasons
+			Tokens::set_lock(ID_2, TEST_TOKEN_ID, &ALICE, 50);
+			assert_eq!(
+				Tokens::spendable_by_reason(TEST_TOKEN_ID, &ALICE),
+				vec![
+					(WithdrawReason::Transfer, 30),
+					(WithdrawReason::Fee, 30),
+					(WithdrawReason::Reserve, 30),
+				]
+			);
+			Tokens::remove_lock(ID_1, TEST_TOKEN_ID, &ALICE);
+			assert_eq!(Tokens::lock_reasons(TEST_TOKEN_ID, &ALICE), vec![]);
+			assert_eq!(
+				Tokens::spendable_by_reason(TEST_TOKEN_ID, &BOB),
+				vec![
+					(WithdrawReason::Transfer, 100),
+					(WithdrawReason::Fee, 100),
+					(WithdrawReason::Reserve, 100),
+				]
+			);
+		});
+}
+
+#[test]
+fn transfer_without_issuance_change_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (POOL, TEST_TOKEN_ID, 100)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer_without_issuance_change(
+				TEST_TOKEN_ID,
+				&POOL,
+				&ALICE,
+				99
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &POOL), 1);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 199);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			// the dust left in a non-protected account would be removed
+			assert_noop!(
+				Tokens::transfer_without_issuance_change(TEST_TOKEN_ID, &ALICE, &BOB, 198),
+				Error::<Runtime>::ExistentialDeposit
+			);
+			assert_noop!(
+				Tokens::transfer_without_issuance_change(TEST_TOKEN_ID, &ALICE, &BOB, 1),
+				Error::<Runtime>::ExistentialDeposit
+			);
+			assert_noop!(
+				Tokens::transfer_without_issuance_change(TEST_TOKEN_ID, &ALICE, &BOB, 200),
+				Error::<Runtime>::BalanceTooLow
+			);
+
+			assert_ok!(Tokens::transfer_without_issuance_change(
+				TEST_TOKEN_ID,
+				&ALICE,
+				&BOB,
+				199
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 199);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+		});
+}
+
+#[test]
+fn transfer_batch_lenient_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer_batch_lenient(Some(ALICE).into(), vec![(BOB, TEST_TOKEN_ID, 1); 4]),
+				Error::<Runtime>::TooManyTransfers
+			);
+
+			assert_ok!(Tokens::transfer_batch_lenient(
+				Some(ALICE).into(),
+				vec![
+					(BOB, TEST_TOKEN_ID, 30),
+					(CHARLIE, TEST_TOKEN_ID, 80),
+					(CHARLIE, TEST_TOKEN_ID, 20),
+				]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 130);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 20);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, CHARLIE, 20));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+			let failed_event = TestEvent::tokens(RawEvent::TransferFailed(1, Error::<Runtime>::BalanceTooLow.into()));
+			assert!(System::events().iter().any(|record| record.event == failed_event));
+		});
+}
+
+#[test]
+fn transfer_batch_lenient_should_not_apply_failed_transfer() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (TREASURY, TEST_TOKEN_ID, 100)])
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(30))
+		.build()
+		.execute_with(|| {
+			// the treasury share of the first transfer fails to be deposited after its balances are moved
+			MockMaxBalancePerAccount::set(Some(100));
+			assert_ok!(Tokens::transfer_batch_lenient(
+				Some(ALICE).into(),
+				vec![(CHARLIE, TEST_TOKEN_ID, 70), (BOB, TEST_TOKEN_ID, 10)]
+			));
+			MockMaxBalancePerAccount::set(None);
+
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 90);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 9);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 199);
+			let failed_event =
+				TestEvent::tokens(RawEvent::TransferFailed(0, Error::<Runtime>::BalanceCapExceeded.into()));
+			assert!(System::events().iter().any(|record| record.event == failed_event));
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, CHARLIE, 63));
+			assert!(!System::events().iter().any(|record| record.event == transferred_event));
+		});
+}
+
+#[test]
+fn balancelock_encoding_is_stable() {
+	// `BalanceLock { id: *b"stakeing", amount: 1000u128 }`, SCALE encoded as
+	// 0x7374616b65696e67e8030000000000000000000000000000
+	let encoded: [u8; 24] = [
+		0x73, 0x74, 0x61, 0x6b, 0x65, 0x69, 0x6e, 0x67, 0xe8, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	];
+
+	let lock = BalanceLock::<u128>::decode(&mut &encoded[..]).expect("stored locks should be decodable");
+	let BalanceLock { id, amount } = lock.clone();
+	assert_eq!(id, *b"stakeing");
+	assert_eq!(amount, 1000u128);
+	assert_eq!(lock.encode(), encoded.to_vec());
+}
+
+#[test]
+fn free_balance_at_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 0), Some(100));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &CHARLIE, 0), None);
+
+			System::set_block_number(3);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 30));
+			System::set_block_number(5);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 10));
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 10));
+
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 2), Some(100));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 3), Some(70));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 4), Some(70));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 5), Some(50));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 100), Some(50));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &BOB, 4), Some(130));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &CHARLIE, 4), None);
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &CHARLIE, 5), Some(20));
+
+			// only the latest `MaxBalanceHistory` changes are retained
+			System::set_block_number(7);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 10));
+			assert_eq!(
+				Tokens::balance_history(TEST_TOKEN_ID, &ALICE),
+				vec![(3, 70), (5, 50), (7, 40)]
+			);
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 2), None);
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 6), Some(50));
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &ALICE, 7), Some(40));
+		});
+}
+
+#[test]
+fn balance_history_should_not_be_recorded_if_disabled() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockMaxBalanceHistory::set(0);
+
+			System::set_block_number(3);
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), CHARLIE, TEST_TOKEN_ID, 30));
+			assert_eq!(Tokens::balance_history(TEST_TOKEN_ID, &ALICE), vec![(0, 100)]);
+			assert_eq!(Tokens::balance_history(TEST_TOKEN_ID, &CHARLIE), vec![]);
+			assert_eq!(Tokens::free_balance_at(TEST_TOKEN_ID, &CHARLIE, 3), None);
+		});
+}
+
+#[test]
+fn total_issuance_should_be_changed_without_balances() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			Tokens::increase_total_issuance(TEST_TOKEN_ID, 10);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 210);
+			Tokens::decrease_total_issuance(TEST_TOKEN_ID, 20);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 190);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+
+			// saturates
+			Tokens::decrease_total_issuance(TEST_TOKEN_ID, 1000);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 0);
+			Tokens::increase_total_issuance(TEST_TOKEN_ID, Balance::max_value());
+			Tokens::increase_total_issuance(TEST_TOKEN_ID, 1);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), Balance::max_value());
+		});
+}
+
+#[test]
+fn transfer_min_received_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer_min_received(TEST_TOKEN_ID, &ALICE, &BOB, 50, 46),
+				Error::<Runtime>::BelowMinimumReceived
+			);
+
+			assert_ok!(Tokens::transfer_min_received(TEST_TOKEN_ID, &ALICE, &BOB, 50, 45));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 50);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 145);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 195);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, BOB, 45));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+		});
+}
+
+#[test]
+fn currency_ids_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 100)])
+		.build()
+		.execute_with(|| {
+			let mut currency_ids = Tokens::currency_ids();
+			currency_ids.sort();
+			assert_eq!(currency_ids, vec![TEST_TOKEN_ID, 2]);
+		});
+}
+
+#[test]
+fn active_currencies_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 100), (ALICE, 3, 100)])
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::withdraw(2, &ALICE, 100));
+			assert_eq!(Tokens::total_issuance(2), 0);
+
+			let mut active_currencies = Tokens::active_currencies();
+			active_currencies.sort();
+			assert_eq!(active_currencies, vec![TEST_TOKEN_ID, 3]);
+		});
+}
+
+#[test]
+fn emergency_shutdown_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 20));
+
+			assert_noop!(Tokens::activate_emergency_shutdown(Some(ALICE).into()), BadOrigin);
+			assert_ok!(Tokens::activate_emergency_shutdown(Origin::ROOT));
+			assert!(Tokens::shutdown_active());
+
+			let activated_event = TestEvent::tokens(RawEvent::EmergencyShutdownActivated);
+			assert!(System::events().iter().any(|record| record.event == activated_event));
+
+			assert_noop!(
+				Tokens::transfer(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 10),
+				Error::<Runtime>::EmergencyShutdown,
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(2, &ALICE, &BOB, 10),
+				Error::<Runtime>::EmergencyShutdown,
+			);
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::EmergencyShutdown,
+			);
+			assert_noop!(
+				Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::EmergencyShutdown,
+			);
+			assert_noop!(
+				Tokens::reserve(TEST_TOKEN_ID, &ALICE, 10),
+				Error::<Runtime>::EmergencyShutdown,
+			);
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 10), 10);
+			assert_eq!(Tokens::reserved_balance(TEST_TOKEN_ID, &ALICE), 20);
+
+			assert_noop!(Tokens::deactivate_emergency_shutdown(Some(ALICE).into()), BadOrigin);
+			assert_ok!(Tokens::deactivate_emergency_shutdown(Origin::ROOT));
+			assert!(!Tokens::shutdown_active());
+
+			let deactivated_event = TestEvent::tokens(RawEvent::EmergencyShutdownDeactivated);
+			assert!(System::events().iter().any(|record| record.event == deactivated_event));
+
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &ALICE, 10), 0);
+			assert_ok!(Tokens::transfer(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 10));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 100);
+		});
+}
+
+#[test]
+fn deposit_to_blocked_recipient_should_fail_by_default() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &BLOCKED, 50),
+				Error::<Runtime>::RecipientBlocked
+			);
+		});
+}
+
+#[test]
+fn transfer_to_blocked_recipient_should_fail() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockDepositBounce::set(DepositBounce::BounceTo(TREASURY));
+
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BLOCKED, TEST_TOKEN_ID, 50),
+				Error::<Runtime>::RecipientBlocked
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BLOCKED, 50),
+				Error::<Runtime>::RecipientBlocked
+			);
+		});
+}
+
+#[test]
+fn deposit_to_blocked_recipient_should_bounce() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockDepositBounce::set(DepositBounce::BounceTo(TREASURY));
+
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &BLOCKED, 50));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BLOCKED), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 50);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 250);
+
+			let bounced_event = TestEvent::tokens(RawEvent::DepositBounced(TEST_TOKEN_ID, BLOCKED, TREASURY, 50));
+			assert!(System::events().iter().any(|record| record.event == bounced_event));
+
+			// the bounce account can't be blocked
+			MockDepositBounce::set(DepositBounce::BounceTo(BLOCKED));
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &BLOCKED, 50),
+				Error::<Runtime>::RecipientBlocked
+			);
+		});
+}
+
+#[test]
+fn total_balance_native_equivalent_should_work() {
+	ExtBuilder::default()
+		.balances(vec![(ALICE, TEST_TOKEN_ID, 100), (ALICE, 2, 40)])
+		.build()
+		.execute_with(|| {
+			System::set_block_number(1);
+
+			assert_noop!(
+				Tokens::set_exchange_rate(Origin::signed(ALICE), 2, Some(FixedU128::from_rational(3, 2))),
+				BadOrigin
+			);
+			assert_ok!(Tokens::set_exchange_rate(
+				Origin::ROOT,
+				2,
+				Some(FixedU128::from_rational(3, 2))
+			));
+			let rate_set_event = TestEvent::tokens(RawEvent::ExchangeRateSet(2, Some(FixedU128::from_rational(3, 2))));
+			assert!(System::events().iter().any(|record| record.event == rate_set_event));
+
+			// currencies without a rate count one to one
+			assert_eq!(Tokens::total_balance_native_equivalent(&ALICE), 160);
+			assert_eq!(Tokens::native_equivalent_cache(ALICE), Some((1, 160)));
+			assert_eq!(Tokens::total_balance_native_equivalent(&BOB), 0);
+
+			// rate changes apply from the next block
+			assert_ok!(Tokens::set_exchange_rate(Origin::ROOT, 2, None));
+			assert_eq!(Tokens::total_balance_native_equivalent(&ALICE), 160);
+			System::set_block_number(2);
+			assert_eq!(Tokens::total_balance_native_equivalent(&ALICE), 140);
+
+			// balance changes apply immediately
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &ALICE, 30));
+			assert_eq!(Tokens::total_balance_native_equivalent(&ALICE), 140);
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::native_equivalent_cache(ALICE), None);
+			assert_eq!(Tokens::total_balance_native_equivalent(&ALICE), 120);
+
+			// the cache only lives within a block
+			Tokens::on_finalize(2);
+			assert_eq!(Tokens::native_equivalent_cache(ALICE), None);
+			assert_eq!(Tokens::native_equivalent_cached_accounts(), vec![]);
+
+			// saturates
+			assert_ok!(Tokens::set_exchange_rate(Origin::ROOT, 2, Some(FixedU128::max_value())));
+			System::set_block_number(3);
+			assert_eq!(Tokens::total_balance_native_equivalent(&ALICE), Balance::max_value());
+		});
+}
+
+#[test]
+fn issuance_since_genesis_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 50),
+			(ALICE, 2, 30),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::genesis_issuance(TEST_TOKEN_ID), 150);
+			assert_eq!(Tokens::genesis_issuance(2), 30);
+			assert_eq!(Tokens::issuance_since_genesis(TEST_TOKEN_ID), 0);
+
+			// mints
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &CHARLIE, 40));
+			assert_eq!(Tokens::issuance_since_genesis(TEST_TOKEN_ID), 40);
+
+			// burns
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 70));
+			assert_eq!(Tokens::issuance_since_genesis(TEST_TOKEN_ID), -30);
+			assert_ok!(Tokens::withdraw(2, &ALICE, 10));
+			assert_eq!(Tokens::issuance_since_genesis(2), -10);
+
+			// transfers don't change issuance
+			assert_ok!(Tokens::transfer(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 20));
+			assert_eq!(Tokens::issuance_since_genesis(TEST_TOKEN_ID), -30);
+
+			// not issued at genesis
+			assert_eq!(Tokens::genesis_issuance(3), 0);
+			assert_ok!(Tokens::deposit(3, &ALICE, 25));
+			assert_eq!(Tokens::issuance_since_genesis(3), 25);
+		});
+}
+
+#[test]
+fn max_balance_per_account_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			MockMaxBalancePerAccount::set(Some(150));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 40));
+
+			// the cap is on the total balance
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 51),
+				Error::<Runtime>::BalanceCapExceeded
+			);
+			assert_noop!(
+				<Tokens as MultiCurrency<_>>::transfer(TEST_TOKEN_ID, &ALICE, &BOB, 51),
+				Error::<Runtime>::BalanceCapExceeded
+			);
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &BOB, 51),
+				Error::<Runtime>::BalanceCapExceeded
+			);
+
+			// reaching the cap exactly
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 50));
+			assert_eq!(Tokens::total_balance(TEST_TOKEN_ID, &BOB), 150);
+			assert_noop!(
+				Tokens::deposit(TEST_TOKEN_ID, &BOB, 1),
+				Error::<Runtime>::BalanceCapExceeded
+			);
+
+			// unreserving doesn't change the total balance
+			assert_eq!(Tokens::unreserve(TEST_TOKEN_ID, &BOB, 40), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 150);
+
+			// uncapped currencies
+			assert_ok!(Tokens::deposit(2, &BOB, 1000));
+			MockMaxBalancePerAccount::set(None);
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &BOB, 1000));
+		});
+}
+
+#[test]
+fn transfer_batch_same_currency_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer_batch_same_currency(
+				TEST_TOKEN_ID,
+				&ALICE,
+				vec![(BOB, 10), (CHARLIE, 20), (TREASURY, 30), (ALICE, 40), (CHARLIE, 5)]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 35);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &BOB), 110);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 25);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &TREASURY), 30);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 200);
+
+			let transferred_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, TREASURY, 30));
+			assert!(System::events().iter().any(|record| record.event == transferred_event));
+
+			// leaving dust burns it
+			assert_ok!(Tokens::transfer_batch_same_currency(
+				TEST_TOKEN_ID,
+				&ALICE,
+				vec![(BOB, 34)]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 199);
+		});
+}
+
+#[test]
+fn transfer_batch_same_currency_should_be_all_or_nothing() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_noop!(
+				Tokens::transfer_batch_same_currency(TEST_TOKEN_ID, &ALICE, vec![(BOB, 60), (CHARLIE, 41)]),
+				Error::<Runtime>::BalanceTooLow
+			);
+			assert_noop!(
+				Tokens::transfer_batch_same_currency(TEST_TOKEN_ID, &ALICE, vec![(BOB, 60), (CHARLIE, 1)]),
+				Error::<Runtime>::ExistentialDeposit
+			);
+
+			// amounts to the same recipient are netted before validation
+			MockMaxBalancePerAccount::set(Some(30));
+			assert_noop!(
+				Tokens::transfer_batch_same_currency(TEST_TOKEN_ID, &ALICE, vec![(CHARLIE, 20), (CHARLIE, 20)]),
+				Error::<Runtime>::BalanceCapExceeded
+			);
+			MockMaxBalancePerAccount::set(None);
+			assert_ok!(Tokens::transfer_batch_same_currency(
+				TEST_TOKEN_ID,
+				&ALICE,
+				vec![(CHARLIE, 1), (CHARLIE, 1)]
+			));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &CHARLIE), 2);
+			let netted_event = TestEvent::tokens(RawEvent::Transferred(TEST_TOKEN_ID, ALICE, CHARLIE, 2));
+			assert!(System::events().iter().any(|record| record.event == netted_event));
+
+			Tokens::set_lock(ID_1, TEST_TOKEN_ID, &ALICE, 50);
+			assert_noop!(
+				Tokens::transfer_batch_same_currency(TEST_TOKEN_ID, &ALICE, vec![(BOB, 30), (CHARLIE, 30)]),
+				Error::<Runtime>::LiquidityRestrictions
+			);
+		});
+}
+
+#[test]
+fn transfer_batch_same_currency_should_route_native_currency() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(MockNativeCurrency::deposit(&ALICE, 100));
+		assert_ok!(Tokens::transfer_batch_same_currency(
+			NATIVE_CURRENCY_ID,
+			&ALICE,
+			vec![(BOB, 10), (CHARLIE, 20)]
+		));
+		assert_eq!(MockNativeCurrency::free_balance(&ALICE), 70);
+		assert_eq!(MockNativeCurrency::free_balance(&CHARLIE), 20);
+		assert_eq!(Tokens::free_balance(NATIVE_CURRENCY_ID, &CHARLIE), 20);
+
+		assert_noop!(
+			Tokens::transfer_batch_same_currency(NATIVE_CURRENCY_ID, &ALICE, vec![(BOB, 60), (CHARLIE, 20)]),
+			DispatchError::Other("native balance too low")
+		);
+
+		assert_ok!(Tokens::transfer_batch_same_currency(
+			NATIVE_CURRENCY_ID,
+			&ALICE,
+			vec![(BOB, 5), (BOB, 5)]
+		));
+		assert_eq!(MockNativeCurrency::free_balance(&BOB), 20);
+		let netted_event = TestEvent::tokens(RawEvent::Transferred(NATIVE_CURRENCY_ID, ALICE, BOB, 10));
+		assert!(System::events().iter().any(|record| record.event == netted_event));
+	});
+}
+
+#[test]
+fn total_reserved_named_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+			(ALICE, 2, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::total_reserved_named(TEST_TOKEN_ID, &ReserveId::Staking), 0);
+
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &BOB, 15));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &CHARLIE, 7));
+			// other reserve ids, unnamed reserves and other currencies are excluded
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 20));
+			assert_ok!(Tokens::reserve(TEST_TOKEN_ID, &BOB, 10));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, 2, &ALICE, 50));
+
+			assert_eq!(Tokens::total_reserved_named(TEST_TOKEN_ID, &ReserveId::Staking), 52);
+			assert_eq!(Tokens::total_reserved_named(TEST_TOKEN_ID, &ReserveId::Auction), 20);
+			assert_eq!(Tokens::total_reserved_named(2, &ReserveId::Staking), 50);
+		});
+}
+
+#[test]
+fn accounts_with_reserve_should_work() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+			(ALICE, 2, 100),
+		])
+		.build()
+		.execute_with(|| {
+			assert_eq!(
+				Tokens::accounts_with_reserve(TEST_TOKEN_ID, &ReserveId::Staking),
+				vec![]
+			);
+
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &ALICE, 30));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &BOB, 15));
+			// accounts with only other reserve ids, or in other currencies, are excluded
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &ALICE, 20));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Auction, TEST_TOKEN_ID, &CHARLIE, 7));
+			assert_ok!(Tokens::reserve_named(&ReserveId::Staking, 2, &ALICE, 50));
+
+			let mut holders = Tokens::accounts_with_reserve(TEST_TOKEN_ID, &ReserveId::Staking);
+			holders.sort();
+			assert_eq!(holders, vec![(ALICE, 30), (BOB, 15)]);
+
+			let mut holders = Tokens::accounts_with_reserve(TEST_TOKEN_ID, &ReserveId::Auction);
+			holders.sort();
+			assert_eq!(holders, vec![(ALICE, 20), (CHARLIE, 7)]);
+
+			// fully released reserves are excluded
+			assert_eq!(Tokens::unreserve_named(&ReserveId::Staking, TEST_TOKEN_ID, &BOB, 15), 0);
+			assert_eq!(
+				Tokens::accounts_with_reserve(TEST_TOKEN_ID, &ReserveId::Staking),
+				vec![(ALICE, 30)]
+			);
+		});
+}
+
+#[test]
+fn split_dust_should_work() {
+	ExtBuilder::default()
+		.dust_split(Permill::from_percent(50))
+		.build()
+		.execute_with(|| {
+			assert_eq!(Tokens::split_dust(100), (50, 50));
+			let (burned, credited) = Tokens::split_dust(101);
+			assert_eq!(burned + credited, 101);
+			assert_eq!(Tokens::split_dust(0), (0, 0));
+		});
+}
+
+#[test]
+fn dust_should_be_split_between_burn_and_receiver() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(DUST_RECEIVER, TEST_TOKEN_ID, 10),
+		])
+		.dust_split(Permill::from_percent(50))
+		.build()
+		.execute_with(|| {
+			// dust of 1 with a 50/50 split
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			let burned = MockDustRemoval::accumulated_dust();
+			let credited = Tokens::free_balance(TEST_TOKEN_ID, &DUST_RECEIVER) - 10;
+			assert_eq!(burned + credited, 1);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 210 - burned);
+		});
+}
+
+#[test]
+fn dust_should_be_reported_per_account() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
+			let dust_lost_event = TestEvent::tokens(RawEvent::DustLost(TEST_TOKEN_ID, ALICE, 1));
+			assert!(System::events().iter().any(|record| record.event == dust_lost_event));
+			assert!(Tokens::block_dust().is_empty());
+		});
+}
+
+#[test]
+fn dust_should_be_consolidated_per_block() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(ALICE, FEE_EXEMPT_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(CHARLIE, TEST_TOKEN_ID, 100),
+		])
+		.build()
+		.execute_with(|| {
+			MockDustEventMode::set(DustEventMode::Consolidated);
+			System::set_block_number(1);
+
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, FEE_EXEMPT_TOKEN_ID, 99));
+			assert_ok!(Tokens::transfer(Some(CHARLIE).into(), BOB, TEST_TOKEN_ID, 99));
+			assert!(!System::events().iter().any(|record| match record.event {
+				TestEvent::tokens(RawEvent::DustLost(..)) => true,
+				_ => false,
+			}));
+			assert_eq!(Tokens::block_dust(), vec![(TEST_TOKEN_ID, 2), (FEE_EXEMPT_TOKEN_ID, 1)]);
+
+			Tokens::on_finalize(1);
+			let consolidated_event = TestEvent::tokens(RawEvent::DustConsolidated(vec![
+				(TEST_TOKEN_ID, 2),
+				(FEE_EXEMPT_TOKEN_ID, 1),
+			]));
+			assert!(System::events().iter().any(|record| record.event == consolidated_event));
+			assert!(Tokens::block_dust().is_empty());
+		});
+}
+
+#[test]
+fn dust_should_be_credited_to_receiver() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(DUST_RECEIVER, TEST_TOKEN_ID, 10),
+		])
+		.dust_split(Permill::zero())
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 99));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &DUST_RECEIVER), 11);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 0);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 210);
+
+			// burned if it would leave the receiver below the existential deposit
+			assert_ok!(Tokens::transfer(Some(DUST_RECEIVER).into(), BOB, TEST_TOKEN_ID, 11));
+			assert_ok!(Tokens::transfer(Some(BOB).into(), ALICE, TEST_TOKEN_ID, 10));
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 9));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &DUST_RECEIVER), 0);
+			assert_eq!(MockDustRemoval::accumulated_dust(), 1);
+			assert_eq!(Tokens::total_issuance(TEST_TOKEN_ID), 209);
+		});
+}
+
+#[test]
+fn on_transfer_record_should_accumulate_volume() {
+	ExtBuilder::default()
+		.balances(vec![
+			(ALICE, TEST_TOKEN_ID, 100),
+			(BOB, TEST_TOKEN_ID, 100),
+			(ALICE, AUTO_METADATA_TOKEN_ID, 100),
+		])
+		.transfer_fee(Permill::from_percent(10), Permill::zero())
+		.build()
+		.execute_with(|| {
+			// dispatchable transfers record the amount debited, before the fee
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 30));
+			assert_ok!(Tokens::transfer_all(
+				Some(ALICE).into(),
+				CHARLIE,
+				AUTO_METADATA_TOKEN_ID
+			));
+			assert_eq!(MockTransferRecord::volume(TEST_TOKEN_ID), 30);
+			assert_eq!(MockTransferRecord::volume(AUTO_METADATA_TOKEN_ID), 100);
+
+			// trait-level transfers
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(
+				TEST_TOKEN_ID,
+				&BOB,
+				&CHARLIE,
+				20
+			));
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(
+				AUTO_METADATA_TOKEN_ID,
+				&CHARLIE,
+				&BOB,
+				40
+			));
+			assert_eq!(MockTransferRecord::volume(TEST_TOKEN_ID), 50);
+			assert_eq!(MockTransferRecord::volume(AUTO_METADATA_TOKEN_ID), 140);
+
+			// batch transfers record each recipient, and transfers into a named reserve are recorded too
+			assert_ok!(Tokens::transfer_batch_same_currency(
+				TEST_TOKEN_ID,
+				&BOB,
+				vec![(ALICE, 5), (CHARLIE, 5)]
+			));
+			assert_ok!(Tokens::transfer_and_reserve_named(
+				Some(ALICE).into(),
+				BOB,
+				TEST_TOKEN_ID,
+				10,
+				ReserveId::Staking
+			));
+			assert_eq!(MockTransferRecord::volume(TEST_TOKEN_ID), 70);
+
+			// zero, self and failed transfers are not recorded
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 0));
+			assert_ok!(<Tokens as MultiCurrency<_>>::transfer(
+				TEST_TOKEN_ID,
+				&ALICE,
+				&ALICE,
+				10
+			));
+			assert_noop!(
+				Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 1000),
+				Error::<Runtime>::BalanceTooLow
+			);
+			assert_eq!(MockTransferRecord::volume(TEST_TOKEN_ID), 70);
+			assert_eq!(MockTransferRecord::volume(AUTO_METADATA_TOKEN_ID), 140);
+		});
+}
+
+#[test]
+fn on_issuance_change_should_work() {
+	ExtBuilder::default()
+		.one_hundred_for_alice_n_bob()
+		.transfer_fee(Permill::from_percent(10), Permill::from_percent(50))
+		.build()
+		.execute_with(|| {
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &ALICE, 50));
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 20));
+			assert_eq!(Tokens::slash(TEST_TOKEN_ID, &BOB, 10), 0);
+			assert_eq!(
+				MockIssuanceChange::changes(),
+				vec![(TEST_TOKEN_ID, 50), (TEST_TOKEN_ID, -20), (TEST_TOKEN_ID, -10)]
+			);
+
+			// the fee of 10 is burned, then half of it re-issued to treasury
+			assert_ok!(Tokens::transfer(Some(ALICE).into(), BOB, TEST_TOKEN_ID, 100));
+			assert_eq!(MockIssuanceChange::net_issuance(TEST_TOKEN_ID), 15);
+
+			// dust
+			assert_ok!(Tokens::withdraw(TEST_TOKEN_ID, &ALICE, 29));
+			assert_eq!(Tokens::free_balance(TEST_TOKEN_ID, &ALICE), 0);
+			let changes = MockIssuanceChange::changes();
+			assert_eq!(
+				changes[changes.len() - 2..],
+				[(TEST_TOKEN_ID, -29), (TEST_TOKEN_ID, -1)]
+			);
+
+			// zero changes are not reported
+			let count = MockIssuanceChange::changes().len();
+			assert_ok!(Tokens::deposit(TEST_TOKEN_ID, &BOB, 0));
+			assert_eq!(MockIssuanceChange::changes().len(), count);
+
+			assert_eq!(
+				Tokens::total_issuance(TEST_TOKEN_ID) as i128,
+				200 + MockIssuanceChange::net_issuance(TEST_TOKEN_ID)
+			);
+		});
 }
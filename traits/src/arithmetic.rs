@@ -88,3 +88,45 @@ impl<
 	> SimpleArithmetic for T
 {
 }
+
+/// Saturating and checked operations of signed integers, which never panic or wrap around on `min_value()`.
+pub trait SaturatingSigned: Signed {
+	/// The absolute value, or `None` for `min_value()`.
+	fn checked_abs(self) -> Option<Self>;
+
+	/// The absolute value, saturating to `max_value()` for `min_value()`.
+	fn saturating_abs(self) -> Self;
+
+	/// The negation, saturating to `max_value()` for `min_value()`.
+	fn saturating_neg(self) -> Self;
+}
+
+macro_rules! impl_saturating_signed {
+	($($t:ty),*) => {
+		$(
+			impl SaturatingSigned for $t {
+				fn checked_abs(self) -> Option<Self> {
+					<$t>::checked_abs(self)
+				}
+
+				fn saturating_abs(self) -> Self {
+					if self == <$t>::min_value() {
+						<$t>::max_value()
+					} else {
+						self.abs()
+					}
+				}
+
+				fn saturating_neg(self) -> Self {
+					if self == <$t>::min_value() {
+						<$t>::max_value()
+					} else {
+						-self
+					}
+				}
+			}
+		)*
+	};
+}
+
+impl_saturating_signed!(i8, i16, i32, i64, i128, isize);
@@ -4,8 +4,9 @@ pub mod arithmetic;
 pub mod auction;
 
 pub use auction::{Auction, AuctionHandler, AuctionInfo, OnNewBidResult};
-use codec::{Codec, FullCodec};
-pub use frame_support::traits::{BalanceStatus, LockIdentifier};
+use codec::{Codec, Decode, Encode, FullCodec};
+pub use frame_support::traits::{BalanceStatus, ExistenceRequirement, LockIdentifier, WithdrawReasons};
+use orml_utilities::with_transaction_result;
 use rstd::{
 	cmp::{Eq, PartialEq},
 	convert::{TryFrom, TryInto},
@@ -14,10 +15,73 @@ use rstd::{
 	result,
 };
 use sp_runtime::{
-	traits::{AtLeast32Bit, MaybeSerializeDeserialize},
-	DispatchError, DispatchResult,
+	traits::{AtLeast32Bit, MaybeSerializeDeserialize, Saturating, Zero},
+	DispatchError, DispatchResult, RuntimeDebug,
 };
 
+/// balance information for an account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct AccountData<Balance> {
+	/// Non-reserved part of the balance. There may still be restrictions on this, but it is the
+	/// total pool what may in principle be transferred, reserved.
+	///
+	/// This is the only balance that matters in terms of most operations on tokens.
+	pub free: Balance,
+	/// Balance which is reserved and may not be used at all.
+	///
+	/// This can still get slashed, but gets slashed last of all.
+	///
+	/// This balance is a 'reserve' balance that other subsystems use in order to set aside tokens
+	/// that are still 'owned' by the account holder, but which are suspendable.
+	pub reserved: Balance,
+	/// The amount that `free` may not drop below when withdrawing.
+	///
+	/// Locks apply to `free` only, so reserved funds are outside of their base, and locked free funds cannot be
+	/// reserved. `free` may still drop below `frozen` if a lock is set above it, in which case nothing more can be
+	/// withdrawn or reserved until `free` exceeds `frozen` again.
+	pub frozen: Balance,
+}
+
+impl<Balance: Saturating + Copy + Ord> AccountData<Balance> {
+	/// The amount that this account's free balance may not be reduced beyond.
+	pub fn frozen(&self) -> Balance {
+		self.frozen
+	}
+	/// The total balance in this account including any that is reserved and ignoring any frozen.
+	pub fn total(&self) -> Balance {
+		self.free.saturating_add(self.reserved)
+	}
+	/// The part of the free balance that is not frozen.
+	pub fn spendable(&self) -> Balance {
+		self.free.saturating_sub(self.frozen)
+	}
+}
+
+/// A single lock on a balance. There can be many of these on an account and they "overlap", so the
+/// same balance is frozen by multiple locks.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BalanceLock<Balance> {
+	/// An identifier for this lock. Only one lock may be in existence for each identifier.
+	pub id: LockIdentifier,
+	/// The amount which the free balance may not drop below when this lock is in effect.
+	pub amount: Balance,
+}
+
+/// Full balance information of an account under a currency, including its locks.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct AccountInfo<Balance> {
+	/// Non-reserved part of the balance.
+	pub free: Balance,
+	/// Balance which is reserved and may not be used at all.
+	pub reserved: Balance,
+	/// The amount that `free` may not drop below when withdrawing.
+	pub frozen: Balance,
+	/// The locks on the balance.
+	pub locks: Vec<BalanceLock<Balance>>,
+	/// The total balance, including any that is reserved.
+	pub total_balance: Balance,
+}
+
 /// Abstraction over a fungible multi-currency system.
 pub trait MultiCurrency<AccountId> {
 	/// The currency identifier.
@@ -37,6 +101,39 @@ pub trait MultiCurrency<AccountId> {
 	// The free balance of `who` under `currency_id`.
 	fn free_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance;
 
+	/// The free balance of `who` under `currency_id` that is available for protocol operations, excluding any
+	/// frozen by locks.
+	///
+	/// The default implementation is the free balance, for currencies without locks.
+	fn effective_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+		Self::free_balance(currency_id, who)
+	}
+
+	/// The free balance of `who` under `currency_id` that could be transferred while keeping the account alive, i.e.
+	/// `free - frozen - existential deposit`, saturating at zero.
+	///
+	/// The default implementation is the effective balance, for currencies without an existential deposit.
+	fn transferable_keep_alive(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+		Self::effective_balance(currency_id, who)
+	}
+
+	/// The balance information of each of `accounts` under `currency_id`, in the same order.
+	///
+	/// The default implementation derives it from `free_balance`, `total_balance` and `effective_balance`.
+	fn balances_batch(currency_id: Self::CurrencyId, accounts: &[AccountId]) -> Vec<AccountData<Self::Balance>> {
+		accounts
+			.iter()
+			.map(|who| {
+				let free = Self::free_balance(currency_id, who);
+				AccountData {
+					free,
+					reserved: Self::total_balance(currency_id, who).saturating_sub(free),
+					frozen: free.saturating_sub(Self::effective_balance(currency_id, who)),
+				}
+			})
+			.collect()
+	}
+
 	/// A dry-run of `withdraw`. Returns `Ok` iff the account is able to make a withdrawal of the given amount.
 	fn ensure_can_withdraw(currency_id: Self::CurrencyId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
 
@@ -50,14 +147,127 @@ pub trait MultiCurrency<AccountId> {
 		amount: Self::Balance,
 	) -> DispatchResult;
 
+	/// Transfer from one account to another, so that `to` receives exactly `receive_amount` after any
+	/// fee-on-transfer is charged from the sent amount.
+	///
+	/// The default implementation charges no fee, and is the same as `transfer`.
+	fn transfer_exact_out(
+		currency_id: Self::CurrencyId,
+		from: &AccountId,
+		to: &AccountId,
+		receive_amount: Self::Balance,
+	) -> DispatchResult {
+		Self::transfer(currency_id, from, to, receive_amount)
+	}
+
+	/// Transfer `amount` from one account to another, failing if `to` would receive less than `min_received` after
+	/// any fee-on-transfer is charged from `amount`.
+	///
+	/// The default implementation charges no fee, so `to` receives `amount` in full.
+	fn transfer_min_received(
+		currency_id: Self::CurrencyId,
+		from: &AccountId,
+		to: &AccountId,
+		amount: Self::Balance,
+		min_received: Self::Balance,
+	) -> DispatchResult {
+		if amount < min_received {
+			return Err(DispatchError::Other("received amount below minimum"));
+		}
+		Self::transfer(currency_id, from, to, amount)
+	}
+
+	/// Transfer from one account to another without changing the total issuance, e.g. for internal protocol moves
+	/// between pool accounts.
+	///
+	/// The default implementation is the same as `transfer`, and asserts the total issuance is unchanged in debug
+	/// builds.
+	fn transfer_without_issuance_change(
+		currency_id: Self::CurrencyId,
+		from: &AccountId,
+		to: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		let total_issuance = Self::total_issuance(currency_id);
+		Self::transfer(currency_id, from, to, amount)?;
+		debug_assert!(Self::total_issuance(currency_id) == total_issuance);
+		Ok(())
+	}
+
+	/// Apply a matrix of `(from, to, currency_id, amount)` transfers, all or nothing.
+	///
+	/// Each transfer is checked by `ensure_can_withdraw` against the balances before the settlement, then all
+	/// transfers are applied in sequence in a storage transaction, which is rolled back if any of them fails.
+	fn settle_matrix(transfers: &[(AccountId, AccountId, Self::CurrencyId, Self::Balance)]) -> DispatchResult {
+		for (from, _, currency_id, amount) in transfers.iter() {
+			Self::ensure_can_withdraw(*currency_id, from, *amount)?;
+		}
+
+		with_transaction_result(|| {
+			for (from, to, currency_id, amount) in transfers.iter() {
+				Self::transfer(*currency_id, from, to, *amount)?;
+			}
+			Ok(())
+		})
+	}
+
 	/// Add `amount` to the balance of `who` under `currency_id` and increase total issuance.
 	fn deposit(currency_id: Self::CurrencyId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
 
 	/// Remove `amount` from the balance of `who` under `currency_id` and reduce total issuance.
 	fn withdraw(currency_id: Self::CurrencyId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
 
+	/// Burn `amount` from the balance of `who` under `currency_id` like `withdraw`, if `predicate` holds.
+	///
+	/// `predicate` is evaluated right before burning, and if it doesn't hold, nothing is burned and `Ok` is returned.
+	fn burn_if(
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		amount: Self::Balance,
+		predicate: impl Fn() -> bool,
+	) -> DispatchResult {
+		if predicate() {
+			Self::withdraw(currency_id, who, amount)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Withdraw `total_needed` from `who`, draining `currencies` in order, each up to its given maximum, and
+	/// returning the amount withdrawn per currency.
+	///
+	/// Amounts are counted one-to-one across currencies, and are drawn from `effective_balance`. Nothing is withdrawn
+	/// if the currencies cannot cover `total_needed`, or if any withdrawal fails.
+	fn withdraw_in_order(
+		who: &AccountId,
+		currencies: &[(Self::CurrencyId, Self::Balance)],
+		total_needed: Self::Balance,
+	) -> result::Result<Vec<(Self::CurrencyId, Self::Balance)>, DispatchError> {
+		with_transaction_result(|| {
+			let mut withdrawals = Vec::new();
+			let mut remaining = total_needed;
+			for (currency_id, max_amount) in currencies.iter() {
+				if remaining.is_zero() {
+					break;
+				}
+				let amount = remaining
+					.min(*max_amount)
+					.min(Self::effective_balance(*currency_id, who));
+				if !amount.is_zero() {
+					Self::withdraw(*currency_id, who, amount)?;
+					withdrawals.push((*currency_id, amount));
+					remaining -= amount;
+				}
+			}
+			if !remaining.is_zero() {
+				return Err(DispatchError::Other("currencies cannot cover total needed"));
+			}
+			Ok(withdrawals)
+		})
+	}
+
 	/// Same result as `slash(currency_id, who, value)` (but without the side-effects) assuming there are no
-	/// balance changes in the meantime and only the reserved balance is not taken into account.
+	/// balance changes in the meantime, i.e. whether the free and reserved balance of `who` together cover `value`.
 	fn can_slash(currency_id: Self::CurrencyId, who: &AccountId, value: Self::Balance) -> bool;
 
 	/// Deduct the balance of `who` by up to `amount`.
@@ -84,7 +294,21 @@ pub trait MultiCurrencyExtended<AccountId>: MultiCurrency<AccountId> {
 	fn update_balance(currency_id: Self::CurrencyId, who: &AccountId, by_amount: Self::Amount) -> DispatchResult;
 }
 
+/// A fungible multi-currency system whose total issuance could be changed without changing any balance, e.g. to
+/// settle imbalances.
+pub trait MultiCurrencyIssuance<AccountId>: MultiCurrency<AccountId> {
+	/// Increase the total issuance of `currency_id` by `amount`, saturating at the maximum balance.
+	fn increase_total_issuance(currency_id: Self::CurrencyId, amount: Self::Balance);
+
+	/// Decrease the total issuance of `currency_id` by `amount`, saturating at zero.
+	fn decrease_total_issuance(currency_id: Self::CurrencyId, amount: Self::Balance);
+}
+
 /// A fungible multi-currency system whose accounts can have liquidity restrictions.
+///
+/// Implementations may reserve some lock identifiers to an owner, changing their locks only through their own API.
+/// For such an identifier, `set_lock`, `extend_lock` and `remove_lock` leave the locks unchanged, and
+/// `try_set_lock` and `try_extend_lock` fail.
 pub trait MultiLockableCurrency<AccountId>: MultiCurrency<AccountId> {
 	/// The quantity used to denote time; usually just a `BlockNumber`.
 	type Moment;
@@ -107,7 +331,46 @@ pub trait MultiLockableCurrency<AccountId>: MultiCurrency<AccountId> {
 	fn extend_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &AccountId, amount: Self::Balance);
 
 	/// Remove an existing lock.
+	///
+	/// Does nothing if the implementation reserves `lock_id` to an owner.
 	fn remove_lock(lock_id: LockIdentifier, currency_id: Self::CurrencyId, who: &AccountId);
+
+	/// Set a lock as `set_lock`, but fail instead if the implementation restricts how much could be locked.
+	/// Also fails if it reserves `lock_id` to an owner.
+	///
+	/// The default implementation doesn't restrict locks.
+	fn try_set_lock(
+		lock_id: LockIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Self::set_lock(lock_id, currency_id, who, amount);
+		Ok(())
+	}
+
+	/// Extend a lock as `extend_lock`, but fail instead if the implementation restricts how much could be locked.
+	/// Also fails if it reserves `lock_id` to an owner.
+	///
+	/// The default implementation doesn't restrict locks.
+	fn try_extend_lock(
+		lock_id: LockIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Self::extend_lock(lock_id, currency_id, who, amount);
+		Ok(())
+	}
+}
+
+/// How `MultiReservableCurrency::reserve_multi_account` handles failed entries.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReserveMode {
+	/// Reserve all entries, or none of them if any could not be reserved.
+	AllOrNothing,
+	/// Reserve each entry independently.
+	AllowPartial,
 }
 
 /// A fungible multi-currency system where funds can be reserved from the user.
@@ -116,6 +379,11 @@ pub trait MultiReservableCurrency<AccountId>: MultiCurrency<AccountId> {
 	/// are no balance changes in the meantime.
 	fn can_reserve(currency_id: Self::CurrencyId, who: &AccountId, value: Self::Balance) -> bool;
 
+	/// Whether balances of `currency_id` could be reserved at all.
+	fn reservable(_currency_id: Self::CurrencyId) -> bool {
+		true
+	}
+
 	/// Deducts up to `value` from reserved balance of `who`. This function cannot fail.
 	///
 	/// As much funds up to `value` will be deducted as possible. If the reserve balance of `who`
@@ -159,6 +427,117 @@ pub trait MultiReservableCurrency<AccountId>: MultiCurrency<AccountId> {
 		value: Self::Balance,
 		status: BalanceStatus,
 	) -> result::Result<Self::Balance, DispatchError>;
+
+	/// Reserve balances of multiple accounts under multiple currencies, returning the result of each entry.
+	///
+	/// In `AllOrNothing` mode, the entries are reserved in sequence in a storage transaction, which is rolled back
+	/// on the first failing entry, and its error is returned.
+	fn reserve_multi_account(
+		reserves: Vec<(Self::CurrencyId, AccountId, Self::Balance)>,
+		mode: ReserveMode,
+	) -> result::Result<Vec<DispatchResult>, DispatchError> {
+		if mode == ReserveMode::AllOrNothing {
+			return with_transaction_result(|| {
+				for (currency_id, who, value) in reserves.iter() {
+					Self::reserve(*currency_id, who, *value)?;
+				}
+				Ok(reserves.iter().map(|_| Ok(())).collect())
+			});
+		}
+
+		Ok(reserves
+			.iter()
+			.map(|(currency_id, who, value)| Self::reserve(*currency_id, who, *value))
+			.collect())
+	}
+}
+
+/// A fungible multi-currency system where funds can be reserved from the user under named identifiers.
+pub trait NamedMultiReservableCurrency<AccountId>: MultiReservableCurrency<AccountId> {
+	/// An identifier for a named reserve, typically an enum with a variant per module reserving funds.
+	type ReserveIdentifier;
+
+	/// The amount of the balance of a given account that is reserved under the identifier `id`.
+	fn reserved_balance_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+	) -> Self::Balance;
+
+	/// Moves `value` from balance to reserved balance under the identifier `id`.
+	///
+	/// If the free balance is lower than `value`, then no funds will be moved and an `Err` will
+	/// be returned to notify of this.
+	fn reserve_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		value: Self::Balance,
+	) -> DispatchResult;
+
+	/// Moves up to `value` from reserved balance under the identifier `id` to free balance. This function cannot
+	/// fail.
+	///
+	/// As much funds up to `value` will be moved as possible. If the named reserve of `who` is less than `value`,
+	/// then the remaining amount will be returned.
+	fn unreserve_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		value: Self::Balance,
+	) -> Self::Balance;
+
+	/// Deducts up to `value` from reserved balance under the identifier `id`. This function cannot fail.
+	///
+	/// As much funds up to `value` will be deducted as possible. If the named reserve of `who` is less than `value`,
+	/// then the amount unable to be slashed will be returned.
+	fn slash_reserved_named(
+		id: &Self::ReserveIdentifier,
+		currency_id: Self::CurrencyId,
+		who: &AccountId,
+		value: Self::Balance,
+	) -> Self::Balance;
+}
+
+/// A fungible multi-currency system which keeps the history of free balances.
+pub trait MultiCurrencyHistory<AccountId, BlockNumber>: MultiCurrency<AccountId> {
+	/// The free balance of `who` under `currency_id` as of the end of `block`, or `None` if not known.
+	fn free_balance_at(currency_id: Self::CurrencyId, who: &AccountId, block: BlockNumber) -> Option<Self::Balance>;
+}
+
+/// A fungible multi-currency system which keeps balance information of each account under each currency.
+pub trait MultiCurrencyAccountData<AccountId>: MultiCurrency<AccountId> {
+	/// All currencies `who` has a non-zero total balance under, with the balance information of each.
+	///
+	/// NOTE: This iterates over all currencies, and should only be used off-chain, e.g. by runtime APIs.
+	fn currencies_of(who: &AccountId) -> Vec<(Self::CurrencyId, AccountData<Self::Balance>)>;
+
+	/// All known currencies.
+	///
+	/// The default implementation knows no currencies.
+	///
+	/// NOTE: This iterates over all currencies, and should only be used off-chain, e.g. by runtime APIs.
+	fn currency_ids() -> Vec<Self::CurrencyId> {
+		Vec::new()
+	}
+
+	/// The balance information and locks of `who` under `currency_id`.
+	fn account_info(currency_id: Self::CurrencyId, who: &AccountId) -> AccountInfo<Self::Balance>;
+
+	/// The amount of the balance of `who` under `currency_id` which is unavailable due to locks or reserves.
+	///
+	/// This is `frozen + reserved`: locks apply to the free balance only, and the reserved balance is separate from
+	/// them. As a lock may be larger than the free balance, this may exceed the total balance.
+	fn encumbered_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+		let info = Self::account_info(currency_id, who);
+		info.frozen.saturating_add(info.reserved)
+	}
+
+	/// The amount of the balance of `who` under `currency_id` which is neither locked nor reserved, i.e. the total
+	/// balance minus `encumbered_balance`, saturating at zero.
+	fn available_balance(currency_id: Self::CurrencyId, who: &AccountId) -> Self::Balance {
+		Self::total_balance(currency_id, who).saturating_sub(Self::encumbered_balance(currency_id, who))
+	}
 }
 
 /// Abstraction over a fungible (single) currency system.
@@ -177,6 +556,14 @@ pub trait BasicCurrency<AccountId> {
 	/// The free balance of `who`.
 	fn free_balance(who: &AccountId) -> Self::Balance;
 
+	/// The free balance of `who` that could be transferred while keeping the account alive, i.e.
+	/// `free - existential deposit`, saturating at zero.
+	///
+	/// The default implementation is the free balance, for currencies without an existential deposit.
+	fn transferable_keep_alive(who: &AccountId) -> Self::Balance {
+		Self::free_balance(who)
+	}
+
 	/// A dry-run of `withdraw`. Returns `Ok` iff the account is able to make a withdrawal of the given amount.
 	fn ensure_can_withdraw(who: &AccountId, amount: Self::Balance) -> DispatchResult;
 
@@ -192,7 +579,7 @@ pub trait BasicCurrency<AccountId> {
 	fn withdraw(who: &AccountId, amount: Self::Balance) -> DispatchResult;
 
 	/// Same result as `slash(who, value)` (but without the side-effects) assuming there are no
-	/// balance changes in the meantime and only the reserved balance is not taken into account.
+	/// balance changes in the meantime, i.e. whether the free and reserved balance of `who` together cover `value`.
 	fn can_slash(who: &AccountId, value: Self::Balance) -> bool;
 
 	/// Deduct the balance of `who` by up to `amount`.
@@ -219,6 +606,19 @@ pub trait BasicCurrencyExtended<AccountId>: BasicCurrency<AccountId> {
 	fn update_balance(who: &AccountId, by_amount: Self::Amount) -> DispatchResult;
 }
 
+/// Extended `BasicCurrency` with transfers for custom withdraw reasons.
+pub trait BasicCurrencyExtendedTransfer<AccountId>: BasicCurrency<AccountId> {
+	/// Transfer some amount from one account to another, withdrawn for `reason` and with the `existence` requirement
+	/// of `from`, so that only locks covering `reason` restrict the transfer.
+	fn transfer_with_reason(
+		from: &AccountId,
+		to: &AccountId,
+		amount: Self::Balance,
+		reason: WithdrawReasons,
+		existence: ExistenceRequirement,
+	) -> DispatchResult;
+}
+
 /// A fungible single currency system whose accounts can have liquidity restrictions.
 pub trait BasicLockableCurrency<AccountId>: BasicCurrency<AccountId> {
 	/// The quantity used to denote time; usually just a `BlockNumber`.
@@ -326,6 +726,74 @@ impl<Balance> OnDustRemoval<Balance> for () {
 	fn on_dust_removal(_: Balance) {}
 }
 
+/// Handler for changes of the total issuance of a currency.
+pub trait OnIssuanceChange<CurrencyId, Balance> {
+	/// Called after `amount` of `currency_id` is minted.
+	fn on_mint(currency_id: CurrencyId, amount: Balance);
+
+	/// Called after `amount` of `currency_id` is burned.
+	fn on_burn(currency_id: CurrencyId, amount: Balance);
+}
+
+impl<CurrencyId, Balance> OnIssuanceChange<CurrencyId, Balance> for () {
+	fn on_mint(_: CurrencyId, _: Balance) {}
+
+	fn on_burn(_: CurrencyId, _: Balance) {}
+}
+
+/// A runtime-defined selection of currencies, e.g. all stablecoins, which could be passed to dispatchables.
+pub trait CurrencyFilter<CurrencyId> {
+	/// Whether `currency_id` is selected.
+	fn matches(&self, currency_id: &CurrencyId) -> bool;
+}
+
+impl<CurrencyId> CurrencyFilter<CurrencyId> for () {
+	fn matches(&self, _: &CurrencyId) -> bool {
+		false
+	}
+}
+
+/// Handler for every successful transfer of a currency, e.g. to accumulate transfer volume.
+pub trait OnTransferRecord<CurrencyId, Balance> {
+	/// Called after `amount` of `currency_id` is transferred between two accounts.
+	fn on_transfer_record(currency_id: CurrencyId, amount: Balance);
+}
+
+impl<CurrencyId, Balance> OnTransferRecord<CurrencyId, Balance> for () {
+	fn on_transfer_record(_: CurrencyId, _: Balance) {}
+}
+
+/// Handler for when a deposit brings an account with zero balance back to existence.
+pub trait OnAccountRecreate<CurrencyId, AccountId, Balance> {
+	/// Called before `amount` of `currency_id` is deposited to `who`, which has zero balance.
+	///
+	/// Returns the re-creation deposit to be withheld from `amount`, or `Err` to reject the deposit.
+	fn on_account_recreate(
+		currency_id: CurrencyId,
+		who: &AccountId,
+		amount: Balance,
+	) -> result::Result<Balance, DispatchError>;
+}
+
+impl<CurrencyId, AccountId, Balance: Zero> OnAccountRecreate<CurrencyId, AccountId, Balance> for () {
+	fn on_account_recreate(_: CurrencyId, _: &AccountId, _: Balance) -> result::Result<Balance, DispatchError> {
+		Ok(Zero::zero())
+	}
+}
+
+/// The balance of an account committed to pending withdrawals, e.g. transfers scheduled to be dispatched, which
+/// could be derived from the projected balance of `orml_schedule_update`.
+pub trait PendingWithdrawals<CurrencyId, AccountId, Balance> {
+	/// The free balance of `who` under `currency_id` committed to pending withdrawals.
+	fn pending_withdrawals(currency_id: CurrencyId, who: &AccountId) -> Balance;
+}
+
+impl<CurrencyId, AccountId, Balance: Zero> PendingWithdrawals<CurrencyId, AccountId, Balance> for () {
+	fn pending_withdrawals(_: CurrencyId, _: &AccountId) -> Balance {
+		Zero::zero()
+	}
+}
+
 #[impl_trait_for_tuples::impl_for_tuples(30)]
 pub trait OnRedundantCall<AccountId> {
 	fn multiple_calls_per_block(who: &AccountId);
@@ -150,6 +150,10 @@ impl FixedU128 {
 		Self(0)
 	}
 
+	pub fn one() -> Self {
+		Self(DIV)
+	}
+
 	pub fn is_zero(&self) -> bool {
 		self.0 == 0
 	}
@@ -317,6 +321,12 @@ mod tests {
 		assert!(!FixedU128::from_natural(1).is_zero());
 	}
 
+	#[test]
+	fn one_works() {
+		assert_eq!(FixedU128::one(), FixedU128::from_natural(1));
+		assert_eq!(FixedU128::one().saturating_mul_int(&7u128), 7);
+	}
+
 	#[test]
 	fn checked_div_with_zero_should_be_none() {
 		let a = FixedU128::from_natural(1);